@@ -1,27 +1,1080 @@
-use crate::db::catalog_db::{CatalogDb, CatalogDbClient};
+use crate::db::catalog_db::{CatalogDbClient, ClothingItem};
 use crate::services::catalog_service::CatalogService;
-use actix_web::{get, web, Responder};
+use actix_web::http::header::{ContentType, EntityTag, Header, IfNoneMatch, ETAG};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, HttpResponseBuilder, Responder};
+use common::dto::api_response::ApiResponse;
+use common::error::ServiceError;
+use common::types::item_ref::ItemRef;
+use common::utilities::request_id::RequestId;
+use event_bus::events::catalog_item_changed_event::CatalogItemChange;
+#[cfg(feature = "debug-endpoints")]
+use event_bus::events::order_placed_event::OrderPlacedEvent;
 use event_bus::EventBus;
+use serde::Deserialize;
 use std::sync::Arc;
 
+// set to `true` on catalog reads while the catalog is degraded, as its stock levels may be stale
+pub const CATALOG_STALE_HEADER: &str = "X-Catalog-Stale";
+
+#[derive(Deserialize)]
+pub struct AvailabilityQuery {
+    #[serde(default)]
+    include_out_of_stock: bool,
+}
+
+// `?pretty=true` indents the JSON response for manual debugging, compact JSON is returned by default
+#[derive(Deserialize)]
+pub struct FormatQuery {
+    #[serde(default)]
+    pretty: bool,
+}
+
 #[get("/catalog")]
-pub async fn get_catalog(catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>) -> impl Responder {
-    let items = catalog_service.get_items();
-    if items.is_empty() {
-        return format!("We are out of stock on everything, sorry!");
+// responds with the items wrapped in an `ApiResponse`
+pub async fn get_catalog(
+    req: HttpRequest,
+    query: web::Query<AvailabilityQuery>,
+    format: web::Query<FormatQuery>,
+    request_id: Option<web::ReqData<RequestId>>,
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> impl Responder {
+    // the version is read before the items so a concurrent change can only make the etag stale, never too new.
+    // The etag is weak as it only validates the items, not the request id the envelope carries with them
+    let etag = EntityTag::new_weak(catalog_service.get_catalog_version().to_string());
+    if is_not_modified(&req, &etag) {
+        return HttpResponse::NotModified().insert_header((ETAG, etag.to_string())).finish();
     }
-    serde_json::to_string(&items).unwrap()
+
+    let items = catalog_service.get_items(query.include_out_of_stock);
+    let response = ApiResponse::success(items, &request_id.map(|id| id.into_inner().0).unwrap_or_default());
+    let body = if format.pretty {
+        serde_json::to_string_pretty(&response).unwrap()
+    } else {
+        serde_json::to_string(&response).unwrap()
+    };
+    ok_response(&catalog_service)
+        .insert_header((ETAG, etag.to_string()))
+        .content_type(ContentType::json())
+        .body(body)
 }
 
-#[get("/catalog/stock/{item_id}")]
-// this request handler would not be exposed by an api gateway
+// starts a `200` response, flagged with the stale header while the catalog is degraded
+fn ok_response(catalog_service: &CatalogService<EventBus, CatalogDbClient>) -> HttpResponseBuilder {
+    let mut response = HttpResponse::Ok();
+    if catalog_service.is_degraded() {
+        response.insert_header((CATALOG_STALE_HEADER, "true"));
+    }
+    response
+}
+
+// checks whether the If-None-Match header of the request matches the current etag
+fn is_not_modified(req: &HttpRequest, etag: &EntityTag) -> bool {
+    match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        Err(_) => false,
+    }
+}
+
+#[get("/catalog/stock/{item}")]
+// this request handler would not be exposed by an api gateway, the item is referenced by its id or sku
+// and its stock is wrapped in an `ApiResponse`
 pub async fn get_stock(
+    item: web::Path<String>,
+    request_id: Option<web::ReqData<RequestId>>,
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> impl Responder {
+    let stock = catalog_service
+        .resolve_item_id(&ItemRef::from(item.as_str()))
+        .and_then(|item_id| catalog_service.get_stock(item_id))
+        .map_err(ServiceError::from);
+    let response = ApiResponse::from_result(stock, &request_id.map(|id| id.into_inner().0).unwrap_or_default());
+    ok_response(&catalog_service).status(response.status_code()).json(response)
+}
+
+#[derive(Deserialize)]
+pub struct StockBatchQuery {
+    ids: String,
+}
+
+#[get("/catalog/stock")]
+// this request handler would not be exposed by an api gateway, responds with the stock of each known item
+pub async fn get_stock_batch(
+    query: web::Query<StockBatchQuery>,
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> Result<impl Responder, ServiceError> {
+    let item_ids = query
+        .ids
+        .split(',')
+        .map(|id| id.trim().parse::<u32>())
+        .collect::<Result<Vec<u32>, _>>()
+        .map_err(|_| ServiceError::BadRequest("The ids must be a comma separated list of item ids.".to_string()))?;
+    Ok(ok_response(&catalog_service).json(catalog_service.get_stock_batch(&item_ids)))
+}
+
+#[get("/catalog/{item}")]
+// the item is referenced by its id or sku
+pub async fn get_catalog_item(
+    item: web::Path<String>,
+    query: web::Query<AvailabilityQuery>,
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> impl Responder {
+    let item_dto = catalog_service
+        .resolve_item_id(&ItemRef::from(item.as_str()))
+        .and_then(|item_id| catalog_service.get_item_dto(item_id, query.include_out_of_stock));
+    match item_dto {
+        Ok(item) => ok_response(&catalog_service).json(item),
+        Err(err) => ServiceError::from(err).to_http_response(),
+    }
+}
+
+#[get("/catalog/{item_id}/orders-summary")]
+pub async fn get_order_summary(
     item_id: web::Path<u32>,
     catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
 ) -> impl Responder {
-    let stock_amount_result = catalog_service.get_stock(item_id.into_inner());
-    if stock_amount_result.is_err() {
-        return format!("This item does not exist.");
+    match catalog_service.get_order_summary(item_id.into_inner()) {
+        Ok(summary) => HttpResponse::Ok().json(summary),
+        Err(err) => ServiceError::from(err).to_http_response(),
+    }
+}
+
+#[get("/metrics/orders")]
+// this request handler would not be exposed by an api gateway, responds with `404` if the metrics are not enabled
+pub async fn get_order_metrics(
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> Result<HttpResponse, ServiceError> {
+    let minutes = catalog_service
+        .get_order_metrics()
+        .ok_or_else(|| ServiceError::NotFound("The order metrics are not enabled.".to_string()))?;
+    Ok(HttpResponse::Ok().json(minutes))
+}
+
+#[get("/metrics/processing")]
+// this request handler would not be exposed by an api gateway
+pub async fn get_processing_metrics(
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> impl Responder {
+    HttpResponse::Ok().json(catalog_service.get_processing_metrics())
+}
+
+#[get("/debug/stats")]
+// this request handler would not be exposed by an api gateway
+pub async fn get_listener_stats(
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> impl Responder {
+    HttpResponse::Ok().json(catalog_service.get_listener_stats())
+}
+
+#[post("/listener/pause")]
+// this request handler would not be exposed by an api gateway, it stops placed orders being taken from
+// the stock, e.g. during a data migration, until the listener is resumed
+pub async fn pause_listener(
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> impl Responder {
+    catalog_service.pause_listener();
+    HttpResponse::NoContent().finish()
+}
+
+#[post("/listener/resume")]
+// this request handler would not be exposed by an api gateway
+pub async fn resume_listener(
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> impl Responder {
+    catalog_service.resume_listener();
+    HttpResponse::NoContent().finish()
+}
+
+#[get("/health")]
+// responds with `503` when the catalog cannot keep its stock levels up to date, unless it is degraded
+// and still serving its last known stock levels
+pub async fn get_health(catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>) -> impl Responder {
+    let health = catalog_service.get_health();
+    if health.healthy || health.degraded {
+        HttpResponse::Ok().json(health)
+    } else {
+        HttpResponse::ServiceUnavailable().json(health)
+    }
+}
+
+#[post("/catalog")]
+// this request handler would not be exposed by an api gateway
+pub async fn save_catalog_item(
+    item: web::Json<ClothingItem>,
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> Result<HttpResponse, ServiceError> {
+    let change = catalog_service.save_item(item.into_inner()).await?;
+    Ok(match change {
+        CatalogItemChange::Added => HttpResponse::Created().finish(),
+        _ => HttpResponse::Ok().finish(),
+    })
+}
+
+#[get("/catalog/export")]
+// this request handler would not be exposed by an api gateway, it backs up the catalog in the form
+// accepted by `POST /catalog/import`
+pub async fn export_catalog_items(
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> impl Responder {
+    HttpResponse::Ok().json(catalog_service.export_snapshot())
+}
+
+#[post("/catalog/import")]
+// this request handler would not be exposed by an api gateway, it seeds the catalog with many items at once
+pub async fn import_catalog_items(
+    items: web::Json<Vec<ClothingItem>>,
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> Result<HttpResponse, ServiceError> {
+    let summary = catalog_service.import_items(items.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+#[delete("/catalog/{item_id}")]
+// this request handler would not be exposed by an api gateway
+pub async fn remove_catalog_item(
+    item_id: web::Path<u32>,
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> Result<HttpResponse, ServiceError> {
+    catalog_service.remove_item(item_id.into_inner()).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// the body of a request setting the stock of an item
+#[derive(Deserialize)]
+pub struct SetStockRequest {
+    stock: u32,
+}
+
+#[put("/catalog/{item_id}/stock")]
+// this request handler would not be exposed by an api gateway, it overwrites the stock after a
+// physical count of the inventory rather than applying orders to it
+pub async fn set_stock(
+    item_id: web::Path<u32>,
+    body: web::Json<SetStockRequest>,
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> Result<HttpResponse, ServiceError> {
+    let stock_change = catalog_service.set_stock(item_id.into_inner(), body.stock).await?;
+    Ok(HttpResponse::Ok().json(stock_change))
+}
+
+// the body of a request reserving stock of an item
+#[derive(Deserialize)]
+pub struct ReserveStockRequest {
+    quantity: u32,
+}
+
+#[post("/catalog/{item_id}/reservations")]
+// holds stock for an order that is yet to be placed, until it is released or expires
+pub async fn reserve_stock(
+    item_id: web::Path<u32>,
+    body: web::Json<ReserveStockRequest>,
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> Result<HttpResponse, ServiceError> {
+    let reservation = catalog_service.reserve_stock(item_id.into_inner(), body.quantity)?;
+    Ok(HttpResponse::Created().json(reservation))
+}
+
+#[delete("/catalog/reservations/{reservation_id}")]
+pub async fn release_reservation(
+    reservation_id: web::Path<u64>,
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> Result<HttpResponse, ServiceError> {
+    catalog_service.release_reservation(reservation_id.into_inner())?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(feature = "debug-endpoints")]
+#[post("/debug/order-placed")]
+// only compiled with the `debug-endpoints` feature, applies an order as if it was received from kafka
+pub async fn inject_order_placed(
+    order_placed: web::Json<OrderPlacedEvent>,
+    catalog_service: web::Data<Arc<CatalogService<EventBus, CatalogDbClient>>>,
+) -> Result<HttpResponse, ServiceError> {
+    let stock = catalog_service.inject_order_placed(order_placed.into_inner()).await?;
+    Ok(ok_response(&catalog_service).body(stock.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::catalog_db::{CatalogDb, ClothingItem};
+    use crate::services::catalog_service::{CatalogHealth, ImportSummary, ListenerStats, OrderSummary};
+    use crate::services::order_metrics::MinuteOrderMetrics;
+    use crate::services::processing_metrics::ProcessingMetricsSnapshot;
+    use crate::services::reservations::Reservation;
+    use actix_web::http::header::IF_NONE_MATCH;
+    use actix_web::http::StatusCode;
+    use actix_web::middleware::from_fn;
+    use actix_web::{test, App};
+    use common::dto::clothing_item_dto::ClothingItemDTO;
+    use common::traits::listener_service::ListenerService;
+    use common::utilities::{request_id, server_config};
+    use event_bus::event::Event;
+    use event_bus::events::order_placed_event::OrderPlacedEvent;
+
+    fn generate_catalog_service() -> Arc<CatalogService<EventBus, CatalogDbClient>> {
+        let mut db = CatalogDbClient::new();
+        db.add_item(ClothingItem {
+            id: 10,
+            sku: String::new(),
+            name: "Sold Out Item".to_string(),
+            description: "An item with no stock".to_string(),
+            sizes: vec!["M".to_string()],
+            price: 10.00,
+            currency: "GBP".to_string(),
+            stock: 0,
+            version: 0,
+            allow_backorder: false,
+            backordered: 0,
+            reserved: 0,
+            images: vec![],
+            video: "https://example.com/sold-out-video.mp4".to_string(),
+        })
+        .unwrap();
+        Arc::new(CatalogService::new(db, EventBus::new("localhost:9092")))
+    }
+
+    #[actix_web::test]
+    async fn test_get_catalog_include_out_of_stock() {
+        // prepare
+        let app =
+            test::init_service(App::new().app_data(web::Data::new(generate_catalog_service())).service(get_catalog))
+                .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/catalog").to_request();
+        let public: ApiResponse<Vec<ClothingItemDTO>> = test::call_and_read_body_json(&app, req).await;
+        let req = test::TestRequest::get().uri("/catalog?include_out_of_stock=true").to_request();
+        let admin: ApiResponse<Vec<ClothingItemDTO>> = test::call_and_read_body_json(&app, req).await;
+
+        // assert
+        let (public, admin) = (public.into_result().unwrap(), admin.into_result().unwrap());
+        assert_eq!(public.len(), 5);
+        assert_eq!(admin.len(), 6);
+        assert!(admin.iter().any(|item| item.id == 10));
+    }
+
+    #[actix_web::test]
+    async fn test_get_catalog_pretty() {
+        // prepare
+        let app =
+            test::init_service(App::new().app_data(web::Data::new(generate_catalog_service())).service(get_catalog))
+                .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/catalog").to_request();
+        let compact = test::call_and_read_body(&app, req).await;
+        let req = test::TestRequest::get().uri("/catalog?pretty=true&include_out_of_stock=true").to_request();
+        let pretty = test::call_and_read_body(&app, req).await;
+
+        // assert
+        let compact = String::from_utf8(compact.to_vec()).unwrap();
+        let pretty = String::from_utf8(pretty.to_vec()).unwrap();
+        assert!(!compact.contains('\n'));
+        assert!(pretty.contains("\n    {\n      \"id\""));
+        let response: ApiResponse<Vec<ClothingItemDTO>> = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(response.into_result().unwrap().len(), 6);
+    }
+
+    #[actix_web::test]
+    async fn test_get_catalog_etag_changes_after_stock_change() {
+        // prepare
+        let catalog_service = generate_catalog_service();
+        let app =
+            test::init_service(App::new().app_data(web::Data::new(catalog_service.clone())).service(get_catalog)).await;
+        let before = test::call_service(&app, test::TestRequest::get().uri("/catalog").to_request()).await;
+
+        // act
+        catalog_service
+            .apply_order_placed(&Event::new(
+                "order_placed".to_string(),
+                OrderPlacedEvent {
+                    item_id: 1,
+                    quantity: 1,
+                    total: None,
+                    reservation_id: None,
+                },
+                "test".to_string(),
+                None,
+                None,
+            ))
+            .await;
+        let after = test::call_service(&app, test::TestRequest::get().uri("/catalog").to_request()).await;
+
+        // assert
+        assert_ne!(before.headers().get(ETAG).unwrap(), after.headers().get(ETAG).unwrap());
+    }
+
+    #[cfg(feature = "debug-endpoints")]
+    #[actix_web::test]
+    async fn test_inject_order_placed_decrements_stock() {
+        // prepare
+        let catalog_service = generate_catalog_service();
+        let stock_before = catalog_service.get_stock(1).unwrap();
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(catalog_service.clone())).service(inject_order_placed),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::post()
+            .uri("/debug/order-placed")
+            .set_json(serde_json::json!({"item_id": 1, "quantity": 2}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(test::read_body(resp).await, (stock_before - 2).to_string());
+        assert_eq!(catalog_service.get_stock(1).unwrap(), stock_before - 2);
+        assert_eq!(catalog_service.get_order_summary(1).unwrap().total_quantity_ordered, 2);
+    }
+
+    #[actix_web::test]
+    async fn test_export_catalog_items_includes_stock() {
+        // prepare
+        let catalog_service = generate_catalog_service();
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(catalog_service.clone())).service(export_catalog_items),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/catalog/export").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::OK);
+        let items: Vec<serde_json::Value> = test::read_body_json(resp).await;
+        assert_eq!(items.len(), catalog_service.export_snapshot().len());
+        let sold_out = items.iter().find(|item| item["id"] == 10).unwrap();
+        assert_eq!(sold_out["stock"], 0);
+        assert_eq!(sold_out["name"], "Sold Out Item");
+    }
+
+    #[actix_web::test]
+    async fn test_import_catalog_items_reports_skipped_items() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(import_catalog_items),
+        )
+        .await;
+        let existing_item = serde_json::json!({
+            "id": 1,
+            "name": "Existing Item",
+            "description": "An item whose id is already in the catalog",
+            "sizes": ["M"],
+            "price": 10.0,
+            "currency": "GBP",
+            "stock": 5,
+            "images": [],
+            "video": ""
+        });
+
+        // act
+        let req = test::TestRequest::post().uri("/catalog/import").set_json(vec![existing_item]).to_request();
+        let resp = test::call_service(&app, req).await;
+        let empty_req = test::TestRequest::post().uri("/catalog/import").set_json(serde_json::json!([])).to_request();
+        let empty_resp = test::call_service(&app, empty_req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::OK);
+        let summary: ImportSummary = test::read_body_json(resp).await;
+        assert_eq!(
+            summary,
+            ImportSummary {
+                inserted: 0,
+                skipped: 1
+            }
+        );
+        let summary: ImportSummary = test::read_body_json(empty_resp).await;
+        assert_eq!(
+            summary,
+            ImportSummary {
+                inserted: 0,
+                skipped: 0
+            }
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_reserve_then_release_stock() {
+        // prepare
+        let catalog_service = generate_catalog_service();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(catalog_service.clone()))
+                .service(release_reservation)
+                .service(reserve_stock),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::post()
+            .uri("/catalog/1/reservations")
+            .set_json(serde_json::json!({"quantity": 30}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let reservation: Reservation = test::read_body_json(resp).await;
+        assert_eq!(catalog_service.get_stock(1).unwrap(), 70);
+        let req = test::TestRequest::delete().uri(&format!("/catalog/reservations/{}", reservation.id)).to_request();
+        let released = test::call_service(&app, req).await;
+        let req = test::TestRequest::delete().uri(&format!("/catalog/reservations/{}", reservation.id)).to_request();
+        let released_again = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(reservation.item_id, 1);
+        assert_eq!(released.status(), StatusCode::NO_CONTENT);
+        assert_eq!(released_again.status(), StatusCode::NOT_FOUND);
+        assert_eq!(catalog_service.get_stock(1).unwrap(), 100);
+    }
+
+    #[actix_web::test]
+    async fn test_reserve_more_than_available_is_conflict() {
+        // prepare
+        let app =
+            test::init_service(App::new().app_data(web::Data::new(generate_catalog_service())).service(reserve_stock))
+                .await;
+
+        // act
+        let req = test::TestRequest::post()
+            .uri("/catalog/1/reservations")
+            .set_json(serde_json::json!({"quantity": 101}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    async fn test_set_stock_unknown_item_is_not_found() {
+        // prepare
+        let app =
+            test::init_service(App::new().app_data(web::Data::new(generate_catalog_service())).service(set_stock))
+                .await;
+
+        // act
+        let req = test::TestRequest::put()
+            .uri("/catalog/99/stock")
+            .set_json(serde_json::json!({"stock": 7}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_set_stock_negative_stock_is_bad_request() {
+        // prepare
+        let catalog_service = generate_catalog_service();
+        let app =
+            test::init_service(App::new().app_data(web::Data::new(catalog_service.clone())).service(set_stock)).await;
+
+        // act
+        let req = test::TestRequest::put()
+            .uri("/catalog/1/stock")
+            .set_json(serde_json::json!({"stock": -1}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(catalog_service.get_stock(1).unwrap(), 100);
+    }
+
+    #[cfg(feature = "debug-endpoints")]
+    #[actix_web::test]
+    async fn test_inject_order_placed_unknown_item_is_not_found() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(inject_order_placed),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::post()
+            .uri("/debug/order-placed")
+            .set_json(serde_json::json!({"item_id": 99, "quantity": 1}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_get_catalog_matching_if_none_match_is_not_modified() {
+        // prepare
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn(request_id::request_id))
+                .app_data(web::Data::new(generate_catalog_service()))
+                .service(get_catalog),
+        )
+        .await;
+        let first = test::call_service(&app, test::TestRequest::get().uri("/catalog").to_request()).await;
+        let etag = first.headers().get(ETAG).unwrap().clone();
+
+        // act with another request id, which the etag does not depend on
+        let req = test::TestRequest::get()
+            .uri("/catalog")
+            .insert_header((request_id::REQUEST_ID_HEADER, "abc-123"))
+            .insert_header((IF_NONE_MATCH, etag.clone()))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert!(etag.to_str().unwrap().starts_with("W/"));
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[actix_web::test]
+    async fn test_get_catalog_stale_if_none_match_is_ok() {
+        // prepare
+        let app =
+            test::init_service(App::new().app_data(web::Data::new(generate_catalog_service())).service(get_catalog))
+                .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/catalog").insert_header((IF_NONE_MATCH, "W/\"100\"")).to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_get_catalog_item_found() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(get_catalog_item),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/catalog/1").to_request();
+        let item: ClothingItemDTO = test::call_and_read_body_json(&app, req).await;
+
+        // assert
+        assert_eq!(item.id, 1);
+        assert_eq!(item.name, "T-Shirt");
+    }
+
+    #[actix_web::test]
+    async fn test_get_catalog_item_out_of_stock() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(get_catalog_item),
+        )
+        .await;
+
+        // act
+        let hidden = test::call_service(&app, test::TestRequest::get().uri("/catalog/10").to_request()).await;
+        let included = test::call_service(
+            &app,
+            test::TestRequest::get().uri("/catalog/10?include_out_of_stock=true").to_request(),
+        )
+        .await;
+
+        // assert
+        assert_eq!(hidden.status(), StatusCode::NOT_FOUND);
+        assert_eq!(included.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_get_catalog_item_missing() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(get_catalog_item),
+        )
+        .await;
+
+        // act
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/catalog/100").to_request()).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_get_catalog_item_by_sku() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(get_catalog_item),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/catalog/JEANS-001").to_request();
+        let item: ClothingItemDTO = test::call_and_read_body_json(&app, req).await;
+        let missing = test::call_service(&app, test::TestRequest::get().uri("/catalog/UNKNOWN-001").to_request()).await;
+
+        // assert
+        assert_eq!(item.id, 2);
+        assert_eq!(item.sku, "JEANS-001");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_get_stock_by_sku() {
+        // prepare
+        let app =
+            test::init_service(App::new().app_data(web::Data::new(generate_catalog_service())).service(get_stock))
+                .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/catalog/stock/JACKET-001").to_request();
+        let stock: ApiResponse<u32> = test::call_and_read_body_json(&app, req).await;
+
+        // assert
+        assert_eq!(stock.into_result(), Ok(30));
+    }
+
+    #[actix_web::test]
+    async fn test_get_stock_envelope() {
+        // prepare
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn(request_id::request_id))
+                .app_data(web::Data::new(generate_catalog_service()))
+                .service(get_stock),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::get()
+            .uri("/catalog/stock/1")
+            .insert_header((request_id::REQUEST_ID_HEADER, "abc-123"))
+            .to_request();
+        let found = test::call_service(&app, req).await;
+        let found_status = found.status();
+        let found: serde_json::Value = test::read_body_json(found).await;
+        let req = test::TestRequest::get()
+            .uri("/catalog/stock/99")
+            .insert_header((request_id::REQUEST_ID_HEADER, "abc-456"))
+            .to_request();
+        let missing = test::call_service(&app, req).await;
+        let missing_status = missing.status();
+        let missing: serde_json::Value = test::read_body_json(missing).await;
+
+        // assert
+        assert_eq!(found_status, StatusCode::OK);
+        assert_eq!(
+            found,
+            serde_json::json!({"data": 100, "error": null, "request_id": "abc-123"})
+        );
+        assert_eq!(missing_status, StatusCode::NOT_FOUND);
+        assert_eq!(
+            missing,
+            serde_json::json!({
+                "data": null,
+                "error": {"status": 404, "message": "This item does not exist."},
+                "request_id": "abc-456"
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_get_order_summary_increments_as_events_arrive() {
+        // prepare
+        let catalog_service = generate_catalog_service();
+        let app =
+            test::init_service(App::new().app_data(web::Data::new(catalog_service.clone())).service(get_order_summary))
+                .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/catalog/1/orders-summary").to_request();
+        let before: OrderSummary = test::call_and_read_body_json(&app, req).await;
+        for quantity in [2, 3] {
+            catalog_service
+                .apply_order_placed(&Event::new(
+                    "order_placed".to_string(),
+                    OrderPlacedEvent {
+                        item_id: 1,
+                        quantity,
+                        total: None,
+                        reservation_id: None,
+                    },
+                    "test".to_string(),
+                    None,
+                    None,
+                ))
+                .await;
+        }
+        let req = test::TestRequest::get().uri("/catalog/1/orders-summary").to_request();
+        let after: OrderSummary = test::call_and_read_body_json(&app, req).await;
+
+        // assert
+        assert_eq!(before.total_quantity_ordered, 0);
+        assert_eq!(after.total_quantity_ordered, 5);
+    }
+
+    #[actix_web::test]
+    async fn test_get_order_summary_unknown_item() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(get_order_summary),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/catalog/99/orders-summary").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_get_order_metrics() {
+        // prepare
+        let mut catalog_service = CatalogService::new(CatalogDbClient::new(), EventBus::new("localhost:9092"));
+        catalog_service.enable_order_metrics(3);
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(Arc::new(catalog_service))).service(get_order_metrics),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/metrics/orders").to_request();
+        let minutes: Vec<MinuteOrderMetrics> = test::call_and_read_body_json(&app, req).await;
+
+        // assert
+        assert_eq!(minutes.len(), 3);
+        assert!(minutes.windows(2).all(|pair| pair[1].minute_start == pair[0].minute_start + 60));
+    }
+
+    #[actix_web::test]
+    async fn test_get_order_metrics_not_enabled() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(get_order_metrics),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/metrics/orders").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_get_processing_metrics_without_events() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(get_processing_metrics),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/metrics/processing").to_request();
+        let metrics: ProcessingMetricsSnapshot = test::call_and_read_body_json(&app, req).await;
+
+        // assert
+        assert_eq!(metrics.processing.count, 0);
+        assert_eq!(metrics.lock_wait.count, 0);
+        assert_eq!(metrics.processing.buckets.last().unwrap().le_ms, None);
+    }
+
+    #[actix_web::test]
+    async fn test_get_listener_stats_without_listeners() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(get_listener_stats),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/debug/stats").to_request();
+        let stats: ListenerStats = test::call_and_read_body_json(&app, req).await;
+
+        // assert
+        assert_eq!(stats.order_placed_receivers, 0);
+    }
+
+    #[actix_web::test]
+    async fn test_get_health_without_listeners_is_unavailable() {
+        // prepare
+        let app =
+            test::init_service(App::new().app_data(web::Data::new(generate_catalog_service())).service(get_health))
+                .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/health").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let health: CatalogHealth = test::read_body_json(resp).await;
+        assert!(!health.order_placed_consumer_connected);
+        assert!(!health.degraded);
+    }
+
+    #[actix_web::test]
+    async fn test_pause_and_resume_listener_are_reported_by_health() {
+        // prepare
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(generate_catalog_service()))
+                .service(pause_listener)
+                .service(resume_listener)
+                .service(get_health),
+        )
+        .await;
+
+        // act
+        let paused = test::call_service(&app, test::TestRequest::post().uri("/listener/pause").to_request()).await;
+        let paused_health: CatalogHealth =
+            test::call_and_read_body_json(&app, test::TestRequest::get().uri("/health").to_request()).await;
+        let resumed = test::call_service(&app, test::TestRequest::post().uri("/listener/resume").to_request()).await;
+        let resumed_health: CatalogHealth =
+            test::call_and_read_body_json(&app, test::TestRequest::get().uri("/health").to_request()).await;
+
+        // assert
+        assert_eq!(paused.status(), StatusCode::NO_CONTENT);
+        assert!(paused_health.listener_paused);
+        assert_eq!(resumed.status(), StatusCode::NO_CONTENT);
+        assert!(!resumed_health.listener_paused);
+    }
+
+    #[actix_web::test]
+    async fn test_catalog_reads_are_stale_after_listener_loss() {
+        // prepare a catalog whose listener has failed, as when its supervisor cannot reconnect
+        let mut catalog_service = CatalogService::new(CatalogDbClient::new(), EventBus::new("127.0.0.1:1"));
+        catalog_service.start_event_listeners();
+        catalog_service.order_placed_listener().unwrap().mock_fail();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Arc::new(catalog_service)))
+                .service(get_catalog)
+                .service(get_stock_batch)
+                .service(get_stock)
+                .service(get_health),
+        )
+        .await;
+
+        // act
+        let mut reads = vec![];
+        for uri in ["/catalog", "/catalog/stock?ids=1,2", "/catalog/stock/1"] {
+            let req = test::TestRequest::get().uri(uri).to_request();
+            reads.push(test::call_service(&app, req).await);
+        }
+        let req = test::TestRequest::get().uri("/health").to_request();
+        let health_resp = test::call_service(&app, req).await;
+
+        // assert
+        for resp in reads {
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(resp.headers().get(CATALOG_STALE_HEADER).unwrap(), "true");
+        }
+        assert_eq!(health_resp.status(), StatusCode::OK);
+        let health: CatalogHealth = test::read_body_json(health_resp).await;
+        assert!(!health.healthy);
+        assert!(health.degraded);
+    }
+
+    #[actix_web::test]
+    async fn test_save_catalog_item_invalid_currency() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(save_catalog_item),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::post()
+            .uri("/catalog")
+            .set_json(serde_json::json!({
+                "id": 11, "name": "Scarf", "description": "A warm scarf", "sizes": [], "price": 12.5,
+                "currency": "XYZ", "stock": 3, "images": [], "video": ""
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_save_catalog_item_oversized_body_is_payload_too_large() {
+        // prepare
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(generate_catalog_service()))
+                .app_data(server_config::json_config(1024))
+                .service(save_catalog_item),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::post()
+            .uri("/catalog")
+            .set_json(serde_json::json!({
+                "id": 11, "name": "Scarf", "description": "A".repeat(2048), "sizes": [], "price": 12.5,
+                "currency": "GBP", "stock": 3, "images": [], "video": ""
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[actix_web::test]
+    async fn test_remove_catalog_item_unknown_item() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(remove_catalog_item),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::delete().uri("/catalog/99").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_get_stock_batch() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(get_stock_batch),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/catalog/stock?ids=1,10,99").to_request();
+        let stock: std::collections::HashMap<u32, u32> = test::call_and_read_body_json(&app, req).await;
+
+        // assert
+        assert_eq!(stock.len(), 2);
+        assert_eq!(stock[&1], 100);
+        assert_eq!(stock[&10], 0);
+    }
+
+    #[actix_web::test]
+    async fn test_get_stock_batch_invalid_ids_is_bad_request() {
+        // prepare
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(generate_catalog_service())).service(get_stock_batch),
+        )
+        .await;
+
+        // act
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get().uri("/catalog/stock?ids=1,a").to_request(),
+        )
+        .await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
-    stock_amount_result.unwrap().to_string()
 }
@@ -1,5 +1,11 @@
+use common::types::item_ref::ItemRef;
+use common::types::money;
 use log::info;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
 
 /// `CatalogDbClient` is a mock database structure used for simulating
 /// a catalog database in a testing or development environment.
@@ -9,8 +15,10 @@ use std::collections::HashMap;
 ///
 /// Fields:
 /// - `items`: A hashmap of `ClothingItem` objects representing the items in the catalog.
+/// - `max_items`: The largest number of items the catalog holds, or `None` for no limit.
 pub struct CatalogDbClient {
     items: HashMap<u32, ClothingItem>,
+    max_items: Option<usize>,
 }
 
 // cannot mock trait automatically due to explicit lifetimes use manual mocking in tests
@@ -43,13 +51,37 @@ pub trait CatalogDb<'a> {
     /// - `Option<&'a ClothingItem>`: An immutable reference to the clothing item if found, or `None` if not.
     fn get_item(&'a self, id: u32) -> Option<&'a ClothingItem>;
 
+    /// Retrieves an immutable reference to a `ClothingItem` by its SKU.
+    ///
+    /// Arguments:
+    /// - `sku`: The SKU of the clothing item.
+    ///
+    /// Returns:
+    /// - `Option<&'a ClothingItem>`: An immutable reference to the clothing item if found, or `None` if
+    ///   not. Items without a SKU are never found.
+    fn get_item_by_sku(&'a self, sku: &str) -> Option<&'a ClothingItem>;
+
     /// Adds a new `ClothingItem` to the catalog.
     ///
-    /// This method is used for inserting a new item into the catalog database.
+    /// This method is used for inserting a new item into the catalog database. The item's
+    /// currency must be a known ISO 4217 currency code, and its SKU must not belong to another item.
+    /// An item replacing an existing item with the same ID takes the next version of that item.
     ///
     /// Arguments:
     /// - `item`: The `ClothingItem` to be added to the catalog.
-    fn add_item(&mut self, item: ClothingItem);
+    ///
+    /// Returns:
+    /// - `Result<(), AddItemError>`: `Ok(())` if the item was added, or an `AddItemError` if it was rejected.
+    fn add_item(&mut self, item: ClothingItem) -> Result<(), AddItemError>;
+
+    /// Removes a `ClothingItem` from the catalog by its ID.
+    ///
+    /// Arguments:
+    /// - `id`: The unique identifier of the clothing item.
+    ///
+    /// Returns:
+    /// - `Option<ClothingItem>`: The removed item if found, or `None` if not.
+    fn remove_item(&mut self, id: u32) -> Option<ClothingItem>;
 
     /// Retrieves the entire catalog as a vector of immutable references to `ClothingItem` objects.
     ///
@@ -58,79 +90,170 @@ pub trait CatalogDb<'a> {
     /// Returns:
     /// - `Vec<&'a ClothingItem>`: A vector containing immutable references to all the items in the catalog.
     fn get_catalog(&'a self) -> Vec<&'a ClothingItem>;
+
+    /// Decrements the stock of a `ClothingItem`, provided the item has not changed since it was read.
+    ///
+    /// This is a compare-and-set: the stock is only decremented if the item's `version` still equals
+    /// `expected_version`, in which case the version is incremented. Callers should re-read the item
+    /// and retry when a `StockUpdateError::VersionConflict` is returned.
+    ///
+    /// Only the available stock can be decremented, the stock reserved for other orders is left
+    /// untouched. An order placed against a reservation has the reservation released first, see
+    /// `CatalogDb::release`.
+    ///
+    /// Arguments:
+    /// - `id`: The unique identifier of the clothing item.
+    /// - `by`: The quantity to remove from the stock.
+    /// - `expected_version`: The version of the item when its stock was read.
+    ///
+    /// Returns:
+    /// - `Result<u32, StockUpdateError>`: The new stock level, or why the stock could not be decremented.
+    ///   `StockUpdateError::InsufficientStock` carries the available stock.
+    fn decrement_stock(&mut self, id: u32, by: u32, expected_version: u64) -> Result<u32, StockUpdateError>;
+
+    /// Records an order placed on backorder for a `ClothingItem`.
+    ///
+    /// As much of the quantity as possible is taken from the available stock, and the remainder is
+    /// added to the item's `backordered` counter. The item's version is incremented.
+    ///
+    /// Arguments:
+    /// - `id`: The unique identifier of the clothing item.
+    /// - `quantity`: The ordered quantity.
+    ///
+    /// Returns:
+    /// - `Result<(u32, u32), StockUpdateError>`: The new stock level and backordered quantity, or
+    ///   `StockUpdateError::NotFound` if the item does not exist.
+    fn record_backorder(&mut self, id: u32, quantity: u32) -> Result<(u32, u32), StockUpdateError>;
+
+    /// Reserves a quantity of the stock of a `ClothingItem`, so it is no longer available to other
+    /// orders until it is released or the order is placed. The item's version is incremented.
+    ///
+    /// Arguments:
+    /// - `id`: The unique identifier of the clothing item.
+    /// - `quantity`: The quantity to reserve.
+    ///
+    /// Returns:
+    /// - `Result<u32, StockUpdateError>`: The new available stock, or why the stock could not be
+    ///   reserved. `StockUpdateError::InsufficientStock` carries the available stock.
+    fn reserve(&mut self, id: u32, quantity: u32) -> Result<u32, StockUpdateError>;
+
+    /// Releases a quantity of the reserved stock of a `ClothingItem`, e.g. when an order is abandoned.
+    /// Releasing more than is reserved releases the whole reservation. The item's version is incremented.
+    ///
+    /// Arguments:
+    /// - `id`: The unique identifier of the clothing item.
+    /// - `quantity`: The quantity to release.
+    ///
+    /// Returns:
+    /// - `Result<u32, StockUpdateError>`: The new available stock, or `StockUpdateError::NotFound` if
+    ///   the item does not exist.
+    fn release(&mut self, id: u32, quantity: u32) -> Result<u32, StockUpdateError>;
 }
 
 impl<'a> CatalogDb<'a> for CatalogDbClient {
     fn new() -> CatalogDbClient {
-        let mut mock_db = CatalogDbClient { items: HashMap::new() };
+        let mut mock_db = CatalogDbClient {
+            items: HashMap::new(),
+            max_items: None,
+        };
         // as this is a mock db encapsulate all initialization within new
         let t_shirt = ClothingItem {
             id: 1,
+            sku: "TSHIRT-001".to_string(),
             name: "T-Shirt".to_string(),
             description: "Comfortable cotton t-shirt, perfect for everyday wear.".to_string(),
             sizes: vec!["S".to_string(), "M".to_string(), "L".to_string(), "XL".to_string()],
             price: 20.00,
+            currency: "GBP".to_string(),
             stock: 100,
+            version: 0,
+            allow_backorder: false,
+            backordered: 0,
+            reserved: 0,
             images: vec![
                 "https://example.com/t-shirt-front.jpg".to_string(),
                 "https://example.com/t-shirt-back.jpg".to_string(),
             ],
             video: "https://example.com/t-shirt-video.mp4".to_string(),
         };
-        mock_db.add_item(t_shirt);
+        mock_db.add_item(t_shirt).expect("Seeded item should be valid");
 
         let jeans = ClothingItem {
             id: 2,
+            sku: "JEANS-001".to_string(),
             name: "Jeans".to_string(),
             description: "Classic blue denim jeans, versatile and durable.".to_string(),
             sizes: vec!["30".to_string(), "32".to_string(), "34".to_string()],
             price: 40.00,
+            currency: "GBP".to_string(),
             stock: 50,
+            version: 0,
+            allow_backorder: false,
+            backordered: 0,
+            reserved: 0,
             images: vec![
                 "https://example.com/jeans-front.jpg".to_string(),
                 "https://example.com/jeans-back.jpg".to_string(),
             ],
             video: "https://example.com/jeans-video.mp4".to_string(),
         };
-        mock_db.add_item(jeans);
+        mock_db.add_item(jeans).expect("Seeded item should be valid");
 
         let jacket = ClothingItem {
             id: 3,
+            sku: "JACKET-001".to_string(),
             name: "Jacket".to_string(),
             description: "Stylish and warm jacket, suitable for cold weather.".to_string(),
             sizes: vec!["M".to_string(), "L".to_string(), "XL".to_string()],
             price: 60.00,
+            currency: "GBP".to_string(),
             stock: 30,
+            version: 0,
+            allow_backorder: false,
+            backordered: 0,
+            reserved: 0,
             images: vec![
                 "https://example.com/jacket-front.jpg".to_string(),
                 "https://example.com/jacket-back.jpg".to_string(),
             ],
             video: "https://example.com/jacket-video.mp4".to_string(),
         };
-        mock_db.add_item(jacket);
+        mock_db.add_item(jacket).expect("Seeded item should be valid");
 
         let sneakers = ClothingItem {
             id: 4,
+            sku: "SNEAKERS-001".to_string(),
             name: "Sneakers".to_string(),
             description: "Trendy and comfortable sneakers for casual outings.".to_string(),
             sizes: vec!["8".to_string(), "9".to_string(), "10".to_string(), "11".to_string()],
             price: 50.00,
+            currency: "GBP".to_string(),
             stock: 75,
+            version: 0,
+            allow_backorder: false,
+            backordered: 0,
+            reserved: 0,
             images: vec![
                 "https://example.com/sneakers-front.jpg".to_string(),
                 "https://example.com/sneakers-side.jpg".to_string(),
             ],
             video: "https://example.com/sneakers-video.mp4".to_string(),
         };
-        mock_db.add_item(sneakers);
+        mock_db.add_item(sneakers).expect("Seeded item should be valid");
 
         let cap = ClothingItem {
             id: 5,
+            sku: "CAP-001".to_string(),
             name: "Cap".to_string(),
             description: "Cool and stylish baseball cap, great for sunny days.".to_string(),
             sizes: vec!["One Size".to_string()],
             price: 15.00,
+            currency: "GBP".to_string(),
             stock: 1,
+            version: 0,
+            allow_backorder: false,
+            backordered: 0,
+            reserved: 0,
             images: vec![
                 "https://example.com/cap-front.jpg".to_string(),
                 "https://example.com/cap-back.jpg".to_string(),
@@ -138,7 +261,7 @@ impl<'a> CatalogDb<'a> for CatalogDbClient {
             video: "https://example.com/cap-video.mp4".to_string(),
         };
 
-        mock_db.add_item(cap);
+        mock_db.add_item(cap).expect("Seeded item should be valid");
         info!("Mock database has been initialized");
         mock_db
     }
@@ -151,42 +274,276 @@ impl<'a> CatalogDb<'a> for CatalogDbClient {
         self.items.get(&id)
     }
 
-    fn add_item(&mut self, item: ClothingItem) {
+    fn get_item_by_sku(&'a self, sku: &str) -> Option<&'a ClothingItem> {
+        find_by_sku(self.items.values(), sku)
+    }
+
+    fn add_item(&mut self, mut item: ClothingItem) -> Result<(), AddItemError> {
+        if !money::is_known_currency(&item.currency) {
+            return Err(AddItemError::InvalidCurrency);
+        }
+        // a numeric path segment refers to an item by its id, so such a sku could never be looked up
+        if matches!(ItemRef::from(item.sku.as_str()), ItemRef::Id(_)) {
+            return Err(AddItemError::NumericSku);
+        }
+        if find_by_sku(self.items.values(), &item.sku).is_some_and(|existing| existing.id != item.id) {
+            return Err(AddItemError::DuplicateSku);
+        }
+        // replacing an existing item never grows the catalog, so only new items are limited
+        if let Some(max_items) = self.max_items {
+            if self.items.len() >= max_items && !self.items.contains_key(&item.id) {
+                return Err(AddItemError::CatalogFull { max_items });
+            }
+        }
+
+        // a replaced item has changed, so a stock update that read the old item must not apply to it
+        if let Some(existing) = self.items.get(&item.id) {
+            item.version = existing.version + 1;
+        }
         self.items.insert(item.id, item);
+        Ok(())
+    }
+
+    fn remove_item(&mut self, id: u32) -> Option<ClothingItem> {
+        self.items.remove(&id)
     }
 
     fn get_catalog(&'a self) -> Vec<&'a ClothingItem> {
         self.items.values().collect()
     }
+
+    fn decrement_stock(&mut self, id: u32, by: u32, expected_version: u64) -> Result<u32, StockUpdateError> {
+        compare_and_decrement(self.items.get_mut(&id), by, expected_version)
+    }
+
+    fn record_backorder(&mut self, id: u32, quantity: u32) -> Result<(u32, u32), StockUpdateError> {
+        apply_backorder(self.items.get_mut(&id), quantity)
+    }
+
+    fn reserve(&mut self, id: u32, quantity: u32) -> Result<u32, StockUpdateError> {
+        apply_reserve(self.items.get_mut(&id), quantity)
+    }
+
+    fn release(&mut self, id: u32, quantity: u32) -> Result<u32, StockUpdateError> {
+        apply_release(self.items.get_mut(&id), quantity)
+    }
+}
+
+impl CatalogDbClient {
+    /// Creates a new `CatalogDbClient` seeded with the items in a JSON file, rather than the
+    /// hardcoded items used by `new`.
+    ///
+    /// Arguments:
+    /// - `path`: The path of a file containing a JSON array of `ClothingItem` objects.
+    ///
+    /// Returns:
+    /// - `io::Result<CatalogDbClient>`: The seeded database, or an error if the file cannot be read,
+    ///   is not a valid JSON array of items, or contains an item with an unknown currency.
+    pub fn from_file(path: &Path) -> io::Result<CatalogDbClient> {
+        let contents = fs::read_to_string(path)?;
+        let items: Vec<ClothingItem> = serde_json::from_str(&contents)?;
+
+        let mut db = CatalogDbClient {
+            items: HashMap::new(),
+            max_items: None,
+        };
+        for item in items {
+            let item_id = item.id;
+            db.add_item(item).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Item {} could not be seeded: {:?}", item_id, e),
+                )
+            })?;
+        }
+        info!("Database has been seeded from: {}", path.display());
+        Ok(db)
+    }
+
+    /// Limits the number of items the catalog holds, so that adding a new item to a full catalog
+    /// fails with `AddItemError::CatalogFull`. Items already held beyond the limit are kept.
+    ///
+    /// Arguments:
+    /// - `max_items`: The largest number of items, or `None` for no limit, which is the default.
+    pub fn set_max_items(&mut self, max_items: Option<usize>) {
+        self.max_items = max_items;
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ClothingItem {
     pub id: u32,
+    /// The identifier of the item given to clients, which should be unique and never purely numeric,
+    /// see `ItemRef`. Items without a SKU can only be referenced by their ID.
+    #[serde(default)]
+    pub sku: String,
     pub name: String,
     pub description: String,
     pub sizes: Vec<String>,
     pub price: f32,
+    pub currency: String,
     pub stock: u32,
+    /// Incremented whenever the stock changes, see `CatalogDb::decrement_stock`.
+    #[serde(default)]
+    pub version: u64,
+    /// Whether the item can be ordered when there is not enough stock, see `CatalogDb::record_backorder`.
+    #[serde(default)]
+    pub allow_backorder: bool,
+    /// The quantity ordered on backorder that is yet to be restocked, see `ClothingItem::fill_backorders`.
+    #[serde(default)]
+    pub backordered: u32,
+    /// The quantity of the on-hand `stock` held for orders that are yet to be placed, see `CatalogDb::reserve`.
+    #[serde(default)]
+    pub reserved: u32,
     pub images: Vec<String>,
     pub video: String,
 }
 
+impl ClothingItem {
+    /// The quantity of the item that can still be ordered, i.e. the on-hand `stock` less the
+    /// `reserved` quantity, which is never negative.
+    pub fn available(&self) -> u32 {
+        self.stock.saturating_sub(self.reserved)
+    }
+
+    /// Takes the `backordered` quantity from the available stock, as far as it goes, as restocked
+    /// stock goes to the orders waiting for it first. Called whenever the stock of the item is raised.
+    pub fn fill_backorders(&mut self) {
+        let filled = self.backordered.min(self.available());
+        self.stock -= filled;
+        self.backordered -= filled;
+    }
+}
+
+/// An error describing why an item could not be added to the catalog.
+///
+/// Variants:
+/// - `InvalidCurrency`: The currency of the item is not known.
+/// - `DuplicateSku`: The SKU of the item belongs to another item.
+/// - `NumericSku`: The SKU of the item is a number, which would be taken for an item id.
+/// - `CatalogFull`: The item is new, but the catalog already holds `max_items` items.
+#[derive(Debug, PartialEq)]
+pub enum AddItemError {
+    InvalidCurrency,
+    DuplicateSku,
+    NumericSku,
+    CatalogFull { max_items: usize },
+}
+
+/// An error describing why the stock of an item could not be decremented.
+///
+/// Variants:
+/// - `NotFound`: No item exists with the given ID.
+/// - `VersionConflict`: The item has been changed since it was read, and is now at `current_version`.
+/// - `InsufficientStock`: The item has less stock than the quantity to remove.
+#[derive(Debug, PartialEq)]
+pub enum StockUpdateError {
+    NotFound,
+    VersionConflict { current_version: u64 },
+    InsufficientStock { stock: u32 },
+}
+
+// finds the item with the given sku, ignoring items without one
+fn find_by_sku<'a>(mut items: impl Iterator<Item = &'a ClothingItem>, sku: &str) -> Option<&'a ClothingItem> {
+    if sku.is_empty() {
+        return None;
+    }
+    items.find(|item| item.sku == sku)
+}
+
+// decrements the available stock of the item if it is still at the expected version, bumping its version
+fn compare_and_decrement(
+    item: Option<&mut ClothingItem>,
+    by: u32,
+    expected_version: u64,
+) -> Result<u32, StockUpdateError> {
+    let item = item.ok_or(StockUpdateError::NotFound)?;
+    if item.version != expected_version {
+        return Err(StockUpdateError::VersionConflict {
+            current_version: item.version,
+        });
+    }
+    if by > item.available() {
+        return Err(StockUpdateError::InsufficientStock {
+            stock: item.available(),
+        });
+    }
+
+    item.stock -= by;
+    item.version += 1;
+    Ok(item.stock)
+}
+
+// takes the ordered quantity from the stock of the item, backordering whatever is not in stock
+fn apply_backorder(item: Option<&mut ClothingItem>, quantity: u32) -> Result<(u32, u32), StockUpdateError> {
+    let item = item.ok_or(StockUpdateError::NotFound)?;
+    let from_stock = quantity.min(item.available());
+    item.stock -= from_stock;
+    item.backordered = item.backordered.saturating_add(quantity - from_stock);
+    item.version += 1;
+    Ok((item.stock, item.backordered))
+}
+
+// reserves the quantity of the item if that much of its stock is available, bumping its version
+fn apply_reserve(item: Option<&mut ClothingItem>, quantity: u32) -> Result<u32, StockUpdateError> {
+    let item = item.ok_or(StockUpdateError::NotFound)?;
+    if quantity > item.available() {
+        return Err(StockUpdateError::InsufficientStock {
+            stock: item.available(),
+        });
+    }
+
+    item.reserved += quantity;
+    item.version += 1;
+    Ok(item.available())
+}
+
+// releases up to the quantity from the reservations of the item, bumping its version
+fn apply_release(item: Option<&mut ClothingItem>, quantity: u32) -> Result<u32, StockUpdateError> {
+    let item = item.ok_or(StockUpdateError::NotFound)?;
+    item.reserved = item.reserved.saturating_sub(quantity);
+    item.version += 1;
+    Ok(item.available())
+}
+
 // mock db for testing
+#[cfg(test)]
 pub struct MockCatalogDb {
     expected_get_item: Option<ClothingItem>,
     expected_vec: Vec<ClothingItem>,
+    concurrent_version_bumps: u32,
+    decrement_stock_calls: u32,
+}
+
+#[cfg(test)]
+impl Default for MockCatalogDb {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 // mocks
+#[cfg(test)]
 impl MockCatalogDb {
     pub fn new() -> Self {
         MockCatalogDb {
             expected_get_item: None,
             expected_vec: vec![],
+            concurrent_version_bumps: 0,
+            decrement_stock_calls: 0,
         }
     }
 
+    /// Simulates another consumer changing the expected item before each of the next `bumps` calls
+    /// to `decrement_stock`, so that they fail with a version conflict.
+    pub fn set_concurrent_version_bumps(&mut self, bumps: u32) {
+        self.concurrent_version_bumps = bumps;
+    }
+
+    pub fn decrement_stock_calls(&self) -> u32 {
+        self.decrement_stock_calls
+    }
+
     pub fn set_expected_get_item(&mut self, item: Option<ClothingItem>) {
         self.expected_get_item = item;
     }
@@ -196,24 +553,66 @@ impl MockCatalogDb {
     }
 }
 
+#[cfg(test)]
 impl<'a> CatalogDb<'a> for MockCatalogDb {
     fn new() -> Self {
         MockCatalogDb::new()
     }
 
+    #[allow(unused_variables)]
     fn get_mut_item(&mut self, id: u32) -> Option<&mut ClothingItem> {
         self.expected_get_item.as_mut()
     }
 
+    #[allow(unused_variables)]
     fn get_item(&self, id: u32) -> Option<&ClothingItem> {
         self.expected_get_item.as_ref()
     }
 
-    fn add_item(&mut self, item: ClothingItem) {}
+    fn get_item_by_sku(&self, sku: &str) -> Option<&ClothingItem> {
+        find_by_sku(self.expected_get_item.iter(), sku)
+    }
+
+    #[allow(unused_variables)]
+    fn add_item(&mut self, item: ClothingItem) -> Result<(), AddItemError> {
+        Ok(())
+    }
+
+    #[allow(unused_variables)]
+    fn remove_item(&mut self, id: u32) -> Option<ClothingItem> {
+        self.expected_get_item.take()
+    }
 
     fn get_catalog(&self) -> Vec<&ClothingItem> {
         self.expected_vec.iter().collect()
     }
+
+    #[allow(unused_variables)]
+    fn decrement_stock(&mut self, id: u32, by: u32, expected_version: u64) -> Result<u32, StockUpdateError> {
+        self.decrement_stock_calls += 1;
+        if self.concurrent_version_bumps > 0 {
+            self.concurrent_version_bumps -= 1;
+            if let Some(item) = self.expected_get_item.as_mut() {
+                item.version += 1;
+            }
+        }
+        compare_and_decrement(self.expected_get_item.as_mut(), by, expected_version)
+    }
+
+    #[allow(unused_variables)]
+    fn record_backorder(&mut self, id: u32, quantity: u32) -> Result<(u32, u32), StockUpdateError> {
+        apply_backorder(self.expected_get_item.as_mut(), quantity)
+    }
+
+    #[allow(unused_variables)]
+    fn reserve(&mut self, id: u32, quantity: u32) -> Result<u32, StockUpdateError> {
+        apply_reserve(self.expected_get_item.as_mut(), quantity)
+    }
+
+    #[allow(unused_variables)]
+    fn release(&mut self, id: u32, quantity: u32) -> Result<u32, StockUpdateError> {
+        apply_release(self.expected_get_item.as_mut(), quantity)
+    }
 }
 
 #[cfg(test)]
@@ -225,22 +624,141 @@ mod tests {
         let mut db = CatalogDbClient::new();
         let test_item = ClothingItem {
             id: 10,
+            sku: String::new(),
             name: "Test Item".to_string(),
             description: "A test item".to_string(),
             sizes: vec!["M".to_string()],
             price: 10.00,
+            currency: "GBP".to_string(),
             stock: 20,
+            version: 0,
+            allow_backorder: false,
+            backordered: 0,
+            reserved: 0,
             images: vec!["https://example.com/test-item.jpg".to_string()],
             video: "https://example.com/test-item-video.mp4".to_string(),
         };
 
-        db.add_item(test_item);
+        db.add_item(test_item).unwrap();
 
         let retrieved_item = db.get_item(10).unwrap();
         assert_eq!(retrieved_item.name, "Test Item");
         assert_eq!(retrieved_item.stock, 20);
     }
 
+    #[test]
+    fn test_remove_item() {
+        let mut db = CatalogDbClient::new();
+
+        let removed = db.remove_item(1);
+
+        assert_eq!(removed.unwrap().name, "T-Shirt");
+        assert!(db.get_item(1).is_none());
+        assert!(db.remove_item(1).is_none());
+    }
+
+    #[test]
+    fn test_add_item_invalid_currency() {
+        let mut db = CatalogDbClient::new();
+        let test_item = ClothingItem {
+            id: 10,
+            sku: String::new(),
+            name: "Test Item".to_string(),
+            description: "A test item".to_string(),
+            sizes: vec!["M".to_string()],
+            price: 10.00,
+            currency: "XYZ".to_string(),
+            stock: 20,
+            version: 0,
+            allow_backorder: false,
+            backordered: 0,
+            reserved: 0,
+            images: vec![],
+            video: "https://example.com/test-item-video.mp4".to_string(),
+        };
+
+        assert_eq!(db.add_item(test_item), Err(AddItemError::InvalidCurrency));
+        assert!(db.get_item(10).is_none());
+    }
+
+    #[test]
+    fn test_get_item_by_sku() {
+        let db = CatalogDbClient::new();
+
+        assert_eq!(db.get_item_by_sku("JEANS-001").unwrap().id, 2);
+        assert!(db.get_item_by_sku("UNKNOWN-001").is_none());
+        assert!(db.get_item_by_sku("").is_none());
+    }
+
+    #[test]
+    fn test_add_item_duplicate_sku() {
+        let mut db = CatalogDbClient::new();
+        let mut test_item = db.get_item(1).unwrap().clone();
+        test_item.id = 10;
+
+        assert_eq!(db.add_item(test_item), Err(AddItemError::DuplicateSku));
+        assert!(db.get_item(10).is_none());
+
+        // replacing an item keeps its own sku
+        let t_shirt = db.get_item(1).unwrap().clone();
+        assert_eq!(db.add_item(t_shirt), Ok(()));
+    }
+
+    #[test]
+    fn test_replacing_an_item_bumps_its_version() {
+        // prepare
+        let mut db = CatalogDbClient::new();
+        db.decrement_stock(1, 1, 0).unwrap();
+        let mut t_shirt = db.get_item(1).unwrap().clone();
+        t_shirt.version = 0;
+
+        // act
+        db.add_item(t_shirt).unwrap();
+
+        // assert that a decrement based on the replaced item conflicts
+        assert_eq!(db.get_item(1).unwrap().version, 2);
+        assert_eq!(
+            db.decrement_stock(1, 1, 1),
+            Err(StockUpdateError::VersionConflict { current_version: 2 })
+        );
+    }
+
+    #[test]
+    fn test_add_item_numeric_sku() {
+        let mut db = CatalogDbClient::new();
+        let mut test_item = db.get_item(1).unwrap().clone();
+        test_item.id = 10;
+        test_item.sku = "12345".to_string();
+
+        assert_eq!(db.add_item(test_item), Err(AddItemError::NumericSku));
+        assert!(db.get_item(10).is_none());
+    }
+
+    #[test]
+    fn test_add_item_beyond_max_items() {
+        // prepare
+        let mut db = CatalogDbClient::new();
+        db.set_max_items(Some(7));
+        let template = db.get_item(1).unwrap().clone();
+        let item = |id: u32| ClothingItem {
+            id,
+            sku: format!("ITEM-{}", id),
+            ..template.clone()
+        };
+
+        // act
+        let filled = (10..12).map(|id| db.add_item(item(id))).collect::<Vec<_>>();
+        let overflow = db.add_item(item(12));
+
+        // assert
+        assert_eq!(filled, vec![Ok(()), Ok(())]);
+        assert_eq!(overflow, Err(AddItemError::CatalogFull { max_items: 7 }));
+        assert!(db.get_item(12).is_none());
+        assert_eq!(db.get_catalog().len(), 7);
+        // replacing an item of a full catalog is still allowed
+        assert_eq!(db.add_item(item(10)), Ok(()));
+    }
+
     #[test]
     fn test_get_non_existent_item() {
         let db = CatalogDbClient::new();
@@ -256,6 +774,145 @@ mod tests {
         assert_eq!(db.get_item(1).unwrap().stock, 101);
     }
 
+    #[test]
+    fn test_decrement_stock() {
+        let mut db = CatalogDbClient::new();
+
+        let result = db.decrement_stock(1, 10, 0);
+
+        assert_eq!(result, Ok(90));
+        let item = db.get_item(1).unwrap();
+        assert_eq!(item.stock, 90);
+        assert_eq!(item.version, 1);
+    }
+
+    #[test]
+    fn test_decrement_stock_version_conflict() {
+        let mut db = CatalogDbClient::new();
+        db.decrement_stock(1, 10, 0).unwrap();
+
+        let result = db.decrement_stock(1, 10, 0);
+
+        assert_eq!(result, Err(StockUpdateError::VersionConflict { current_version: 1 }));
+        assert_eq!(db.get_item(1).unwrap().stock, 90);
+    }
+
+    #[test]
+    fn test_decrement_stock_insufficient_stock() {
+        let mut db = CatalogDbClient::new();
+
+        let result = db.decrement_stock(5, 2, 0);
+
+        assert_eq!(result, Err(StockUpdateError::InsufficientStock { stock: 1 }));
+        assert_eq!(db.get_item(5).unwrap().version, 0);
+    }
+
+    #[test]
+    fn test_decrement_stock_not_found() {
+        let mut db = CatalogDbClient::new();
+        assert_eq!(db.decrement_stock(100, 1, 0), Err(StockUpdateError::NotFound));
+    }
+
+    #[test]
+    fn test_record_backorder() {
+        let mut db = CatalogDbClient::new();
+
+        let result = db.record_backorder(5, 3);
+
+        assert_eq!(result, Ok((0, 2)));
+        let item = db.get_item(5).unwrap();
+        assert_eq!(item.backordered, 2);
+        assert_eq!(item.version, 1);
+    }
+
+    #[test]
+    fn test_record_backorder_not_found() {
+        let mut db = CatalogDbClient::new();
+        assert_eq!(db.record_backorder(100, 1), Err(StockUpdateError::NotFound));
+    }
+
+    #[test]
+    fn test_reserve() {
+        let mut db = CatalogDbClient::new();
+
+        let result = db.reserve(1, 30);
+
+        assert_eq!(result, Ok(70));
+        let item = db.get_item(1).unwrap();
+        assert_eq!(item.stock, 100);
+        assert_eq!(item.reserved, 30);
+        assert_eq!(item.version, 1);
+    }
+
+    #[test]
+    fn test_reserve_insufficient_available_stock() {
+        let mut db = CatalogDbClient::new();
+        db.reserve(1, 80).unwrap();
+
+        let result = db.reserve(1, 30);
+
+        assert_eq!(result, Err(StockUpdateError::InsufficientStock { stock: 20 }));
+        assert_eq!(db.get_item(1).unwrap().reserved, 80);
+    }
+
+    #[test]
+    fn test_release() {
+        let mut db = CatalogDbClient::new();
+        db.reserve(1, 30).unwrap();
+
+        assert_eq!(db.release(1, 10), Ok(80));
+        // releasing more than is reserved releases the whole reservation
+        assert_eq!(db.release(1, 50), Ok(100));
+        assert_eq!(db.get_item(1).unwrap().reserved, 0);
+        assert_eq!(db.release(100, 1), Err(StockUpdateError::NotFound));
+    }
+
+    #[test]
+    fn test_decrement_stock_leaves_reservations() {
+        let mut db = CatalogDbClient::new();
+        db.reserve(1, 30).unwrap();
+
+        let result = db.decrement_stock(1, 10, 1);
+
+        assert_eq!(result, Ok(90));
+        let item = db.get_item(1).unwrap();
+        assert_eq!(item.reserved, 30);
+        assert_eq!(item.available(), 60);
+    }
+
+    #[test]
+    fn test_decrement_stock_beyond_available_stock() {
+        let mut db = CatalogDbClient::new();
+        db.reserve(1, 80).unwrap();
+
+        let result = db.decrement_stock(1, 30, 1);
+
+        assert_eq!(result, Err(StockUpdateError::InsufficientStock { stock: 20 }));
+        assert_eq!(db.get_item(1).unwrap().stock, 100);
+    }
+
+    #[test]
+    fn test_fill_backorders_takes_the_available_stock() {
+        let mut item = CatalogDbClient::new().get_item(1).unwrap().clone();
+        item.stock = 10;
+        item.reserved = 4;
+        item.backordered = 8;
+
+        item.fill_backorders();
+
+        assert_eq!(item.stock, 4);
+        assert_eq!(item.backordered, 2);
+        assert_eq!(item.available(), 0);
+    }
+
+    #[test]
+    fn test_available_is_never_negative() {
+        let mut item = CatalogDbClient::new().get_item(5).unwrap().clone();
+        item.reserved = item.stock + 5;
+
+        assert_eq!(item.available(), 0);
+    }
+
     #[test]
     fn test_get_catalog() {
         let db = CatalogDbClient::new();
@@ -263,4 +920,57 @@ mod tests {
         assert!(!catalog.is_empty());
         assert_eq!(catalog.len(), 5);
     }
+
+    // writes the contents to a uniquely named file in the temp directory and returns its path
+    fn write_temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}_{}.json", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_from_file() {
+        // prepare
+        let path = write_temp_file(
+            "catalog_seed",
+            r#"[{"id":7,"name":"Scarf","description":"A warm scarf","sizes":["One Size"],"price":12.5,
+            "currency":"EUR","stock":3,"images":[],"video":"https://example.com/scarf-video.mp4"}]"#,
+        );
+
+        // act
+        let db = CatalogDbClient::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // assert
+        assert_eq!(db.get_catalog().len(), 1);
+        let item = db.get_item(7).unwrap();
+        assert_eq!(item.name, "Scarf");
+        assert_eq!(item.price, 12.5);
+        assert_eq!(item.currency, "EUR");
+        assert_eq!(item.stock, 3);
+    }
+
+    #[test]
+    fn test_from_file_invalid_currency() {
+        // prepare
+        let path = write_temp_file(
+            "catalog_seed_invalid_currency",
+            r#"[{"id":7,"name":"Scarf","description":"A warm scarf","sizes":[],"price":12.5,
+            "currency":"ABC","stock":3,"images":[],"video":""}]"#,
+        );
+
+        // act
+        let result = CatalogDbClient::from_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        // assert
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_from_file_missing_file() {
+        let result = CatalogDbClient::from_file(Path::new("/does/not/exist.json"));
+
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::NotFound);
+    }
 }
@@ -0,0 +1,50 @@
+use log::warn;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Acquires a read guard of a lock, recovering the guard if the lock is poisoned.
+///
+/// A lock is poisoned when a thread panics whilst holding its write guard. Some changes made under
+/// the catalog's locks update several fields, e.g. adding an item or reserving and decrementing its
+/// stock, so a panic midway may leave the data half updated. The guard is recovered regardless, as
+/// serving possibly inconsistent stock is preferred to panicking on every later request, and the
+/// recovery is logged as a warning so that the data can be checked.
+pub fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|e| {
+        warn!("Recovering a lock poisoned by a panic");
+        e.into_inner()
+    })
+}
+
+/// Acquires a write guard of a lock, recovering the guard if the lock is poisoned, see `read`.
+pub fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|e| {
+        warn!("Recovering a lock poisoned by a panic");
+        e.into_inner()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_recovers_poisoned_lock() {
+        // prepare
+        let lock = Arc::new(RwLock::new(1));
+        let poisoner = lock.clone();
+        let _ = thread::spawn(move || {
+            let _guard = poisoner.write().unwrap();
+            panic!("poisoning the lock");
+        })
+        .join();
+        assert!(lock.is_poisoned());
+
+        // act
+        *write(&lock) += 1;
+
+        // assert
+        assert_eq!(*read(&lock), 2);
+    }
+}
@@ -1 +1,3 @@
 pub mod catalog_db;
+pub mod locks;
+pub mod stock_store;
@@ -0,0 +1,388 @@
+use crate::db::catalog_db::{CatalogDb, StockUpdateError};
+use crate::db::locks;
+use crate::services::processing_metrics::LatencyHistogram;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+/// The `StockStore` trait holds the stock level of each catalog item, separately from the rest of
+/// the item's details held in the `CatalogDb`.
+///
+/// Every method is atomic for a single item, so a backend can apply stock changes without locking
+/// the whole catalog, e.g. a Redis counter per item.
+#[async_trait]
+pub trait StockStore: Send + Sync {
+    /// Retrieves the stock level of an item, with the version of the item it was read at.
+    ///
+    /// Arguments:
+    /// - `item_id`: The unique identifier of the clothing item.
+    ///
+    /// Returns:
+    /// - `Option<StockLevel>`: The stock level of the item, or `None` if the item is not in the store.
+    async fn get(&self, item_id: u32) -> Option<StockLevel>;
+
+    /// Removes a quantity from the stock of an item, only if the item has not changed since its
+    /// stock was read and has enough stock.
+    ///
+    /// Arguments:
+    /// - `item_id`: The unique identifier of the clothing item.
+    /// - `by`: The quantity to remove from the stock.
+    /// - `expected_version`: The `version` of the `StockLevel` the caller decided the decrement on.
+    ///
+    /// Returns:
+    /// - `Result<u32, StockUpdateError>`: The new stock level, or why the stock could not be
+    ///   decremented. A `VersionConflict` means the item changed since it was read, and the stock
+    ///   can be read again and the decrement retried.
+    async fn decrement(&self, item_id: u32, by: u32, expected_version: u64) -> Result<u32, StockUpdateError>;
+
+    /// Adds a quantity to the stock of an item, filling any quantity of the item on backorder first.
+    ///
+    /// Arguments:
+    /// - `item_id`: The unique identifier of the clothing item.
+    /// - `by`: The quantity to add to the stock.
+    ///
+    /// Returns:
+    /// - `Result<u32, StockUpdateError>`: The new stock level, or `StockUpdateError::NotFound` if the
+    ///   item is not in the store.
+    async fn increment(&self, item_id: u32, by: u32) -> Result<u32, StockUpdateError>;
+}
+
+/// The stock level of an item read from a `StockStore`.
+///
+/// Fields:
+/// - `stock`: The stock level of the item.
+/// - `version`: The version of the item when its stock was read, changed by every update.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StockLevel {
+    pub stock: u32,
+    pub version: u64,
+}
+
+/// A `StockStore` backed by the stock held on each item of a `CatalogDb`.
+///
+/// This is the default store of the `CatalogService`, so that the stock changed by the event
+/// listener is the stock reported by the catalog. The stock level of an item is its available stock,
+/// so the stock reserved for other orders is never taken by a placed order.
+///
+/// Fields:
+/// - `db`: The catalog database holding the stock, shared with the `CatalogService`.
+/// - `lock_wait`: Where the time each decrement waits for the write lock of `db` is recorded, if anywhere.
+pub struct CatalogDbStockStore<D: for<'a> CatalogDb<'a>> {
+    db: Arc<RwLock<D>>,
+    lock_wait: Option<Arc<LatencyHistogram>>,
+}
+
+impl<D: for<'a> CatalogDb<'a>> CatalogDbStockStore<D> {
+    pub fn new(db: Arc<RwLock<D>>) -> Self {
+        CatalogDbStockStore { db, lock_wait: None }
+    }
+
+    /// Records the time each decrement waits to acquire the write lock of the catalog database, which
+    /// it contends for with the HTTP handlers changing the catalog.
+    ///
+    /// Arguments:
+    /// - `lock_wait`: The histogram the waits are recorded in.
+    pub fn set_lock_wait(&mut self, lock_wait: Arc<LatencyHistogram>) {
+        self.lock_wait = Some(lock_wait);
+    }
+}
+
+#[async_trait]
+impl<D: for<'a> CatalogDb<'a> + Send + Sync> StockStore for CatalogDbStockStore<D> {
+    async fn get(&self, item_id: u32) -> Option<StockLevel> {
+        locks::read(&self.db).get_item(item_id).map(|item| StockLevel {
+            stock: item.available(),
+            version: item.version,
+        })
+    }
+
+    async fn decrement(&self, item_id: u32, by: u32, expected_version: u64) -> Result<u32, StockUpdateError> {
+        let lock_requested = Instant::now();
+        let mut db = locks::write(&self.db);
+        if let Some(lock_wait) = &self.lock_wait {
+            lock_wait.record(lock_requested.elapsed());
+        }
+        db.decrement_stock(item_id, by, expected_version)
+    }
+
+    async fn increment(&self, item_id: u32, by: u32) -> Result<u32, StockUpdateError> {
+        let mut db = locks::write(&self.db);
+        let item = db.get_mut_item(item_id).ok_or(StockUpdateError::NotFound)?;
+        item.stock = item.stock.saturating_add(by);
+        item.fill_backorders();
+        item.version += 1;
+        Ok(item.available())
+    }
+}
+
+/// A `StockStore` holding the stock of each item in an atomic counter, so that stock changes to
+/// different items, and reads of the same item, never wait on each other.
+///
+/// Each counter packs the stock of the item into its low 32 bits and the version of the item into
+/// its high 32 bits, so the version check and the decrement are a single compare-and-swap. The
+/// version wraps after 2^32 updates of an item.
+#[derive(Default)]
+pub struct InMemoryStockStore {
+    stock: RwLock<HashMap<u32, AtomicU64>>,
+}
+
+impl InMemoryStockStore {
+    pub fn new() -> Self {
+        InMemoryStockStore::default()
+    }
+
+    /// Sets the stock level of an item, adding the item to the store if needed.
+    ///
+    /// Arguments:
+    /// - `item_id`: The unique identifier of the clothing item.
+    /// - `stock`: The stock level of the item.
+    pub fn set(&self, item_id: u32, stock: u32) {
+        let mut items = locks::write(&self.stock);
+        let version = items.get(&item_id).map_or(0, |counter| unpack(counter.load(Ordering::SeqCst)).version);
+        items.insert(item_id, AtomicU64::new(pack(stock, version + 1)));
+    }
+}
+
+// packs a stock level into the value of an `InMemoryStockStore` counter
+fn pack(stock: u32, version: u64) -> u64 {
+    (version << 32) | stock as u64
+}
+
+// unpacks the value of an `InMemoryStockStore` counter
+fn unpack(counter: u64) -> StockLevel {
+    StockLevel {
+        stock: counter as u32,
+        version: counter >> 32,
+    }
+}
+
+#[async_trait]
+impl StockStore for InMemoryStockStore {
+    async fn get(&self, item_id: u32) -> Option<StockLevel> {
+        locks::read(&self.stock).get(&item_id).map(|counter| unpack(counter.load(Ordering::SeqCst)))
+    }
+
+    async fn decrement(&self, item_id: u32, by: u32, expected_version: u64) -> Result<u32, StockUpdateError> {
+        let items = locks::read(&self.stock);
+        let counter = items.get(&item_id).ok_or(StockUpdateError::NotFound)?;
+        counter
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |counter| {
+                let level = unpack(counter);
+                match level.stock.checked_sub(by) {
+                    Some(stock) if level.version == expected_version => Some(pack(stock, level.version + 1)),
+                    _ => None,
+                }
+            })
+            .map(|previous| unpack(previous).stock - by)
+            .map_err(|current| {
+                let level = unpack(current);
+                if level.version != expected_version {
+                    StockUpdateError::VersionConflict {
+                        current_version: level.version,
+                    }
+                } else {
+                    StockUpdateError::InsufficientStock { stock: level.stock }
+                }
+            })
+    }
+
+    async fn increment(&self, item_id: u32, by: u32) -> Result<u32, StockUpdateError> {
+        let items = locks::read(&self.stock);
+        let counter = items.get(&item_id).ok_or(StockUpdateError::NotFound)?;
+        let previous = counter
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |counter| {
+                let level = unpack(counter);
+                Some(pack(level.stock.saturating_add(by), level.version + 1))
+            })
+            .unwrap();
+        Ok(unpack(previous).stock.saturating_add(by))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::catalog_db::CatalogDbClient;
+
+    // decrements the stock as the catalog's listener does, reading it again after a version conflict
+    async fn decrement_with_retry(store: &dyn StockStore, item_id: u32, by: u32) -> Result<u32, StockUpdateError> {
+        loop {
+            let level = store.get(item_id).await.ok_or(StockUpdateError::NotFound)?;
+            match store.decrement(item_id, by, level.version).await {
+                Err(StockUpdateError::VersionConflict { .. }) => continue,
+                result => return result,
+            }
+        }
+    }
+
+    fn stock_level(stock: u32, version: u64) -> Option<StockLevel> {
+        Some(StockLevel { stock, version })
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_decrement() {
+        let store = InMemoryStockStore::new();
+        store.set(1, 10);
+
+        assert_eq!(store.decrement(1, 4, 1).await, Ok(6));
+        assert_eq!(store.get(1).await, stock_level(6, 2));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_decrement_insufficient_stock() {
+        let store = InMemoryStockStore::new();
+        store.set(1, 3);
+
+        assert_eq!(
+            store.decrement(1, 4, 1).await,
+            Err(StockUpdateError::InsufficientStock { stock: 3 })
+        );
+        assert_eq!(store.get(1).await, stock_level(3, 1));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_increment() {
+        let store = InMemoryStockStore::new();
+        store.set(1, 3);
+
+        assert_eq!(store.increment(1, 4).await, Ok(7));
+        assert_eq!(store.get(1).await, stock_level(7, 2));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_unknown_item() {
+        let store = InMemoryStockStore::new();
+
+        assert_eq!(store.get(1).await, None);
+        assert_eq!(store.decrement(1, 1, 0).await, Err(StockUpdateError::NotFound));
+        assert_eq!(store.increment(1, 1).await, Err(StockUpdateError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_decrement_of_changed_item_conflicts() {
+        // prepare, changing the item after its stock was read
+        let store = InMemoryStockStore::new();
+        store.set(1, 10);
+        let read = store.get(1).await.unwrap();
+        store.increment(1, 1).await.unwrap();
+
+        // act
+        let result = store.decrement(1, 4, read.version).await;
+
+        // assert
+        assert_eq!(result, Err(StockUpdateError::VersionConflict { current_version: 2 }));
+        assert_eq!(store.get(1).await, stock_level(11, 2));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_in_memory_concurrent_decrements() {
+        // prepare
+        let store = Arc::new(InMemoryStockStore::new());
+        store.set(1, 100);
+
+        // act by racing more decrements than there is stock for
+        let handles: Vec<_> = (0..150)
+            .map(|_| {
+                let store = store.clone();
+                tokio::spawn(async move { decrement_with_retry(store.as_ref(), 1, 1).await })
+            })
+            .collect();
+        let mut succeeded = 0;
+        for handle in handles {
+            if handle.await.unwrap().is_ok() {
+                succeeded += 1;
+            }
+        }
+
+        // assert that the stock is never oversold
+        assert_eq!(succeeded, 100);
+        assert_eq!(store.get(1).await.unwrap().stock, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_in_memory_concurrent_decrements_and_increments() {
+        // prepare
+        let store = Arc::new(InMemoryStockStore::new());
+        store.set(1, 50);
+
+        // act
+        let handles: Vec<_> = (0..100)
+            .map(|i| {
+                let store = store.clone();
+                tokio::spawn(async move {
+                    if i % 2 == 0 {
+                        decrement_with_retry(store.as_ref(), 1, 1).await
+                    } else {
+                        store.increment(1, 1).await
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+
+        // assert that no update was lost
+        assert_eq!(store.get(1).await, stock_level(50, 101));
+    }
+
+    #[tokio::test]
+    async fn test_catalog_db_store_decrements_and_increments_item_stock() {
+        let db = Arc::new(RwLock::new(CatalogDbClient::new()));
+        let store = CatalogDbStockStore::new(db.clone());
+
+        assert_eq!(store.decrement(1, 10, 0).await, Ok(90));
+        assert_eq!(store.increment(1, 5).await, Ok(95));
+        assert_eq!(store.get(1).await, stock_level(95, 2));
+        assert_eq!(db.read().unwrap().get_item(1).unwrap().version, 2);
+    }
+
+    #[tokio::test]
+    async fn test_catalog_db_store_records_the_wait_for_the_db_lock() {
+        // prepare
+        let db = Arc::new(RwLock::new(CatalogDbClient::new()));
+        let lock_wait = Arc::new(LatencyHistogram::default());
+        let mut store = CatalogDbStockStore::new(db.clone());
+        store.set_lock_wait(lock_wait.clone());
+        let (locked, lock_held) = std::sync::mpsc::channel();
+        let writer = std::thread::spawn(move || {
+            let _db = db.write().unwrap();
+            locked.send(()).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        });
+        lock_held.recv().unwrap();
+
+        // act whilst another writer holds the lock
+        let result = store.decrement(1, 10, 0).await;
+
+        // assert
+        writer.join().unwrap();
+        assert_eq!(result, Ok(90));
+        let snapshot = lock_wait.snapshot();
+        assert_eq!(snapshot.count, 1);
+        assert!(snapshot.sum_ms >= 40.0);
+    }
+
+    #[tokio::test]
+    async fn test_catalog_db_store_unknown_item() {
+        let store = CatalogDbStockStore::new(Arc::new(RwLock::new(CatalogDbClient::new())));
+
+        assert_eq!(store.get(100).await, None);
+        assert_eq!(store.decrement(100, 1, 0).await, Err(StockUpdateError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_catalog_db_store_decrement_of_changed_item_conflicts() {
+        let store = CatalogDbStockStore::new(Arc::new(RwLock::new(CatalogDbClient::new())));
+        let read = store.get(1).await.unwrap();
+        store.increment(1, 1).await.unwrap();
+
+        assert_eq!(
+            store.decrement(1, 10, read.version).await,
+            Err(StockUpdateError::VersionConflict { current_version: 1 })
+        );
+        assert_eq!(store.get(1).await, stock_level(101, 1));
+    }
+}
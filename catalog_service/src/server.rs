@@ -0,0 +1,239 @@
+use crate::api;
+use crate::db::catalog_db::{CatalogDb, CatalogDbClient};
+use crate::services::catalog_service::{CatalogService, OrderPlacedRetryConfig};
+use crate::services::{order_metrics, reservations};
+use actix_web::dev::{Server, ServerHandle};
+use actix_web::middleware::{from_fn, NormalizePath, TrailingSlash};
+use actix_web::{web, App, HttpServer};
+use common::constants::global_constants;
+use common::traits::listener_service::ListenerService;
+use common::utilities::{not_found, request_id, server_config};
+use event_bus::topic::TopicConfig;
+use event_bus::utilities::listeners::BatchCommitConfig;
+use event_bus::EventBus;
+use log::error;
+use std::env;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+// environment variable used to select the listener's stock underflow policy: skip or clamp_to_zero
+const STOCK_UNDERFLOW_POLICY_VAR: &str = "STOCK_UNDERFLOW_POLICY";
+// environment variable used to seed the catalog from a JSON file instead of the hardcoded items
+const CATALOG_SEED_FILE_VAR: &str = "CATALOG_SEED_FILE";
+// environment variable used to limit the number of items the catalog holds
+const MAX_CATALOG_ITEMS_VAR: &str = "MAX_CATALOG_ITEMS";
+// environment variable used to record every produced event to an audit topic
+const AUDIT_TOPIC_VAR: &str = "AUDIT_TOPIC";
+// environment variable used to set how many minutes of order metrics are kept, `0` disables them
+const ORDER_METRICS_WINDOW_MINUTES_VAR: &str = "ORDER_METRICS_WINDOW_MINUTES";
+// environment variables used to set how failed order placed events are retried before being dead-lettered
+const ORDER_PLACED_MAX_ATTEMPTS_VAR: &str = "ORDER_PLACED_MAX_ATTEMPTS";
+const ORDER_PLACED_RETRY_DELAY_MS_VAR: &str = "ORDER_PLACED_RETRY_DELAY_MS";
+// environment variable used to set how many seconds a stock reservation lasts before it is released
+const RESERVATION_TTL_SECS_VAR: &str = "RESERVATION_TTL_SECS";
+
+/// `CatalogServer` is the HTTP server of the Catalog Microservice, bound to its address, with the
+/// event listeners of its catalog started.
+///
+/// Fields:
+/// - `server`: The HTTP server, which serves requests once it is run.
+/// - `catalog_service`: The catalog served, drained once the server has stopped.
+/// - `addrs`: The addresses the server is bound to.
+pub struct CatalogServer {
+    server: Server,
+    catalog_service: Arc<CatalogService<EventBus, CatalogDbClient>>,
+    addrs: Vec<SocketAddr>,
+}
+
+impl CatalogServer {
+    /// Returns the addresses the server is bound to, e.g. to learn the port of a server bound to port `0`.
+    pub fn addrs(&self) -> &[SocketAddr] {
+        &self.addrs
+    }
+
+    /// Returns a handle used to stop the server, e.g. when another service of the process stops.
+    pub fn handle(&self) -> ServerHandle {
+        self.server.handle()
+    }
+
+    /// Serves requests until the server is stopped, then finishes processing any events the
+    /// listener has already received.
+    pub async fn run(self) -> std::io::Result<()> {
+        self.server.await?;
+        self.catalog_service.drain().await;
+        Ok(())
+    }
+}
+
+/// Starts the Catalog Microservice on `127.0.0.1:8081`, configured from the environment, serving
+/// requests until it is stopped, e.g. by Ctrl-C.
+pub async fn initialize_server() -> std::io::Result<()> {
+    bind_server((global_constants::HOST, global_constants::CATALOG_SERVICE_PORT), true)?.run().await
+}
+
+/// Builds the catalog from the environment, starts its event listeners and binds its HTTP server.
+///
+/// Arguments:
+/// - `bind_address`: The address the HTTP server binds to, e.g. `127.0.0.1:0` for any free port.
+/// - `handle_signals`: Whether the server stops itself on Ctrl-C, which should be `false` when whoever
+///   runs it handles the signals, e.g. to stop it gracefully.
+///
+/// Returns:
+/// - `CatalogServer`: The bound server, which serves requests once it is run.
+pub fn bind_server(bind_address: impl ToSocketAddrs, handle_signals: bool) -> std::io::Result<CatalogServer> {
+    let mut mock_db: CatalogDbClient = match env::var(CATALOG_SEED_FILE_VAR) {
+        Ok(path) => CatalogDbClient::from_file(Path::new(&path)).unwrap_or_else(|e| {
+            error!(
+                "Failed to seed the catalog from {}: {}, defaulting to the built-in items",
+                path, e
+            );
+            CatalogDbClient::new()
+        }),
+        Err(_) => CatalogDbClient::new(),
+    };
+    if let Ok(max_items) = env::var(MAX_CATALOG_ITEMS_VAR) {
+        match max_items.parse() {
+            Ok(max_items) => mock_db.set_max_items(Some(max_items)),
+            Err(e) => error!(
+                "Invalid {}: {}, {}, defaulting to no limit",
+                MAX_CATALOG_ITEMS_VAR, max_items, e
+            ),
+        }
+    }
+    let mut event_bus = EventBus::new(&format!(
+        "{}:{}",
+        global_constants::HOST,
+        global_constants::EVENT_BUS_PORT
+    ));
+    if let Ok(audit_topic) = env::var(AUDIT_TOPIC_VAR) {
+        if let Err(e) = event_bus.set_audit_topic(&audit_topic, "Catalog") {
+            error!(
+                "Invalid audit topic: {}, {}, events will not be audited",
+                audit_topic, e
+            );
+        }
+    }
+    if let Some(batch_commit) = BatchCommitConfig::from_env() {
+        event_bus.set_batch_commit_config(batch_commit);
+    }
+    let mut raw_catalog_service = CatalogService::new(mock_db, event_bus);
+    if let Ok(policy) = env::var(STOCK_UNDERFLOW_POLICY_VAR) {
+        match policy.parse() {
+            Ok(policy) => raw_catalog_service.set_stock_underflow_policy(policy),
+            Err(e) => error!("{}, defaulting to skip", e),
+        }
+    }
+    let order_metrics_window = match env::var(ORDER_METRICS_WINDOW_MINUTES_VAR) {
+        Ok(window) => window.parse().unwrap_or_else(|e| {
+            error!(
+                "Invalid {}: {}, {}, defaulting to {} minutes",
+                ORDER_METRICS_WINDOW_MINUTES_VAR,
+                window,
+                e,
+                order_metrics::DEFAULT_WINDOW_MINUTES
+            );
+            order_metrics::DEFAULT_WINDOW_MINUTES
+        }),
+        Err(_) => order_metrics::DEFAULT_WINDOW_MINUTES,
+    };
+    if order_metrics_window > 0 {
+        raw_catalog_service.enable_order_metrics(order_metrics_window);
+    }
+    raw_catalog_service.set_order_placed_retry_config(retry_config_from_env());
+    if let Ok(ttl_secs) = env::var(RESERVATION_TTL_SECS_VAR) {
+        match ttl_secs.parse() {
+            Ok(ttl_secs) => raw_catalog_service.set_reservation_ttl(Duration::from_secs(ttl_secs)),
+            Err(e) => error!(
+                "Invalid {}: {}, {}, defaulting to {}s",
+                RESERVATION_TTL_SECS_VAR,
+                ttl_secs,
+                e,
+                reservations::DEFAULT_RESERVATION_TTL.as_secs()
+            ),
+        }
+    }
+    raw_catalog_service.set_topic_config(TopicConfig::from_env());
+    raw_catalog_service.start_event_listeners();
+    let catalog_service = Arc::new(raw_catalog_service);
+    let server_catalog_service = catalog_service.clone();
+    let max_payload_bytes = server_config::max_payload_bytes_from_env();
+    let mut server = HttpServer::new(move || {
+        let app = App::new()
+            .wrap(NormalizePath::new(TrailingSlash::Trim))
+            .wrap(from_fn(request_id::request_id))
+            .app_data(web::Data::new(server_catalog_service.clone()))
+            .app_data(server_config::json_config(max_payload_bytes))
+            .app_data(server_config::payload_config(max_payload_bytes))
+            .service(api::get_catalog)
+            .service(api::get_stock)
+            // registered before get_catalog_item, whose path would otherwise match /catalog/stock and /catalog/export
+            .service(api::get_stock_batch)
+            .service(api::export_catalog_items)
+            .service(api::get_catalog_item)
+            .service(api::get_order_summary)
+            .service(api::get_order_metrics)
+            .service(api::get_processing_metrics)
+            .service(api::get_listener_stats)
+            .service(api::get_health)
+            .service(api::pause_listener)
+            .service(api::resume_listener)
+            .service(api::import_catalog_items)
+            .service(api::save_catalog_item)
+            .service(api::remove_catalog_item)
+            .service(api::set_stock)
+            .service(api::release_reservation)
+            .service(api::reserve_stock)
+            .default_service(web::to(not_found::not_found));
+        #[cfg(feature = "debug-endpoints")]
+        let app = app.service(api::inject_order_placed);
+        app
+    })
+    .keep_alive(server_config::keep_alive_from_env());
+    if let Some(workers) = server_config::workers_from_env() {
+        server = server.workers(workers);
+    }
+    if !handle_signals {
+        server = server.disable_signals();
+    }
+    let server = server.bind(bind_address)?;
+    let addrs = server.addrs();
+    Ok(CatalogServer {
+        server: server.run(),
+        catalog_service,
+        addrs,
+    })
+}
+
+// reads how failed order placed events are retried, keeping the default of each variable that is unset or invalid
+// and logging the invalid ones
+fn retry_config_from_env() -> OrderPlacedRetryConfig {
+    let mut config = OrderPlacedRetryConfig::default();
+    if let Ok(max_attempts) = env::var(ORDER_PLACED_MAX_ATTEMPTS_VAR) {
+        match max_attempts.parse::<u32>() {
+            Ok(parsed) if parsed > 0 => config.max_attempts = parsed,
+            Ok(_) => error!(
+                "Invalid {}: {}, must be at least 1, defaulting to {}",
+                ORDER_PLACED_MAX_ATTEMPTS_VAR, max_attempts, config.max_attempts
+            ),
+            Err(e) => error!(
+                "Invalid {}: {}, {}, defaulting to {}",
+                ORDER_PLACED_MAX_ATTEMPTS_VAR, max_attempts, e, config.max_attempts
+            ),
+        }
+    }
+    if let Ok(delay_ms) = env::var(ORDER_PLACED_RETRY_DELAY_MS_VAR) {
+        match delay_ms.parse() {
+            Ok(parsed) => config.delay = Duration::from_millis(parsed),
+            Err(e) => error!(
+                "Invalid {}: {}, {}, defaulting to {}ms",
+                ORDER_PLACED_RETRY_DELAY_MS_VAR,
+                delay_ms,
+                e,
+                config.delay.as_millis()
+            ),
+        }
+    }
+    config
+}
@@ -1,11 +1,48 @@
-use crate::db::catalog_db::{CatalogDb, ClothingItem};
+use crate::db::catalog_db::{AddItemError, CatalogDb, ClothingItem, StockUpdateError};
+use crate::db::locks;
+use crate::db::stock_store::{CatalogDbStockStore, StockStore};
+use crate::services::order_metrics::{MinuteOrderMetrics, OrderMetrics};
+use crate::services::processing_metrics::{ProcessingMetrics, ProcessingMetricsSnapshot};
+use crate::services::reservations::{self, Reservation, ReservationStore};
+use common::dto::clothing_item_dto::ClothingItemDTO;
+use common::error::ServiceError;
 use common::traits::listener_service::ListenerService;
+use common::types::item_ref::ItemRef;
+use common::types::money::Money;
+use event_bus::clock::{Clock, SystemClock};
 use event_bus::event::Event;
+use event_bus::events::backorder_placed_event::BackorderPlacedEvent;
+use event_bus::events::catalog_item_changed_event::{CatalogItemChange, CatalogItemChangedEvent};
 use event_bus::events::order_placed_event::OrderPlacedEvent;
-use event_bus::{topic, EventListener};
-use log::{error, info};
+use event_bus::topic::TopicConfig;
+use event_bus::utilities::deduplicator::EventDeduplicator;
+use event_bus::utilities::listeners::{KafkaListener, ListenerHealth};
+use event_bus::{topic, EventListener, EventProducer};
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::broadcast::error::TryRecvError;
+use tokio::sync::{broadcast, watch, Notify};
+use tokio::task::JoinHandle;
+
+// the number of recently processed event ids remembered to detect redelivered events
+const DEDUPLICATION_CAPACITY: usize = 1000;
+// the number of times a stock update is attempted when the item keeps changing whilst being updated
+const MAX_STOCK_UPDATE_ATTEMPTS: u32 = 3;
+// the number of events held whilst the listener is paused, before further events are left in its channel
+const MAX_PAUSED_EVENTS: usize = 1000;
+// how often the sweeper releases the stock of expired reservations
+const RESERVATION_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+// the consumer group of the retry listener, separate from the group of the `ORDER_PLACED` listener so
+// that each commits its own offsets
+const ORDER_PLACED_RETRY_GROUP_ID: &str = "group-1-retry";
+// the consumer group of the `BACKORDER_PLACED` listener, so that it commits its own offsets
+const BACKORDER_PLACED_GROUP_ID: &str = "group-1-backorder";
 
 /// `CatalogService` provides functionality to interact with a catalog database.
 ///
@@ -14,46 +51,768 @@ use std::sync::{Arc, RwLock};
 ///
 /// Fields:
 /// - `db`: An instance of `MockCatalogDb` representing the mock catalog database.
+/// - `stock_store`: The `StockStore` the listener applies `OrderPlacedEvent`s to.
+/// - `stock_underflow_policy`: How the listener reacts to an order for more than the available stock.
+/// - `catalog_version`: A counter bumped whenever the catalog changes, used to build `ETag`s.
+/// - `ordered_quantities`: The total quantity ordered of each item, maintained from `OrderPlacedEvent`s.
+/// - `shutdown`: Signals the listener task to drain and stop, see `drain`.
+/// - `listener_task`: The task processing `OrderPlacedEvent`s, once the listeners are started.
+/// - `order_placed_listener`: The listener of `OrderPlacedEvent`s, retained to report its statistics.
+/// - `order_metrics`: The per-minute order metrics, when enabled by `enable_order_metrics`.
+/// - `metrics_task`: The task aggregating `OrderPlacedEvent`s into `order_metrics`, once the listeners are started.
+/// - `processing_metrics`: How long the listener takes to apply each `OrderPlacedEvent`.
+/// - `paused`: Whether the listener task holds `OrderPlacedEvent`s rather than applying them, see `pause_listener`.
+/// - `resumed`: Wakes the listener task when it is resumed, so it applies the events it held.
+/// - `retry_config`: How `OrderPlacedEvent`s that fail to be applied are retried.
+/// - `retry_task`: The task reprocessing retried `OrderPlacedEvent`s, once the listeners are started.
+/// - `reservations`: The reservations of stock that have not been placed as orders or released.
+/// - `reservation_ttl`: How long a reservation holds its stock before the sweeper releases it.
+/// - `clock`: The source of the current time, used to expire reservations.
+/// - `sweeper_task`: The task releasing expired reservations, once the listeners are started.
+/// - `backorder_task`: The task applying `BackorderPlacedEvent`s, once the listeners are started.
+/// - `topics`: The concrete topics events are produced to and consumed from.
 pub struct CatalogService<E: EventListener, D: for<'a> CatalogDb<'a>> {
-    event_bus: E,
+    event_bus: Arc<E>,
     db: Arc<RwLock<D>>,
+    stock_store: Arc<dyn StockStore>,
+    stock_underflow_policy: StockUnderflowPolicy,
+    catalog_version: Arc<AtomicU64>,
+    ordered_quantities: Arc<RwLock<HashMap<u32, u64>>>,
+    shutdown: watch::Sender<bool>,
+    listener_task: Mutex<Option<JoinHandle<()>>>,
+    order_placed_listener: Mutex<Option<Arc<KafkaListener<Event<OrderPlacedEvent>>>>>,
+    order_metrics: Option<Arc<OrderMetrics>>,
+    metrics_task: Mutex<Option<JoinHandle<()>>>,
+    processing_metrics: Arc<ProcessingMetrics>,
+    paused: Arc<AtomicBool>,
+    resumed: Arc<Notify>,
+    retry_config: OrderPlacedRetryConfig,
+    retry_task: Mutex<Option<JoinHandle<()>>>,
+    reservations: Arc<ReservationStore>,
+    reservation_ttl: Duration,
+    clock: Arc<dyn Clock>,
+    sweeper_task: Mutex<Option<JoinHandle<()>>>,
+    backorder_task: Mutex<Option<JoinHandle<()>>>,
+    topics: TopicConfig,
+}
+
+/// `ListenerStats` reports the state of the catalog's event listeners, for debugging.
+///
+/// Fields:
+/// - `order_placed_receivers`: The number of receivers subscribed to the `ORDER_PLACED` listener,
+///   which is `0` if the listeners were not started or every receiver has been dropped.
+/// - `order_placed_topics`: The topics the `ORDER_PLACED` listener is subscribed to, which is empty
+///   if the listeners were not started.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ListenerStats {
+    pub order_placed_receivers: usize,
+    #[serde(default)]
+    pub order_placed_topics: Vec<String>,
+}
+
+/// `CatalogHealth` reports whether the catalog can keep its stock levels up to date.
+///
+/// Fields:
+/// - `healthy`: Whether every check below passed.
+/// - `order_placed_consumer_connected`: Whether the consumer of the `ORDER_PLACED` listener has been
+///   assigned partitions, which is `false` if the listeners were not started or cannot reach Kafka.
+/// - `degraded`: Whether the listeners were started but the supervisor of the consumer gave up restarting
+///   it, in which case the catalog is still up and serves its last known stock levels, which may be stale.
+/// - `listener_paused`: Whether the `ORDER_PLACED` listener is paused, see `CatalogService::pause_listener`.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CatalogHealth {
+    pub healthy: bool,
+    pub order_placed_consumer_connected: bool,
+    #[serde(default)]
+    pub degraded: bool,
+    #[serde(default)]
+    pub listener_paused: bool,
+}
+
+/// `OrderSummary` reports how much of a catalog item has been ordered.
+///
+/// Fields:
+/// - `item_id`: The identifier of the catalog item.
+/// - `total_quantity_ordered`: The sum of the quantities of every order placed for the item.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct OrderSummary {
+    pub item_id: u32,
+    pub total_quantity_ordered: u64,
+}
+
+/// `ImportSummary` reports the outcome of importing items with `import_items`.
+///
+/// Fields:
+/// - `inserted`: The number of items added to the catalog.
+/// - `skipped`: The number of items skipped as their id is already in the catalog, or earlier in the import.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ImportSummary {
+    pub inserted: usize,
+    pub skipped: usize,
+}
+
+/// `StockChange` reports the stock of a catalog item before and after it was set by `set_stock`.
+///
+/// Fields:
+/// - `item_id`: The identifier of the catalog item.
+/// - `previous_stock`: The stock of the item before it was set.
+/// - `stock`: The stock the item was set to.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct StockChange {
+    pub item_id: u32,
+    pub previous_stock: u32,
+    pub stock: u32,
+}
+
+/// `StockUnderflowPolicy` determines how an `OrderPlacedEvent` is applied when the ordered
+/// quantity exceeds the stock currently held in the catalog.
+///
+/// Variants:
+/// - `Skip`: The event is logged and dropped, leaving the stock unchanged. The catalog keeps an
+///   accurate count of what it believes is on hand, but the order recorded by the order service
+///   is never reflected in the catalog.
+/// - `ClampToZero`: The stock is set to zero. Every order is reflected in the catalog, but the
+///   shortfall between the ordered quantity and the stock is lost, so the item is oversold and
+///   the catalog no longer matches the sum of the orders placed against it.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StockUnderflowPolicy {
+    #[default]
+    Skip,
+    ClampToZero,
+}
+
+impl FromStr for StockUnderflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(StockUnderflowPolicy::Skip),
+            "clamp_to_zero" => Ok(StockUnderflowPolicy::ClampToZero),
+            _ => Err(format!("Unknown stock underflow policy: {}", s)),
+        }
+    }
+}
+
+/// `OrderPlacedRetryConfig` controls how `OrderPlacedEvent`s that fail to be applied to the stock,
+/// e.g. because the item kept changing whilst being updated, are retried.
+///
+/// A failed event is republished to `topic::ORDER_PLACED_RETRY` with its retry count, and the time
+/// `delay` from now, in its `metadata`, and applied again by the retry listener at that time. Once
+/// it has been attempted `max_attempts` times it is republished to `topic::ORDER_PLACED_DEAD_LETTER`
+/// instead.
+///
+/// Fields:
+/// - `max_attempts`: The number of times an event is applied before it is dead-lettered, including
+///   the first attempt. Defaults to `3`, and `1` dead-letters failed events straight away.
+/// - `delay`: How long after failing a retried event is applied again. Defaults to 5 seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderPlacedRetryConfig {
+    pub max_attempts: u32,
+    pub delay: Duration,
+}
+
+impl Default for OrderPlacedRetryConfig {
+    fn default() -> Self {
+        OrderPlacedRetryConfig {
+            max_attempts: 3,
+            delay: Duration::from_secs(5),
+        }
+    }
+}
+
+// the outcome of applying an order placed event to the stock
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StockUpdateOutcome {
+    Changed,
+    Unchanged,
+    Failed,
 }
 
-impl<E: EventListener, D: for<'a> CatalogDb<'a> + Send + Sync + 'static> ListenerService for CatalogService<E, D> {
+impl<E, D> ListenerService for CatalogService<E, D>
+where
+    E: EventListener + EventProducer + Send + Sync + 'static,
+    D: for<'a> CatalogDb<'a> + Send + Sync + 'static,
+{
     fn start_event_listeners(&mut self) {
         let listener = self
             .event_bus
-            .create_event_listener::<Event<OrderPlacedEvent>>("group-1", &[topic::ORDER_PLACED])
-            .expect(format!("Failed to initialize the {} listener", topic::ORDER_PLACED).as_str());
+            .create_event_listener::<Event<OrderPlacedEvent>>("group-1", &[self.topics.resolve(topic::ORDER_PLACED)])
+            .unwrap_or_else(|_| panic!("Failed to initialize the {} listener", topic::ORDER_PLACED));
+        let listener = Arc::new(listener);
+        *self.order_placed_listener.lock().unwrap() = Some(listener.clone());
 
-        let db_clone = self.db.clone();
+        let stock_store = self.stock_store.clone();
+        let catalog_version = self.catalog_version.clone();
+        let ordered_quantities = self.ordered_quantities.clone();
+        let stock_underflow_policy = self.stock_underflow_policy;
+        let paused = self.paused.clone();
+        let resumed = self.resumed.clone();
+        let event_bus = self.event_bus.clone();
+        let retry_config = self.retry_config;
+        let topics = self.topics.clone();
+        let processing_metrics = self.processing_metrics.clone();
+        let db = self.db.clone();
+        let reservations = self.reservations.clone();
         let mut receiver = listener.get_receiver();
-        tokio::spawn(async move {
-            while let Ok(event) = receiver.recv().await {
-                let mut db = db_clone.write().unwrap();
-                let item_result = db.get_mut_item(event.payload.item_id);
-                match item_result {
-                    None => {}
-                    Some(item) => {
-                        let mut stock_amount = item.stock;
-                        if event.payload.quantity > stock_amount {
-                            error!("Event to change stock levels has failed, Source: {}, Amount to change: {}, Current Amount: {}",
-                                event.source,
-                                event.payload.quantity,
-                                item.stock);
-                            continue;
+        let mut shutdown = self.shutdown.subscribe();
+        let handle = tokio::spawn(async move {
+            let mut deduplicator = EventDeduplicator::new(DEDUPLICATION_CAPACITY);
+            // events are held here whilst the listener is paused, and applied in order once it is resumed
+            let mut pending = VecDeque::new();
+            loop {
+                if !paused.load(Ordering::SeqCst) {
+                    while let Some(event) = pending.pop_front() {
+                        release_order_reservation(db.as_ref(), &reservations, &catalog_version, &event);
+                        let outcome = process_order_placed(
+                            stock_store.as_ref(),
+                            &catalog_version,
+                            &ordered_quantities,
+                            &mut deduplicator,
+                            stock_underflow_policy,
+                            &processing_metrics,
+                            &event,
+                        )
+                        .await;
+                        if outcome == StockUpdateOutcome::Failed {
+                            retry_order_placed(event_bus.as_ref(), &topics, retry_config, event).await;
                         }
-                        stock_amount -= event.payload.quantity;
-                        item.stock = stock_amount;
-                        info!("Stock level for item: {} is now: {}", item.id, stock_amount);
                     }
                 }
+
+                let received = if pending.len() >= MAX_PAUSED_EVENTS {
+                    // leave any further events in the listener's channel until the listener is resumed
+                    tokio::select! {
+                        _ = resumed.notified() => continue,
+                        _ = shutdown.changed() => Received::Shutdown,
+                    }
+                } else {
+                    tokio::select! {
+                        _ = resumed.notified() => continue,
+                        received = next_until_shutdown(&mut receiver, &mut shutdown) => received,
+                    }
+                };
+                match received {
+                    Received::Event(event) => pending.push_back(event),
+                    Received::Shutdown => break,
+                    Received::Closed => return,
+                }
+            }
+
+            // stop pulling new messages, then finish the ones already received, including any held
+            // whilst paused, before committing them
+            info!("Draining the {} listener", topic::ORDER_PLACED);
+            listener.unsubscribe();
+            pending.extend(std::iter::from_fn(|| try_next_received(&mut receiver)));
+            for event in pending {
+                release_order_reservation(db.as_ref(), &reservations, &catalog_version, &event);
+                let outcome = process_order_placed(
+                    stock_store.as_ref(),
+                    &catalog_version,
+                    &ordered_quantities,
+                    &mut deduplicator,
+                    stock_underflow_policy,
+                    &processing_metrics,
+                    &event,
+                )
+                .await;
+                if outcome == StockUpdateOutcome::Failed {
+                    retry_order_placed(event_bus.as_ref(), &topics, retry_config, event).await;
+                }
+            }
+            if let Err(e) = listener.commit() {
+                error!("Failed to commit the {} offsets: {:?}", topic::ORDER_PLACED, e);
             }
         });
+        *self.listener_task.lock().unwrap() = Some(handle);
+        if let Some(order_metrics) = self.order_metrics.clone() {
+            let receiver = self.order_placed_listener.lock().unwrap().as_ref().unwrap().get_receiver();
+            let handle = tokio::spawn(aggregate_order_metrics(
+                order_metrics,
+                receiver,
+                self.shutdown.subscribe(),
+            ));
+            *self.metrics_task.lock().unwrap() = Some(handle);
+        }
+
+        let retry_listener = self
+            .event_bus
+            .create_event_listener::<Event<OrderPlacedEvent>>(
+                ORDER_PLACED_RETRY_GROUP_ID,
+                &[self.topics.resolve(topic::ORDER_PLACED_RETRY)],
+            )
+            .unwrap_or_else(|_| panic!("Failed to initialize the {} listener", topic::ORDER_PLACED_RETRY));
+        let handle = tokio::spawn(process_order_placed_retries(
+            self.event_bus.clone(),
+            retry_listener.get_receiver(),
+            Arc::new(retry_listener),
+            self.stock_store.clone(),
+            self.catalog_version.clone(),
+            self.stock_underflow_policy,
+            self.retry_config,
+            self.topics.clone(),
+            self.shutdown.subscribe(),
+        ));
+        *self.retry_task.lock().unwrap() = Some(handle);
+
+        let handle = tokio::spawn(sweep_expired_reservations(
+            self.db.clone(),
+            self.reservations.clone(),
+            self.catalog_version.clone(),
+            self.clock.clone(),
+            self.shutdown.subscribe(),
+        ));
+        *self.sweeper_task.lock().unwrap() = Some(handle);
+
+        let backorder_listener = self
+            .event_bus
+            .create_event_listener::<Event<BackorderPlacedEvent>>(
+                BACKORDER_PLACED_GROUP_ID,
+                &[self.topics.resolve(topic::BACKORDER_PLACED)],
+            )
+            .unwrap_or_else(|_| panic!("Failed to initialize the {} listener", topic::BACKORDER_PLACED));
+        let handle = tokio::spawn(process_backorders_placed(
+            self.db.clone(),
+            self.catalog_version.clone(),
+            self.ordered_quantities.clone(),
+            backorder_listener.get_receiver(),
+            Arc::new(backorder_listener),
+            self.shutdown.subscribe(),
+        ));
+        *self.backorder_task.lock().unwrap() = Some(handle);
+    }
+}
+
+// applies every backorder placed event to the stock levels, skipping events that have already been
+// processed, until shutdown is signalled. On shutdown the listener stops pulling new messages, finishes
+// the events already received and commits their offsets
+async fn process_backorders_placed<D: for<'a> CatalogDb<'a>>(
+    db: Arc<RwLock<D>>,
+    catalog_version: Arc<AtomicU64>,
+    ordered_quantities: Arc<RwLock<HashMap<u32, u64>>>,
+    mut receiver: broadcast::Receiver<Event<BackorderPlacedEvent>>,
+    listener: Arc<KafkaListener<Event<BackorderPlacedEvent>>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut deduplicator = EventDeduplicator::new(DEDUPLICATION_CAPACITY);
+    let mut process = |event: Event<BackorderPlacedEvent>| {
+        if deduplicator.is_duplicate(&event.event_id) {
+            warn!("Skipping duplicate event: {}, Source: {}", event.event_id, event.source);
+            return;
+        }
+        handle_backorder_placed(&db, &catalog_version, &ordered_quantities, &event);
+    };
+    loop {
+        match next_until_shutdown(&mut receiver, &mut shutdown).await {
+            Received::Event(event) => process(event),
+            Received::Shutdown => break,
+            Received::Closed => return,
+        }
+    }
+
+    info!("Draining the {} listener", topic::BACKORDER_PLACED);
+    listener.unsubscribe();
+    while let Some(event) = try_next_received(&mut receiver) {
+        process(event);
+    }
+    if let Err(e) = listener.commit() {
+        error!("Failed to commit the {} offsets: {:?}", topic::BACKORDER_PLACED, e);
+    }
+}
+
+// releases the stock of expired reservations every `RESERVATION_SWEEP_INTERVAL`, until shutdown is signalled
+async fn sweep_expired_reservations<D: for<'a> CatalogDb<'a>>(
+    db: Arc<RwLock<D>>,
+    reservations: Arc<ReservationStore>,
+    catalog_version: Arc<AtomicU64>,
+    clock: Arc<dyn Clock>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    loop {
+        tokio::select! {
+            biased;
+            _ = shutdown.changed() => return,
+            _ = tokio::time::sleep(RESERVATION_SWEEP_INTERVAL) => {
+                release_expired_reservations(&db, &reservations, &catalog_version, clock.now());
+            }
+        }
+    }
+}
+
+// releases the stock of every reservation that has expired by `now` back to its item, returning how many
+// reservations were released
+fn release_expired_reservations<D: for<'a> CatalogDb<'a>>(
+    db: &RwLock<D>,
+    reservations: &ReservationStore,
+    catalog_version: &AtomicU64,
+    now: SystemTime,
+) -> usize {
+    let expired = reservations.take_expired(now);
+    for reservation in &expired {
+        info!(
+            "Releasing expired reservation: {} of {} of item: {}",
+            reservation.id, reservation.quantity, reservation.item_id
+        );
+        release_reserved(db, catalog_version, reservation);
+    }
+    expired.len()
+}
+
+// releases the reservation an order was placed against, if it is still held, so that the reserved quantity
+// is available to the order's decrement. A redelivered event finds the reservation already released and
+// releases nothing
+fn release_order_reservation<D: for<'a> CatalogDb<'a>>(
+    db: &RwLock<D>,
+    reservations: &ReservationStore,
+    catalog_version: &AtomicU64,
+    event: &Event<OrderPlacedEvent>,
+) {
+    let Some(reservation) = event.payload.reservation_id.and_then(|id| reservations.remove(id)) else {
+        return;
+    };
+    if reservation.item_id != event.payload.item_id {
+        warn!(
+            "Event: {} was placed against reservation: {} of another item: {}",
+            event.event_id, reservation.id, reservation.item_id
+        );
+    }
+    release_reserved(db, catalog_version, &reservation);
+}
+
+// returns the quantity of a reservation to the available stock of its item, which may have been removed since
+fn release_reserved<D: for<'a> CatalogDb<'a>>(db: &RwLock<D>, catalog_version: &AtomicU64, reservation: &Reservation) {
+    match locks::write(db).release(reservation.item_id, reservation.quantity) {
+        Ok(_) => {
+            catalog_version.fetch_add(1, Ordering::SeqCst);
+        }
+        Err(e) => warn!(
+            "Could not release reservation: {} of item: {}, {:?}",
+            reservation.id, reservation.item_id, e
+        ),
+    }
+}
+
+// records every order placed event in the order metrics, until shutdown is signalled and the events
+// already received have been recorded
+async fn aggregate_order_metrics(
+    order_metrics: Arc<OrderMetrics>,
+    mut receiver: broadcast::Receiver<Event<OrderPlacedEvent>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut deduplicator = EventDeduplicator::new(DEDUPLICATION_CAPACITY);
+    let mut record = |event: Event<OrderPlacedEvent>| {
+        if !deduplicator.is_duplicate(&event.event_id) {
+            order_metrics.record(event.timestamp, event.payload.quantity);
+        }
+    };
+    loop {
+        match next_until_shutdown(&mut receiver, &mut shutdown).await {
+            Received::Event(event) => record(event),
+            Received::Shutdown => break,
+            Received::Closed => return,
+        }
+    }
+    while let Some(event) = try_next_received(&mut receiver) {
+        record(event);
+    }
+}
+
+// applies the order placed events republished to the retry topic once they are due, retrying them again
+// or dead-lettering them if they still fail, until shutdown is signalled. Each event is scheduled on its
+// own, so an event waiting for its retry time does not hold back events that are due sooner. On shutdown
+// the events still waiting are republished to the retry topic, to be applied by the next consumer, before
+// the received events are committed
+#[allow(clippy::too_many_arguments)]
+async fn process_order_placed_retries<E: EventProducer>(
+    event_bus: Arc<E>,
+    mut receiver: broadcast::Receiver<Event<OrderPlacedEvent>>,
+    listener: Arc<KafkaListener<Event<OrderPlacedEvent>>>,
+    stock_store: Arc<dyn StockStore>,
+    catalog_version: Arc<AtomicU64>,
+    stock_underflow_policy: StockUnderflowPolicy,
+    retry_config: OrderPlacedRetryConfig,
+    topics: TopicConfig,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    // every retry of an event shares its event id, so redelivered retries are detected by attempt
+    let mut deduplicator = EventDeduplicator::new(DEDUPLICATION_CAPACITY);
+    // the events waiting for their retry time, in the order they are due
+    let mut scheduled: VecDeque<(tokio::time::Instant, Event<OrderPlacedEvent>)> = VecDeque::new();
+    loop {
+        let next_due = scheduled.front().map(|(due, _)| *due);
+        tokio::select! {
+            biased;
+            _ = shutdown.changed() => break,
+            _ = tokio::time::sleep_until(next_due.unwrap_or_else(tokio::time::Instant::now)), if next_due.is_some() => {
+                let (_, event) = scheduled.pop_front().unwrap();
+                if deduplicator.is_duplicate(&format!("{}:{}", event.event_id, event.retry_attempt())) {
+                    warn!(
+                        "Skipping duplicate retry of event: {}, Source: {}",
+                        event.event_id, event.source
+                    );
+                    continue;
+                }
+
+                info!("Retrying event: {}, attempt: {}", event.event_id, event.retry_attempt());
+                match handle_order_placed(stock_store.as_ref(), stock_underflow_policy, &event).await {
+                    StockUpdateOutcome::Changed => {
+                        catalog_version.fetch_add(1, Ordering::SeqCst);
+                    }
+                    StockUpdateOutcome::Unchanged => {}
+                    StockUpdateOutcome::Failed => {
+                        retry_order_placed(event_bus.as_ref(), &topics, retry_config, event).await
+                    }
+                }
+            }
+            received = receiver.recv() => match received {
+                Ok(event) => {
+                    let due = retry_due(event_bus.clock(), retry_config, &event);
+                    let index = scheduled.partition_point(|(scheduled_due, _)| *scheduled_due <= due);
+                    scheduled.insert(index, (due, event));
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Skipped {} retried events", skipped)
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+        }
+    }
+
+    info!("Draining the {} listener", topic::ORDER_PLACED_RETRY);
+    listener.unsubscribe();
+    let waiting = scheduled
+        .into_iter()
+        .map(|(_, event)| event)
+        .chain(std::iter::from_fn(|| try_next_received(&mut receiver)));
+    for event in waiting {
+        let (event_id, item_id) = (event.event_id.clone(), event.payload.item_id);
+        let topic_name = topics.resolve(topic::ORDER_PLACED_RETRY);
+        if let Err(e) = event_bus.broadcast_event(event, topic_name, item_id).await {
+            error!("Failed to republish event: {} to {}: {:?}", event_id, topic_name, e);
+        }
+    }
+    if let Err(e) = listener.commit() {
+        error!("Failed to commit the {} offsets: {:?}", topic::ORDER_PLACED_RETRY, e);
+    }
+}
+
+// the instant a retried event is due to be applied: its recorded retry time, or `delay` from now for a
+// retry republished without one
+fn retry_due(
+    clock: &dyn Clock,
+    retry_config: OrderPlacedRetryConfig,
+    event: &Event<OrderPlacedEvent>,
+) -> tokio::time::Instant {
+    let wait = match event.retry_at() {
+        Some(retry_at) => retry_at.duration_since(clock.now()).unwrap_or_default(),
+        None => retry_config.delay,
+    };
+    tokio::time::Instant::now() + wait
+}
+
+// republishes an order placed event that failed to be applied to the retry topic, or to the dead letter
+// topic once it has been attempted `max_attempts` times, logging rather than propagating any failure
+async fn retry_order_placed<E: EventProducer>(
+    event_bus: &E,
+    topics: &TopicConfig,
+    retry_config: OrderPlacedRetryConfig,
+    event: Event<OrderPlacedEvent>,
+) {
+    let attempts = event.retry_attempt() + 1;
+    let (topic_name, event) = if attempts < retry_config.max_attempts {
+        warn!(
+            "Event: {} failed to be applied, attempt: {}, retrying",
+            event.event_id, attempts
+        );
+        let retry_at = event_bus.clock().now() + retry_config.delay;
+        (
+            topics.resolve(topic::ORDER_PLACED_RETRY),
+            event.with_retry_attempt(attempts).with_retry_at(retry_at),
+        )
+    } else {
+        error!(
+            "Event: {} failed to be applied after {} attempts, dead-lettering",
+            event.event_id, attempts
+        );
+        (topics.resolve(topic::ORDER_PLACED_DEAD_LETTER), event)
+    };
+    let (event_id, item_id) = (event.event_id.clone(), event.payload.item_id);
+    if let Err(e) = event_bus.broadcast_event(event, topic_name, item_id).await {
+        error!("Failed to republish event: {} to {}: {:?}", event_id, topic_name, e);
+    }
+}
+
+// what a listener task receives whilst waiting for its next event
+#[derive(Debug, PartialEq)]
+enum Received<T> {
+    Event(T),
+    Shutdown,
+    Closed,
+}
+
+// waits for the next received event unless shutdown is signalled first, the caller finishes handling
+// each event before waiting again so that an event being handled is never interrupted by shutdown
+async fn next_until_shutdown<T: Clone>(
+    receiver: &mut broadcast::Receiver<T>,
+    shutdown: &mut watch::Receiver<bool>,
+) -> Received<T> {
+    tokio::select! {
+        biased;
+        _ = shutdown.changed() => Received::Shutdown,
+        received = receiver.recv() => match received {
+            Ok(event) => Received::Event(event),
+            Err(_) => Received::Closed,
+        },
+    }
+}
+
+// returns the next event already received without waiting for new ones
+fn try_next_received<T: Clone>(receiver: &mut broadcast::Receiver<T>) -> Option<T> {
+    loop {
+        match receiver.try_recv() {
+            Ok(event) => return Some(event),
+            Err(TryRecvError::Lagged(skipped)) => warn!("Skipped {} events whilst draining", skipped),
+            Err(_) => return None,
+        }
+    }
+}
+
+// applies an order placed event to the stock levels and ordered quantities, skipping events that have
+// already been processed and bumping the catalog version when the stock changes, and records how long
+// the event took to apply in the processing metrics
+async fn process_order_placed(
+    stock_store: &dyn StockStore,
+    catalog_version: &AtomicU64,
+    ordered_quantities: &RwLock<HashMap<u32, u64>>,
+    deduplicator: &mut EventDeduplicator,
+    stock_underflow_policy: StockUnderflowPolicy,
+    processing_metrics: &ProcessingMetrics,
+    event: &Event<OrderPlacedEvent>,
+) -> StockUpdateOutcome {
+    let started = Instant::now();
+    if deduplicator.is_duplicate(&event.event_id) {
+        warn!("Skipping duplicate event: {}, Source: {}", event.event_id, event.source);
+        return StockUpdateOutcome::Unchanged;
+    }
+
+    record_ordered_quantity(ordered_quantities, event.payload.item_id, event.payload.quantity);
+    let outcome = handle_order_placed(stock_store, stock_underflow_policy, event).await;
+    if outcome == StockUpdateOutcome::Changed {
+        catalog_version.fetch_add(1, Ordering::SeqCst);
+    }
+    processing_metrics.processing.record(started.elapsed());
+    outcome
+}
+
+// adds the quantity of a placed order to the running total for the ordered item
+fn record_ordered_quantity(ordered_quantities: &RwLock<HashMap<u32, u64>>, item_id: u32, quantity: u32) {
+    let mut ordered_quantities = locks::write(ordered_quantities);
+    *ordered_quantities.entry(item_id).or_insert(0) += quantity as u64;
+}
+
+/// Applies a `BackorderPlacedEvent` to the stock levels held in the catalog database.
+///
+/// Unlike an `OrderPlacedEvent`, the ordered quantity is never rejected for exceeding the stock:
+/// whatever is available is taken and the rest is added to the item's backordered quantity, which
+/// is filled when the item is restocked. Events for unknown items are ignored.
+///
+/// Arguments:
+/// - `db`: The catalog database shared with the `CatalogService`.
+/// - `catalog_version`: Bumped when the stock of the item changes.
+/// - `ordered_quantities`: The running total of the quantity ordered of each item.
+/// - `event`: The backorder placed event received from the event bus.
+fn handle_backorder_placed<D: for<'a> CatalogDb<'a>>(
+    db: &RwLock<D>,
+    catalog_version: &AtomicU64,
+    ordered_quantities: &RwLock<HashMap<u32, u64>>,
+    event: &Event<BackorderPlacedEvent>,
+) {
+    let item_id = event.payload.item_id;
+    record_ordered_quantity(ordered_quantities, item_id, event.payload.quantity);
+    match locks::write(db).record_backorder(item_id, event.payload.quantity) {
+        Ok((stock, backordered)) => {
+            info!(
+                "Stock level for item: {} is now: {}, with {} backordered",
+                item_id, stock, backordered
+            );
+            catalog_version.fetch_add(1, Ordering::SeqCst);
+        }
+        Err(e) => error!("Failed to record a backorder for item: {}, {:?}", item_id, e),
+    }
+}
+
+/// Applies an `OrderPlacedEvent` to the stock levels held in the stock store.
+///
+/// The stock of the ordered item is decremented by the ordered quantity. If the quantity exceeds
+/// the current stock the `stock_underflow_policy` decides whether the event is skipped or the stock
+/// is clamped to zero. Events for unknown items are ignored.
+///
+/// The stock store only decrements the stock if the item is still at the version its stock was
+/// read at, so if another consumer changes the item between reading and decrementing its stock, the
+/// stock is read again and the update retried, up to `MAX_STOCK_UPDATE_ATTEMPTS` times.
+///
+/// Arguments:
+/// - `stock_store`: The stock store shared with the `CatalogService`.
+/// - `stock_underflow_policy`: How to apply an event whose quantity exceeds the current stock.
+/// - `event`: The order placed event received from the event bus.
+///
+/// Returns:
+/// - `StockUpdateOutcome`: Whether the stock level of an item was changed, left unchanged, e.g. for an
+///   unknown item, or could not be updated because the item kept changing, in which case the event
+///   can be retried.
+async fn handle_order_placed(
+    stock_store: &dyn StockStore,
+    stock_underflow_policy: StockUnderflowPolicy,
+    event: &Event<OrderPlacedEvent>,
+) -> StockUpdateOutcome {
+    let item_id = event.payload.item_id;
+    for attempt in 1..=MAX_STOCK_UPDATE_ATTEMPTS {
+        let (stock, version) = match stock_store.get(item_id).await {
+            None => return StockUpdateOutcome::Unchanged,
+            Some(level) => (level.stock, level.version),
+        };
+
+        let mut decrement_by = event.payload.quantity;
+        if decrement_by > stock {
+            match stock_underflow_policy {
+                StockUnderflowPolicy::Skip => {
+                    error!(
+                        "Event to change stock levels has failed, Source: {}, Amount to change: {}, Current Amount: {}",
+                        event.source, event.payload.quantity, stock
+                    );
+                    return StockUpdateOutcome::Unchanged;
+                }
+                StockUnderflowPolicy::ClampToZero => {
+                    warn!(
+                        "Stock level for item: {} clamped to zero, Source: {}, \
+                        Amount to change: {}, Current Amount: {}",
+                        item_id, event.source, event.payload.quantity, stock
+                    );
+                    decrement_by = stock;
+                }
+            }
+        }
+
+        match stock_store.decrement(item_id, decrement_by, version).await {
+            Ok(stock_amount) => {
+                info!("Stock level for item: {} is now: {}", item_id, stock_amount);
+                return StockUpdateOutcome::Changed;
+            }
+            Err(StockUpdateError::NotFound) => return StockUpdateOutcome::Unchanged,
+            Err(e) => warn!(
+                "Stock level for item: {} changed whilst being updated, attempt: {}, {:?}",
+                item_id, attempt, e
+            ),
+        }
     }
+
+    error!(
+        "Event to change stock levels has failed after {} attempts, Source: {}, Item: {}",
+        MAX_STOCK_UPDATE_ATTEMPTS, event.source, item_id
+    );
+    StockUpdateOutcome::Failed
 }
 
-impl<E: EventListener, D: for<'a> CatalogDb<'a>> CatalogService<E, D> {
+impl<E: EventListener, D: for<'a> CatalogDb<'a> + Send + Sync + 'static> CatalogService<E, D> {
     /// Creates a new instance of `CatalogService`.
     ///
     /// This method initializes the service with a given mock catalog database.
@@ -66,90 +825,667 @@ impl<E: EventListener, D: for<'a> CatalogDb<'a>> CatalogService<E, D> {
     /// - `CatalogService`: A new instance of `CatalogService`.
     pub fn new(db: D, event_bus: E) -> CatalogService<E, D> {
         let db = Arc::new(RwLock::new(db));
-        CatalogService { event_bus, db }
+        let processing_metrics = Arc::new(ProcessingMetrics::new());
+        let mut stock_store = CatalogDbStockStore::new(db.clone());
+        stock_store.set_lock_wait(processing_metrics.lock_wait.clone());
+        CatalogService {
+            event_bus: Arc::new(event_bus),
+            stock_store: Arc::new(stock_store),
+            db,
+            stock_underflow_policy: StockUnderflowPolicy::default(),
+            catalog_version: Arc::new(AtomicU64::new(0)),
+            ordered_quantities: Arc::new(RwLock::new(HashMap::new())),
+            shutdown: watch::channel(false).0,
+            listener_task: Mutex::new(None),
+            order_placed_listener: Mutex::new(None),
+            order_metrics: None,
+            metrics_task: Mutex::new(None),
+            processing_metrics,
+            paused: Arc::new(AtomicBool::new(false)),
+            resumed: Arc::new(Notify::new()),
+            retry_config: OrderPlacedRetryConfig::default(),
+            retry_task: Mutex::new(None),
+            reservations: Arc::new(ReservationStore::new()),
+            reservation_ttl: reservations::DEFAULT_RESERVATION_TTL,
+            clock: Arc::new(SystemClock),
+            sweeper_task: Mutex::new(None),
+            backorder_task: Mutex::new(None),
+            topics: TopicConfig::new(),
+        }
+    }
+
+    /// Retrieves statistics about the event listeners, for debugging their lifecycle.
+    pub fn get_listener_stats(&self) -> ListenerStats {
+        let order_placed_listener = self.order_placed_listener.lock().unwrap();
+        let order_placed_receivers = order_placed_listener.as_ref().map_or(0, |listener| listener.receiver_count());
+        let order_placed_topics =
+            order_placed_listener.as_ref().map_or_else(Vec::new, |listener| listener.topics().to_vec());
+        ListenerStats {
+            order_placed_receivers,
+            order_placed_topics,
+        }
+    }
+
+    /// Checks the health of the catalog, which depends on its `ORDER_PLACED` consumer being connected
+    /// to Kafka, as otherwise placed orders are never taken from the stock.
+    pub fn get_health(&self) -> CatalogHealth {
+        let order_placed_consumer_connected =
+            self.order_placed_listener.lock().unwrap().as_ref().is_some_and(|listener| listener.is_connected());
+        CatalogHealth {
+            healthy: order_placed_consumer_connected,
+            order_placed_consumer_connected,
+            degraded: self.is_degraded(),
+            listener_paused: self.paused.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Pauses applying `OrderPlacedEvent`s to the stock, e.g. during a catalog data migration.
+    ///
+    /// The partitions of the listener's consumer are paused, so further events wait in Kafka, and
+    /// the events already received whilst pausing are held by the listener, up to
+    /// `MAX_PAUSED_EVENTS` of them with the rest left in its channel. The held events are applied, in
+    /// order, by `resume_listener`, or by `drain`.
+    pub fn pause_listener(&self) {
+        info!("Pausing the {} listener", topic::ORDER_PLACED);
+        self.paused.store(true, Ordering::SeqCst);
+        if let Some(listener) = self.order_placed_listener.lock().unwrap().as_ref() {
+            if let Err(e) = listener.pause() {
+                error!("Failed to pause the {} consumer: {:?}", topic::ORDER_PLACED, e);
+            }
+        }
+    }
+
+    /// Resumes applying `OrderPlacedEvent`s to the stock, first applying those held whilst paused.
+    pub fn resume_listener(&self) {
+        info!("Resuming the {} listener", topic::ORDER_PLACED);
+        if let Some(listener) = self.order_placed_listener.lock().unwrap().as_ref() {
+            if let Err(e) = listener.resume() {
+                error!("Failed to resume the {} consumer: {:?}", topic::ORDER_PLACED, e);
+            }
+        }
+        self.paused.store(false, Ordering::SeqCst);
+        self.resumed.notify_one();
+    }
+
+    /// Checks whether the catalog is degraded, i.e. its `ORDER_PLACED` listener was started but has
+    /// `ListenerHealth::Failed`, as its supervisor could not restart its consumer. Reads are still
+    /// served from the db, but placed orders are no longer taken from the stock.
+    pub fn is_degraded(&self) -> bool {
+        self.order_placed_listener
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|listener| listener.health() == ListenerHealth::Failed)
+    }
+
+    // retrieves the `ORDER_PLACED` listener once the listeners are started, so tests can simulate its loss
+    #[cfg(test)]
+    pub(crate) fn order_placed_listener(&self) -> Option<Arc<KafkaListener<Event<OrderPlacedEvent>>>> {
+        self.order_placed_listener.lock().unwrap().clone()
+    }
+
+    /// Drains and stops the event listener, e.g. when the service is shutting down.
+    ///
+    /// The listener stops pulling new messages, finishes processing every event it has already
+    /// received and commits their offsets before stopping. Does nothing if the listeners were not
+    /// started.
+    pub async fn drain(&self) {
+        // sending only fails when the listener task has already ended
+        let _ = self.shutdown.send(true);
+        let handle = self.listener_task.lock().unwrap().take();
+        if let Some(handle) = handle {
+            if let Err(e) = handle.await {
+                error!(
+                    "The {} listener task died whilst draining: {:?}",
+                    topic::ORDER_PLACED,
+                    e
+                );
+            }
+        }
+        let handle = self.metrics_task.lock().unwrap().take();
+        if let Some(handle) = handle {
+            if let Err(e) = handle.await {
+                error!("The order metrics task died whilst draining: {:?}", e);
+            }
+        }
+        let handle = self.retry_task.lock().unwrap().take();
+        if let Some(handle) = handle {
+            if let Err(e) = handle.await {
+                error!(
+                    "The {} listener task died whilst draining: {:?}",
+                    topic::ORDER_PLACED_RETRY,
+                    e
+                );
+            }
+        }
+        let handle = self.sweeper_task.lock().unwrap().take();
+        if let Some(handle) = handle {
+            if let Err(e) = handle.await {
+                error!("The reservation sweeper task died whilst draining: {:?}", e);
+            }
+        }
+        let handle = self.backorder_task.lock().unwrap().take();
+        if let Some(handle) = handle {
+            if let Err(e) = handle.await {
+                error!(
+                    "The {} listener task died whilst draining: {:?}",
+                    topic::BACKORDER_PLACED,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Retrieves the current version of the catalog.
+    ///
+    /// The version is bumped whenever the stock of an item changes, so it can be used to detect
+    /// whether the catalog has changed since it was last retrieved.
+    ///
+    /// Returns:
+    /// - `u64`: The current catalog version.
+    pub fn get_catalog_version(&self) -> u64 {
+        self.catalog_version.load(Ordering::SeqCst)
+    }
+
+    // applies an order placed event without going through the event listener, used to drive the service in
+    // tests and by the debug endpoints
+    #[cfg(any(test, feature = "debug-endpoints"))]
+    pub(crate) async fn apply_order_placed(&self, event: &Event<OrderPlacedEvent>) {
+        record_ordered_quantity(&self.ordered_quantities, event.payload.item_id, event.payload.quantity);
+        if handle_order_placed(self.stock_store.as_ref(), self.stock_underflow_policy, event).await
+            == StockUpdateOutcome::Changed
+        {
+            self.catalog_version.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Applies an `OrderPlacedEvent` to the stock as if it had been received by the event listener,
+    /// so the stock can be driven over HTTP without Kafka, see `POST /debug/order-placed`.
+    ///
+    /// Arguments:
+    /// - `order_placed`: The placed order to apply.
+    ///
+    /// Returns:
+    /// - `Result<u32, ItemNotFoundError>`: The stock of the item once the order has been applied, or
+    ///   `Err(ItemNotFoundError)` if the item does not exist.
+    #[cfg(feature = "debug-endpoints")]
+    pub async fn inject_order_placed(&self, order_placed: OrderPlacedEvent) -> Result<u32, ItemNotFoundError> {
+        let item_id = order_placed.item_id;
+        self.get_stock(item_id)?;
+        info!("Injecting an order placed event for item: {}", item_id);
+        let event = Event::new(
+            "order_placed".to_string(),
+            order_placed,
+            "Debug".to_string(),
+            None,
+            None,
+        );
+        self.apply_order_placed(&event).await;
+        self.get_stock(item_id)
+    }
+
+    /// Enables the per-minute order metrics, which are aggregated from `OrderPlacedEvent`s by a
+    /// separate task of the `ORDER_PLACED` listener.
+    ///
+    /// This must be called before `start_event_listeners` to take effect.
+    ///
+    /// Arguments:
+    /// - `window_minutes`: The number of most recent minutes the metrics are kept for.
+    pub fn enable_order_metrics(&mut self, window_minutes: usize) {
+        self.order_metrics = Some(Arc::new(OrderMetrics::new(window_minutes)));
+    }
+
+    /// Retrieves the number of orders placed, and their total quantity, in each minute of the
+    /// metrics window ending now, oldest first.
+    ///
+    /// Returns:
+    /// - `Option<Vec<MinuteOrderMetrics>>`: The metrics of each minute, or `None` if the order
+    ///   metrics are not enabled.
+    pub fn get_order_metrics(&self) -> Option<Vec<MinuteOrderMetrics>> {
+        self.order_metrics.as_ref().map(|order_metrics| order_metrics.get_minutes(SystemTime::now()))
+    }
+
+    /// Retrieves how long the `ORDER_PLACED` listener has taken to apply each event, and to acquire
+    /// the write lock of the catalog database to decrement the stock whilst doing so.
+    ///
+    /// Returns:
+    /// - `ProcessingMetricsSnapshot`: The latency histograms of the listener.
+    pub fn get_processing_metrics(&self) -> ProcessingMetricsSnapshot {
+        self.processing_metrics.snapshot()
+    }
+
+    /// Sets the policy applied by the event listener when an order exceeds the available stock.
+    ///
+    /// This must be called before `start_event_listeners` to take effect.
+    ///
+    /// Arguments:
+    /// - `stock_underflow_policy`: The `StockUnderflowPolicy` to apply.
+    pub fn set_stock_underflow_policy(&mut self, stock_underflow_policy: StockUnderflowPolicy) {
+        self.stock_underflow_policy = stock_underflow_policy;
+    }
+
+    /// Sets how `OrderPlacedEvent`s that fail to be applied to the stock are retried.
+    ///
+    /// This must be called before `start_event_listeners` to take effect.
+    ///
+    /// Arguments:
+    /// - `retry_config`: The `OrderPlacedRetryConfig` to apply.
+    pub fn set_order_placed_retry_config(&mut self, retry_config: OrderPlacedRetryConfig) {
+        self.retry_config = retry_config;
+    }
+
+    /// Sets the store the event listener applies `OrderPlacedEvent`s to, instead of the stock held
+    /// on each item in the catalog database.
+    ///
+    /// This must be called before `start_event_listeners` to take effect. The `lock_wait` of the
+    /// processing metrics is only recorded by the default store, so it stays empty once this is set.
+    ///
+    /// Arguments:
+    /// - `stock_store`: The `StockStore` to apply stock changes to.
+    pub fn set_stock_store(&mut self, stock_store: Arc<dyn StockStore>) {
+        self.stock_store = stock_store;
+    }
+
+    /// Sets how long a reservation holds its stock before the sweeper releases it back to the
+    /// available stock, `DEFAULT_RESERVATION_TTL` unless set.
+    ///
+    /// Arguments:
+    /// - `reservation_ttl`: How long each reservation made from now on lasts.
+    pub fn set_reservation_ttl(&mut self, reservation_ttl: Duration) {
+        self.reservation_ttl = reservation_ttl;
+    }
+
+    /// Sets the concrete topics events are produced to and consumed from, instead of the topics named
+    /// by the constants of `event_bus::topic`.
+    ///
+    /// This must be called before `start_event_listeners` to take effect for the listeners.
+    ///
+    /// Arguments:
+    /// - `topics`: The `TopicConfig` to resolve topics through.
+    pub fn set_topic_config(&mut self, topics: TopicConfig) {
+        self.topics = topics;
+    }
+
+    // mock method necessary for testing, sets the clock reservations are expired by, which must be set
+    // before `start_event_listeners` to take effect for the sweeper
+    #[cfg(test)]
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
     }
 
     /// Retrieves a list of available catalog items.
     ///
     /// This method returns a vector of `ClothingItemDTO` representing the items
-    /// currently available in the catalog. It filters out items that have a stock of 0 or less,
-    /// ensuring only items available for purchase are returned.
+    /// currently available in the catalog. Unless `include_out_of_stock` is set, it filters out
+    /// items that have a stock of 0 or less, ensuring only items available for purchase are returned.
+    ///
+    /// Arguments:
+    /// - `include_out_of_stock`: Whether items with no stock should also be returned.
     ///
     /// Returns:
     /// - `Vec<ClothingItemDTO>`: A vector of DTOs for each available item in the catalog.
-    pub fn get_items(&self) -> Vec<ClothingItemDTO> {
+    pub fn get_items(&self, include_out_of_stock: bool) -> Vec<ClothingItemDTO> {
         info!("Handling a request view the catalog");
-        let db = self.db.read().unwrap();
+        let db = locks::read(&self.db);
         let items = db.get_catalog();
-        items.into_iter().filter(|item| item.stock > 0).map(ClothingItemDTO::from).collect()
+        items
+            .into_iter()
+            .filter(|item| include_out_of_stock || item.stock > 0)
+            .map(ClothingItemDTO::from)
+            .collect()
+    }
+
+    /// Resolves a reference to a catalog item into the item's ID.
+    ///
+    /// Arguments:
+    /// - `item`: The `ItemRef` referencing the item by its ID or by its SKU.
+    ///
+    /// Returns:
+    /// - `Result<u32, ItemNotFoundError>`: The ID of the item, or `Err(ItemNotFoundError)` if no item
+    ///   has the referenced ID or SKU.
+    pub fn resolve_item_id(&self, item: &ItemRef) -> Result<u32, ItemNotFoundError> {
+        let db = locks::read(&self.db);
+        let item = match item {
+            ItemRef::Id(item_id) => db.get_item(*item_id),
+            ItemRef::Sku(sku) => db.get_item_by_sku(sku),
+        };
+        item.map(|item| item.id).ok_or(ItemNotFoundError)
     }
 
     /// Retrieves the stock quantity of a specific item in the catalog.
     ///
     /// This method searches the catalog database for an item with the given `item_id`.
-    /// If the item exists, it returns the current stock quantity of that item.
+    /// If the item exists, it returns the stock of that item that is available to order.
     ///
     /// Arguments:
     /// - `item_id`: A `u32` identifier of the catalog item whose stock is being queried.
     ///
     /// Returns:
     /// - `Result<u32, ItemNotFoundError>`: On success, returns `Ok(u32)` representing the
-    ///   stock quantity of the item. If the item is not found in the catalog, returns
-    ///   `Err(ItemNotFoundError)`.
+    ///   available stock of the item, i.e. its on-hand stock less its reserved quantity. If the
+    ///   item is not found in the catalog, returns `Err(ItemNotFoundError)`.
     ///
     /// Example:
-    /// ```
+    /// ```ignore
     /// let service = CatalogService::new(mock_db);
-    /// let stock = service.get_stock(123).expect("Item should exist");
+    /// // the on-hand stock of the item, less the quantity currently reserved
+    /// let available_stock = service.get_stock(123).expect("Item should exist");
     /// ```
     pub fn get_stock(&self, item_id: u32) -> Result<u32, ItemNotFoundError> {
         info!("Handling a request to get the stock of item: {}", item_id);
-        let db = self.db.read().unwrap();
+        let db = locks::read(&self.db);
         let item = db.get_item(item_id);
         if item.is_none() {
             return Err(ItemNotFoundError);
         }
 
-        Ok(item.unwrap().stock)
+        Ok(item.unwrap().available())
     }
-}
 
-/// `ClothingItemDTO` is a Data Transfer Object for `ClothingItem`.
-///
-/// This struct is used to communicate data about clothing items to clients,
-/// excluding certain fields that are not necessary or should be kept private.
-/// Specifically, it omits the `stock` field present in the `ClothingItem` struct.
-/// The purpose of this struct is for transmitting it to the client so that they have
-/// no knowledge of the stock of the item.
-///
-/// Fields:
-/// - `id`: The unique identifier for the clothing item.
-/// - `name`: The name of the clothing item.
-/// - `description`: A description of the clothing item.
-/// - `sizes`: A list of available sizes for the clothing item.
-/// - `price`: The price of the clothing item.
-/// - `images`: URLs to images of the clothing item.
-/// - `video`: A URL to a video showcasing the clothing item.
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct ClothingItemDTO {
-    pub id: u32,
-    pub name: String,
-    pub description: String,
-    pub sizes: Vec<String>,
-    pub price: f32,
-    pub images: Vec<String>,
-    pub video: String,
-}
+    /// Retrieves the stock levels of several items at once.
+    ///
+    /// Arguments:
+    /// - `item_ids`: The identifiers of the catalog items whose stock is being queried.
+    ///
+    /// Returns:
+    /// - `HashMap<u32, u32>`: The available stock of each item keyed by its identifier. Items that are not in
+    ///   the catalog are omitted.
+    pub fn get_stock_batch(&self, item_ids: &[u32]) -> HashMap<u32, u32> {
+        info!("Handling a request to get the stock of items: {:?}", item_ids);
+        let db = locks::read(&self.db);
+        item_ids
+            .iter()
+            .filter_map(|item_id| db.get_item(*item_id).map(|item| (item.id, item.available())))
+            .collect()
+    }
 
-impl From<&ClothingItem> for ClothingItemDTO {
-    fn from(item: &ClothingItem) -> Self {
-        ClothingItemDTO {
-            id: item.id.clone(),
-            name: item.name.clone(),
-            description: item.description.clone(),
-            sizes: item.sizes.clone(),
-            price: item.price.clone(),
+    /// Retrieves the details of a single catalog item.
+    ///
+    /// This method searches the catalog database for an item with the given `item_id` and
+    /// returns it as a `ClothingItemDTO`. Items with a stock of 0 are treated as not found,
+    /// matching the filtering applied by `get_items`, unless `include_out_of_stock` is set.
+    ///
+    /// Arguments:
+    /// - `item_id`: A `u32` identifier of the catalog item being queried.
+    /// - `include_out_of_stock`: Whether an item with no stock should still be returned.
+    ///
+    /// Returns:
+    /// - `Result<ClothingItemDTO, ItemNotFoundError>`: On success, returns the DTO of the item.
+    ///   If the item is not found, or is out of stock and not included, returns `Err(ItemNotFoundError)`.
+    pub fn get_item_dto(&self, item_id: u32, include_out_of_stock: bool) -> Result<ClothingItemDTO, ItemNotFoundError> {
+        info!("Handling a request to get the details of item: {}", item_id);
+        let db = locks::read(&self.db);
+        match db.get_item(item_id) {
+            Some(item) if include_out_of_stock || item.stock > 0 => Ok(ClothingItemDTO::from(item)),
+            _ => Err(ItemNotFoundError),
+        }
+    }
+
+    /// Retrieves a summary of the orders placed for a single catalog item.
+    ///
+    /// The ordered quantity is maintained by the event listener from the `OrderPlacedEvent` stream,
+    /// so it includes orders whose quantity could not be taken from the stock.
+    ///
+    /// Arguments:
+    /// - `item_id`: A `u32` identifier of the catalog item being queried.
+    ///
+    /// Returns:
+    /// - `Result<OrderSummary, ItemNotFoundError>`: On success, returns the `OrderSummary` of the item.
+    ///   If the item is not found in the catalog, returns `Err(ItemNotFoundError)`.
+    pub fn get_order_summary(&self, item_id: u32) -> Result<OrderSummary, ItemNotFoundError> {
+        info!("Handling a request to get the order summary of item: {}", item_id);
+        if locks::read(&self.db).get_item(item_id).is_none() {
+            return Err(ItemNotFoundError);
+        }
+
+        let ordered_quantities = locks::read(&self.ordered_quantities);
+        Ok(OrderSummary {
+            item_id,
+            total_quantity_ordered: ordered_quantities.get(&item_id).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl<E: EventListener + EventProducer, D: for<'a> CatalogDb<'a>> CatalogService<E, D> {
+    /// Adds an item to the catalog, or replaces the existing item with the same ID.
+    ///
+    /// On success the catalog version is bumped and a `CatalogItemChangedEvent` is broadcast so that
+    /// other services can invalidate anything they hold about the item. A failure to broadcast the
+    /// event is logged but not propagated.
+    ///
+    /// Arguments:
+    /// - `item`: The `ClothingItem` to save.
+    ///
+    /// Returns:
+    /// - `Result<CatalogItemChange, AddItemError>`: Whether the item was `Added` or `Updated`, or an
+    ///   `AddItemError` if the item was rejected.
+    pub async fn save_item(&self, item: ClothingItem) -> Result<CatalogItemChange, AddItemError> {
+        info!("Handling a request to save item: {}", item.id);
+        let item_id = item.id;
+        let change = {
+            let mut db = locks::write(&self.db);
+            let change = if db.get_item(item_id).is_some() {
+                CatalogItemChange::Updated
+            } else {
+                CatalogItemChange::Added
+            };
+            db.add_item(item)?;
+            change
+        };
+
+        self.catalog_version.fetch_add(1, Ordering::SeqCst);
+        self.broadcast_item_changed(item_id, change).await;
+        Ok(change)
+    }
+
+    /// Imports several items into the catalog at once, e.g. when seeding the catalog for a test environment.
+    ///
+    /// The import is all-or-nothing: items whose id is already in the catalog, or earlier in the import,
+    /// are skipped, but if any other item is rejected none of the items are added. Once the items are
+    /// added the catalog version is bumped and a `CatalogItemChangedEvent` is broadcast for each of them.
+    ///
+    /// Arguments:
+    /// - `items`: The items to import.
+    ///
+    /// Returns:
+    /// - `Result<ImportSummary, ImportItemsError>`: The number of items inserted and skipped, or the
+    ///   first item that was rejected and why.
+    pub async fn import_items(&self, items: Vec<ClothingItem>) -> Result<ImportSummary, ImportItemsError> {
+        info!("Handling a request to import {} items", items.len());
+        let mut inserted = Vec::new();
+        let mut skipped = 0;
+        {
+            let mut db = locks::write(&self.db);
+            let mut seen = HashSet::new();
+            for item in items {
+                let item_id = item.id;
+                if !seen.insert(item_id) || db.get_item(item_id).is_some() {
+                    skipped += 1;
+                    continue;
+                }
+                if let Err(error) = db.add_item(item) {
+                    // every inserted item was new, so removing them restores the catalog
+                    for inserted_id in inserted {
+                        db.remove_item(inserted_id);
+                    }
+                    return Err(ImportItemsError { item_id, error });
+                }
+                inserted.push(item_id);
+            }
+        }
+
+        if !inserted.is_empty() {
+            self.catalog_version.fetch_add(1, Ordering::SeqCst);
+        }
+        for item_id in &inserted {
+            self.broadcast_item_changed(*item_id, CatalogItemChange::Added).await;
+        }
+        Ok(ImportSummary {
+            inserted: inserted.len(),
+            skipped,
+        })
+    }
+
+    /// Exports every item of the catalog, including its stock, ordered by id, e.g. to back up the
+    /// catalog or inspect its state. The export can be restored to an empty catalog with `import_items`.
+    ///
+    /// The reservations are not exported, as they could never be released once the export is restored,
+    /// so every item is exported with no `reserved` quantity and its reserved stock is available again.
+    ///
+    /// Returns:
+    /// - `Vec<ClothingItem>`: A copy of every item in the catalog.
+    pub fn export_snapshot(&self) -> Vec<ClothingItem> {
+        info!("Handling a request to export the catalog");
+        let db = locks::read(&self.db);
+        let mut items: Vec<ClothingItem> = db.get_catalog().into_iter().cloned().collect();
+        items.sort_by_key(|item| item.id);
+        // the reserved quantity is part of the on-hand stock, so releasing it leaves the stock as it is
+        for item in &mut items {
+            item.reserved = 0;
+        }
+        items
+    }
+
+    /// Removes an item from the catalog.
+    ///
+    /// On success the catalog version is bumped and a `CatalogItemChangedEvent` is broadcast. A
+    /// failure to broadcast the event is logged but not propagated.
+    ///
+    /// Arguments:
+    /// - `item_id`: A `u32` identifier of the catalog item to remove.
+    ///
+    /// Returns:
+    /// - `Result<(), ItemNotFoundError>`: `Ok(())` if the item was removed, or `Err(ItemNotFoundError)`
+    ///   if it does not exist.
+    pub async fn remove_item(&self, item_id: u32) -> Result<(), ItemNotFoundError> {
+        info!("Handling a request to remove item: {}", item_id);
+        if locks::write(&self.db).remove_item(item_id).is_none() {
+            return Err(ItemNotFoundError);
+        }
+
+        self.catalog_version.fetch_add(1, Ordering::SeqCst);
+        self.broadcast_item_changed(item_id, CatalogItemChange::Removed).await;
+        Ok(())
+    }
+
+    /// Sets the stock of an item to an absolute value, e.g. after a physical count of the inventory.
+    ///
+    /// Unlike the stock changes made by the event listener, the stock is overwritten regardless of
+    /// the orders applied to it. The new stock first fills the item's backordered quantity, see
+    /// `ClothingItem::fill_backorders`. The version of the item is bumped, so a concurrent decrement
+    /// of the stock it replaced is retried against the new stock. On success the catalog version is
+    /// bumped and a `CatalogItemChangedEvent` is broadcast, so that other services forget the stock
+    /// they hold for the item. A failure to broadcast the event is logged but not propagated.
+    ///
+    /// Arguments:
+    /// - `item_id`: A `u32` identifier of the catalog item.
+    /// - `stock`: The new stock of the item.
+    ///
+    /// Returns:
+    /// - `Result<StockChange, ItemNotFoundError>`: The stock before it was set and the stock left
+    ///   once the backorders were filled, or `Err(ItemNotFoundError)` if the item does not exist.
+    pub async fn set_stock(&self, item_id: u32, stock: u32) -> Result<StockChange, ItemNotFoundError> {
+        info!("Handling a request to set the stock of item: {} to: {}", item_id, stock);
+        let (previous_stock, stock) = {
+            let mut db = locks::write(&self.db);
+            let item = db.get_mut_item(item_id).ok_or(ItemNotFoundError)?;
+            let previous_stock = item.stock;
+            item.stock = stock;
+            item.fill_backorders();
+            item.version += 1;
+            (previous_stock, item.stock)
+        };
+
+        self.catalog_version.fetch_add(1, Ordering::SeqCst);
+        self.broadcast_item_changed(item_id, CatalogItemChange::Updated).await;
+        Ok(StockChange {
+            item_id,
+            previous_stock,
+            stock,
+        })
+    }
+
+    /// Reserves a quantity of the stock of an item for an order that is yet to be placed.
+    ///
+    /// The quantity is no longer available to other orders until the reservation is released, or
+    /// until it expires after the reservation TTL, when the sweeper releases it.
+    ///
+    /// Arguments:
+    /// - `item_id`: A `u32` identifier of the catalog item.
+    /// - `quantity`: The quantity to reserve.
+    ///
+    /// Returns:
+    /// - `Result<Reservation, ReservationError>`: The reservation, or why the stock could not be reserved.
+    pub fn reserve_stock(&self, item_id: u32, quantity: u32) -> Result<Reservation, ReservationError> {
+        info!("Handling a request to reserve {} of item: {}", quantity, item_id);
+        locks::write(&self.db).reserve(item_id, quantity).map_err(|err| match err {
+            StockUpdateError::InsufficientStock { stock } => ReservationError::InsufficientStock { available: stock },
+            _ => ReservationError::ItemNotFound,
+        })?;
+
+        self.catalog_version.fetch_add(1, Ordering::SeqCst);
+        Ok(self.reservations.add(item_id, quantity, self.clock.now() + self.reservation_ttl))
+    }
+
+    /// Releases a reservation before it expires, e.g. when its order is abandoned, returning its
+    /// quantity to the available stock of the item.
+    ///
+    /// Arguments:
+    /// - `reservation_id`: The identifier of the reservation.
+    ///
+    /// Returns:
+    /// - `Result<Reservation, ReservationError>`: The released reservation, or
+    ///   `Err(ReservationError::ReservationNotFound)` if it was already released or has expired.
+    pub fn release_reservation(&self, reservation_id: u64) -> Result<Reservation, ReservationError> {
+        info!("Handling a request to release reservation: {}", reservation_id);
+        let reservation = self.reservations.remove(reservation_id).ok_or(ReservationError::ReservationNotFound)?;
+        release_reserved(&self.db, &self.catalog_version, &reservation);
+        Ok(reservation)
+    }
+
+    // mock method necessary for testing, releases the stock of every expired reservation as the sweeper
+    // does periodically, returning how many were released
+    #[cfg(test)]
+    pub fn release_expired_reservations(&self) -> usize {
+        release_expired_reservations(&self.db, &self.reservations, &self.catalog_version, self.clock.now())
+    }
+
+    // notifies other services that an item has changed, logging rather than propagating any failure
+    async fn broadcast_item_changed(&self, item_id: u32, change: CatalogItemChange) {
+        let event = self.event_bus.create_event(
+            "catalog_item_changed".to_string(),
+            CatalogItemChangedEvent { item_id, change },
+            "Catalog".to_string(),
+            None,
+            None,
+        );
+
+        self.event_bus
+            .broadcast_event(event, self.topics.resolve(topic::CATALOG_ITEM_CHANGED), item_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Could not send {} event, error occurred: {:?}",
+                    topic::CATALOG_ITEM_CHANGED,
+                    err
+                );
+            })
+            .ok();
+    }
+}
+
+impl From<&ClothingItem> for ClothingItemDTO {
+    fn from(item: &ClothingItem) -> Self {
+        ClothingItemDTO {
+            id: item.id,
+            sku: item.sku.clone(),
+            name: item.name.clone(),
+            description: item.description.clone(),
+            sizes: item.sizes.clone(),
+            price: Money::from_major(item.price as f64, &item.currency),
+            currency: item.currency.clone(),
             images: item.images.clone(),
+            allow_backorder: item.allow_backorder,
             video: item.video.clone(),
         }
     }
@@ -158,20 +1494,103 @@ impl From<&ClothingItem> for ClothingItemDTO {
 #[derive(Debug)]
 pub struct ItemNotFoundError;
 
+impl From<ItemNotFoundError> for ServiceError {
+    fn from(_: ItemNotFoundError) -> Self {
+        ServiceError::NotFound("This item does not exist.".to_string())
+    }
+}
+
+/// `ImportItemsError` describes the item that caused an import to be rejected.
+///
+/// Fields:
+/// - `item_id`: The identifier of the rejected item.
+/// - `error`: Why the item was rejected.
+#[derive(Debug, PartialEq)]
+pub struct ImportItemsError {
+    pub item_id: u32,
+    pub error: AddItemError,
+}
+
+impl From<ImportItemsError> for ServiceError {
+    fn from(err: ImportItemsError) -> Self {
+        let prefix = format!("Nothing was imported, item {} was rejected:", err.item_id);
+        match ServiceError::from(err.error) {
+            ServiceError::BadRequest(message) => ServiceError::BadRequest(format!("{} {}", prefix, message)),
+            ServiceError::Conflict(message) => ServiceError::Conflict(format!("{} {}", prefix, message)),
+            other => other,
+        }
+    }
+}
+
+/// `ReservationError` describes why stock could not be reserved or a reservation released.
+///
+/// Variants:
+/// - `ItemNotFound`: The item does not exist.
+/// - `ReservationNotFound`: The reservation does not exist, or was already released or has expired.
+/// - `InsufficientStock`: Less than the quantity is available, carrying the available stock.
+#[derive(Debug, PartialEq)]
+pub enum ReservationError {
+    ItemNotFound,
+    ReservationNotFound,
+    InsufficientStock { available: u32 },
+}
+
+impl From<ReservationError> for ServiceError {
+    fn from(err: ReservationError) -> Self {
+        match err {
+            ReservationError::ItemNotFound => ServiceError::NotFound("This item does not exist.".to_string()),
+            ReservationError::ReservationNotFound => {
+                ServiceError::NotFound("This reservation does not exist or has expired.".to_string())
+            }
+            ReservationError::InsufficientStock { available } => {
+                ServiceError::Conflict(format!("Only {} of the item is available to reserve.", available))
+            }
+        }
+    }
+}
+
+impl From<AddItemError> for ServiceError {
+    fn from(err: AddItemError) -> Self {
+        match err {
+            AddItemError::InvalidCurrency => {
+                ServiceError::BadRequest("The currency of the item is not known.".to_string())
+            }
+            AddItemError::DuplicateSku => {
+                ServiceError::Conflict("The SKU of the item belongs to another item.".to_string())
+            }
+            AddItemError::NumericSku => {
+                ServiceError::BadRequest("The SKU of the item must not be a number.".to_string())
+            }
+            AddItemError::CatalogFull { max_items } => ServiceError::Conflict(format!(
+                "The catalog is full, it cannot hold more than {} items.",
+                max_items
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::catalog_db::MockCatalogDb;
+    use crate::db::catalog_db::{CatalogDbClient, MockCatalogDb};
+    use crate::db::stock_store::InMemoryStockStore;
+    use common::utilities::logger;
     use event_bus::*;
 
     fn generate_random_item(item_id: u32, stock: u32) -> ClothingItem {
         ClothingItem {
             id: item_id,
+            sku: String::new(),
             name: "random_item".to_string(),
             description: "desc".to_string(),
             sizes: vec!["S".to_string(), "M".to_string(), "L".to_string(), "XL".to_string()],
             price: 20.00,
+            currency: "GBP".to_string(),
             stock,
+            version: 0,
+            allow_backorder: false,
+            backordered: 0,
+            reserved: 0,
             images: vec![
                 "https://example.com/t-shirt-front.jpg".to_string(),
                 "https://example.com/t-shirt-back.jpg".to_string(),
@@ -195,6 +1614,49 @@ mod tests {
         assert_eq!(sut.get_stock(6).unwrap(), t_shirt.stock);
     }
 
+    #[test]
+    fn test_get_stock_subtracts_reserved() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        let mut t_shirt = generate_random_item(6, 50);
+        t_shirt.reserved = 20;
+        mock_catalog_db.set_expected_get_item(Some(t_shirt));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        let available = sut.get_stock(6).unwrap();
+        sut.db.write().unwrap().get_mut_item(6).unwrap().reserved = 80;
+        let over_reserved = sut.get_stock_batch(&[6]);
+
+        // assert
+        assert_eq!(available, 30);
+        assert_eq!(over_reserved.get(&6), Some(&0));
+    }
+
+    #[test]
+    fn test_reads_survive_poisoned_db_lock() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(6, 50)));
+        mock_catalog_db.set_expected_vec(vec![generate_random_item(6, 50)]);
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        let db = sut.db.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = db.write().unwrap();
+            panic!("poisoning the db lock");
+        })
+        .join();
+        assert!(sut.db.is_poisoned());
+
+        // act
+        let items = sut.get_items(false);
+        let stock = sut.get_stock(6);
+
+        // assert
+        assert_eq!(items.len(), 1);
+        assert_eq!(stock.unwrap(), 50);
+    }
+
     #[test]
     fn test_get_items() {
         // prepare
@@ -207,11 +1669,40 @@ mod tests {
         let sut = CatalogService::new(mock_catalog_db, mock_event_listener);
 
         // assert
-        let result = sut.get_items();
+        let result = sut.get_items(false);
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].name, String::from("random_item"));
     }
 
+    #[test]
+    fn test_get_items_excludes_out_of_stock() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_vec(vec![generate_random_item(1, 25), generate_random_item(2, 0)]);
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        let result = sut.get_items(false);
+
+        // assert
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, 1);
+    }
+
+    #[test]
+    fn test_get_items_includes_out_of_stock() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_vec(vec![generate_random_item(1, 25), generate_random_item(2, 0)]);
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        let result = sut.get_items(true);
+
+        // assert
+        assert_eq!(result.len(), 2);
+    }
+
     #[test]
     fn test_get_stock_success() {
         // prepare
@@ -240,7 +1731,78 @@ mod tests {
 
         // assert
         let result = sut.get_stock(1);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_resolve_item_id_by_sku() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        let mut item = generate_random_item(7, 5);
+        item.sku = "RANDOM-007".to_string();
+        mock_catalog_db.set_expected_get_item(Some(item));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        let result = sut.resolve_item_id(&ItemRef::Sku("RANDOM-007".to_string()));
+
+        // assert
+        assert_eq!(result.unwrap(), 7);
+    }
+
+    #[test]
+    fn test_resolve_item_id_unknown_sku() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        let mut item = generate_random_item(7, 5);
+        item.sku = "RANDOM-007".to_string();
+        mock_catalog_db.set_expected_get_item(Some(item));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        let result = sut.resolve_item_id(&ItemRef::Sku("RANDOM-008".to_string()));
+
+        // assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_get_item_dto_success() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 5)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        let result = sut.get_item_dto(1, false);
+
+        // assert
+        let item = result.unwrap();
+        assert_eq!(item.id, 1);
+        assert_eq!(item.price, Money::new(2000, "GBP"));
+        assert_eq!(item.currency, "GBP");
+    }
+
+    #[test]
+    fn test_get_item_dto_out_of_stock() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 0)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act & assert
+        assert!(sut.get_item_dto(1, false).is_err());
+        assert_eq!(sut.get_item_dto(1, true).unwrap().id, 1);
+    }
+
+    #[test]
+    fn test_item_not_found_error_into_service_error() {
+        let service_error = ServiceError::from(ItemNotFoundError);
+
+        assert_eq!(
+            service_error,
+            ServiceError::NotFound("This item does not exist.".to_string())
+        );
     }
 
     #[tokio::test]
@@ -255,4 +1817,1177 @@ mod tests {
         // assert
         sut.start_event_listeners();
     }
+
+    #[tokio::test]
+    async fn test_drain_processes_received_event() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        sut.start_event_listeners();
+        sut.event_bus.publish(topic::ORDER_PLACED, &generate_order_placed_event(1, 4));
+
+        // act
+        sut.drain().await;
+
+        // assert
+        assert_eq!(sut.get_stock(1).unwrap(), 6);
+        assert!(sut.listener_task.lock().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_order_placed_leaves_other_reservations() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        sut.reserve_stock(1, 8).unwrap();
+        sut.start_event_listeners();
+        sut.event_bus.publish(topic::ORDER_PLACED, &generate_order_placed_event(1, 4));
+
+        // act
+        sut.drain().await;
+
+        // assert that the order is rejected rather than taking the reserved stock
+        let item = sut.db.read().unwrap().get_item(1).unwrap().clone();
+        assert_eq!(item.stock, 10);
+        assert_eq!(item.reserved, 8);
+    }
+
+    #[tokio::test]
+    async fn test_order_placed_against_a_reservation_takes_the_reserved_stock() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        let reservation = sut.reserve_stock(1, 8).unwrap();
+        sut.start_event_listeners();
+        let mut event = generate_order_placed_event(1, 8);
+        event.payload.reservation_id = Some(reservation.id);
+        sut.event_bus.publish(topic::ORDER_PLACED, &event);
+
+        // act
+        sut.drain().await;
+
+        // assert
+        let item = sut.db.read().unwrap().get_item(1).unwrap().clone();
+        assert_eq!(item.stock, 2);
+        assert_eq!(item.reserved, 0);
+        assert_eq!(
+            sut.release_reservation(reservation.id),
+            Err(ReservationError::ReservationNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_listener_applies_events_to_the_configured_stock_store() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        let stock_store = Arc::new(InMemoryStockStore::new());
+        stock_store.set(1, 20);
+        sut.set_stock_store(stock_store.clone());
+        sut.start_event_listeners();
+        sut.event_bus.publish(topic::ORDER_PLACED, &generate_order_placed_event(1, 4));
+
+        // act
+        sut.drain().await;
+
+        // assert that the stock of the catalog database is untouched
+        assert_eq!(stock_store.get(1).await.unwrap().stock, 16);
+        assert_eq!(sut.db.read().unwrap().decrement_stock_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_order_metrics_aggregate_received_events() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        sut.enable_order_metrics(5);
+        sut.start_event_listeners();
+        let event = generate_order_placed_event(1, 4);
+        sut.event_bus.publish(topic::ORDER_PLACED, &event);
+        sut.event_bus.publish(topic::ORDER_PLACED, &event);
+        sut.event_bus.publish(topic::ORDER_PLACED, &generate_order_placed_event(1, 2));
+
+        // act
+        sut.drain().await;
+
+        // assert that the redelivered event is only counted once
+        let minutes = sut.get_order_metrics().unwrap();
+        assert_eq!(minutes.len(), 5);
+        assert_eq!(minutes.iter().map(|minute| minute.orders).sum::<u64>(), 2);
+        assert_eq!(minutes.iter().map(|minute| minute.quantity).sum::<u64>(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_processing_metrics_record_a_sample_per_processed_event() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        sut.start_event_listeners();
+        let event = generate_order_placed_event(1, 1);
+        sut.event_bus.publish(topic::ORDER_PLACED, &event);
+        sut.event_bus.publish(topic::ORDER_PLACED, &event);
+        sut.event_bus.publish(topic::ORDER_PLACED, &generate_order_placed_event(1, 2));
+        sut.event_bus.publish(topic::ORDER_PLACED, &generate_order_placed_event(1, 3));
+
+        // act
+        sut.drain().await;
+
+        // assert that the redelivered event is not timed again
+        let metrics = sut.get_processing_metrics();
+        assert_eq!(metrics.processing.count, 3);
+        assert_eq!(
+            metrics.processing.buckets.iter().map(|bucket| bucket.count).sum::<u64>(),
+            3
+        );
+        assert_eq!(metrics.lock_wait.count, 3);
+        assert_eq!(sut.get_stock(1).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_order_metrics_disabled_by_default() {
+        let sut = CatalogService::new(MockCatalogDb::new(), MockEventBus::new());
+
+        assert!(sut.get_order_metrics().is_none());
+    }
+
+    // yields to the listener task until the stock of the item reaches the expected level, or gives up
+    async fn wait_for_stock<E: EventListener>(sut: &CatalogService<E, MockCatalogDb>, expected: u32) -> u32 {
+        for _ in 0..100 {
+            if sut.get_stock(1).unwrap() == expected {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        sut.get_stock(1).unwrap()
+    }
+
+    // waits for the listener to produce an order placed event to the topic, as the mock bus only records it,
+    // sleeping rather than yielding so that the retry delay can elapse
+    async fn wait_for_produced(event_bus: &MockEventBus, topic_name: &str) -> Event<OrderPlacedEvent> {
+        for _ in 0..100 {
+            if let Some((_, _, payload)) = event_bus.produced().into_iter().find(|(topic, _, _)| topic == topic_name) {
+                return serde_json::from_slice(&payload).unwrap();
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Nothing was produced to {}", topic_name);
+    }
+
+    #[tokio::test]
+    async fn test_failed_order_placed_is_retried_then_succeeds() {
+        // prepare with the item changing on every attempt of the first delivery
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        mock_catalog_db.set_concurrent_version_bumps(MAX_STOCK_UPDATE_ATTEMPTS);
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        sut.set_order_placed_retry_config(OrderPlacedRetryConfig {
+            max_attempts: 3,
+            delay: Duration::ZERO,
+        });
+        sut.start_event_listeners();
+        let event = generate_order_placed_event(1, 4);
+
+        // act
+        sut.event_bus.publish(topic::ORDER_PLACED, &event);
+        let retried = wait_for_produced(&sut.event_bus, topic::ORDER_PLACED_RETRY).await;
+        sut.event_bus.publish(topic::ORDER_PLACED_RETRY, &retried);
+
+        // assert
+        assert_eq!(retried.event_id, event.event_id);
+        assert_eq!(retried.retry_attempt(), 1);
+        assert!(retried.retry_at().is_some());
+        for _ in 0..100 {
+            if sut.get_stock(1).unwrap() == 6 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(sut.get_stock(1).unwrap(), 6);
+        assert_eq!(sut.get_order_summary(1).unwrap().total_quantity_ordered, 4);
+        sut.drain().await;
+        assert!(sut.event_bus.produced().iter().all(|(topic, _, _)| topic != topic::ORDER_PLACED_DEAD_LETTER));
+    }
+
+    #[tokio::test]
+    async fn test_failed_order_placed_is_dead_lettered_once_retries_are_exhausted() {
+        // prepare with the item changing on every attempt
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        mock_catalog_db.set_concurrent_version_bumps(u32::MAX);
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        sut.set_order_placed_retry_config(OrderPlacedRetryConfig {
+            max_attempts: 2,
+            delay: Duration::ZERO,
+        });
+        sut.start_event_listeners();
+
+        // act
+        sut.event_bus.publish(topic::ORDER_PLACED, &generate_order_placed_event(1, 4));
+        let retried = wait_for_produced(&sut.event_bus, topic::ORDER_PLACED_RETRY).await;
+        sut.event_bus.publish(topic::ORDER_PLACED_RETRY, &retried);
+        let dead_lettered = wait_for_produced(&sut.event_bus, topic::ORDER_PLACED_DEAD_LETTER).await;
+
+        // assert
+        assert_eq!(dead_lettered.event_id, retried.event_id);
+        assert_eq!(dead_lettered.retry_attempt(), 1);
+        assert_eq!(sut.get_stock(1).unwrap(), 10);
+        let retries =
+            sut.event_bus.produced().iter().filter(|(topic, _, _)| topic == topic::ORDER_PLACED_RETRY).count();
+        assert_eq!(retries, 1);
+        sut.drain().await;
+    }
+
+    #[tokio::test]
+    async fn test_retry_due_sooner_is_not_held_back_by_a_later_retry() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        sut.start_event_listeners();
+        let now = sut.event_bus.clock().now();
+        let later =
+            generate_order_placed_event(1, 4).with_retry_attempt(1).with_retry_at(now + Duration::from_secs(60));
+        let due = generate_order_placed_event(1, 3).with_retry_attempt(1).with_retry_at(now);
+
+        // act
+        sut.event_bus.publish(topic::ORDER_PLACED_RETRY, &later);
+        sut.event_bus.publish(topic::ORDER_PLACED_RETRY, &due);
+        for _ in 0..100 {
+            if sut.get_stock(1).unwrap() == 7 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // assert
+        assert_eq!(sut.get_stock(1).unwrap(), 7);
+        sut.drain().await;
+    }
+
+    #[tokio::test]
+    async fn test_drain_republishes_waiting_retries() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        sut.start_event_listeners();
+        let retry_at = sut.event_bus.clock().now() + Duration::from_secs(60);
+        let event = generate_order_placed_event(1, 4).with_retry_attempt(1).with_retry_at(retry_at);
+        sut.event_bus.publish(topic::ORDER_PLACED_RETRY, &event);
+
+        // act
+        sut.drain().await;
+
+        // assert that the retry is left for the next consumer rather than applied or lost
+        let republished = wait_for_produced(&sut.event_bus, topic::ORDER_PLACED_RETRY).await;
+        assert_eq!(republished.event_id, event.event_id);
+        assert_eq!(republished.retry_attempt(), 1);
+        assert_eq!(republished.retry_at(), event.retry_at());
+        assert_eq!(sut.get_stock(1).unwrap(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_paused_listener_applies_events_once_resumed() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        sut.start_event_listeners();
+
+        // act
+        sut.pause_listener();
+        sut.event_bus.publish(topic::ORDER_PLACED, &generate_order_placed_event(1, 4));
+        sut.event_bus.publish(topic::ORDER_PLACED, &generate_order_placed_event(1, 3));
+        let paused_stock = wait_for_stock(&sut, 3).await;
+        let paused_health = sut.get_health();
+        sut.resume_listener();
+        let resumed_stock = wait_for_stock(&sut, 3).await;
+
+        // assert
+        assert_eq!(paused_stock, 10);
+        assert!(paused_health.listener_paused);
+        assert_eq!(resumed_stock, 3);
+        assert!(!sut.get_health().listener_paused);
+    }
+
+    #[tokio::test]
+    async fn test_drain_applies_events_held_whilst_paused() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let mut sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        sut.start_event_listeners();
+        sut.pause_listener();
+        sut.event_bus.publish(topic::ORDER_PLACED, &generate_order_placed_event(1, 4));
+        wait_for_stock(&sut, 6).await;
+
+        // act
+        sut.drain().await;
+
+        // assert
+        assert_eq!(sut.get_stock(1).unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_listener_stats_follow_listener_lifecycle() {
+        // prepare
+        let mut sut = CatalogService::new(MockCatalogDb::new(), MockEventBus::new());
+        let before_start = sut.get_listener_stats();
+
+        // act
+        sut.start_event_listeners();
+        let started = sut.get_listener_stats();
+        sut.drain().await;
+        let drained = sut.get_listener_stats();
+
+        // assert
+        assert_eq!(before_start.order_placed_receivers, 0);
+        assert!(before_start.order_placed_topics.is_empty());
+        assert_eq!(started.order_placed_receivers, 1);
+        assert_eq!(started.order_placed_topics, vec![topic::ORDER_PLACED.to_string()]);
+        assert_eq!(drained.order_placed_receivers, 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_health_reports_disconnected_consumer() {
+        // prepare
+        let mut sut = CatalogService::new(MockCatalogDb::new(), MockEventBus::new());
+        let before_start = sut.get_health();
+
+        // act with a mocked listener, whose consumer is never assigned partitions
+        sut.start_event_listeners();
+        let started = sut.get_health();
+        sut.order_placed_listener().unwrap().mock_fail();
+        let failed = sut.get_health();
+
+        // assert
+        assert_eq!(
+            before_start,
+            CatalogHealth {
+                healthy: false,
+                order_placed_consumer_connected: false,
+                degraded: false,
+                listener_paused: false,
+            }
+        );
+        assert_eq!(
+            started,
+            CatalogHealth {
+                healthy: false,
+                order_placed_consumer_connected: false,
+                degraded: false,
+                listener_paused: false,
+            }
+        );
+        assert_eq!(
+            failed,
+            CatalogHealth {
+                healthy: false,
+                order_placed_consumer_connected: false,
+                degraded: true,
+                listener_paused: false,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_drain_without_listeners() {
+        // prepare
+        let sut = CatalogService::new(MockCatalogDb::new(), MockEventBus::new());
+
+        // act and assert that draining returns immediately
+        sut.drain().await;
+    }
+
+    #[tokio::test]
+    async fn test_next_until_shutdown_then_try_next_received() {
+        // prepare
+        let (tx, mut receiver) = broadcast::channel(10);
+        let (shutdown_tx, mut shutdown) = watch::channel(false);
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        shutdown_tx.send(true).unwrap();
+
+        // act
+        let next = next_until_shutdown(&mut receiver, &mut shutdown).await;
+        let drained: Vec<_> = std::iter::from_fn(|| try_next_received(&mut receiver)).collect();
+
+        // assert that shutdown is seen before the events already received, which are then drained
+        assert_eq!(next, Received::Shutdown);
+        assert_eq!(drained, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_next_until_shutdown_closed() {
+        // prepare
+        let (tx, mut receiver) = broadcast::channel::<u32>(10);
+        let (_shutdown_tx, mut shutdown) = watch::channel(false);
+        drop(tx);
+
+        // act
+        let next = next_until_shutdown(&mut receiver, &mut shutdown).await;
+
+        // assert
+        assert_eq!(next, Received::Closed);
+    }
+
+    fn generate_order_placed_event(item_id: u32, quantity: u32) -> Event<OrderPlacedEvent> {
+        Event::new(
+            "order_placed".to_string(),
+            OrderPlacedEvent {
+                item_id,
+                quantity,
+                total: None,
+                reservation_id: None,
+            },
+            "test".to_string(),
+            None,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_handle_order_placed_decrements_stock() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 50)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        handle_order_placed(
+            sut.stock_store.as_ref(),
+            StockUnderflowPolicy::Skip,
+            &generate_order_placed_event(1, 20),
+        )
+        .await;
+
+        // assert
+        assert_eq!(sut.get_stock(1).unwrap(), 30);
+    }
+
+    #[tokio::test]
+    async fn test_handle_order_placed_insufficient_stock() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        let log_sink = logger::initialize_for_test();
+
+        // act
+        handle_order_placed(
+            sut.stock_store.as_ref(),
+            StockUnderflowPolicy::Skip,
+            &generate_order_placed_event(1, 11),
+        )
+        .await;
+
+        // assert
+        assert_eq!(sut.get_stock(1).unwrap(), 10);
+        assert!(log_sink.contains(
+            "[ERROR] - Event to change stock levels has failed, Source: test, Amount to change: 11, Current Amount: 10"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_handle_order_placed_exact_stock_sells_out_item() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        handle_order_placed(
+            sut.stock_store.as_ref(),
+            StockUnderflowPolicy::Skip,
+            &generate_order_placed_event(1, 10),
+        )
+        .await;
+
+        // assert
+        assert_eq!(sut.get_stock(1).unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_handle_order_placed_insufficient_stock_is_not_partially_applied() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        handle_order_placed(
+            sut.stock_store.as_ref(),
+            StockUnderflowPolicy::Skip,
+            &generate_order_placed_event(1, 11),
+        )
+        .await;
+        handle_order_placed(
+            sut.stock_store.as_ref(),
+            StockUnderflowPolicy::Skip,
+            &generate_order_placed_event(1, 4),
+        )
+        .await;
+
+        // assert that the rejected event did not consume any stock
+        assert_eq!(sut.get_stock(1).unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_handle_order_placed_retries_on_version_conflict() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        mock_catalog_db.set_concurrent_version_bumps(1);
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        let outcome = handle_order_placed(
+            sut.stock_store.as_ref(),
+            StockUnderflowPolicy::Skip,
+            &generate_order_placed_event(1, 4),
+        )
+        .await;
+
+        // assert
+        assert_eq!(outcome, StockUpdateOutcome::Changed);
+        assert_eq!(sut.get_stock(1).unwrap(), 6);
+        let db = sut.db.read().unwrap();
+        assert_eq!(db.decrement_stock_calls(), 2);
+        assert_eq!(db.get_item(1).unwrap().version, 2);
+    }
+
+    #[tokio::test]
+    async fn test_handle_order_placed_gives_up_after_repeated_conflicts() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        mock_catalog_db.set_concurrent_version_bumps(MAX_STOCK_UPDATE_ATTEMPTS);
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        let outcome = handle_order_placed(
+            sut.stock_store.as_ref(),
+            StockUnderflowPolicy::Skip,
+            &generate_order_placed_event(1, 4),
+        )
+        .await;
+
+        // assert
+        assert_eq!(outcome, StockUpdateOutcome::Failed);
+        assert_eq!(sut.get_stock(1).unwrap(), 10);
+        assert_eq!(
+            sut.db.read().unwrap().decrement_stock_calls(),
+            MAX_STOCK_UPDATE_ATTEMPTS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_order_placed_clamp_to_zero_policy() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        handle_order_placed(
+            sut.stock_store.as_ref(),
+            StockUnderflowPolicy::ClampToZero,
+            &generate_order_placed_event(1, 11),
+        )
+        .await;
+
+        // assert
+        assert_eq!(sut.get_stock(1).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_parse_stock_underflow_policy() {
+        assert_eq!("skip".parse(), Ok(StockUnderflowPolicy::Skip));
+        assert_eq!("CLAMP_TO_ZERO".parse(), Ok(StockUnderflowPolicy::ClampToZero));
+        assert!("unknown".parse::<StockUnderflowPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_set_stock_underflow_policy() {
+        // prepare
+        let mut sut = CatalogService::new(MockCatalogDb::new(), MockEventBus::new());
+        assert_eq!(sut.stock_underflow_policy, StockUnderflowPolicy::Skip);
+
+        // act
+        sut.set_stock_underflow_policy(StockUnderflowPolicy::ClampToZero);
+
+        // assert
+        assert_eq!(sut.stock_underflow_policy, StockUnderflowPolicy::ClampToZero);
+    }
+
+    #[tokio::test]
+    async fn test_handle_order_placed_unknown_item() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(None);
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        handle_order_placed(
+            sut.stock_store.as_ref(),
+            StockUnderflowPolicy::Skip,
+            &generate_order_placed_event(99, 1),
+        )
+        .await;
+
+        // assert
+        assert!(sut.get_stock(99).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_process_order_placed_skips_duplicate_event() {
+        // prepare
+        let mock_event_listener = MockEventBus::new();
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 50)));
+        let sut = CatalogService::new(mock_catalog_db, mock_event_listener);
+        let mut deduplicator = EventDeduplicator::new(10);
+        let event = generate_order_placed_event(1, 5);
+
+        // act
+        process_order_placed(
+            sut.stock_store.as_ref(),
+            &sut.catalog_version,
+            &sut.ordered_quantities,
+            &mut deduplicator,
+            StockUnderflowPolicy::Skip,
+            &sut.processing_metrics,
+            &event,
+        )
+        .await;
+        process_order_placed(
+            sut.stock_store.as_ref(),
+            &sut.catalog_version,
+            &sut.ordered_quantities,
+            &mut deduplicator,
+            StockUnderflowPolicy::Skip,
+            &sut.processing_metrics,
+            &event.clone(),
+        )
+        .await;
+
+        // assert
+        assert_eq!(sut.get_stock(1).unwrap(), 45);
+    }
+
+    #[tokio::test]
+    async fn test_process_order_placed_bumps_catalog_version() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        let mut deduplicator = EventDeduplicator::new(10);
+        let initial_version = sut.get_catalog_version();
+
+        // act
+        process_order_placed(
+            sut.stock_store.as_ref(),
+            &sut.catalog_version,
+            &sut.ordered_quantities,
+            &mut deduplicator,
+            StockUnderflowPolicy::Skip,
+            &sut.processing_metrics,
+            &generate_order_placed_event(1, 11),
+        )
+        .await;
+        let rejected_version = sut.get_catalog_version();
+        process_order_placed(
+            sut.stock_store.as_ref(),
+            &sut.catalog_version,
+            &sut.ordered_quantities,
+            &mut deduplicator,
+            StockUnderflowPolicy::Skip,
+            &sut.processing_metrics,
+            &generate_order_placed_event(1, 5),
+        )
+        .await;
+
+        // assert
+        assert_eq!(rejected_version, initial_version);
+        assert_eq!(sut.get_catalog_version(), initial_version + 1);
+    }
+
+    #[tokio::test]
+    async fn test_process_order_placed_applies_distinct_events() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 50)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        let mut deduplicator = EventDeduplicator::new(10);
+
+        // act
+        process_order_placed(
+            sut.stock_store.as_ref(),
+            &sut.catalog_version,
+            &sut.ordered_quantities,
+            &mut deduplicator,
+            StockUnderflowPolicy::Skip,
+            &sut.processing_metrics,
+            &generate_order_placed_event(1, 5),
+        )
+        .await;
+        process_order_placed(
+            sut.stock_store.as_ref(),
+            &sut.catalog_version,
+            &sut.ordered_quantities,
+            &mut deduplicator,
+            StockUnderflowPolicy::Skip,
+            &sut.processing_metrics,
+            &generate_order_placed_event(1, 5),
+        )
+        .await;
+
+        // assert
+        assert_eq!(sut.get_stock(1).unwrap(), 40);
+    }
+
+    #[tokio::test]
+    async fn test_process_order_placed_increments_order_summary() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 50)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        let mut deduplicator = EventDeduplicator::new(10);
+        let initial_summary = sut.get_order_summary(1).unwrap();
+
+        // act
+        let first_event = generate_order_placed_event(1, 5);
+        for event in [&first_event, &first_event, &generate_order_placed_event(1, 60)] {
+            process_order_placed(
+                sut.stock_store.as_ref(),
+                &sut.catalog_version,
+                &sut.ordered_quantities,
+                &mut deduplicator,
+                StockUnderflowPolicy::Skip,
+                &sut.processing_metrics,
+                event,
+            )
+            .await;
+        }
+
+        // assert
+        assert_eq!(initial_summary.total_quantity_ordered, 0);
+        assert_eq!(
+            sut.get_order_summary(1).unwrap(),
+            OrderSummary {
+                item_id: 1,
+                total_quantity_ordered: 65
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_order_summary_item_not_found() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(None);
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        let result = sut.get_order_summary(99);
+
+        // assert
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_save_item_added() {
+        // prepare
+        let sut = CatalogService::new(MockCatalogDb::new(), MockEventBus::new());
+        let initial_version = sut.get_catalog_version();
+
+        // act
+        let result = sut.save_item(generate_random_item(1, 10)).await;
+
+        // assert
+        assert_eq!(result, Ok(CatalogItemChange::Added));
+        assert_eq!(sut.get_catalog_version(), initial_version + 1);
+    }
+
+    #[tokio::test]
+    async fn test_import_items_inserts_every_item() {
+        // prepare
+        let sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+        let initial_version = sut.get_catalog_version();
+
+        // act
+        let result = sut.import_items(vec![generate_random_item(20, 5), generate_random_item(21, 0)]).await;
+
+        // assert
+        assert_eq!(
+            result,
+            Ok(ImportSummary {
+                inserted: 2,
+                skipped: 0
+            })
+        );
+        assert_eq!(sut.get_stock(20).unwrap(), 5);
+        assert!(sut.get_stock(21).is_ok());
+        assert_eq!(sut.get_catalog_version(), initial_version + 1);
+        assert_eq!(sut.event_bus.produced().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_items_skips_duplicate_ids() {
+        // prepare
+        let sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+
+        // act with an id already in the catalog, and an id repeated within the import
+        let result = sut
+            .import_items(vec![
+                generate_random_item(1, 5),
+                generate_random_item(20, 5),
+                generate_random_item(20, 9),
+            ])
+            .await;
+
+        // assert
+        assert_eq!(
+            result,
+            Ok(ImportSummary {
+                inserted: 1,
+                skipped: 2
+            })
+        );
+        assert_eq!(sut.get_stock(1).unwrap(), 100);
+        assert_eq!(sut.get_stock(20).unwrap(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_import_items_empty() {
+        // prepare
+        let sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+        let initial_version = sut.get_catalog_version();
+
+        // act
+        let result = sut.import_items(vec![]).await;
+
+        // assert
+        assert_eq!(
+            result,
+            Ok(ImportSummary {
+                inserted: 0,
+                skipped: 0
+            })
+        );
+        assert_eq!(sut.get_catalog_version(), initial_version);
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_snapshot_round_trips_through_import() {
+        // prepare
+        let sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+        sut.set_stock(1, 7).await.unwrap();
+        sut.reserve_stock(2, 3).unwrap();
+        let snapshot = sut.export_snapshot();
+        let reserved_stock = sut.db.read().unwrap().get_item(2).unwrap().stock;
+
+        // act
+        for item in &snapshot {
+            sut.remove_item(item.id).await.unwrap();
+        }
+        let cleared = sut.export_snapshot();
+        let result = sut.import_items(snapshot.clone()).await;
+
+        // assert
+        assert!(cleared.is_empty());
+        assert_eq!(result.unwrap().inserted, snapshot.len());
+        assert!(snapshot.windows(2).all(|items| items[0].id < items[1].id));
+        assert_eq!(
+            serde_json::to_value(sut.export_snapshot()).unwrap(),
+            serde_json::to_value(&snapshot).unwrap()
+        );
+        assert_eq!(sut.get_stock(1).unwrap(), 7);
+        // the reserved stock is available again, as its reservation cannot be restored
+        assert_eq!(sut.get_stock(2).unwrap(), reserved_stock);
+    }
+
+    #[tokio::test]
+    async fn test_import_items_is_all_or_nothing() {
+        // prepare
+        let sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+        let mut invalid_item = generate_random_item(21, 5);
+        invalid_item.currency = "XXX".to_string();
+
+        // act
+        let result = sut.import_items(vec![generate_random_item(20, 5), invalid_item]).await;
+
+        // assert
+        assert_eq!(
+            result,
+            Err(ImportItemsError {
+                item_id: 21,
+                error: AddItemError::InvalidCurrency,
+            })
+        );
+        assert!(sut.get_stock(20).is_err());
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_save_item_updated() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+
+        // act
+        let result = sut.save_item(generate_random_item(1, 20)).await;
+
+        // assert
+        assert_eq!(result, Ok(CatalogItemChange::Updated));
+    }
+
+    #[tokio::test]
+    async fn test_save_item_broadcast_failure_is_not_propagated() {
+        // prepare
+        let mut mock_event_bus = MockEventBus::new();
+        mock_event_bus.set_produces_error(true);
+        let sut = CatalogService::new(MockCatalogDb::new(), mock_event_bus);
+
+        // act
+        let result = sut.save_item(generate_random_item(1, 10)).await;
+
+        // assert
+        assert_eq!(result, Ok(CatalogItemChange::Added));
+    }
+
+    #[tokio::test]
+    async fn test_save_item_beyond_max_items() {
+        // prepare with a catalog filled to its limit
+        let mut catalog_db = CatalogDbClient::new();
+        catalog_db.set_max_items(Some(6));
+        let sut = CatalogService::new(catalog_db, MockEventBus::new());
+        assert_eq!(
+            sut.save_item(generate_random_item(10, 10)).await,
+            Ok(CatalogItemChange::Added)
+        );
+        let version = sut.get_catalog_version();
+
+        // act
+        let result = sut.save_item(generate_random_item(11, 10)).await;
+
+        // assert
+        assert_eq!(result, Err(AddItemError::CatalogFull { max_items: 6 }));
+        assert_eq!(sut.get_catalog_version(), version);
+        assert_eq!(
+            ServiceError::from(result.unwrap_err()).status_code(),
+            actix_web::http::StatusCode::CONFLICT
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_stock_overwrites_stock() {
+        // prepare
+        let sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+        let initial_version = sut.get_catalog_version();
+
+        // act
+        let result = sut.set_stock(1, 7).await;
+
+        // assert
+        assert_eq!(
+            result.unwrap(),
+            StockChange {
+                item_id: 1,
+                previous_stock: 100,
+                stock: 7,
+            }
+        );
+        assert_eq!(sut.get_stock(1).unwrap(), 7);
+        assert_eq!(sut.get_catalog_version(), initial_version + 1);
+        let produced = sut.event_bus.produced();
+        assert_eq!(produced.len(), 1);
+        assert_eq!(produced[0].0, topic::CATALOG_ITEM_CHANGED);
+    }
+
+    #[tokio::test]
+    async fn test_set_stock_fills_backorders() {
+        // prepare
+        let sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+        sut.db.write().unwrap().get_mut_item(1).unwrap().backordered = 5;
+
+        // act
+        let result = sut.set_stock(1, 7).await;
+
+        // assert
+        assert_eq!(result.unwrap().stock, 2);
+        assert_eq!(sut.db.read().unwrap().get_item(1).unwrap().backordered, 0);
+    }
+
+    #[tokio::test]
+    async fn test_backorder_listener_skips_duplicates_and_drains() {
+        // prepare
+        let mut sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+        sut.start_event_listeners();
+        let event = Event::new(
+            "backorder_placed".to_string(),
+            BackorderPlacedEvent {
+                item_id: 5,
+                quantity: 31,
+                backordered_quantity: 30,
+                total: None,
+            },
+            "test".to_string(),
+            None,
+            None,
+        );
+        sut.event_bus.publish(topic::BACKORDER_PLACED, &event);
+        sut.event_bus.publish(topic::BACKORDER_PLACED, &event);
+
+        // act
+        sut.drain().await;
+
+        // assert
+        let item = sut.db.read().unwrap().get_item(5).unwrap().clone();
+        assert_eq!(item.stock, 0);
+        assert_eq!(item.backordered, 30);
+        assert_eq!(sut.get_order_summary(5).unwrap().total_quantity_ordered, 31);
+        assert!(sut.backorder_task.lock().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_configured_topics_are_used_to_produce_and_subscribe() {
+        // prepare
+        let mut topics = TopicConfig::new();
+        topics.set(topic::ORDER_PLACED, "staging.order-placed").unwrap();
+        topics.set(topic::CATALOG_ITEM_CHANGED, "staging.catalog-item-changed").unwrap();
+        let mut sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+        sut.set_topic_config(topics);
+        sut.start_event_listeners();
+
+        // act
+        sut.event_bus.publish(topic::ORDER_PLACED, &generate_order_placed_event(1, 4));
+        sut.event_bus.publish("staging.order-placed", &generate_order_placed_event(1, 2));
+        sut.drain().await;
+        sut.set_stock(2, 7).await.unwrap();
+
+        // assert
+        assert_eq!(sut.get_stock(1).unwrap(), 98);
+        assert_eq!(
+            sut.get_listener_stats().order_placed_topics,
+            vec!["staging.order-placed".to_string()]
+        );
+        let produced = sut.event_bus.produced();
+        assert_eq!(produced.len(), 1);
+        assert_eq!(produced[0].0, "staging.catalog-item-changed");
+    }
+
+    #[tokio::test]
+    async fn test_set_stock_item_not_found() {
+        // prepare
+        let sut = CatalogService::new(MockCatalogDb::new(), MockEventBus::new());
+
+        // act
+        let result = sut.set_stock(99, 7).await;
+
+        // assert
+        assert!(result.is_err());
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
+    #[test]
+    fn test_expired_reservation_is_released() {
+        // prepare
+        let clock = Arc::new(clock::MockClock::new(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        ));
+        let mut sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+        sut.set_clock(clock.clone());
+        sut.set_reservation_ttl(Duration::from_secs(60));
+        let reservation = sut.reserve_stock(1, 30).unwrap();
+        assert_eq!(sut.get_stock(1).unwrap(), 70);
+
+        // act
+        clock.advance(Duration::from_secs(59));
+        let released_before_ttl = sut.release_expired_reservations();
+        clock.advance(Duration::from_secs(2));
+        let released_after_ttl = sut.release_expired_reservations();
+
+        // assert
+        assert_eq!(released_before_ttl, 0);
+        assert_eq!(released_after_ttl, 1);
+        assert_eq!(sut.get_stock(1).unwrap(), 100);
+        assert_eq!(
+            sut.release_reservation(reservation.id),
+            Err(ReservationError::ReservationNotFound)
+        );
+    }
+
+    #[test]
+    fn test_release_reservation_returns_stock() {
+        // prepare
+        let sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+        let reservation = sut.reserve_stock(1, 30).unwrap();
+        let version = sut.get_catalog_version();
+
+        // act
+        let released = sut.release_reservation(reservation.id);
+
+        // assert
+        assert_eq!(released, Ok(reservation));
+        assert_eq!(sut.get_stock(1).unwrap(), 100);
+        assert_eq!(sut.get_catalog_version(), version + 1);
+        assert_eq!(sut.release_expired_reservations(), 0);
+    }
+
+    #[test]
+    fn test_reserve_stock_rejected() {
+        // prepare
+        let sut = CatalogService::new(CatalogDbClient::new(), MockEventBus::new());
+
+        // act
+        let insufficient = sut.reserve_stock(1, 101);
+        let unknown = sut.reserve_stock(99, 1);
+
+        // assert
+        assert_eq!(
+            insufficient,
+            Err(ReservationError::InsufficientStock { available: 100 })
+        );
+        assert_eq!(unknown, Err(ReservationError::ItemNotFound));
+        assert_eq!(sut.get_stock(1).unwrap(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_remove_item() {
+        // prepare
+        let mut mock_catalog_db = MockCatalogDb::new();
+        mock_catalog_db.set_expected_get_item(Some(generate_random_item(1, 10)));
+        let sut = CatalogService::new(mock_catalog_db, MockEventBus::new());
+        let initial_version = sut.get_catalog_version();
+
+        // act
+        let result = sut.remove_item(1).await;
+
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(sut.get_catalog_version(), initial_version + 1);
+    }
+
+    #[tokio::test]
+    async fn test_remove_item_not_found() {
+        // prepare
+        let sut = CatalogService::new(MockCatalogDb::new(), MockEventBus::new());
+        let initial_version = sut.get_catalog_version();
+
+        // act
+        let result = sut.remove_item(1).await;
+
+        // assert
+        assert!(result.is_err());
+        assert_eq!(sut.get_catalog_version(), initial_version);
+    }
 }
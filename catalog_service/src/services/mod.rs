@@ -1 +1,4 @@
 pub mod catalog_service;
+pub mod order_metrics;
+pub mod processing_metrics;
+pub mod reservations;
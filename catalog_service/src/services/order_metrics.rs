@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The number of minutes of order metrics kept when no window is configured.
+pub const DEFAULT_WINDOW_MINUTES: usize = 60;
+
+/// `OrderMetrics` holds a rolling count of the orders placed, and their total quantity, per minute.
+///
+/// The minutes are kept in a ring buffer of `window_minutes` buckets, indexed by the minute since
+/// the Unix epoch. A bucket holding an older minute is reset when an order of a newer minute maps
+/// onto it, so each bucket starts cleanly from zero at the start of its minute. Orders that are
+/// older than the minute held by their bucket are outside the window and are ignored.
+///
+/// Fields:
+/// - `buckets`: The ring buffer of per-minute buckets.
+pub struct OrderMetrics {
+    buckets: Mutex<Vec<MinuteBucket>>,
+}
+
+// the orders placed during a single minute
+#[derive(Clone, Copy, Default)]
+struct MinuteBucket {
+    minute: u64,
+    orders: u64,
+    quantity: u64,
+}
+
+/// The orders placed during a single minute, as reported by `GET /metrics/orders`.
+///
+/// Fields:
+/// - `minute_start`: The start of the minute, in seconds since the Unix epoch.
+/// - `orders`: The number of orders placed during the minute.
+/// - `quantity`: The total quantity ordered during the minute.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct MinuteOrderMetrics {
+    pub minute_start: u64,
+    pub orders: u64,
+    pub quantity: u64,
+}
+
+impl OrderMetrics {
+    /// Creates empty order metrics covering the given number of minutes, which is at least one.
+    pub fn new(window_minutes: usize) -> Self {
+        OrderMetrics {
+            buckets: Mutex::new(vec![MinuteBucket::default(); window_minutes.max(1)]),
+        }
+    }
+
+    /// Records an order in the bucket of the minute it was placed in.
+    ///
+    /// Arguments:
+    /// - `placed_at`: When the order was placed, typically the timestamp of its event.
+    /// - `quantity`: The ordered quantity.
+    pub fn record(&self, placed_at: SystemTime, quantity: u32) {
+        let minute = minute_of(placed_at);
+        let mut buckets = self.buckets.lock().unwrap();
+        let index = (minute % buckets.len() as u64) as usize;
+        let bucket = &mut buckets[index];
+        if bucket.minute < minute {
+            *bucket = MinuteBucket {
+                minute,
+                ..MinuteBucket::default()
+            };
+        } else if bucket.minute > minute {
+            return;
+        }
+        bucket.orders += 1;
+        bucket.quantity += quantity as u64;
+    }
+
+    /// Retrieves the metrics of each minute in the window ending at the minute of `now`, oldest
+    /// first. Minutes without orders are reported with zero orders.
+    ///
+    /// Arguments:
+    /// - `now`: The current time, which decides the last minute of the window.
+    pub fn get_minutes(&self, now: SystemTime) -> Vec<MinuteOrderMetrics> {
+        let current_minute = minute_of(now);
+        let buckets = self.buckets.lock().unwrap();
+        let window = buckets.len() as u64;
+        (current_minute.saturating_sub(window - 1)..=current_minute)
+            .map(|minute| {
+                let bucket = buckets[(minute % window) as usize];
+                let (orders, quantity) = if bucket.minute == minute {
+                    (bucket.orders, bucket.quantity)
+                } else {
+                    (0, 0)
+                };
+                MinuteOrderMetrics {
+                    minute_start: minute * 60,
+                    orders,
+                    quantity,
+                }
+            })
+            .collect()
+    }
+}
+
+// the number of whole minutes since the unix epoch, treating earlier times as the epoch
+fn minute_of(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO).as_secs() / 60
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the given number of seconds after a time that falls on the start of a minute
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_700_000_040 + seconds)
+    }
+
+    #[test]
+    fn test_records_orders_in_the_same_minute() {
+        // prepare
+        let sut = OrderMetrics::new(3);
+
+        // act
+        sut.record(at(0), 2);
+        sut.record(at(59), 3);
+
+        // assert
+        let minutes = sut.get_minutes(at(59));
+        assert_eq!(minutes.len(), 3);
+        assert_eq!(
+            minutes[2],
+            MinuteOrderMetrics {
+                minute_start: 1_700_000_040,
+                orders: 2,
+                quantity: 5,
+            }
+        );
+        assert_eq!(minutes[1].orders, 0);
+    }
+
+    #[test]
+    fn test_buckets_reset_across_minute_boundaries() {
+        // prepare
+        let sut = OrderMetrics::new(2);
+
+        // act with the first and third minutes sharing a bucket
+        sut.record(at(59), 4);
+        sut.record(at(60), 1);
+        sut.record(at(120), 2);
+
+        // assert
+        let minutes = sut.get_minutes(at(120));
+        assert_eq!(
+            minutes.iter().map(|minute| (minute.orders, minute.quantity)).collect::<Vec<_>>(),
+            vec![(1, 1), (1, 2)]
+        );
+        assert_eq!(minutes[1].minute_start, 1_700_000_160);
+    }
+
+    #[test]
+    fn test_ignores_orders_outside_the_window() {
+        // prepare
+        let sut = OrderMetrics::new(2);
+        sut.record(at(120), 2);
+
+        // act with a late order whose bucket already holds a newer minute
+        sut.record(at(0), 5);
+
+        // assert
+        let minutes = sut.get_minutes(at(120));
+        assert_eq!(minutes.iter().map(|minute| minute.quantity).sum::<u64>(), 2);
+    }
+
+    #[test]
+    fn test_get_minutes_skips_expired_buckets() {
+        // prepare
+        let sut = OrderMetrics::new(3);
+        sut.record(at(0), 2);
+
+        // act with no orders for longer than the window
+        let minutes = sut.get_minutes(at(300));
+
+        // assert
+        assert!(minutes.iter().all(|minute| minute.orders == 0));
+        assert_eq!(minutes[0].minute_start, 1_700_000_040 + 180);
+    }
+}
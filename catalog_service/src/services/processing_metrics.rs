@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The upper bounds, in milliseconds, of the buckets of a `LatencyHistogram`. Samples slower than
+/// the last bound are counted in a final, unbounded bucket.
+pub const BUCKET_BOUNDS_MS: [u64; 10] = [1, 2, 5, 10, 25, 50, 100, 250, 500, 1000];
+
+/// `ProcessingMetrics` holds how long the `ORDER_PLACED` listener takes to process each event.
+///
+/// Fields:
+/// - `processing`: The time from the listener taking an event to the stock being written.
+/// - `lock_wait`: The part of `processing` spent waiting to acquire the write lock of the catalog
+///   database to decrement the stock, so that contention with the HTTP handlers changing the catalog
+///   is visible on its own. Shared with the `CatalogDbStockStore`, so nothing is recorded while the
+///   stock is held in another `StockStore`.
+#[derive(Default)]
+pub struct ProcessingMetrics {
+    pub processing: LatencyHistogram,
+    pub lock_wait: Arc<LatencyHistogram>,
+}
+
+/// The processing latencies of the `ORDER_PLACED` listener, as reported by `GET /metrics/processing`.
+///
+/// Fields:
+/// - `processing`: The time from the listener taking an event to the stock being written.
+/// - `lock_wait`: The time spent waiting to acquire the write lock of the catalog database.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ProcessingMetricsSnapshot {
+    pub processing: HistogramSnapshot,
+    pub lock_wait: HistogramSnapshot,
+}
+
+impl ProcessingMetrics {
+    pub fn new() -> Self {
+        ProcessingMetrics::default()
+    }
+
+    /// Retrieves the current counts of both histograms.
+    pub fn snapshot(&self) -> ProcessingMetricsSnapshot {
+        ProcessingMetricsSnapshot {
+            processing: self.processing.snapshot(),
+            lock_wait: self.lock_wait.snapshot(),
+        }
+    }
+}
+
+/// `LatencyHistogram` counts latency samples in fixed buckets, see `BUCKET_BOUNDS_MS`.
+///
+/// Every counter is atomic, so samples are recorded without locking and never contend with the
+/// processing being measured.
+///
+/// Fields:
+/// - `buckets`: The number of samples in each bucket, the last of which is unbounded.
+/// - `count`: The total number of samples.
+/// - `sum_micros`: The total latency of the samples, in microseconds.
+pub struct LatencyHistogram {
+    buckets: [AtomicU64; BUCKET_BOUNDS_MS.len() + 1],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+/// The counts of a `LatencyHistogram` at a point in time.
+///
+/// Fields:
+/// - `buckets`: The number of samples in each bucket, in order of their bounds.
+/// - `count`: The total number of samples.
+/// - `sum_ms`: The total latency of the samples, in milliseconds.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct HistogramSnapshot {
+    pub buckets: Vec<HistogramBucket>,
+    pub count: u64,
+    pub sum_ms: f64,
+}
+
+/// A single bucket of a `HistogramSnapshot`.
+///
+/// Fields:
+/// - `le_ms`: The upper bound of the bucket in milliseconds, or `None` for the unbounded bucket.
+/// - `count`: The number of samples at most `le_ms` and above the bound of the previous bucket.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct HistogramBucket {
+    pub le_ms: Option<u64>,
+    pub count: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        LatencyHistogram {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+}
+
+impl LatencyHistogram {
+    /// Records a sample in the first bucket whose bound it does not exceed.
+    ///
+    /// Arguments:
+    /// - `latency`: The measured latency.
+    pub fn record(&self, latency: Duration) {
+        let index = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|bound| latency <= Duration::from_millis(*bound))
+            .unwrap_or(BUCKET_BOUNDS_MS.len());
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Retrieves the current counts of the histogram.
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let buckets = self
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(index, count)| HistogramBucket {
+                le_ms: BUCKET_BOUNDS_MS.get(index).copied(),
+                count: count.load(Ordering::Relaxed),
+            })
+            .collect();
+        HistogramSnapshot {
+            buckets,
+            count: self.count.load(Ordering::Relaxed),
+            sum_ms: self.sum_micros.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_samples_in_their_buckets() {
+        // prepare
+        let sut = LatencyHistogram::default();
+
+        // act
+        sut.record(Duration::from_micros(500));
+        sut.record(Duration::from_millis(1));
+        sut.record(Duration::from_millis(30));
+        sut.record(Duration::from_secs(2));
+
+        // assert
+        let snapshot = sut.snapshot();
+        assert_eq!(snapshot.count, 4);
+        assert_eq!(snapshot.buckets.len(), BUCKET_BOUNDS_MS.len() + 1);
+        assert_eq!(
+            snapshot.buckets[0],
+            HistogramBucket {
+                le_ms: Some(1),
+                count: 2
+            }
+        );
+        assert_eq!(
+            snapshot.buckets[5],
+            HistogramBucket {
+                le_ms: Some(50),
+                count: 1
+            }
+        );
+        assert_eq!(snapshot.buckets[10], HistogramBucket { le_ms: None, count: 1 });
+        assert_eq!(snapshot.buckets.iter().map(|bucket| bucket.count).sum::<u64>(), 4);
+        assert_eq!(snapshot.sum_ms, 2031.5);
+    }
+
+    #[test]
+    fn test_empty_histogram() {
+        let snapshot = LatencyHistogram::default().snapshot();
+
+        assert_eq!(snapshot.count, 0);
+        assert_eq!(snapshot.sum_ms, 0.0);
+        assert!(snapshot.buckets.iter().all(|bucket| bucket.count == 0));
+    }
+}
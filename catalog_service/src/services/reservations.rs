@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a reservation holds its stock when no TTL is configured.
+pub const DEFAULT_RESERVATION_TTL: Duration = Duration::from_secs(300);
+
+/// `ReservationStore` holds the reservations of stock that are yet to be placed as orders or
+/// released, each with the time it expires at.
+///
+/// The store only tracks the reservations, the reserved quantity itself is held on each item in the
+/// catalog database, see `CatalogDb::reserve`. Expired reservations are taken from the store by the
+/// sweeper of the catalog, which releases their quantity back to the available stock.
+///
+/// Fields:
+/// - `reservations`: The reservations that have not been released, keyed by their id.
+/// - `next_id`: The id given to the next reservation.
+#[derive(Default)]
+pub struct ReservationStore {
+    reservations: Mutex<HashMap<u64, Reservation>>,
+    next_id: AtomicU64,
+}
+
+/// A quantity of the stock of an item held for an order, as returned by
+/// `POST /catalog/{item_id}/reservations`.
+///
+/// Fields:
+/// - `id`: The identifier of the reservation, used to release it.
+/// - `item_id`: The identifier of the reserved catalog item.
+/// - `quantity`: The reserved quantity.
+/// - `expires_at`: When the reservation is released unless it is released earlier, in milliseconds
+///   since the Unix epoch.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Reservation {
+    pub id: u64,
+    pub item_id: u32,
+    pub quantity: u32,
+    pub expires_at: u64,
+}
+
+impl ReservationStore {
+    pub fn new() -> Self {
+        ReservationStore::default()
+    }
+
+    /// Records a reservation of an item, returning it with its id.
+    ///
+    /// Arguments:
+    /// - `item_id`: The identifier of the reserved catalog item.
+    /// - `quantity`: The reserved quantity.
+    /// - `expires_at`: When the reservation expires.
+    pub fn add(&self, item_id: u32, quantity: u32, expires_at: SystemTime) -> Reservation {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let reservation = Reservation {
+            id,
+            item_id,
+            quantity,
+            expires_at: millis_of(expires_at),
+        };
+        self.reservations.lock().unwrap().insert(id, reservation.clone());
+        reservation
+    }
+
+    /// Removes a reservation, returning it, or `None` if it was already released or has expired.
+    ///
+    /// Arguments:
+    /// - `id`: The identifier of the reservation.
+    pub fn remove(&self, id: u64) -> Option<Reservation> {
+        self.reservations.lock().unwrap().remove(&id)
+    }
+
+    /// Removes every reservation that has expired by `now`, returning them.
+    ///
+    /// Arguments:
+    /// - `now`: The current time.
+    pub fn take_expired(&self, now: SystemTime) -> Vec<Reservation> {
+        let now = millis_of(now);
+        let mut reservations = self.reservations.lock().unwrap();
+        let expired: Vec<u64> = reservations
+            .values()
+            .filter(|reservation| reservation.expires_at <= now)
+            .map(|reservation| reservation.id)
+            .collect();
+        expired.iter().filter_map(|id| reservations.remove(id)).collect()
+    }
+}
+
+// the number of milliseconds since the unix epoch, treating earlier times as the epoch
+fn millis_of(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO).as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_700_000_000 + seconds)
+    }
+
+    #[test]
+    fn test_add_assigns_distinct_ids() {
+        let sut = ReservationStore::new();
+
+        let first = sut.add(1, 2, at(60));
+        let second = sut.add(1, 3, at(60));
+
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.expires_at, 1_700_000_060_000);
+    }
+
+    #[test]
+    fn test_remove_releases_once() {
+        let sut = ReservationStore::new();
+        let reservation = sut.add(1, 2, at(60));
+
+        assert_eq!(sut.remove(reservation.id), Some(reservation.clone()));
+        assert_eq!(sut.remove(reservation.id), None);
+    }
+
+    #[test]
+    fn test_take_expired_leaves_live_reservations() {
+        let sut = ReservationStore::new();
+        let expiring = sut.add(1, 2, at(60));
+        let live = sut.add(2, 3, at(120));
+
+        let expired = sut.take_expired(at(60));
+
+        assert_eq!(expired, vec![expiring]);
+        assert!(sut.take_expired(at(60)).is_empty());
+        assert_eq!(sut.remove(live.id), Some(live));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::error::ServiceError;
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
+use serde::{Deserialize, Serialize};
+
+/// `ApiResponse` is the envelope the body of an API response is wrapped in, so that clients can
+/// read the result of every endpoint the same way.
+///
+/// Exactly one of `data` and `error` is set, and both are always serialized, as `null` when unset.
+///
+/// Fields:
+/// - `data`: The result of a successful request.
+/// - `error`: Why the request failed.
+/// - `request_id`: The id of the request, as returned in the `X-Request-Id` header, or empty if the
+///   request was not assigned one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ApiResponse<T> {
+    pub data: Option<T>,
+    pub error: Option<ApiError>,
+    pub request_id: String,
+}
+
+/// `ApiError` describes why a request wrapped in an `ApiResponse` failed.
+///
+/// Fields:
+/// - `status`: The HTTP status code of the response, e.g. `404`.
+/// - `message`: A human readable description of the error.
+/// - `field`: The field of the request body that was rejected, only set for invalid bodies, see `JsonBody`.
+/// - `reason`: Why the `field` was rejected, only set along with it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl<T> ApiResponse<T> {
+    /// Creates the envelope of a successful request.
+    ///
+    /// Arguments:
+    /// - `data`: The result of the request.
+    /// - `request_id`: The id of the request.
+    pub fn success(data: T, request_id: &str) -> Self {
+        ApiResponse {
+            data: Some(data),
+            error: None,
+            request_id: request_id.to_string(),
+        }
+    }
+
+    /// Creates the envelope of a failed request.
+    ///
+    /// Arguments:
+    /// - `error`: Why the request failed, which decides the status code of the response.
+    /// - `request_id`: The id of the request.
+    pub fn error(error: &ServiceError, request_id: &str) -> Self {
+        ApiResponse {
+            data: None,
+            error: Some(ApiError {
+                status: error.status_code().as_u16(),
+                message: error.message().to_string(),
+                field: None,
+                reason: None,
+            }),
+            request_id: request_id.to_string(),
+        }
+    }
+
+    /// Creates the envelope of a request from its result.
+    ///
+    /// Arguments:
+    /// - `result`: The result of the request.
+    /// - `request_id`: The id of the request.
+    pub fn from_result(result: Result<T, ServiceError>, request_id: &str) -> Self {
+        match result {
+            Ok(data) => ApiResponse::success(data, request_id),
+            Err(err) => ApiResponse::error(&err, request_id),
+        }
+    }
+
+    /// Returns the HTTP status code of the response, `200` unless the request failed.
+    pub fn status_code(&self) -> StatusCode {
+        self.error
+            .as_ref()
+            .and_then(|error| StatusCode::from_u16(error.status).ok())
+            .unwrap_or(StatusCode::OK)
+    }
+
+    /// Consumes the envelope, returning its data, or its error if the request failed.
+    pub fn into_result(self) -> Result<T, ApiError> {
+        match (self.data, self.error) {
+            (Some(data), None) => Ok(data),
+            (_, Some(error)) => Err(error),
+            (None, None) => Err(ApiError {
+                status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                message: "The response carried neither data nor an error.".to_string(),
+                field: None,
+                reason: None,
+            }),
+        }
+    }
+}
+
+impl<T: Serialize> ApiResponse<T> {
+    /// Converts the envelope into an `HttpResponse` with its status code and a JSON body.
+    pub fn to_http_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+    use serde_json::{json, Value};
+
+    async fn read_body(response: HttpResponse) -> Value {
+        let body = to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_success_envelope() {
+        // prepare
+        let response = ApiResponse::success(5u32, "abc-123");
+
+        // act
+        let http_response = response.to_http_response();
+
+        // assert
+        assert_eq!(http_response.status(), StatusCode::OK);
+        assert_eq!(
+            read_body(http_response).await,
+            json!({"data": 5, "error": null, "request_id": "abc-123"})
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_error_envelope() {
+        // prepare
+        let response = ApiResponse::<u32>::error(&ServiceError::NotFound("Missing".to_string()), "abc-123");
+
+        // act
+        let http_response = response.to_http_response();
+
+        // assert
+        assert_eq!(http_response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            read_body(http_response).await,
+            json!({"data": null, "error": {"status": 404, "message": "Missing"}, "request_id": "abc-123"})
+        );
+    }
+
+    #[test]
+    fn test_into_result() {
+        let success = ApiResponse::from_result(Ok(5u32), "");
+        let failure = ApiResponse::<u32>::from_result(Err(ServiceError::Conflict("Taken".to_string())), "");
+
+        assert_eq!(success.into_result(), Ok(5));
+        assert_eq!(failure.into_result().unwrap_err().status, 409);
+    }
+}
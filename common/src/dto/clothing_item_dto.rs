@@ -0,0 +1,83 @@
+use crate::types::money::Money;
+use serde::{Deserialize, Serialize};
+
+/// `ClothingItemDTO` is a Data Transfer Object for the catalog's `ClothingItem`.
+///
+/// This struct is used to communicate data about clothing items to clients and other
+/// microservices, excluding certain fields that are not necessary or should be kept private.
+/// Specifically, it omits the `stock` field present in the `ClothingItem` struct.
+/// The purpose of this struct is for transmitting it to the client so that they have
+/// no knowledge of the stock of the item.
+///
+/// Fields:
+/// - `id`: The unique identifier for the clothing item.
+/// - `sku`: The SKU of the clothing item, which can be used instead of `id` to reference it. Only
+///   serialized when the item has a SKU.
+/// - `name`: The name of the clothing item.
+/// - `description`: A description of the clothing item.
+/// - `sizes`: A list of available sizes for the clothing item.
+/// - `price`: The price of the clothing item.
+/// - `currency`: The ISO 4217 code of the currency the item is priced in.
+/// - `images`: URLs to images of the clothing item.
+/// - `allow_backorder`: Whether the item can be ordered when there is not enough stock. Only
+///   serialized when `true`.
+/// - `video`: A URL to a video showcasing the clothing item.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClothingItemDTO {
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sku: String,
+    pub name: String,
+    pub description: String,
+    pub sizes: Vec<String>,
+    pub price: Money,
+    pub currency: String,
+    pub images: Vec<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub allow_backorder: bool,
+    pub video: String,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_clothing_item_dto() {
+        // prepare
+        let dto = ClothingItemDTO {
+            id: 1,
+            sku: String::new(),
+            name: "T-Shirt".to_string(),
+            description: "desc".to_string(),
+            sizes: vec!["M".to_string()],
+            price: Money::new(2000, "GBP"),
+            currency: "GBP".to_string(),
+            images: vec!["https://example.com/t-shirt-front.jpg".to_string()],
+            allow_backorder: false,
+            video: "https://example.com/t-shirt-video.mp4".to_string(),
+        };
+
+        // act
+        let json = serde_json::to_value(&dto).unwrap();
+
+        // assert
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": 1,
+                "name": "T-Shirt",
+                "description": "desc",
+                "sizes": ["M"],
+                "price": "GBP 20.00",
+                "currency": "GBP",
+                "images": ["https://example.com/t-shirt-front.jpg"],
+                "video": "https://example.com/t-shirt-video.mp4"
+            })
+        );
+    }
+}
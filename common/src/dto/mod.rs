@@ -0,0 +1,2 @@
+pub mod api_response;
+pub mod clothing_item_dto;
@@ -0,0 +1,127 @@
+use crate::dto::api_response::ApiResponse;
+use crate::utilities::request_id;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use std::fmt::{Display, Formatter};
+
+/// A common error type shared by all microservices, used at the API boundary.
+///
+/// Each service converts its own errors into a `ServiceError` so that they can be rendered
+/// into HTTP responses consistently.
+///
+/// # Variants
+///
+/// * `NotFound`: The requested resource does not exist.
+/// * `BadRequest`: The request could not be fulfilled due to invalid input.
+/// * `Conflict`: The request conflicts with the current state of a resource, e.g. insufficient stock.
+/// * `Upstream`: A service that this service depends on failed or could not be reached.
+/// * `UnsupportedMediaType`: The request body is not in a format the endpoint accepts, e.g. not JSON.
+/// * `Internal`: An unexpected error occurred within the service.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceError {
+    NotFound(String),
+    BadRequest(String),
+    Conflict(String),
+    Upstream(String),
+    UnsupportedMediaType(String),
+    Internal(String),
+}
+
+impl ServiceError {
+    /// Returns the HTTP status code that corresponds to the error.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ServiceError::NotFound(_) => StatusCode::NOT_FOUND,
+            ServiceError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ServiceError::Conflict(_) => StatusCode::CONFLICT,
+            ServiceError::Upstream(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ServiceError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ServiceError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Returns the human readable message describing the error.
+    pub fn message(&self) -> &str {
+        match self {
+            ServiceError::NotFound(message)
+            | ServiceError::BadRequest(message)
+            | ServiceError::Conflict(message)
+            | ServiceError::Upstream(message)
+            | ServiceError::UnsupportedMediaType(message)
+            | ServiceError::Internal(message) => message,
+        }
+    }
+
+    /// Converts the error into an `HttpResponse` with the matching status code and a body of the form
+    /// `{"data": null, "error": {"status": <status>, "message": "<message>"}, "request_id": "<id>"}`,
+    /// the `ApiResponse` envelope every error is rendered in. The request id is that of the request
+    /// being handled, see `request_id::current`.
+    pub fn to_http_response(&self) -> HttpResponse {
+        ApiResponse::<()>::error(self, &request_id::current()).to_http_response()
+    }
+}
+
+impl Display for ServiceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+// allows handlers to return `Result<_, ServiceError>` and have actix render the error response
+impl ResponseError for ServiceError {
+    fn status_code(&self) -> StatusCode {
+        ServiceError::status_code(self)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        self.to_http_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+
+    #[test]
+    fn test_status_code() {
+        assert_eq!(
+            ServiceError::NotFound("".to_string()).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ServiceError::BadRequest("".to_string()).status_code(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            ServiceError::Conflict("".to_string()).status_code(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ServiceError::Upstream("".to_string()).status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            ServiceError::UnsupportedMediaType("".to_string()).status_code(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+        assert_eq!(
+            ServiceError::Internal("".to_string()).status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_to_http_response() {
+        let response = ServiceError::NotFound("This item does not exist.".to_string()).to_http_response();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(
+            body,
+            r#"{"data":null,"error":{"status":404,"message":"This item does not exist."},"request_id":""}"#
+        );
+    }
+}
@@ -1,3 +1,6 @@
 pub mod constants;
+pub mod dto;
+pub mod error;
 pub mod traits;
+pub mod types;
 pub mod utilities;
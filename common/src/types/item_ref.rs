@@ -0,0 +1,57 @@
+use std::fmt::{Display, Formatter};
+
+/// A reference to a catalog item, either by its numeric id or by its SKU.
+///
+/// The numeric id is internal to the catalog, whereas the SKU is the human readable identifier
+/// given to clients, e.g. `"TSHIRT-001"`. A reference taken from a URL path segment is an `Id`
+/// when the segment is a number and a `Sku` otherwise, so SKUs should never be purely numeric.
+///
+/// # Variants
+///
+/// * `Id`: The numeric id of the item.
+/// * `Sku`: The SKU of the item.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ItemRef {
+    Id(u32),
+    Sku(String),
+}
+
+impl From<&str> for ItemRef {
+    fn from(value: &str) -> Self {
+        match value.parse::<u32>() {
+            Ok(id) => ItemRef::Id(id),
+            Err(_) => ItemRef::Sku(value.to_string()),
+        }
+    }
+}
+
+impl Display for ItemRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemRef::Id(id) => write!(f, "{}", id),
+            ItemRef::Sku(sku) => write!(f, "{}", sku),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_numeric_segment_is_id() {
+        assert_eq!(ItemRef::from("42"), ItemRef::Id(42));
+    }
+
+    #[test]
+    fn test_from_non_numeric_segment_is_sku() {
+        assert_eq!(ItemRef::from("TSHIRT-001"), ItemRef::Sku("TSHIRT-001".to_string()));
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for item_ref in [ItemRef::Id(42), ItemRef::Sku("TSHIRT-001".to_string())] {
+            assert_eq!(ItemRef::from(item_ref.to_string().as_str()), item_ref);
+        }
+    }
+}
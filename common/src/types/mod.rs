@@ -0,0 +1,2 @@
+pub mod item_ref;
+pub mod money;
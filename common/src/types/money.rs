@@ -0,0 +1,303 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// the number of decimal places of every currency not listed below
+const DEFAULT_DECIMAL_PLACES: u32 = 2;
+// active ISO 4217 currencies without a minor unit, e.g. yen
+const ZERO_DECIMAL_CURRENCIES: [&str; 16] = [
+    "BIF", "CLP", "DJF", "GNF", "ISK", "JPY", "KMF", "KRW", "PYG", "RWF", "UGX", "VND", "VUV", "XAF", "XOF", "XPF",
+];
+// active ISO 4217 currencies with three decimal places, e.g. the dinars
+const THREE_DECIMAL_CURRENCIES: [&str; 7] = ["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+
+// active ISO 4217 currency codes
+const CURRENCY_CODES: [&str; 153] = [
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT", "BGN", "BHD", "BIF",
+    "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD", "CDF", "CHF", "CLP", "CNY", "COP", "CRC",
+    "CUP", "CVE", "CZK", "DJF", "DKK", "DOP", "DZD", "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS",
+    "GIP", "GMD", "GNF", "GTQ", "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR", "ISK", "JMD",
+    "JOD", "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD", "KZT", "LAK", "LBP", "LKR", "LRD", "LSL",
+    "LYD", "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRU", "MUR", "MVR", "MWK", "MXN", "MYR", "MZN", "NAD",
+    "NGN", "NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN", "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON", "RSD",
+    "RUB", "RWF", "SAR", "SBD", "SCR", "SDG", "SEK", "SGD", "SHP", "SLE", "SOS", "SRD", "SSP", "STN", "SYP", "SZL",
+    "THB", "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX", "USD", "UYU", "UZS", "VES", "VND",
+    "VUV", "WST", "XAF", "XCD", "XOF", "XPF", "YER", "ZAR", "ZMW",
+];
+
+/// Checks whether the given code is a known ISO 4217 currency code.
+///
+/// # Arguments
+///
+/// * `code`: The currency code to check, which must be upper case, e.g. `"GBP"`.
+pub fn is_known_currency(code: &str) -> bool {
+    CURRENCY_CODES.contains(&code)
+}
+
+/// Returns the number of decimal places of a currency, i.e. how many digits its minor unit has,
+/// as defined by ISO 4217, e.g. `2` for `"GBP"` and `0` for `"JPY"`.
+///
+/// # Arguments
+///
+/// * `code`: The currency code, which must be upper case. Unknown codes are assumed to have two.
+pub fn decimal_places(code: &str) -> u32 {
+    if ZERO_DECIMAL_CURRENCIES.contains(&code) {
+        0
+    } else if THREE_DECIMAL_CURRENCIES.contains(&code) {
+        3
+    } else {
+        DEFAULT_DECIMAL_PLACES
+    }
+}
+
+/// Represents an amount of money in a specific currency.
+///
+/// The amount is stored as an integer number of minor units (e.g. pence or cents) so that
+/// arithmetic on prices never suffers from floating point drift. The size of a minor unit depends
+/// on the currency, see `decimal_places`, so `2000` is 20.00 in `"USD"` but 2000 in `"JPY"`.
+/// Arithmetic is checked, failing with `MoneyError::Overflow` rather than wrapping.
+///
+/// `Money` is serialized as a string containing the currency code followed by the amount in
+/// major units, for example `"USD 20.00"` or `"JPY 2000"`.
+///
+/// # Fields
+///
+/// * `amount`: The amount in minor units.
+/// * `currency`: The ISO 4217 currency code, e.g. `"GBP"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Money {
+    amount: i64,
+    currency: String,
+}
+
+impl Money {
+    /// Creates a new `Money` from an amount in minor units.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount`: The amount in minor units, e.g. `2000` for 20.00.
+    /// * `currency`: The ISO 4217 currency code.
+    pub fn new(amount: i64, currency: &str) -> Self {
+        Money {
+            amount,
+            currency: currency.to_uppercase(),
+        }
+    }
+
+    /// Creates a new `Money` from an amount in major units, rounding to the nearest minor unit.
+    ///
+    /// This is intended for converting legacy floating point prices and should not be used for arithmetic.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount`: The amount in major units, e.g. `20.0`.
+    /// * `currency`: The ISO 4217 currency code.
+    pub fn from_major(amount: f64, currency: &str) -> Self {
+        let currency = currency.to_uppercase();
+        let minor_units_per_major = minor_units_per_major(&currency);
+        Money::new((amount * minor_units_per_major as f64).round() as i64, &currency)
+    }
+
+    /// Returns the amount in minor units.
+    pub fn amount(&self) -> i64 {
+        self.amount
+    }
+
+    /// Returns the ISO 4217 currency code.
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    /// Adds two amounts of money together.
+    ///
+    /// # Returns
+    ///
+    /// Returns the sum, or a `MoneyError::CurrencyMismatch` if the currencies differ.
+    pub fn checked_add(&self, other: &Money) -> Result<Money, MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch);
+        }
+
+        let amount = self.amount.checked_add(other.amount).ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(amount, &self.currency))
+    }
+
+    /// Multiplies an amount of money by a quantity, e.g. to price several of an item.
+    ///
+    /// # Returns
+    ///
+    /// Returns the product, or a `MoneyError::Overflow` if it does not fit in the amount.
+    pub fn checked_mul(&self, quantity: u32) -> Result<Money, MoneyError> {
+        let amount = self.amount.checked_mul(i64::from(quantity)).ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(amount, &self.currency))
+    }
+}
+
+// the number of minor units in a single major unit of a currency, e.g. 100 pence in a pound
+fn minor_units_per_major(currency: &str) -> i64 {
+    10i64.pow(decimal_places(currency))
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.amount < 0 { "-" } else { "" };
+        let amount = self.amount.unsigned_abs();
+        let places = decimal_places(&self.currency) as usize;
+        let minor_units = minor_units_per_major(&self.currency) as u64;
+        write!(f, "{} {}{}", self.currency, sign, amount / minor_units)?;
+        if places > 0 {
+            write!(f, ".{:0places$}", amount % minor_units)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Money {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (currency, amount) = s.trim().split_once(' ').ok_or(MoneyError::InvalidFormat)?;
+        if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(MoneyError::InvalidFormat);
+        }
+
+        let (negative, amount) = match amount.strip_prefix('-') {
+            Some(stripped) => (true, stripped),
+            None => (false, amount),
+        };
+        let currency = currency.to_uppercase();
+        let places = decimal_places(&currency) as usize;
+        let (major, minor) = match amount.split_once('.') {
+            Some((major, minor)) => (major, Some(minor)),
+            None => (amount, None),
+        };
+        let is_digits = |value: &str| !value.is_empty() && value.chars().all(|c| c.is_ascii_digit());
+        if !is_digits(major) || minor.is_some_and(|minor| !is_digits(minor) || minor.len() > places) {
+            return Err(MoneyError::InvalidFormat);
+        }
+
+        let major = major.parse::<i64>().map_err(|_| MoneyError::InvalidFormat)?;
+        let minor = match minor {
+            Some(minor) => format!("{:0<places$}", minor).parse::<i64>().map_err(|_| MoneyError::InvalidFormat)?,
+            None => 0,
+        };
+        let total = major
+            .checked_mul(minor_units_per_major(&currency))
+            .and_then(|major| major.checked_add(minor))
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(if negative { -total } else { total }, &currency))
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(|_| serde::de::Error::custom(format!("Invalid money value: {}", value)))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MoneyError {
+    CurrencyMismatch,
+    InvalidFormat,
+    Overflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_known_currency() {
+        assert!(is_known_currency("GBP"));
+        assert!(is_known_currency("USD"));
+        assert!(!is_known_currency("ABC"));
+        assert!(!is_known_currency("gbp"));
+    }
+
+    #[test]
+    fn test_checked_add() {
+        let sum = Money::new(10, "USD").checked_add(&Money::new(20, "USD")).unwrap();
+
+        assert_eq!(sum, Money::new(30, "USD"));
+    }
+
+    #[test]
+    fn test_checked_add_currency_mismatch() {
+        let result = Money::new(10, "USD").checked_add(&Money::new(20, "GBP"));
+
+        assert_eq!(result, Err(MoneyError::CurrencyMismatch));
+    }
+
+    #[test]
+    fn test_arithmetic_has_no_float_drift() {
+        let mut total = Money::new(0, "GBP");
+        for _ in 0..10 {
+            total = total.checked_add(&Money::from_major(0.1, "GBP")).unwrap();
+        }
+
+        assert_eq!(total, Money::new(100, "GBP"));
+        assert_eq!(Money::new(1999, "GBP").checked_mul(3), Ok(Money::new(5997, "GBP")));
+    }
+
+    #[test]
+    fn test_checked_arithmetic_overflow() {
+        assert_eq!(Money::new(i64::MAX, "GBP").checked_mul(2), Err(MoneyError::Overflow));
+        assert_eq!(
+            Money::new(i64::MAX, "GBP").checked_add(&Money::new(1, "GBP")),
+            Err(MoneyError::Overflow)
+        );
+        assert_eq!("GBP 99999999999999999".parse::<Money>(), Err(MoneyError::Overflow));
+    }
+
+    #[test]
+    fn test_decimal_places_per_currency() {
+        assert_eq!(decimal_places("GBP"), 2);
+        assert_eq!(decimal_places("JPY"), 0);
+        assert_eq!(decimal_places("KWD"), 3);
+    }
+
+    #[test]
+    fn test_zero_and_three_decimal_currencies() {
+        assert_eq!(Money::new(2000, "JPY").to_string(), "JPY 2000");
+        assert_eq!(Money::from_major(2000.0, "jpy"), Money::new(2000, "JPY"));
+        assert_eq!("JPY 2000".parse(), Ok(Money::new(2000, "JPY")));
+        assert_eq!("JPY 20.00".parse::<Money>(), Err(MoneyError::InvalidFormat));
+        assert_eq!(Money::new(1500, "KWD").to_string(), "KWD 1.500");
+        assert_eq!("KWD 1.5".parse(), Ok(Money::new(1500, "KWD")));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Money::new(2000, "usd").to_string(), "USD 20.00");
+        assert_eq!(Money::new(5, "GBP").to_string(), "GBP 0.05");
+        assert_eq!(Money::new(-150, "GBP").to_string(), "GBP -1.50");
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("USD 20.00".parse(), Ok(Money::new(2000, "USD")));
+        assert_eq!("GBP 3.5".parse(), Ok(Money::new(350, "GBP")));
+        assert_eq!("GBP -1.50".parse(), Ok(Money::new(-150, "GBP")));
+        assert_eq!("GBP 7".parse(), Ok(Money::new(700, "GBP")));
+        assert_eq!("20.00".parse::<Money>(), Err(MoneyError::InvalidFormat));
+        assert_eq!("GBP 1.234".parse::<Money>(), Err(MoneyError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let price = Money::new(2000, "USD");
+
+        let json = serde_json::to_string(&price).unwrap();
+        let deserialized: Money = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(json, "\"USD 20.00\"");
+        assert_eq!(deserialized, price);
+    }
+}
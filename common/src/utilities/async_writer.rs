@@ -0,0 +1,171 @@
+use std::io::{self, Write};
+use std::mem;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+
+/// What an `AsyncWriter` does when its channel to the writer thread is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backpressure {
+    /// Waits for the writer thread to catch up, so no log lines are lost.
+    #[default]
+    Block,
+    /// Discards the log line, so logging never waits on disk I/O.
+    Drop,
+}
+
+/// An `io::Write` that hands its output to a background thread, which performs the actual
+/// (potentially slow) writes to the inner writer.
+///
+/// Bytes are buffered until `flush` is called and then forwarded as a single message, so a log
+/// record is either written whole or, with `Backpressure::Drop`, not at all. `flush` only forwards
+/// the buffer and never waits for the inner writer. Dropping the `AsyncWriter` waits for every
+/// forwarded message to be written.
+pub struct AsyncWriter {
+    tx: Option<SyncSender<Vec<u8>>>,
+    buffer: Vec<u8>,
+    backpressure: Backpressure,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl AsyncWriter {
+    /// Creates a new `AsyncWriter` and spawns its writer thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The writer the background thread writes to, e.g. a log file.
+    /// * `capacity` - The number of messages that can be queued before backpressure applies.
+    /// * `backpressure` - Whether to block or drop messages when the queue is full.
+    pub fn new<W: Write + Send + 'static>(mut inner: W, capacity: usize, backpressure: Backpressure) -> Self {
+        let (tx, rx) = mpsc::sync_channel::<Vec<u8>>(capacity);
+        let handle = thread::spawn(move || {
+            for message in rx {
+                if inner.write_all(&message).and_then(|_| inner.flush()).is_err() {
+                    // written to stderr rather than logged, as the failed log file is where a log line would go
+                    eprintln!("Failed to write log message to the log file");
+                }
+            }
+        });
+
+        AsyncWriter {
+            tx: Some(tx),
+            buffer: vec![],
+            backpressure,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Write for AsyncWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let message = mem::take(&mut self.buffer);
+        let tx = match &self.tx {
+            Some(tx) => tx,
+            None => return Err(io::Error::new(io::ErrorKind::BrokenPipe, "writer thread has stopped")),
+        };
+        let result = match self.backpressure {
+            Backpressure::Block => tx.send(message).map_err(|_| ()),
+            Backpressure::Drop => match tx.try_send(message) {
+                Ok(()) | Err(TrySendError::Full(_)) => Ok(()),
+                Err(TrySendError::Disconnected(_)) => Err(()),
+            },
+        };
+        result.map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "writer thread has stopped"))
+    }
+}
+
+impl Drop for AsyncWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+        // closing the channel ends the writer thread once it has written every queued message
+        self.tx.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Log;
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+
+    // a writer that waits on a shared lock before every write, used to simulate slow disk I/O
+    struct GatedWriter {
+        gate: Arc<Mutex<()>>,
+        written: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Write for GatedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let _gate = self.gate.lock().unwrap();
+            self.written.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_log_lines_reach_file_in_order() {
+        // prepare
+        let path = std::env::temp_dir().join(format!("async_writer_test_{}.log", std::process::id()));
+        let file = fs::File::create(&path).unwrap();
+        let writer: Box<dyn Write + Send> = Box::new(AsyncWriter::new(file, 16, Backpressure::Block));
+        let (_, logger) = fern::Dispatch::new().chain(writer).into_log();
+
+        // act
+        for i in 0..1000 {
+            logger.log(&log::Record::builder().args(format_args!("line {}", i)).build());
+        }
+        drop(logger);
+
+        // assert
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 1000);
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(*line, format!("line {}", i));
+        }
+    }
+
+    #[test]
+    fn test_drop_backpressure_does_not_block_on_slow_writer() {
+        // prepare
+        let gate = Arc::new(Mutex::new(()));
+        let written = Arc::new(Mutex::new(vec![]));
+        let inner = GatedWriter {
+            gate: gate.clone(),
+            written: written.clone(),
+        };
+        let mut writer = AsyncWriter::new(inner, 1, Backpressure::Drop);
+        let held_gate = gate.lock().unwrap();
+
+        // act
+        for i in 0..100 {
+            writeln!(writer, "line {}", i).unwrap();
+            writer.flush().unwrap();
+        }
+        drop(held_gate);
+        drop(writer);
+
+        // assert
+        let written = String::from_utf8(written.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert!(!lines.is_empty());
+        assert!(lines.len() < 100);
+        assert_eq!(lines[0], "line 0");
+    }
+}
@@ -0,0 +1,315 @@
+use crate::dto::api_response::{ApiError, ApiResponse};
+use crate::utilities::request_id;
+use actix_web::dev::Payload;
+use actix_web::http::StatusCode;
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse, ResponseError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+
+/// A JSON request body extracted like `web::Json`, whose deserialization errors name the field
+/// that failed and why, e.g. a `quantity` sent as a string.
+///
+/// The body is read through `web::Json`, so the `JsonConfig` of the app still limits its size and
+/// requires a JSON `Content-Type`. A body that is valid JSON, but does not match `T`, is rejected
+/// with a `400` and a `FieldError`.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[post("/order")]
+/// pub async fn place_order(order_request: JsonBody<OrderRequest>) -> impl Responder { ... }
+/// ```
+#[derive(Debug)]
+pub struct JsonBody<T>(pub T);
+
+impl<T> JsonBody<T> {
+    /// Unwraps the deserialized body.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for JsonBody<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for JsonBody<T> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = web::Json::<Value>::from_request(req, payload);
+        Box::pin(async move {
+            let value = json.await?.into_inner();
+            from_value(value).map(JsonBody).map_err(Into::into)
+        })
+    }
+}
+
+/// The field of a JSON body that could not be deserialized, rendered as a `400` with an `ApiResponse`
+/// whose error names the field, e.g.
+///
+/// ```json
+/// {
+///     "data": null,
+///     "error": {"status": 400, "message": "<message>", "field": "<field>", "reason": "<reason>"},
+///     "request_id": "<id>"
+/// }
+/// ```
+///
+/// # Fields
+///
+/// * `field`: The path of the field, e.g. `quantity`, `[1].quantity` for the second order of a
+///   list, or empty when the body as a whole has the wrong type.
+/// * `reason`: Why the field could not be deserialized, as reported by serde, e.g.
+///   `invalid type: string "2", expected u32` or `missing field `name``.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "Invalid request body: {}", self.reason)
+        } else {
+            write!(f, "Invalid field `{}`: {}", self.field, self.reason)
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+impl ResponseError for FieldError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let response = ApiResponse::<()> {
+            data: None,
+            error: Some(ApiError {
+                status: self.status_code().as_u16(),
+                message: self.to_string(),
+                field: Some(self.field.clone()),
+                reason: Some(self.reason.clone()),
+            }),
+            request_id: request_id::current(),
+        };
+        response.to_http_response()
+    }
+}
+
+/// Deserializes a JSON value, naming the field that failed if it does not match `T`.
+///
+/// # Arguments
+///
+/// * `value` - The JSON value, e.g. a request body.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, FieldError> {
+    // TODO: track the path with `serde_path_to_error` once it can be added to the workspace, it is not
+    // yet a locked dependency, so the path is located from the position of a reparse of the value instead.
+    // Errors deserializing a value carry no position, so they are only located when one occurs
+    let reason = match T::deserialize(&value) {
+        Ok(deserialized) => return Ok(deserialized),
+        Err(e) => e.to_string(),
+    };
+    // parsing the value as text fails at the same field, with the position of its value
+    let text = value.to_string();
+    let column = serde_json::from_str::<T>(&text).err().map(|e| e.column()).unwrap_or_default();
+    let mut path = path_at(&text, column);
+    if let Some(missing) = missing_field(&reason) {
+        // the object the field is missing from has been closed when the error is raised
+        path.push(Some(Segment::Field(missing.to_string())));
+    }
+    Err(FieldError {
+        field: render_path(&path),
+        reason,
+    })
+}
+
+// a step on the path to a field, either an object's field or an array's index
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+}
+
+// the field named by serde's missing field error, e.g. `missing field `name``
+fn missing_field(reason: &str) -> Option<&str> {
+    reason.strip_prefix("missing field `")?.strip_suffix('`')
+}
+
+// the path to the value being parsed at the given byte offset of compact, valid json, with a `None`
+// for each object whose first field is yet to be read
+fn path_at(text: &str, offset: usize) -> Vec<Option<Segment>> {
+    let bytes = text.as_bytes();
+    // the path so far, and whether the next string of the innermost object is a field name
+    let mut path: Vec<Option<Segment>> = vec![];
+    let mut expects_field = false;
+    let mut i = 0;
+    while i < offset.min(bytes.len()) {
+        match bytes[i] {
+            b'"' => {
+                let end = string_end(bytes, i);
+                if expects_field {
+                    let field = serde_json::from_str(&text[i..=end]).unwrap_or_default();
+                    if let Some(segment) = path.last_mut() {
+                        *segment = Some(Segment::Field(field));
+                    }
+                    expects_field = false;
+                }
+                i = end;
+            }
+            b'{' => {
+                path.push(None);
+                expects_field = true;
+            }
+            b'[' => path.push(Some(Segment::Index(0))),
+            b',' => match path.last_mut() {
+                Some(Some(Segment::Index(index))) => *index += 1,
+                _ => expects_field = true,
+            },
+            b'}' | b']' => {
+                path.pop();
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    path
+}
+
+// the index of the quote closing the string starting at `start`, skipping escaped characters
+fn string_end(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() && bytes[i] != b'"' {
+        i += if bytes[i] == b'\\' { 2 } else { 1 };
+    }
+    i
+}
+
+// renders a path as it is written in javascript, e.g. `[1].quantity`
+fn render_path(path: &[Option<Segment>]) -> String {
+    let mut rendered = String::new();
+    for segment in path.iter().flatten() {
+        match segment {
+            Segment::Field(field) if rendered.is_empty() => rendered.push_str(field),
+            Segment::Field(field) => {
+                rendered.push('.');
+                rendered.push_str(field);
+            }
+            Segment::Index(index) => rendered.push_str(&format!("[{}]", index)),
+        }
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Order {
+        name: String,
+        #[serde(default)]
+        quantity: u32,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Basket {
+        owner: String,
+        orders: Vec<Order>,
+    }
+
+    #[test]
+    fn test_from_value_valid() {
+        let order: Order = from_value(json!({"name": "James", "quantity": 2})).unwrap();
+
+        assert_eq!(order.quantity, 2);
+    }
+
+    #[test]
+    fn test_from_value_names_wrong_typed_field() {
+        let err = from_value::<Order>(json!({"name": "James", "quantity": "2"})).unwrap_err();
+
+        assert_eq!(
+            err,
+            FieldError {
+                field: "quantity".to_string(),
+                reason: r#"invalid type: string "2", expected u32"#.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_value_names_missing_field() {
+        let err = from_value::<Order>(json!({"quantity": 2})).unwrap_err();
+
+        assert_eq!(err.field, "name");
+        assert_eq!(err.reason, "missing field `name`");
+    }
+
+    #[test]
+    fn test_from_value_names_nested_fields() {
+        let basket = json!({"owner": "James", "orders": [{"name": "a"}, {"name": "b\\\"", "quantity": -1}]});
+        assert_eq!(from_value::<Basket>(basket).unwrap_err().field, "orders[1].quantity");
+
+        let basket = json!({"owner": "James", "orders": [{"name": "a"}, {}]});
+        assert_eq!(from_value::<Basket>(basket).unwrap_err().field, "orders[1].name");
+
+        let orders = json!([{"name": "a"}, {"name": 1}]);
+        assert_eq!(from_value::<Vec<Order>>(orders).unwrap_err().field, "[1].name");
+    }
+
+    #[test]
+    fn test_from_value_wrong_typed_body_has_no_field() {
+        let err = from_value::<Order>(json!("James")).unwrap_err();
+
+        assert_eq!(err.field, "");
+        assert_eq!(err.to_string(), format!("Invalid request body: {}", err.reason));
+    }
+
+    #[actix_web::test]
+    async fn test_field_error_response() {
+        // prepare
+        let err = FieldError {
+            field: "quantity".to_string(),
+            reason: "invalid type: string \"2\", expected u32".to_string(),
+        };
+
+        // act
+        let response = err.error_response();
+
+        // assert
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "data": null,
+                "error": {
+                    "status": 400,
+                    "message": "Invalid field `quantity`: invalid type: string \"2\", expected u32",
+                    "field": "quantity",
+                    "reason": "invalid type: string \"2\", expected u32"
+                },
+                "request_id": ""
+            })
+        );
+    }
+}
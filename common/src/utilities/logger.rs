@@ -1,9 +1,13 @@
+use crate::utilities::async_writer::{AsyncWriter, Backpressure};
 use chrono::Local;
 use colored::*;
 use fern::Dispatch;
-use log::{error, info, LevelFilter};
-use std::io;
+use log::kv::{self, Key, Value, VisitSource};
+use log::{error, info, LevelFilter, Record};
+use std::fmt::Write as _;
+use std::io::{self, Write};
 use std::string::ToString;
+use std::sync::{Arc, Mutex, OnceLock};
 
 const ERROR_LEVEL: &str = "ERROR";
 const WARN_LEVEL: &str = "WARN";
@@ -11,16 +15,104 @@ const INFO_LEVEL: &str = "INFO";
 const DEBUG_LEVEL: &str = "DEBUG";
 const TRACE_LEVEL: &str = "TRACE";
 
-/// Initializes the logging macros for the entire application. You can configure the logging level
-/// directly here within the code.
+/// Configures the levels at which log messages are emitted.
+///
+/// A global level applies to every target, and can be overridden for individual targets, for
+/// example to silence noisy `rdkafka` logs while keeping the microservices at `Debug`. An override
+/// for a target also applies to all of its submodules.
+///
+/// # Examples
+///
+/// ```ignore
+/// let config = LoggerConfig::new()
+///     .level(LevelFilter::Debug)
+///     .level_for("rdkafka", LevelFilter::Warn);
+/// logger::initialize_with_config("catalog_output.log", "Catalog", config);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoggerConfig {
+    level: LevelFilter,
+    target_levels: Vec<(String, LevelFilter)>,
+    async_file: Option<(usize, Backpressure)>,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        LoggerConfig {
+            level: LevelFilter::Info,
+            target_levels: vec![],
+            async_file: None,
+        }
+    }
+}
+
+impl LoggerConfig {
+    /// Creates a new `LoggerConfig` that logs everything at `Info` and above.
+    pub fn new() -> Self {
+        LoggerConfig::default()
+    }
+
+    /// Sets the global level applied to every target without an override.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The most verbose level that is logged.
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Overrides the level for a target and all of its submodules.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target prefix, e.g. `rdkafka` or `catalog_service::api`.
+    /// * `level` - The most verbose level that is logged for the target.
+    pub fn level_for(mut self, target: &str, level: LevelFilter) -> Self {
+        self.target_levels.push((target.to_string(), level));
+        self
+    }
+
+    /// Writes the log file on a background thread, so logging calls don't block on disk I/O.
+    ///
+    /// Log lines still queued when the process exits may be lost.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of log lines that can be queued for the background thread.
+    /// * `backpressure` - Whether to block or drop log lines when the queue is full.
+    pub fn async_file(mut self, capacity: usize, backpressure: Backpressure) -> Self {
+        self.async_file = Some((capacity, backpressure));
+        self
+    }
+
+    // applies the global level and every target override to the dispatch
+    fn apply_levels(&self, dispatch: Dispatch) -> Dispatch {
+        self.target_levels.iter().fold(dispatch.level(self.level), |dispatch, (target, level)| {
+            dispatch.level_for(target.clone(), *level)
+        })
+    }
+}
+
+/// Initializes the logging macros for the entire application, logging everything at `Info` and above.
 ///
 /// # Arguments
 ///
 /// * `log_output_file` - The path to the logging file
 /// * `microservice_name` - The name of the microservice you are initializing the logger for
 pub fn initialize(log_output_file: &str, microservice_name: &str) {
-    let verbose = false;
-    match configure_logger(verbose, log_output_file) {
+    initialize_with_config(log_output_file, microservice_name, LoggerConfig::default());
+}
+
+/// Initializes the logging macros for the entire application using the given `LoggerConfig`.
+///
+/// # Arguments
+///
+/// * `log_output_file` - The path to the logging file
+/// * `microservice_name` - The name of the microservice you are initializing the logger for
+/// * `config` - The levels at which messages are logged
+pub fn initialize_with_config(log_output_file: &str, microservice_name: &str, config: LoggerConfig) {
+    match configure_logger(&config, log_output_file) {
         Ok(()) => {
             info!("{} microservice started", microservice_name);
             info!("Logger successfully configured");
@@ -32,12 +124,71 @@ pub fn initialize(log_output_file: &str, microservice_name: &str) {
     }
 }
 
-fn configure_logger(verbose: bool, log_output_file: &str) -> Result<(), fern::InitError> {
-    let mut verbosity = LevelFilter::Info;
-    if verbose {
-        verbosity = LevelFilter::Debug;
+/// A log output that keeps every formatted log line in memory, so tests can assert on the log
+/// output of the code under test.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLogSink {
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl MemoryLogSink {
+    /// Creates a new, empty `MemoryLogSink`.
+    pub fn new() -> Self {
+        MemoryLogSink::default()
     }
 
+    /// Returns a copy of every log line captured so far.
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().clone()
+    }
+
+    /// Returns whether any captured log line contains the given text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to search the captured log lines for.
+    pub fn contains(&self, text: &str) -> bool {
+        self.lines.lock().unwrap().iter().any(|line| line.contains(text))
+    }
+
+    /// Removes every captured log line.
+    pub fn clear(&self) {
+        self.lines.lock().unwrap().clear();
+    }
+}
+
+impl From<MemoryLogSink> for fern::Output {
+    fn from(sink: MemoryLogSink) -> Self {
+        fern::Output::call(move |record| sink.lines.lock().unwrap().push(record.args().to_string()))
+    }
+}
+
+static TEST_LOG_SINK: OnceLock<MemoryLogSink> = OnceLock::new();
+
+/// Initializes the logging macros to capture every log line, at all levels, in a `MemoryLogSink`
+/// rather than writing them to stdout and a file.
+///
+/// The logger can only be initialized once per process, so every call returns the same sink. As
+/// tests run in parallel, the sink also captures log lines from other tests, and assertions should
+/// look for specific lines via `MemoryLogSink::contains` rather than on the full output.
+pub fn initialize_for_test() -> MemoryLogSink {
+    TEST_LOG_SINK
+        .get_or_init(|| {
+            let sink = MemoryLogSink::new();
+            let result = Dispatch::new().level(LevelFilter::Trace).format(format_file_line).chain(sink.clone()).apply();
+            // reported to the logger that was initialized instead
+            if let Err(e) = result {
+                error!(
+                    "A logger has already been initialized, log lines will not be captured: {}",
+                    e
+                );
+            }
+            sink
+        })
+        .clone()
+}
+
+fn configure_logger(config: &LoggerConfig, log_output_file: &str) -> Result<(), fern::InitError> {
     // configure a logger for the console to include the ANSI color codes
     let console_dispatch = Dispatch::new()
         // format: specify log line format
@@ -50,35 +201,160 @@ fn configure_logger(verbose: bool, log_output_file: &str) -> Result<(), fern::In
                 log::Level::Trace => TRACE_LEVEL.cyan(),
             };
             out.finish(format_args!(
-                "{} [{}] [{}] - {}",
+                "{} [{}] [{}] - {}{}",
                 Local::now().format("[%Y-%m-%d][%H:%M:%S]").to_string().blue(),
                 record.target().to_uppercase().green(),
                 level_string,
-                message
+                message,
+                format_key_values(record).cyan()
             ))
         })
         // output: specify log output
-        .chain(io::stdout()) // log to stdout
-        .level(verbosity);
+        .chain(io::stdout()); // log to stdout
+
+    let log_file = fern::log_file(log_output_file)?;
+    let file_output: fern::Output = match config.async_file {
+        Some((capacity, backpressure)) => {
+            let writer: Box<dyn Write + Send> = Box::new(AsyncWriter::new(log_file, capacity, backpressure));
+            writer.into()
+        }
+        None => log_file.into(),
+    };
 
     // configure a logger for the file to exclude ANSI color codes
     let file_dispatch = Dispatch::new()
         // format: specify log line format
-        .format(|out, message, record| {
-            out.finish(format_args!(
-                "{} [{}] [{}] - {}",
-                chrono::Local::now().format("[%Y-%m-%d][%H:%M:%S]").to_string(),
-                record.target().to_uppercase(),
-                record.level(),
-                message
-            ))
-        })
+        .format(format_file_line)
         // output: specify log output
-        .chain(fern::log_file(log_output_file)?) // log to a file
-        .level(verbosity);
+        .chain(file_output); // log to a file
 
-    // implement both loggers on the base dispatch logger
-    Dispatch::new().chain(file_dispatch).chain(console_dispatch).apply()?;
+    // implement both loggers on the base dispatch logger, which filters messages for both
+    config.apply_levels(Dispatch::new()).chain(file_dispatch).chain(console_dispatch).apply()?;
 
     Ok(())
 }
+
+fn format_file_line(out: fern::FormatCallback, message: &std::fmt::Arguments, record: &Record) {
+    out.finish(format_args!(
+        "{} [{}] [{}] - {}{}",
+        Local::now().format("[%Y-%m-%d][%H:%M:%S]"),
+        record.target().to_uppercase(),
+        record.level(),
+        message,
+        format_key_values(record)
+    ))
+}
+
+// collects the structured key-value pairs attached to a record, e.g. `info!(order_id = 1; "...")`
+struct KeyValueCollector(String);
+
+impl<'kvs> VisitSource<'kvs> for KeyValueCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        write!(self.0, " {}={}", key, value).map_err(|_| kv::Error::msg("failed to format key-value pair"))
+    }
+}
+
+/// Formats the key-value pairs attached to a log record as ` key=value` pairs, in the order in
+/// which they were attached. Returns an empty string if the record has no key-value pairs.
+///
+/// # Arguments
+///
+/// * `record` - The log record to format the key-value pairs of.
+fn format_key_values(record: &Record) -> String {
+    let mut collector = KeyValueCollector(String::new());
+    let _ = record.key_values().visit(&mut collector);
+    collector.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::{Level, Log, Metadata};
+
+    fn is_enabled(config: &LoggerConfig, target: &str, level: Level) -> bool {
+        let (_, logger) = config.apply_levels(Dispatch::new()).chain(fern::Output::call(|_| {})).into_log();
+        logger.enabled(&Metadata::builder().target(target).level(level).build())
+    }
+
+    #[test]
+    fn test_default_config_logs_info() {
+        let config = LoggerConfig::new();
+
+        assert!(is_enabled(&config, "catalog_service", Level::Info));
+        assert!(!is_enabled(&config, "catalog_service", Level::Debug));
+    }
+
+    #[test]
+    fn test_level_for_suppresses_target() {
+        let config = LoggerConfig::new().level(LevelFilter::Debug).level_for("rdkafka", LevelFilter::Warn);
+
+        assert!(!is_enabled(&config, "rdkafka::client", Level::Info));
+        assert!(is_enabled(&config, "rdkafka::client", Level::Error));
+        assert!(is_enabled(&config, "catalog_service", Level::Debug));
+    }
+
+    #[test]
+    fn test_level_for_allows_target() {
+        let config = LoggerConfig::new()
+            .level(LevelFilter::Warn)
+            .level_for("order_service::services", LevelFilter::Debug);
+
+        assert!(is_enabled(
+            &config,
+            "order_service::services::order_service",
+            Level::Debug
+        ));
+        assert!(!is_enabled(&config, "order_service::api", Level::Info));
+    }
+
+    #[test]
+    fn test_key_values_are_appended_to_file_line() {
+        // prepare
+        let lines = Arc::new(Mutex::new(vec![]));
+        let captured = lines.clone();
+        let (_, logger) = Dispatch::new()
+            .format(format_file_line)
+            .chain(fern::Output::call(move |record| {
+                captured.lock().unwrap().push(record.args().to_string())
+            }))
+            .into_log();
+        let key_values = [("order_id", 7), ("item_id", 3)];
+
+        // act
+        logger.log(
+            &Record::builder()
+                .target("order_service")
+                .level(Level::Info)
+                .args(format_args!("Order placed"))
+                .key_values(&key_values)
+                .build(),
+        );
+
+        // assert
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with("[ORDER_SERVICE] [INFO] - Order placed order_id=7 item_id=3"));
+    }
+
+    #[test]
+    fn test_format_key_values_without_pairs() {
+        // act
+        let formatted = format_key_values(&Record::builder().args(format_args!("no context")).build());
+
+        // assert
+        assert_eq!(formatted, "");
+    }
+
+    #[test]
+    fn test_initialize_for_test_captures_log_lines() {
+        // prepare
+        let sink = initialize_for_test();
+
+        // act
+        error!(item_id = 42; "Memory log sink test message");
+
+        // assert
+        assert!(sink.contains("[ERROR] - Memory log sink test message item_id=42"));
+        assert!(Arc::ptr_eq(&sink.lines, &initialize_for_test().lines));
+    }
+}
@@ -1 +1,6 @@
-pub mod logger;
\ No newline at end of file
+pub mod async_writer;
+pub mod json_body;
+pub mod logger;
+pub mod not_found;
+pub mod request_id;
+pub mod server_config;
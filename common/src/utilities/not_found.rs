@@ -0,0 +1,55 @@
+use crate::error::ServiceError;
+use actix_web::{HttpRequest, HttpResponse};
+use log::warn;
+
+/// The default service of a microservice, responding to requests that match no route with a `404`
+/// wrapped in an `ApiResponse`, like every other error, and logging the unmatched path.
+///
+/// # Examples
+///
+/// ```ignore
+/// App::new().default_service(web::to(not_found::not_found))
+/// ```
+pub async fn not_found(req: HttpRequest) -> HttpResponse {
+    warn!("No route matches the request: {} {}", req.method(), req.path());
+    ServiceError::NotFound(format!("No route matches {} {}", req.method(), req.path())).to_http_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::logger;
+    use actix_web::http::StatusCode;
+    use actix_web::{test, web, App};
+
+    async fn known_route() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn test_unknown_path_is_not_found_json() {
+        // prepare
+        let logs = logger::initialize_for_test();
+        let app = test::init_service(
+            App::new().route("/known", web::get().to(known_route)).default_service(web::to(not_found)),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::get().uri("/unknown/route").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "data": null,
+                "error": {"status": 404, "message": "No route matches GET /unknown/route"},
+                "request_id": ""
+            })
+        );
+        assert!(logs.contains("No route matches the request: GET /unknown/route"));
+    }
+}
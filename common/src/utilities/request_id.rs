@@ -0,0 +1,142 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage};
+use uuid::Uuid;
+
+/// The header a request id is read from and returned in.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+/// The header the id of the request that caused a request is forwarded in by other microservices,
+/// which is reused as the request id when no `X-Request-Id` is supplied.
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+
+// ids supplied by clients that are longer than this are replaced with a generated id
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    // the id of the request being handled, for responses rendered without access to the request
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// The id of an incoming HTTP request, stashed in the request's extensions by the `request_id`
+/// middleware. Handlers can extract it with `web::ReqData<RequestId>`, e.g. to use it as the
+/// correlation id of the events the request produces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(pub String);
+
+/// Middleware assigning every request a `RequestId` and returning it in the `X-Request-Id` header.
+///
+/// The id supplied by the client in the `X-Request-Id` header is reused when present, followed by
+/// the `X-Correlation-Id` header forwarded by other microservices, so that a request can be traced
+/// across microservices, otherwise a random UUID is generated.
+///
+/// # Examples
+///
+/// ```ignore
+/// App::new().wrap(actix_web::middleware::from_fn(request_id::request_id))
+/// ```
+pub async fn request_id(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let id = [REQUEST_ID_HEADER, CORRELATION_ID_HEADER]
+        .iter()
+        .filter_map(|header| req.headers().get(*header))
+        .filter_map(|value| value.to_str().ok())
+        .find(|value| !value.is_empty() && value.len() <= MAX_REQUEST_ID_LENGTH)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let mut res = CURRENT_REQUEST_ID.scope(id.clone(), next.call(req)).await?;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        res.headers_mut().insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+    Ok(res)
+}
+
+/// Retrieves the id of the request being handled, e.g. to render it in an error response, which has
+/// no access to the request.
+///
+/// # Returns
+///
+/// Returns the id assigned by the `request_id` middleware, or an empty string outside of a request
+/// handled by it.
+pub fn current() -> String {
+    CURRENT_REQUEST_ID.try_with(String::clone).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::middleware::from_fn;
+    use actix_web::{test, web, App, HttpResponse};
+
+    async fn echo_request_id(request_id: web::ReqData<RequestId>) -> HttpResponse {
+        HttpResponse::Ok().body(request_id.into_inner().0)
+    }
+
+    #[actix_web::test]
+    async fn test_request_id_reuses_supplied_header() {
+        // prepare
+        let app =
+            test::init_service(App::new().wrap(from_fn(request_id)).route("/", web::get().to(echo_request_id))).await;
+
+        // act
+        let req = test::TestRequest::get().uri("/").insert_header((REQUEST_ID_HEADER, "abc-123")).to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.headers().get(REQUEST_ID_HEADER).unwrap(), "abc-123");
+        assert_eq!(test::read_body(resp).await, "abc-123");
+    }
+
+    #[actix_web::test]
+    async fn test_request_id_falls_back_to_correlation_id_header() {
+        // prepare
+        let app =
+            test::init_service(App::new().wrap(from_fn(request_id)).route("/", web::get().to(echo_request_id))).await;
+
+        // act
+        let req = test::TestRequest::get().uri("/").insert_header((CORRELATION_ID_HEADER, "order-123")).to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.headers().get(REQUEST_ID_HEADER).unwrap(), "order-123");
+        assert_eq!(test::read_body(resp).await, "order-123");
+    }
+
+    #[actix_web::test]
+    async fn test_request_id_is_generated_when_missing() {
+        // prepare
+        let app =
+            test::init_service(App::new().wrap(from_fn(request_id)).route("/", web::get().to(echo_request_id))).await;
+
+        // act
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+
+        // assert
+        let header = resp.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap().to_string();
+        assert!(Uuid::parse_str(&header).is_ok());
+        assert_eq!(test::read_body(resp).await, header);
+    }
+
+    #[actix_web::test]
+    async fn test_request_id_replaces_oversized_header() {
+        // prepare
+        let app =
+            test::init_service(App::new().wrap(from_fn(request_id)).route("/", web::get().to(echo_request_id))).await;
+
+        // act
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((REQUEST_ID_HEADER, "a".repeat(MAX_REQUEST_ID_LENGTH + 1)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        let header = resp.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap();
+        assert!(Uuid::parse_str(header).is_ok());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::error::ServiceError;
+use actix_web::error::JsonPayloadError;
+use actix_web::{web, HttpRequest};
+use log::error;
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Environment variable used to set the number of actix worker threads of a microservice.
+pub const HTTP_WORKERS_VAR: &str = "HTTP_WORKERS";
+/// Environment variable used to set the largest request body, in bytes, a microservice accepts.
+pub const MAX_PAYLOAD_BYTES_VAR: &str = "MAX_PAYLOAD_BYTES";
+/// Environment variable used to set how long, in seconds, an idle connection is kept alive.
+pub const KEEP_ALIVE_SECS_VAR: &str = "KEEP_ALIVE_SECS";
+
+/// The largest request body accepted when `MAX_PAYLOAD_BYTES` is unset.
+pub const DEFAULT_MAX_PAYLOAD_BYTES: usize = 64 * 1024;
+/// How long an idle connection is kept alive when `KEEP_ALIVE_SECS` is unset.
+pub const DEFAULT_KEEP_ALIVE_SECS: u64 = 5;
+
+/// Reads the number of actix worker threads from the `HTTP_WORKERS` environment variable.
+///
+/// # Returns
+///
+/// * `Option<usize>` - The number of workers, or `None` to keep actix's default of one worker per
+///   CPU when the variable is unset or invalid.
+pub fn workers_from_env() -> Option<usize> {
+    parse_workers(env::var(HTTP_WORKERS_VAR).ok().as_deref())
+}
+
+/// Parses a number of actix worker threads, logging an error if it is not a positive integer.
+///
+/// # Arguments
+///
+/// * `value` - The configured number of workers, if any.
+pub fn parse_workers(value: Option<&str>) -> Option<usize> {
+    parse_positive(HTTP_WORKERS_VAR, value?, "one worker per CPU")
+}
+
+/// Reads the largest accepted request body from the `MAX_PAYLOAD_BYTES` environment variable,
+/// defaulting to `DEFAULT_MAX_PAYLOAD_BYTES` when it is unset or invalid.
+pub fn max_payload_bytes_from_env() -> usize {
+    parse_max_payload_bytes(env::var(MAX_PAYLOAD_BYTES_VAR).ok().as_deref())
+}
+
+/// Parses the largest accepted request body in bytes, logging an error if it is not a positive
+/// integer.
+///
+/// # Arguments
+///
+/// * `value` - The configured limit, if any.
+pub fn parse_max_payload_bytes(value: Option<&str>) -> usize {
+    value
+        .and_then(|value| parse_positive(MAX_PAYLOAD_BYTES_VAR, value, &DEFAULT_MAX_PAYLOAD_BYTES.to_string()))
+        .unwrap_or(DEFAULT_MAX_PAYLOAD_BYTES)
+}
+
+/// Reads the keep-alive timeout from the `KEEP_ALIVE_SECS` environment variable, defaulting to
+/// `DEFAULT_KEEP_ALIVE_SECS` when it is unset or invalid.
+pub fn keep_alive_from_env() -> Duration {
+    parse_keep_alive(env::var(KEEP_ALIVE_SECS_VAR).ok().as_deref())
+}
+
+/// Parses a keep-alive timeout in seconds, logging an error if it is not a positive integer.
+///
+/// # Arguments
+///
+/// * `value` - The configured timeout, if any.
+pub fn parse_keep_alive(value: Option<&str>) -> Duration {
+    let secs = value
+        .and_then(|value| parse_positive(KEEP_ALIVE_SECS_VAR, value, &DEFAULT_KEEP_ALIVE_SECS.to_string()))
+        .unwrap_or(DEFAULT_KEEP_ALIVE_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Creates a `JsonConfig` rejecting JSON bodies larger than `limit` bytes with a `413`, and bodies
+/// without a JSON `Content-Type`, e.g. `text/plain`, with a `415`.
+pub fn json_config(limit: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(limit)
+        .content_type_required(true)
+        .error_handler(json_error_handler)
+}
+
+// renders a missing or non-JSON content type as a `ServiceError`, leaving any other error to actix
+fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    match err {
+        JsonPayloadError::ContentType => {
+            ServiceError::UnsupportedMediaType("The Content-Type must be application/json.".to_string()).into()
+        }
+        err => err.into(),
+    }
+}
+
+/// Creates a `PayloadConfig` rejecting raw bodies larger than `limit` bytes with a `413`.
+pub fn payload_config(limit: usize) -> web::PayloadConfig {
+    web::PayloadConfig::new(limit)
+}
+
+// parses a positive integer, logging the variable and the default used instead when it is invalid
+fn parse_positive<T: FromStr + PartialOrd + Default>(var: &str, value: &str, default: &str) -> Option<T> {
+    match value.trim().parse::<T>() {
+        Ok(parsed) if parsed > T::default() => Some(parsed),
+        _ => {
+            error!("Invalid {}: {}, defaulting to {}", var, value, default);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_workers() {
+        assert_eq!(parse_workers(Some("4")), Some(4));
+        assert_eq!(parse_workers(Some(" 2 ")), Some(2));
+    }
+
+    #[test]
+    fn test_parse_workers_defaults_when_unset_or_invalid() {
+        assert_eq!(parse_workers(None), None);
+        assert_eq!(parse_workers(Some("0")), None);
+        assert_eq!(parse_workers(Some("-1")), None);
+        assert_eq!(parse_workers(Some("many")), None);
+    }
+
+    #[test]
+    fn test_parse_max_payload_bytes() {
+        assert_eq!(parse_max_payload_bytes(Some("1024")), 1024);
+        assert_eq!(parse_max_payload_bytes(None), DEFAULT_MAX_PAYLOAD_BYTES);
+        assert_eq!(parse_max_payload_bytes(Some("0")), DEFAULT_MAX_PAYLOAD_BYTES);
+        assert_eq!(parse_max_payload_bytes(Some("1kb")), DEFAULT_MAX_PAYLOAD_BYTES);
+    }
+
+    #[test]
+    fn test_parse_keep_alive() {
+        assert_eq!(parse_keep_alive(Some("30")), Duration::from_secs(30));
+        assert_eq!(parse_keep_alive(None), Duration::from_secs(DEFAULT_KEEP_ALIVE_SECS));
+        assert_eq!(
+            parse_keep_alive(Some("forever")),
+            Duration::from_secs(DEFAULT_KEEP_ALIVE_SECS)
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use catalog_service::server::CatalogServer;
+use log::info;
+use order_service::config::OrderServiceConfig;
+use order_service::server::OrderServer;
+use std::future::Future;
+use std::net::SocketAddr;
+
+/// `DevRunner` runs the Catalog and Order Microservices, each with its event listeners, in a single
+/// process for local development and demos, so they do not need to be started separately.
+///
+/// The order service is pointed at the catalog of the runner, and both services are stopped together:
+/// when the runner is shut down, e.g. by Ctrl-C, or as soon as either service stops on its own.
+///
+/// # Examples
+///
+/// ```ignore
+/// let runner = DevRunner::bind("127.0.0.1:8081", OrderServiceConfig::from_env())?;
+/// runner.run(async { let _ = tokio::signal::ctrl_c().await; }).await
+/// ```
+///
+/// Fields:
+/// - `catalog`: The server of the Catalog Microservice.
+/// - `order`: The server of the Order Microservice, calling the catalog of the runner.
+pub struct DevRunner {
+    catalog: CatalogServer,
+    order: OrderServer,
+}
+
+impl DevRunner {
+    /// Builds both services, starts their event listeners and binds their HTTP servers, which do not
+    /// handle signals themselves, so that shutting down is left to `run`.
+    ///
+    /// Arguments:
+    /// - `catalog_bind_address`: The address the catalog binds to, e.g. `127.0.0.1:0` for any free port.
+    /// - `order_config`: The configuration of the order service, whose `catalog_base_url` is replaced
+    ///   by the address the catalog is bound to.
+    ///
+    /// Returns:
+    /// - `DevRunner`: The bound services, which serve requests once they are run.
+    pub fn bind(catalog_bind_address: &str, mut order_config: OrderServiceConfig) -> std::io::Result<DevRunner> {
+        // the servers leave Ctrl-C to the runner, which would otherwise race their own forced shutdown
+        let catalog = catalog_service::server::bind_server(catalog_bind_address, false)?;
+        order_config.catalog_base_url = format!("http://{}", catalog.addrs()[0]);
+        let order = order_service::server::bind_server(order_config, false)?;
+        Ok(DevRunner { catalog, order })
+    }
+
+    /// Returns the address the catalog is bound to.
+    pub fn catalog_addr(&self) -> SocketAddr {
+        self.catalog.addrs()[0]
+    }
+
+    /// Returns the address the order service is bound to.
+    pub fn order_addr(&self) -> SocketAddr {
+        self.order.addrs()[0]
+    }
+
+    /// Serves requests with both services until `shutdown` completes or either service stops, then
+    /// stops both gracefully, waiting for the catalog to finish processing the events it has received.
+    ///
+    /// Arguments:
+    /// - `shutdown`: Completes when the services should be shut down, e.g. `tokio::signal::ctrl_c()`.
+    ///
+    /// Returns:
+    /// - `std::io::Result<()>`: The error of the first service that failed, if any.
+    pub async fn run(self, shutdown: impl Future<Output = ()>) -> std::io::Result<()> {
+        let catalog_handle = self.catalog.handle();
+        let order_handle = self.order.handle();
+        // whichever service stops first stops the other, so neither keeps running on its own
+        let catalog = async {
+            let result = self.catalog.run().await;
+            order_handle.stop(true).await;
+            result
+        };
+        let order = async {
+            let result = self.order.run().await;
+            catalog_handle.stop(true).await;
+            result
+        };
+        let services = async {
+            let (catalog, order) = tokio::join!(catalog, order);
+            catalog.and(order)
+        };
+        tokio::pin!(services);
+
+        tokio::select! {
+            result = &mut services => result,
+            _ = shutdown => {
+                info!("Shutting down the catalog and order services");
+                // the servers only act on the stop once polled, so they are awaited alongside it
+                let stop = async { tokio::join!(catalog_handle.stop(true), order_handle.stop(true)) };
+                let (_, result) = tokio::join!(stop, services);
+                result
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio::sync::oneshot;
+
+    // requests a path that no service serves, returning the status line of the response
+    async fn request_status_line(address: SocketAddr) -> String {
+        let mut connection = TcpStream::connect(address).await.unwrap();
+        connection
+            .write_all(b"GET /missing HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        connection.read_to_string(&mut response).await.unwrap();
+        response.lines().next().unwrap_or_default().to_string()
+    }
+
+    #[actix_web::test]
+    async fn test_runner_starts_and_stops_cleanly() {
+        // prepare
+        let order_config = OrderServiceConfig {
+            bind_address: "127.0.0.1:0".to_string(),
+            workers: Some(1),
+            ..OrderServiceConfig::default()
+        };
+        let sut = DevRunner::bind("127.0.0.1:0", order_config).unwrap();
+        let catalog_addr = sut.catalog_addr();
+        let order_addr = sut.order_addr();
+        let (stop, stopped) = oneshot::channel();
+
+        // act
+        let run = sut.run(async {
+            let _ = stopped.await;
+        });
+        let requests = async {
+            let statuses = (
+                request_status_line(catalog_addr).await,
+                request_status_line(order_addr).await,
+            );
+            stop.send(()).unwrap();
+            statuses
+        };
+        let (result, (catalog_status, order_status)) =
+            tokio::time::timeout(Duration::from_secs(10), async { tokio::join!(run, requests) })
+                .await
+                .expect("The runner did not stop");
+
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(catalog_status, "HTTP/1.1 404 Not Found");
+        assert_eq!(order_status, "HTTP/1.1 404 Not Found");
+        assert!(TcpStream::connect(catalog_addr).await.is_err());
+        assert!(TcpStream::connect(order_addr).await.is_err());
+    }
+}
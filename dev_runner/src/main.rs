@@ -0,0 +1,17 @@
+use common::utilities::logger;
+use dev_runner::DevRunner;
+use order_service::config::OrderServiceConfig;
+
+// the address the catalog binds to, which the order service of the runner calls
+const CATALOG_BIND_ADDRESS: &str = "127.0.0.1:8081";
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    logger::initialize("dev_runner_output.log", "DevRunner");
+    let runner = DevRunner::bind(CATALOG_BIND_ADDRESS, OrderServiceConfig::from_env())?;
+    runner
+        .run(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+}
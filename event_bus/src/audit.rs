@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// A compact record of an event produced by an `EventBus`, sent to its audit topic.
+///
+/// # Fields
+///
+/// * `source`: The name of the service that produced the event.
+/// * `topic`: The topic the event was produced to.
+/// * `key`: The key the event was produced with, empty for a keyless event.
+/// * `timestamp_ms`: When the event was produced, in milliseconds since the Unix epoch.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    pub source: String,
+    pub topic: String,
+    pub key: String,
+    pub timestamp_ms: i64,
+}
+
+impl AuditRecord {
+    /// Creates a new `AuditRecord`.
+    pub fn new(source: &str, topic: &str, key: &str, timestamp_ms: i64) -> Self {
+        AuditRecord {
+            source: source.to_string(),
+            topic: topic.to_string(),
+            key: key.to_string(),
+            timestamp_ms,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+#[cfg(any(test, feature = "mock-clock"))]
+use std::sync::Mutex;
+#[cfg(any(test, feature = "mock-clock"))]
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// A source of the current time, used when stamping events.
+///
+/// Abstracting the clock allows the time assigned to produced events to be controlled,
+/// which makes event timestamps deterministic in tests.
+pub trait Clock: Send + Sync {
+    /// Returns the current time according to this clock.
+    fn now(&self) -> SystemTime;
+}
+
+/// The default `Clock`, backed by the real system time.
+#[derive(Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+// mock clock necessary for testing, always returns the time it was last set to
+#[cfg(any(test, feature = "mock-clock"))]
+pub struct MockClock {
+    time: Mutex<SystemTime>,
+}
+
+#[cfg(any(test, feature = "mock-clock"))]
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        *self.time.lock().unwrap()
+    }
+}
+
+#[cfg(any(test, feature = "mock-clock"))]
+impl MockClock {
+    pub fn new(time: SystemTime) -> Self {
+        MockClock { time: Mutex::new(time) }
+    }
+
+    pub fn set_time(&self, time: SystemTime) {
+        *self.time.lock().unwrap() = time;
+    }
+
+    // moves the clock forward, so a clock shared behind an `Arc` can be advanced whilst in use
+    pub fn advance(&self, by: Duration) {
+        *self.time.lock().unwrap() += by;
+    }
+}
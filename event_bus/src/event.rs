@@ -1,6 +1,28 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// The schema version of events that do not specify one.
+pub const DEFAULT_SCHEMA_VERSION: u32 = 1;
+
+/// The `metadata` entry counting the number of times handling an event has been retried, see
+/// `Event::retry_attempt`.
+pub const RETRY_ATTEMPT_METADATA_KEY: &str = "retry_attempt";
+
+/// The `metadata` entry holding when a retried event is due to be handled again, in milliseconds
+/// since the Unix epoch, see `Event::retry_at`.
+pub const RETRY_AT_METADATA_KEY: &str = "retry_at";
+
+/// An `Event` whose payload is kept as raw JSON, so that its envelope, e.g. its type, source,
+/// timestamp and correlation id, can be read without knowing the type of its payload, such as by a
+/// consumer monitoring every topic. Use `RawEvent::typed` to decode its payload once its type is known.
+pub type RawEvent = Event<serde_json::Value>;
+
+fn default_schema_version() -> u32 {
+    DEFAULT_SCHEMA_VERSION
+}
 
 /// Represents an event to be sent across an event bus in a microservices architecture.
 ///
@@ -13,6 +35,14 @@ use std::time::SystemTime;
 ///
 /// # Fields
 ///
+/// * `event_id`: A `String` holding a UUID that uniquely identifies this event. Consumers can use
+///   it to detect redelivered messages. Payloads produced before this field existed deserialize
+///   with an empty `event_id`.
+///
+/// * `schema_version`: The version of the schema of the payload, which consumers use to decode
+///   payloads of older versions, see `versioning::decode_versioned`. Payloads produced before this
+///   field existed deserialize as version `1`.
+///
 /// * `event_type`: A `String` that specifies the type of the event. This is typically used
 ///   for routing and handling the event appropriately.
 ///
@@ -26,18 +56,25 @@ use std::time::SystemTime;
 ///   microservice or system component that generated it.
 ///
 /// * `correlation_id`: An optional `String` used for correlating related events in a
-///   distributed system. Useful for tracing and debugging complex flows.
+///   distributed system. Useful for tracing and debugging complex flows. Omitted from the
+///   serialized event when `None`.
 ///
 /// * `metadata`: An optional `HashMap<String, String>` providing additional, free-form
 ///   metadata about the event. Can be used for adding any extra information that is
-///   relevant to the event or its handling.
+///   relevant to the event or its handling. Omitted from the serialized event when `None`.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Event<T> {
+    #[serde(default)]
+    pub event_id: String,
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
     pub event_type: String,
     pub payload: T,
     pub timestamp: SystemTime,
     pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub correlation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<collections::HashMap<String, String>>,
 }
 
@@ -45,7 +82,8 @@ impl<T> Event<T> {
     /// Creates a new `Event` with the specified properties.
     ///
     /// Instantiates an `Event` with a given type, payload, source, optional correlation ID, and
-    /// optional metadata. The timestamp is set to the current system time.
+    /// optional metadata. The timestamp is set to the current system time and a new UUID is
+    /// generated as the `event_id`.
     ///
     /// # Arguments
     ///
@@ -61,7 +99,7 @@ impl<T> Event<T> {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// let event = Event::new(
     ///     "user_created".to_string(),
     ///     UserPayload { name: "John Doe".to_string(), age: 30 },
@@ -76,14 +114,287 @@ impl<T> Event<T> {
         source: String,
         correlation_id: Option<String>,
         metadata: Option<collections::HashMap<String, String>>,
+    ) -> Self {
+        Event::with_timestamp(event_type, payload, source, correlation_id, metadata, SystemTime::now())
+    }
+
+    /// Creates a new `Event` with an explicit timestamp.
+    ///
+    /// Behaves like `Event::new`, except the timestamp is supplied by the caller rather than read
+    /// from the system time. This is used by producers that stamp events from a `Clock`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_type`: The type of the event.
+    /// * `payload`: The payload of the event.
+    /// * `source`: The source identifier of the event.
+    /// * `correlation_id`: An optional correlation ID for the event.
+    /// * `metadata`: Optional metadata for the event.
+    /// * `timestamp`: The time at which the event was created.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new instance of `Event<T>`.
+    pub fn with_timestamp(
+        event_type: String,
+        payload: T,
+        source: String,
+        correlation_id: Option<String>,
+        metadata: Option<collections::HashMap<String, String>>,
+        timestamp: SystemTime,
     ) -> Self {
         Event {
+            event_id: Uuid::new_v4().to_string(),
+            schema_version: DEFAULT_SCHEMA_VERSION,
             event_type,
             payload,
-            timestamp: SystemTime::now(),
+            timestamp,
             source,
             correlation_id,
             metadata,
         }
     }
+
+    /// Sets the schema version of the event's payload, which is `DEFAULT_SCHEMA_VERSION` unless set.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_version`: The version of the schema the payload conforms to.
+    pub fn with_schema_version(mut self, schema_version: u32) -> Self {
+        self.schema_version = schema_version;
+        self
+    }
+
+    /// Retrieves the number of times handling the event has been retried, as recorded in its
+    /// `metadata` by `with_retry_attempt`. Events that have not been retried, or whose count is not
+    /// a number, have been retried `0` times.
+    pub fn retry_attempt(&self) -> u32 {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(RETRY_ATTEMPT_METADATA_KEY))
+            .and_then(|attempt| attempt.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Records the number of times handling the event has been retried in its `metadata`, keeping
+    /// any other entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt`: The number of retries, starting at `1` for the first retry.
+    pub fn with_retry_attempt(mut self, attempt: u32) -> Self {
+        self.metadata
+            .get_or_insert_with(collections::HashMap::new)
+            .insert(RETRY_ATTEMPT_METADATA_KEY.to_string(), attempt.to_string());
+        self
+    }
+
+    /// Retrieves when a retried event is due to be handled again, as recorded in its `metadata` by
+    /// `with_retry_at`, or `None` if no time, or an invalid time, was recorded.
+    pub fn retry_at(&self) -> Option<SystemTime> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(RETRY_AT_METADATA_KEY))
+            .and_then(|retry_at| retry_at.parse().ok())
+            .map(|millis| UNIX_EPOCH + Duration::from_millis(millis))
+    }
+
+    /// Records when a retried event is due to be handled again in its `metadata`, keeping any other
+    /// entries, so the consumer of the retry can schedule it however long it waited to be received.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry_at`: The time the event is due to be handled again, recorded to the millisecond.
+    pub fn with_retry_at(mut self, retry_at: SystemTime) -> Self {
+        let millis = retry_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        self.metadata
+            .get_or_insert_with(collections::HashMap::new)
+            .insert(RETRY_AT_METADATA_KEY.to_string(), millis.to_string());
+        self
+    }
+}
+
+impl RawEvent {
+    /// Decodes the raw payload of the event into a payload of type `T`, keeping its envelope.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T`: The type of the payload, e.g. chosen from the `event_type` of the event.
+    ///
+    /// # Returns
+    ///
+    /// Returns the event with its decoded payload, or a `serde_json::Error` if the payload is not a `T`.
+    pub fn typed<T: DeserializeOwned>(self) -> Result<Event<T>, serde_json::Error> {
+        let payload = serde_json::from_value(self.payload)?;
+        Ok(Event {
+            event_id: self.event_id,
+            schema_version: self.schema_version,
+            event_type: self.event_type,
+            payload,
+            timestamp: self.timestamp,
+            source: self.source,
+            correlation_id: self.correlation_id,
+            metadata: self.metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::order_placed_event::OrderPlacedEvent;
+
+    fn generate_event() -> Event<u32> {
+        Event::new("test_event".to_string(), 1, "test".to_string(), None, None)
+    }
+
+    #[test]
+    fn test_new_event_generates_distinct_ids() {
+        let first = generate_event();
+        let second = generate_event();
+
+        assert!(Uuid::parse_str(&first.event_id).is_ok());
+        assert_ne!(first.event_id, second.event_id);
+    }
+
+    #[test]
+    fn test_event_id_is_serialized() {
+        let event = generate_event();
+
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["event_id"], event.event_id);
+    }
+
+    #[test]
+    fn test_absent_optional_fields_are_not_serialized() {
+        // prepare
+        let event = generate_event();
+
+        // act
+        let json = serde_json::to_value(&event).unwrap();
+
+        // assert
+        assert!(json.get("correlation_id").is_none());
+        assert!(json.get("metadata").is_none());
+        let event = serde_json::from_value::<Event<u32>>(json).unwrap();
+        assert_eq!(event.correlation_id, None);
+        assert_eq!(event.metadata, None);
+    }
+
+    #[test]
+    fn test_present_optional_fields_are_serialized() {
+        // prepare
+        let event = Event::new(
+            "test_event".to_string(),
+            1,
+            "test".to_string(),
+            Some("correlation".to_string()),
+            Some(collections::HashMap::new()),
+        );
+
+        // act
+        let json = serde_json::to_value(&event).unwrap();
+
+        // assert
+        assert_eq!(json["correlation_id"], "correlation");
+        assert_eq!(json["metadata"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_retry_attempt_round_trips_through_metadata() {
+        // prepare
+        let mut metadata = collections::HashMap::new();
+        metadata.insert("origin".to_string(), "web".to_string());
+        let event = Event::new("test_event".to_string(), 1, "test".to_string(), None, Some(metadata));
+
+        // act
+        let retried = event.with_retry_attempt(2);
+
+        // assert
+        assert_eq!(retried.retry_attempt(), 2);
+        assert_eq!(retried.metadata.as_ref().unwrap()["origin"], "web");
+        assert_eq!(generate_event().retry_attempt(), 0);
+        assert_eq!(
+            generate_event().with_retry_attempt(1).with_retry_attempt(3).retry_attempt(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_retry_at_round_trips_through_metadata() {
+        // prepare
+        let retry_at = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+
+        // act
+        let retried = generate_event().with_retry_attempt(1).with_retry_at(retry_at);
+
+        // assert
+        assert_eq!(retried.retry_at(), Some(retry_at));
+        assert_eq!(retried.retry_attempt(), 1);
+        assert_eq!(generate_event().retry_at(), None);
+    }
+
+    #[test]
+    fn test_deserialize_event_without_event_id() {
+        let json = r#"{
+            "event_type": "test_event",
+            "payload": 1,
+            "timestamp": { "secs_since_epoch": 0, "nanos_since_epoch": 0 },
+            "source": "test",
+            "correlation_id": null,
+            "metadata": null
+        }"#;
+
+        let event = serde_json::from_str::<Event<u32>>(json).unwrap();
+
+        assert!(event.event_id.is_empty());
+        assert_eq!(event.schema_version, DEFAULT_SCHEMA_VERSION);
+        assert_eq!(event.payload, 1);
+    }
+
+    #[test]
+    fn test_raw_event_reads_envelope_and_specializes_payload() {
+        // prepare
+        let event = Event::new(
+            "OrderPlaced".to_string(),
+            OrderPlacedEvent {
+                item_id: 1,
+                quantity: 2,
+                total: None,
+                reservation_id: None,
+            },
+            "order-service".to_string(),
+            Some("correlation".to_string()),
+            None,
+        );
+        let json = serde_json::to_string(&event).unwrap();
+
+        // act
+        let raw = serde_json::from_str::<RawEvent>(&json).unwrap();
+
+        // assert
+        assert_eq!(raw.event_id, event.event_id);
+        assert_eq!(raw.event_type, "OrderPlaced");
+        assert_eq!(raw.source, "order-service");
+        assert_eq!(raw.timestamp, event.timestamp);
+        assert_eq!(raw.correlation_id.as_deref(), Some("correlation"));
+        let typed = raw.typed::<OrderPlacedEvent>().unwrap();
+        assert_eq!(typed.event_id, event.event_id);
+        assert_eq!(typed.payload.item_id, 1);
+        assert_eq!(typed.payload.quantity, 2);
+    }
+
+    #[test]
+    fn test_raw_event_with_mismatched_payload_fails_to_specialize() {
+        // prepare
+        let json = serde_json::to_string(&generate_event()).unwrap();
+        let raw = serde_json::from_str::<RawEvent>(&json).unwrap();
+
+        // act
+        let result = raw.typed::<OrderPlacedEvent>();
+
+        // assert
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,14 @@
+use common::types::money::Money;
+use serde::{Deserialize, Serialize};
+
+/// Broadcast instead of an `OrderPlacedEvent` when an order is placed for more than the stock of an
+/// item that can be backordered. `backordered_quantity` is the part of `quantity` that was not in
+/// stock when the order was placed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BackorderPlacedEvent {
+    pub item_id: u32,
+    pub quantity: u32,
+    pub backordered_quantity: u32,
+    #[serde(default)]
+    pub total: Option<Money>,
+}
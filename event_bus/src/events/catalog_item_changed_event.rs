@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// The kind of change made to a catalog item.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum CatalogItemChange {
+    Added,
+    Updated,
+    Removed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CatalogItemChangedEvent {
+    pub item_id: u32,
+    pub change: CatalogItemChange,
+}
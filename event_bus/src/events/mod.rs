@@ -1 +1,4 @@
+pub mod backorder_placed_event;
+pub mod catalog_item_changed_event;
+pub mod order_cancelled_event;
 pub mod order_placed_event;
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+/// Broadcast when a placed order is cancelled, so that its quantity can be restocked.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OrderCancelledEvent {
+    pub order_id: u32,
+    pub item_id: u32,
+    pub quantity: u32,
+}
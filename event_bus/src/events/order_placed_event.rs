@@ -1,7 +1,12 @@
+use common::types::money::Money;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OrderPlacedEvent {
     pub item_id: u32,
     pub quantity: u32,
+    #[serde(default)]
+    pub total: Option<Money>,
+    #[serde(default)]
+    pub reservation_id: Option<u64>,
 }
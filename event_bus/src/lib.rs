@@ -1,24 +1,113 @@
+use crate::audit::AuditRecord;
+use crate::clock::{Clock, SystemClock};
+use crate::event::Event;
+use crate::message_key::{MessageKey, NO_KEY};
+use crate::retry::RetryPolicy;
+use crate::serialization::{EventDeserializer, EventSerializer, JsonCodec};
 use crate::utilities::listeners;
-use crate::utilities::listeners::KafkaListener;
+use crate::utilities::listeners::{BatchCommitConfig, KafkaListener, OffsetReset, SupervisorConfig};
 use async_trait::async_trait;
 use log::{error, info};
 use rdkafka::consumer::{Consumer, StreamConsumer};
 use rdkafka::error::KafkaError;
 use rdkafka::producer::{FutureProducer, FutureRecord};
-use rdkafka::ClientConfig;
+use rdkafka::{ClientConfig, Offset, TopicPartitionList};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::error::Error;
-use std::time::Duration;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::sync::{broadcast, Semaphore};
 
+pub mod audit;
+pub mod clock;
 pub mod event;
 pub mod events;
+pub mod message_key;
+pub mod partitioner;
+pub mod retry;
+pub mod serialization;
 pub mod topic;
 pub mod utilities;
+pub mod versioning;
 
-pub struct EventBus {
+// the largest message accepted by a kafka broker with its default `message.max.bytes`
+const DEFAULT_MAX_MESSAGE_BYTES: usize = 1_048_576;
+// how long `broadcast_event_sync` waits for the broker to acknowledge a message by default
+const DEFAULT_DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+// how many messages the event bus' producer may be sending at once by default
+const DEFAULT_MAX_IN_FLIGHT: usize = 1000;
+
+pub struct EventBus<C = JsonCodec> {
     broker: String,
     producer: FutureProducer,
+    // created on the first `broadcast_event_sync`, so that an event bus which never waits for
+    // acknowledgements does not hold a second connection to the broker
+    acked_producer: OnceLock<FutureProducer>,
+    delivery_timeout: Duration,
+    clock: Box<dyn Clock>,
+    codec: C,
+    max_message_bytes: usize,
+    supervisor_config: SupervisorConfig,
+    batch_commit: Option<BatchCommitConfig>,
+    retry_policy: RetryPolicy,
+    in_flight: Semaphore,
+    audit_topic: Option<String>,
+    audit_source: String,
+}
+
+/// An error that prevents an event from being broadcast.
+///
+/// # Variants
+///
+/// * `MessageTooLarge`: The serialized message is `size` bytes, which exceeds the event bus'
+///   `limit`. The message is rejected before it is sent to the broker.
+/// * `InvalidPartition`: The requested partition is negative, which librdkafka would treat as
+///   unassigned and silently fall back to key-based partitioning.
+/// * `Enqueue`: The message could not be queued for sending by the producer, e.g. because its queue is full.
+/// * `Nack`: The message was queued, but the broker did not acknowledge it before the delivery timeout.
+#[derive(Debug, PartialEq)]
+pub enum BroadcastError {
+    MessageTooLarge { size: usize, limit: usize },
+    InvalidPartition(i32),
+    Enqueue(KafkaError),
+    Nack(KafkaError),
+}
+
+impl Display for BroadcastError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BroadcastError::MessageTooLarge { size, limit } => write!(
+                f,
+                "The message is {} bytes, which exceeds the limit of {} bytes",
+                size, limit
+            ),
+            BroadcastError::InvalidPartition(partition) => {
+                write!(
+                    f,
+                    "The partition {} is invalid, partitions must not be negative",
+                    partition
+                )
+            }
+            BroadcastError::Enqueue(e) => write!(f, "The message could not be queued for sending: {}", e),
+            BroadcastError::Nack(e) => write!(f, "The message was not acknowledged by the broker: {}", e),
+        }
+    }
+}
+
+impl Error for BroadcastError {}
+
+// rejects negative partitions, which the producer would treat as unassigned instead of pinned
+fn validate_partition(partition: i32) -> Result<(), BroadcastError> {
+    if partition < 0 {
+        error!("Invalid partition {}, partitions must not be negative", partition);
+        return Err(BroadcastError::InvalidPartition(partition));
+    }
+
+    Ok(())
 }
 
 pub trait EventListener {
@@ -31,12 +120,12 @@ pub trait EventListener {
     /// This function sets up a Kafka consumer and wraps it in a `KafkaListener` to facilitate
     /// asynchronous message handling. The `KafkaListener` will use a `StreamConsumer` to
     /// subscribe to the given topics and listen for messages of type `T`, which is determined
-    /// by the caller. The messages received will be deserialized from JSON into type `T`.
+    /// by the caller. The messages received will be decoded into type `T` using the event bus' codec.
     ///
     /// # Type Parameters
     ///
-    /// * `T`: The type into which the JSON messages from Kafka will be deserialized.
-    ///        `T` must implement the `serde::de::DeserializeOwned` trait.
+    /// * `T`: The type into which the messages from Kafka will be deserialized.
+    ///   `T` must implement the `serde::de::DeserializeOwned` trait.
     ///
     /// # Arguments
     ///
@@ -52,11 +141,12 @@ pub trait EventListener {
     /// # Errors
     ///
     /// Returns `KafkaError` if there's an issue creating the `StreamConsumer`, or if
-    /// there's a problem subscribing to the specified topics.
+    /// there's a problem subscribing to the specified topics. Returns a `topic::TopicError`
+    /// if any of the topic names is not a valid Kafka topic name.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// // Assuming `service` is an instance with `create_listener` method.
     /// use event_bus::EventBus;
     /// let group_id = "my_consumer_group";
@@ -74,15 +164,105 @@ pub trait EventListener {
         group_id: &str,
         topics: &[&str],
     ) -> Result<listeners::KafkaListener<T>, Box<dyn Error>>
+    where
+        T: Send + DeserializeOwned + 'static + Clone,
+    {
+        self.create_event_listener_with_offset_reset(group_id, topics, OffsetReset::default())
+    }
+
+    /// Creates a new `KafkaListener` like `create_event_listener`, choosing where it starts reading
+    /// its topics when the consumer group has no committed offset.
+    ///
+    /// `create_event_listener` replays the topics of a new consumer group from the earliest message,
+    /// which suits services rebuilding their state from events. Services that only react to new
+    /// events can use `OffsetReset::Latest` instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id`: The consumer group ID to be used by the Kafka consumer.
+    /// * `topics`: A slice of topic names to which the consumer should subscribe.
+    /// * `offset_reset`: Where to start reading when the consumer group has no committed offset.
+    fn create_event_listener_with_offset_reset<T>(
+        &self,
+        group_id: &str,
+        topics: &[&str],
+        offset_reset: OffsetReset,
+    ) -> Result<listeners::KafkaListener<T>, Box<dyn Error>>
     where
         T: Send + DeserializeOwned + 'static + Clone;
+
+    /// Creates a new `KafkaListener` like `create_event_listener`, whose consumer never commits its
+    /// offsets on its own, whatever the commit mode of the event bus, so that a message is only
+    /// committed once it is passed to `KafkaListener::commit_message`. Messages the consumer has
+    /// received but that are not committed are received again by the next consumer of the group.
+    ///
+    /// Buses whose listeners are not fed by Kafka, e.g. a `MockEventBus`, have no offsets to commit
+    /// and create a listener like `create_event_listener`.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id`: The consumer group ID to be used by the Kafka consumer.
+    /// * `topics`: A slice of topic names to which the consumer should subscribe.
+    fn create_manual_commit_listener<T>(
+        &self,
+        group_id: &str,
+        topics: &[&str],
+    ) -> Result<listeners::KafkaListener<T>, Box<dyn Error>>
+    where
+        T: Send + DeserializeOwned + 'static + Clone,
+    {
+        self.create_event_listener(group_id, topics)
+    }
+
+    /// Whether this is a mock bus, e.g. a `MockEventBus`, whose listeners are fed by the test
+    /// rather than by Kafka. Lets test setup branch on the bus it runs against without downcasting.
+    fn is_mock(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait]
 pub trait EventProducer {
+    /// Returns the `Clock` used to timestamp events created by this producer.
+    fn clock(&self) -> &dyn Clock;
+
+    /// Creates a new `Event` timestamped using the producer's `Clock`.
+    ///
+    /// Events that are to be broadcast should be created through this method rather than
+    /// `Event::new`, so that the time assigned to them can be controlled by the producer.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_type`: The type of the event.
+    /// * `payload`: The payload of the event.
+    /// * `source`: The source identifier of the event.
+    /// * `correlation_id`: An optional correlation ID for the event.
+    /// * `metadata`: Optional metadata for the event.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new instance of `Event<T>`.
+    fn create_event<T>(
+        &self,
+        event_type: String,
+        payload: T,
+        source: String,
+        correlation_id: Option<String>,
+        metadata: Option<HashMap<String, String>>,
+    ) -> Event<T> {
+        Event::with_timestamp(
+            event_type,
+            payload,
+            source,
+            correlation_id,
+            metadata,
+            self.clock().now(),
+        )
+    }
+
     /// Broadcasts an event to a specified Kafka topic.
     ///
-    /// This function serializes the given payload into a JSON string and sends it
+    /// This function serializes the given payload using the producer's codec and sends it
     /// to the specified Kafka topic using the `produce` method. The payload must
     /// implement the `serde::Serialize` trait to enable serialization.
     ///
@@ -92,9 +272,10 @@ pub trait EventProducer {
     ///
     /// # Arguments
     ///
-    /// * `payload`: The payload of the event, which will be serialized to JSON.
+    /// * `payload`: The payload of the event, which will be serialized using the producer's codec.
     /// * `topic_name`: The name of the Kafka topic to which the event will be sent.
-    /// * `key`: A key associated with the event, used by Kafka for partitioning.
+    /// * `key`: A key associated with the event, used by Kafka for partitioning, e.g. an item id, or
+    ///   `MessageKey::none()` for a keyless message, see `MessageKey`.
     /// * `source`: The source identifier of the event.
     ///
     /// # Returns
@@ -106,12 +287,14 @@ pub trait EventProducer {
     /// # Errors
     ///
     /// This function can return errors in the following cases:
-    /// - If serialization of the payload to JSON fails.
+    /// - If serialization of the payload fails.
+    /// - If the serialized message exceeds the producer's size limit, as a `BroadcastError::MessageTooLarge`.
+    /// - If the topic name is not a valid Kafka topic name, as a `topic::TopicError`.
     /// - If sending the message through Kafka encounters an error.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// #[derive(serde::Serialize)]
     /// struct MyPayload {
     ///     data: String,
@@ -131,43 +314,238 @@ pub trait EventProducer {
         &self,
         payload: T,
         topic_name: &str,
-        key: &str,
+        key: impl Into<MessageKey> + Send,
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Broadcasts an event to a specified Kafka topic with an explicit message timestamp.
+    ///
+    /// Behaves like `broadcast_event`, except that the Kafka record timestamp is set to the
+    /// provided value rather than being assigned by the broker. This is useful when backfilling
+    /// historical events.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload`: The payload of the event, which will be serialized using the producer's codec.
+    /// * `topic_name`: The name of the Kafka topic to which the event will be sent.
+    /// * `key`: A key associated with the event, used by Kafka for partitioning, e.g. an item id, or
+    ///   `MessageKey::none()` for a keyless message, see `MessageKey`.
+    /// * `timestamp_ms`: The record timestamp in milliseconds since the Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on successful broadcast of the event, or `Err(Box<dyn Error>)` if
+    /// serialization or sending the message fails.
+    async fn broadcast_event_at<T: serde::Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+        timestamp_ms: i64,
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Broadcasts an event to a specified Kafka topic and waits until every in-sync replica of the
+    /// broker has acknowledged it (`acks=all`).
+    ///
+    /// Behaves like `broadcast_event`, except that success means the event has been durably
+    /// written rather than only accepted by the producer, which suits critical events.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload`: The payload of the event, which will be serialized using the producer's codec.
+    /// * `topic_name`: The name of the Kafka topic to which the event will be sent.
+    /// * `key`: A key associated with the event, used by Kafka for partitioning, e.g. an item id, or
+    ///   `MessageKey::none()` for a keyless message, see `MessageKey`.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once the broker has acknowledged the event, or `Err(Box<dyn Error>)` if it
+    /// could not be broadcast. A `BroadcastError::Enqueue` means the event was never sent, whereas a
+    /// `BroadcastError::Nack` means it was sent but not acknowledged within the delivery timeout.
+    async fn broadcast_event_sync<T: serde::Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Broadcasts an event to a specific partition of a Kafka topic.
+    ///
+    /// Behaves like `broadcast_event`, except that the record is pinned to the given partition
+    /// instead of the partition being chosen from its key. Every event broadcast this way to the
+    /// same partition is consumed in order, e.g. for debugging or strict ordering across a whole
+    /// logical stream, but this disables the load balancing of key-based partitioning, so a single
+    /// partition and its consumer take every such event.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload`: The payload of the event, which will be serialized using the producer's codec.
+    /// * `topic_name`: The name of the Kafka topic to which the event will be sent.
+    /// * `key`: A key associated with the event, which is still recorded on the message but no longer
+    ///   decides its partition, or `MessageKey::none()` for a keyless message, see `MessageKey`.
+    /// * `partition`: The partition of the topic the event is sent to, which must exist and must not
+    ///   be negative.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on successful broadcast of the event, or `Err(Box<dyn Error>)` if
+    /// serialization or sending the message fails, e.g. because the partition does not exist, or
+    /// `BroadcastError::InvalidPartition` if the partition is negative.
+    async fn broadcast_event_to_partition<T: serde::Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+        partition: i32,
     ) -> Result<(), Box<dyn Error>>;
 }
 
-impl EventListener for EventBus {
-    fn create_event_listener<T>(
+impl<C> EventListener for EventBus<C>
+where
+    C: EventDeserializer + Clone + 'static,
+{
+    fn create_event_listener_with_offset_reset<T>(
+        &self,
+        group_id: &str,
+        topics: &[&str],
+        offset_reset: OffsetReset,
+    ) -> Result<listeners::KafkaListener<T>, Box<dyn Error>>
+    where
+        T: Send + DeserializeOwned + 'static + Clone,
+    {
+        self.create_listener(
+            group_id,
+            topics,
+            offset_reset,
+            ListenerCommitMode::from(self.batch_commit),
+        )
+    }
+
+    fn create_manual_commit_listener<T>(
+        &self,
+        group_id: &str,
+        topics: &[&str],
+    ) -> Result<listeners::KafkaListener<T>, Box<dyn Error>>
+    where
+        T: Send + DeserializeOwned + 'static + Clone,
+    {
+        self.create_listener(group_id, topics, OffsetReset::default(), ListenerCommitMode::Manual)
+    }
+}
+
+// how the consumer of a listener commits the offsets of the messages it receives
+#[derive(Clone, Copy)]
+enum ListenerCommitMode {
+    // committed automatically in the background
+    Auto,
+    // committed by the listener once per batch of messages delivered to its channel
+    Batched(BatchCommitConfig),
+    // only committed when the listener is asked to
+    Manual,
+}
+
+impl From<Option<BatchCommitConfig>> for ListenerCommitMode {
+    fn from(batch_commit: Option<BatchCommitConfig>) -> Self {
+        match batch_commit {
+            Some(batch_commit) => ListenerCommitMode::Batched(batch_commit),
+            None => ListenerCommitMode::Auto,
+        }
+    }
+}
+
+impl<C> EventBus<C>
+where
+    C: EventDeserializer + Clone + 'static,
+{
+    // creates a supervised listener whose consumers subscribe to the topics and commit as `commit_mode` says
+    fn create_listener<T>(
         &self,
         group_id: &str,
         topics: &[&str],
+        offset_reset: OffsetReset,
+        commit_mode: ListenerCommitMode,
     ) -> Result<listeners::KafkaListener<T>, Box<dyn Error>>
     where
         T: Send + DeserializeOwned + 'static + Clone,
     {
-        let consumer = self.create_consumer(group_id, topics).map_err(|e| Box::new(e) as Box<dyn Error>)?;
-        Ok(listeners::KafkaListener::new(consumer, 100))
+        validate_topic_names(topics)?;
+        let broker = self.broker.clone();
+        let group_id = group_id.to_string();
+        let topics: Vec<String> = topics.iter().map(|topic| topic.to_string()).collect();
+        let (auto_commit, batch_commit) = match commit_mode {
+            ListenerCommitMode::Auto => (true, None),
+            ListenerCommitMode::Batched(batch_commit) => (false, Some(batch_commit)),
+            ListenerCommitMode::Manual => (false, None),
+        };
+        let create_consumer = move || {
+            let topics: Vec<&str> = topics.iter().map(String::as_str).collect();
+            create_consumer(&broker, &group_id, &topics, auto_commit, offset_reset)
+        };
+        listeners::KafkaListener::supervised(
+            create_consumer,
+            100,
+            self.codec.clone(),
+            self.supervisor_config,
+            batch_commit,
+        )
+        .map_err(|e| Box::new(e) as Box<dyn Error>)
     }
 }
 
 #[async_trait]
-impl EventProducer for EventBus {
+impl<C: EventSerializer> EventProducer for EventBus<C> {
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
     async fn broadcast_event<T: serde::Serialize + Send>(
         &self,
         payload: T,
         topic_name: &str,
-        key: &str,
+        key: impl Into<MessageKey> + Send,
     ) -> Result<(), Box<dyn Error>> {
-        // serialize the event object to JSON
-        let message = serde_json::to_string(&payload).map_err(|e| {
-            error!("Error serializing message: {:?}", e);
+        self.serialize_and_produce(payload, topic_name, key.into().as_str(), None, None).await
+    }
+
+    async fn broadcast_event_at<T: serde::Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+        timestamp_ms: i64,
+    ) -> Result<(), Box<dyn Error>> {
+        self.serialize_and_produce(payload, topic_name, key.into().as_str(), Some(timestamp_ms), None).await
+    }
+
+    async fn broadcast_event_sync<T: serde::Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+    ) -> Result<(), Box<dyn Error>> {
+        let key = key.into();
+        let key = key.as_str();
+        topic::validate_topic_name(topic_name)?;
+        let message = self.encode(&payload)?;
+        self.check_message_size(&message)?;
+
+        self.produce_and_await_ack(topic_name, &message, key).await.map_err(|e| {
+            error!("Error sending message to Kafka: {}", e);
             Box::new(e) as Box<dyn Error>
         })?;
 
-        // broadcast the event to kafka via our single producer
-        self.produce(topic_name, &message, key).await.map_err(|e| {
-            error!("Error sending message to Kafka: {:?}", e);
-            Box::new(e) as Box<dyn Error>
-        })
+        self.audit(topic_name, key, None).await;
+        Ok(())
+    }
+
+    async fn broadcast_event_to_partition<T: serde::Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+        partition: i32,
+    ) -> Result<(), Box<dyn Error>> {
+        validate_partition(partition)?;
+        self.serialize_and_produce(payload, topic_name, key.into().as_str(), None, Some(partition)).await
     }
 }
 
@@ -182,7 +560,7 @@ impl EventBus {
     /// # Arguments
     ///
     /// * `broker` - A string slice that holds the reference to the broker's address.
-    ///              This address is used to configure the Kafka producer.
+    ///   This address is used to configure the Kafka producer.
     ///
     /// # Returns
     ///
@@ -190,7 +568,7 @@ impl EventBus {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// let event_bus = EventBus::new("localhost:9092");
     /// ```
     ///
@@ -209,87 +587,1106 @@ impl EventBus {
     /// Additionally, there is only a single producer in this event bus. You could improve the design by implementing
     /// a multiple producer pattern.
     pub fn new(broker: &str) -> Self {
-        let producer: FutureProducer =
-            ClientConfig::new().set("bootstrap.servers", broker).create().expect("Producer creation error");
+        EventBus::with_clock(broker, Box::new(SystemClock))
+    }
+
+    /// Creates a new instance of `EventBus` that timestamps produced events using the given `Clock`.
+    ///
+    /// # Arguments
+    ///
+    /// * `broker` - A string slice that holds the reference to the broker's address.
+    /// * `clock` - The `Clock` used when creating events on the producer side.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the Kafka producer cannot be created.
+    pub fn with_clock(broker: &str, clock: Box<dyn Clock>) -> Self {
+        EventBus::with_codec(broker, clock, JsonCodec)
+    }
+}
+
+impl<C: EventSerializer> EventBus<C> {
+    /// Creates a new instance of `EventBus` that encodes and decodes messages using the given codec.
+    ///
+    /// The same codec is used by the producer and by every listener created from this event bus,
+    /// which keeps both sides of a topic consistent.
+    ///
+    /// # Arguments
+    ///
+    /// * `broker` - A string slice that holds the reference to the broker's address.
+    /// * `clock` - The `Clock` used when creating events on the producer side.
+    /// * `codec` - The codec used to serialize produced messages and deserialize consumed messages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the Kafka producer cannot be created.
+    pub fn with_codec(broker: &str, clock: Box<dyn Clock>, codec: C) -> Self {
+        // murmur2_random matches the java client's partitioner, see `partitioner::default_partition`
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", broker)
+            .set("partitioner", "murmur2_random")
+            .create()
+            .expect("Producer creation error");
 
         EventBus {
             broker: broker.to_string(),
             producer,
+            acked_producer: OnceLock::new(),
+            delivery_timeout: DEFAULT_DELIVERY_TIMEOUT,
+            clock,
+            codec,
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            supervisor_config: SupervisorConfig::default(),
+            batch_commit: None,
+            retry_policy: RetryPolicy::default(),
+            in_flight: Semaphore::new(DEFAULT_MAX_IN_FLIGHT),
+            audit_topic: None,
+            audit_source: String::new(),
+        }
+    }
+
+    /// Creates a Kafka consumer that is pinned to specific partitions of a topic.
+    ///
+    /// Unlike the consumers behind `create_event_listener`, which subscribe to their topics and have
+    /// partitions assigned automatically by the consumer group, this consumer is explicitly assigned the
+    /// given partitions. This is useful for debugging or replaying a partition in order. The consumer can
+    /// be wrapped with `KafkaListener::with_deserializer` to receive decoded messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id` - The consumer group ID, used when committing offsets.
+    /// * `topic` - The topic to consume from.
+    /// * `partitions` - The partitions of the topic to assign to the consumer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the assigned `StreamConsumer`, or a `KafkaError` if the consumer cannot be created or assigned.
+    pub fn create_assigned_consumer(
+        &self,
+        group_id: &str,
+        topic: &str,
+        partitions: &[i32],
+    ) -> Result<StreamConsumer, KafkaError> {
+        topic::validate_topic_name(topic).map_err(|e| KafkaError::Subscription(e.to_string()))?;
+        let consumer = build_consumer(&self.broker, group_id)?;
+        consumer.assign(&build_partition_list(topic, partitions))?;
+        Ok(consumer)
+    }
+
+    /// Sets how listeners created by this event bus restart their consumer task when it ends.
+    ///
+    /// # Arguments
+    ///
+    /// * `supervisor_config` - The `SupervisorConfig` applied to listeners created after this call.
+    pub fn set_supervisor_config(&mut self, supervisor_config: SupervisorConfig) {
+        self.supervisor_config = supervisor_config;
+    }
+
+    /// Commits the offsets of messages received by listeners in batches rather than automatically.
+    ///
+    /// By default, consumers commit their offsets automatically in the background. In batched-commit
+    /// mode, a listener commits the offsets of the messages it has delivered to its channel, once per
+    /// batch as described by the `BatchCommitConfig`. Offsets are committed once a message is
+    /// delivered, not once a subscriber has handled it, so a crash may still lose messages that were
+    /// delivered but not yet handled.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_commit` - The `BatchCommitConfig` applied to listeners created after this call.
+    pub fn set_batch_commit_config(&mut self, batch_commit: BatchCommitConfig) {
+        self.batch_commit = Some(batch_commit);
+    }
+
+    /// Sets how producing a message is retried when it fails with a transient error, e.g. because the
+    /// producer's queue is full. Applies to `broadcast_event` and `broadcast_event_at`.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry_policy` - The `RetryPolicy` applied to messages produced after this call.
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Sets how many messages may be in flight at once, i.e. sent by `broadcast_event` or
+    /// `broadcast_event_at` but not yet completed. Further broadcasts wait until an in-flight
+    /// message completes, so a burst of events cannot exhaust the producer's queue. Defaults to 1000.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_in_flight` - The largest number of in-flight messages. A value of 0 would block every
+    ///   broadcast forever, so it is raised to 1.
+    pub fn set_max_in_flight(&mut self, max_in_flight: usize) {
+        self.in_flight = Semaphore::new(max_in_flight.max(1));
+    }
+
+    /// Sets how long `broadcast_event_sync` waits for the broker to acknowledge an event before
+    /// failing with a `BroadcastError::Nack`. Defaults to 30 seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `delivery_timeout` - The delivery timeout, which is rounded down to whole milliseconds.
+    pub fn set_delivery_timeout(&mut self, delivery_timeout: Duration) {
+        self.delivery_timeout = delivery_timeout;
+        // the acknowledging producer is recreated with the new timeout when it is next needed
+        self.acked_producer = OnceLock::new();
+    }
+
+    /// Sets the largest serialized message, in bytes, that the event bus will attempt to send.
+    ///
+    /// This should not exceed the broker's `message.max.bytes`, so that oversized events are
+    /// rejected with a clear `BroadcastError::MessageTooLarge` rather than by the broker. Defaults
+    /// to 1 MiB.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_message_bytes` - The size limit in bytes.
+    pub fn set_max_message_bytes(&mut self, max_message_bytes: usize) {
+        self.max_message_bytes = max_message_bytes;
+    }
+
+    /// Records every event produced by this event bus to an audit topic.
+    ///
+    /// After an event is produced successfully, a compact `AuditRecord` with the source, topic, key
+    /// and timestamp of the event is produced to the audit topic. Failing to produce the audit
+    /// record is logged, but does not fail the broadcast of the event.
+    ///
+    /// # Arguments
+    ///
+    /// * `audit_topic` - The topic to which audit records are produced.
+    /// * `source` - The name of the service producing events, recorded in every audit record.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `topic::TopicError` if the audit topic is not a valid Kafka topic name.
+    pub fn set_audit_topic(&mut self, audit_topic: &str, source: &str) -> Result<(), topic::TopicError> {
+        topic::validate_topic_name(audit_topic)?;
+        self.audit_topic = Some(audit_topic.to_string());
+        self.audit_source = source.to_string();
+        Ok(())
+    }
+
+    // rejects messages larger than the configured limit before they reach the broker
+    fn check_message_size(&self, message: &[u8]) -> Result<(), BroadcastError> {
+        if message.len() > self.max_message_bytes {
+            error!(
+                "Message of {} bytes exceeds the limit of {} bytes",
+                message.len(),
+                self.max_message_bytes
+            );
+            return Err(BroadcastError::MessageTooLarge {
+                size: message.len(),
+                limit: self.max_message_bytes,
+            });
         }
+
+        Ok(())
     }
 
-    // sends a raw message via kafka using the event bus' single producer
-    async fn produce(&self, topic_name: &str, message: &str, key: &str) -> Result<(), KafkaError> {
-        let record = FutureRecord::to(topic_name).payload(message).key(key);
+    // encodes the payload into the bytes sent to kafka using the event bus' codec
+    fn encode<T: serde::Serialize>(&self, payload: &T) -> Result<Vec<u8>, Box<dyn Error>> {
+        self.codec.serialize(payload).map_err(|e| {
+            error!("Error serializing message: {:?}", e);
+            e as Box<dyn Error>
+        })
+    }
 
-        self.producer
-            .send(record, Duration::from_secs(0))
+    // serializes the payload and broadcasts it, using the broker time when no timestamp is given and
+    // the key to choose the partition when none is given
+    async fn serialize_and_produce<T: serde::Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: Option<&str>,
+        timestamp_ms: Option<i64>,
+        partition: Option<i32>,
+    ) -> Result<(), Box<dyn Error>> {
+        topic::validate_topic_name(topic_name)?;
+        let message = self.encode(&payload)?;
+        self.check_message_size(&message)?;
+
+        // broadcast the event to kafka via our single producer
+        self.produce(topic_name, &message, key, timestamp_ms, partition)
             .await
-            .map(|_| info!("Message with topic: {topic_name} and key: {key} sent successfully to Kafka"))
-            .map_err(|(e, _)| {
-                error!("Error sending message to Kafka: {:?}", e);
-                e
-            })
-    }
-
-    // creates and configures the raw kafka consumer
-    fn create_consumer(&self, group_id: &str, topics: &[&str]) -> Result<StreamConsumer, KafkaError> {
-        let consumer: StreamConsumer = ClientConfig::new()
-            .set("group.id", group_id)
-            .set("bootstrap.servers", &self.broker)
-            .set("auto.offset.reset", "earliest")
-            .create()?;
-
-        consumer.subscribe(topics)?;
-        Ok(consumer)
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        self.audit(topic_name, key, timestamp_ms).await;
+        Ok(())
+    }
+
+    // records a produced event to the audit topic if one is set, only logging failures
+    async fn audit(&self, topic_name: &str, key: Option<&str>, timestamp_ms: Option<i64>) {
+        let Some((audit_topic, message)) = self.audit_message(topic_name, key, timestamp_ms) else {
+            return;
+        };
+        // producing failures are already logged by `produce`
+        if self.produce(audit_topic, &message, key, None, None).await.is_err() {
+            error!(
+                "Failed to record message with topic: {} and key: {} to the audit topic: {}",
+                topic_name,
+                key.unwrap_or(NO_KEY),
+                audit_topic
+            );
+        }
+    }
+
+    // encodes the audit record of a produced event, returning it with the audit topic to produce it to. There is
+    // nothing to record without an audit topic, for events produced to the audit topic itself, or when the
+    // record cannot be encoded, which is already logged by `encode`
+    fn audit_message(&self, topic_name: &str, key: Option<&str>, timestamp_ms: Option<i64>) -> Option<(&str, Vec<u8>)> {
+        let audit_topic = self.audit_topic.as_deref().filter(|audit_topic| *audit_topic != topic_name)?;
+        let record = build_audit_record(self.clock.as_ref(), &self.audit_source, topic_name, key, timestamp_ms);
+        let message = self.encode(&record).ok()?;
+        Some((audit_topic, message))
+    }
+
+    // returns the acknowledging producer, creating it on first use
+    fn acked_producer(&self) -> Result<&FutureProducer, KafkaError> {
+        if let Some(acked_producer) = self.acked_producer.get() {
+            return Ok(acked_producer);
+        }
+        let acked_producer = build_acked_producer(&self.broker, self.delivery_timeout)?;
+        Ok(self.acked_producer.get_or_init(|| acked_producer))
+    }
+
+    // sends a raw message via the acknowledging producer, separating failures to queue it from failures to deliver it
+    async fn produce_and_await_ack(
+        &self,
+        topic_name: &str,
+        message: &[u8],
+        key: Option<&str>,
+    ) -> Result<(), BroadcastError> {
+        let record = build_record(topic_name, message, key, None, None);
+        let acked_producer = self.acked_producer().map_err(BroadcastError::Enqueue)?;
+        let delivery = acked_producer.send_result(record).map_err(|(e, _)| BroadcastError::Enqueue(e))?;
+        match delivery.await {
+            Ok(Ok(_)) => {
+                info!(
+                    "Message with topic: {topic_name} and key: {} acknowledged by Kafka",
+                    key.unwrap_or(NO_KEY)
+                );
+                Ok(())
+            }
+            Ok(Err((e, _))) => Err(BroadcastError::Nack(e)),
+            // the producer was dropped before the delivery report arrived
+            Err(_) => Err(BroadcastError::Nack(KafkaError::Canceled)),
+        }
+    }
+
+    // sends a raw message via kafka using the event bus' single producer, retrying transient failures
+    async fn produce(
+        &self,
+        topic_name: &str,
+        message: &[u8],
+        key: Option<&str>,
+        timestamp_ms: Option<i64>,
+        partition: Option<i32>,
+    ) -> Result<(), KafkaError> {
+        self.bounded(self.retry_policy.run(|| async {
+            let record = build_record(topic_name, message, key, timestamp_ms, partition);
+            self.producer.send(record, Duration::from_secs(0)).await.map_err(|(e, _)| e)
+        }))
+        .await
+        .map(|_| {
+            info!(
+                "Message with topic: {topic_name} and key: {} sent successfully to Kafka",
+                key.unwrap_or(NO_KEY)
+            )
+        })
+        .map_err(|e| {
+            error!("Error sending message to Kafka: {:?}", e);
+            e
+        })
+    }
+
+    // runs a send once fewer than the maximum number of messages are in flight, holding a permit until it completes
+    async fn bounded<F: Future>(&self, send: F) -> F::Output {
+        // the semaphore is never closed, so acquiring a permit cannot fail
+        let _permit = self.in_flight.acquire().await.expect("In-flight semaphore closed");
+        send.await
+    }
+}
+
+// builds the audit record of a produced event, using the producer's clock when no timestamp is given
+fn build_audit_record(
+    clock: &dyn Clock,
+    source: &str,
+    topic_name: &str,
+    key: Option<&str>,
+    timestamp_ms: Option<i64>,
+) -> AuditRecord {
+    let timestamp_ms = timestamp_ms.unwrap_or_else(|| {
+        clock.now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis() as i64).unwrap_or_default()
+    });
+    AuditRecord::new(source, topic_name, key.unwrap_or_default(), timestamp_ms)
+}
+
+// checks that every topic name is valid before a consumer is subscribed to them
+fn validate_topic_names(topics: &[&str]) -> Result<(), Box<dyn Error>> {
+    for topic_name in topics {
+        topic::validate_topic_name(topic_name).map_err(|e| {
+            error!("Invalid topic name: {}, {}", topic_name, e);
+            Box::new(e) as Box<dyn Error>
+        })?;
+    }
+    Ok(())
+}
+
+// creates and configures the raw kafka consumer, subscribing it to the topics
+fn create_consumer(
+    broker: &str,
+    group_id: &str,
+    topics: &[&str],
+    auto_commit: bool,
+    offset_reset: OffsetReset,
+) -> Result<StreamConsumer, KafkaError> {
+    let consumer: StreamConsumer = consumer_config(broker, group_id, auto_commit, offset_reset).create()?;
+    consumer.subscribe(topics)?;
+    Ok(consumer)
+}
+
+// creates the producer used for messages that must be acknowledged by every in-sync replica
+fn build_acked_producer(broker: &str, delivery_timeout: Duration) -> Result<FutureProducer, KafkaError> {
+    ClientConfig::new()
+        .set("bootstrap.servers", broker)
+        .set("partitioner", "murmur2_random")
+        .set("acks", "all")
+        .set("message.timeout.ms", delivery_timeout.as_millis().to_string())
+        .create()
+}
+
+// creates a raw kafka consumer that is neither subscribed nor assigned
+fn build_consumer(broker: &str, group_id: &str) -> Result<StreamConsumer, KafkaError> {
+    consumer_config(broker, group_id, true, OffsetReset::Earliest).create()
+}
+
+// configures a raw kafka consumer, which only commits offsets when asked to if `auto_commit` is false
+fn consumer_config(broker: &str, group_id: &str, auto_commit: bool, offset_reset: OffsetReset) -> ClientConfig {
+    let mut config = ClientConfig::new();
+    config
+        .set("group.id", group_id)
+        .set("bootstrap.servers", broker)
+        .set("auto.offset.reset", offset_reset.as_config_value())
+        .set("enable.auto.commit", auto_commit.to_string());
+    config
+}
+
+// builds the list of partitions assigned to a consumer, each starting from its stored offset
+fn build_partition_list(topic: &str, partitions: &[i32]) -> TopicPartitionList {
+    let mut partition_list = TopicPartitionList::with_capacity(partitions.len());
+    for partition in partitions {
+        partition_list.add_partition_offset(topic, *partition, Offset::Stored).expect("Offset is valid");
+    }
+    partition_list
+}
+
+// builds the kafka record, leaving the key unset for keyless messages, the timestamp unset so the
+// broker assigns one when none is given, and the partition unset so the producer chooses it from the key
+fn build_record<'a>(
+    topic_name: &'a str,
+    message: &'a [u8],
+    key: Option<&'a str>,
+    timestamp_ms: Option<i64>,
+    partition: Option<i32>,
+) -> FutureRecord<'a, str, [u8]> {
+    let record = FutureRecord::to(topic_name).payload(message);
+    let record = match key {
+        Some(key) => record.key(key),
+        None => record,
+    };
+    let record = match timestamp_ms {
+        Some(timestamp) => record.timestamp(timestamp),
+        None => record,
+    };
+    match partition {
+        Some(partition) => record.partition(partition),
+        None => record,
     }
 }
 
 pub struct MockEventBus {
     produces_error: bool,
+    clock: Box<dyn Clock>,
+    messages: broadcast::Sender<(String, MessageKey, Vec<u8>)>,
+    audit_topic: Option<String>,
+    audit_source: String,
+    produced: Mutex<Vec<(String, String, Vec<u8>)>>,
 }
 
 impl EventListener for MockEventBus {
+    // mock listeners only receive messages published after they are created, whatever the offset reset
     #[allow(unused_variables)]
-    fn create_event_listener<T>(
+    fn create_event_listener_with_offset_reset<T>(
         &self,
         group_id: &str,
         topics: &[&str],
+        offset_reset: OffsetReset,
     ) -> Result<listeners::KafkaListener<T>, Box<dyn Error>>
     where
         T: Send + DeserializeOwned + 'static + Clone,
     {
-        return if self.produces_error {
+        validate_topic_names(topics)?;
+        if self.produces_error {
             Err(Box::new(KafkaError::Canceled) as Box<dyn Error>)
         } else {
-            Ok(KafkaListener::mock())
-        };
+            Ok(KafkaListener::mock_with_messages(self.messages.subscribe(), topics))
+        }
+    }
+
+    fn is_mock(&self) -> bool {
+        true
     }
 }
 
 #[async_trait]
 impl EventProducer for MockEventBus {
-    #[allow(unused_variables)]
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
     async fn broadcast_event<T: Serialize + Send>(
         &self,
         payload: T,
         topic_name: &str,
-        key: &str,
+        key: impl Into<MessageKey> + Send,
     ) -> Result<(), Box<dyn Error>> {
-        return if self.produces_error {
-            Err(Box::new(KafkaError::Canceled) as Box<dyn Error>)
-        } else {
-            Ok(())
-        };
+        self.record_produced(&payload, topic_name, key.into().as_str(), None)
+    }
+
+    async fn broadcast_event_at<T: Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+        timestamp_ms: i64,
+    ) -> Result<(), Box<dyn Error>> {
+        self.record_produced(&payload, topic_name, key.into().as_str(), Some(timestamp_ms))
+    }
+
+    async fn broadcast_event_sync<T: Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+    ) -> Result<(), Box<dyn Error>> {
+        self.record_produced(&payload, topic_name, key.into().as_str(), None)
+    }
+
+    // the mock has no partitions, so a valid event is recorded like any other
+    async fn broadcast_event_to_partition<T: Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+        partition: i32,
+    ) -> Result<(), Box<dyn Error>> {
+        validate_partition(partition)?;
+        self.record_produced(&payload, topic_name, key.into().as_str(), None)
+    }
+}
+
+impl Default for MockEventBus {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl MockEventBus {
     pub fn new() -> Self {
-        MockEventBus { produces_error: false }
+        let (messages, _) = broadcast::channel(100);
+        MockEventBus {
+            produces_error: false,
+            clock: Box::new(SystemClock),
+            messages,
+            audit_topic: None,
+            audit_source: String::new(),
+            produced: Mutex::new(vec![]),
+        }
+    }
+
+    /// Returns the topic, key and JSON payload of every message produced by this mock, including
+    /// audit records, in the order they were produced. Keyless messages have an empty key.
+    pub fn produced(&self) -> Vec<(String, String, Vec<u8>)> {
+        self.produced.lock().unwrap().clone()
+    }
+
+    /// Records every event broadcast by this mock to an audit topic, like `EventBus::set_audit_topic`.
+    pub fn set_audit_topic(&mut self, audit_topic: &str, source: &str) -> Result<(), topic::TopicError> {
+        topic::validate_topic_name(audit_topic)?;
+        self.audit_topic = Some(audit_topic.to_string());
+        self.audit_source = source.to_string();
+        Ok(())
+    }
+
+    // records a broadcast message, and its audit record if an audit topic is set, instead of sending them
+    fn record_produced<T: Serialize>(
+        &self,
+        payload: &T,
+        topic_name: &str,
+        key: Option<&str>,
+        timestamp_ms: Option<i64>,
+    ) -> Result<(), Box<dyn Error>> {
+        topic::validate_topic_name(topic_name)?;
+        if self.produces_error {
+            return Err(Box::new(KafkaError::Canceled) as Box<dyn Error>);
+        }
+
+        let mut produced = self.produced.lock().unwrap();
+        produced.push((
+            topic_name.to_string(),
+            key.unwrap_or_default().to_string(),
+            JsonCodec.serialize(payload).map_err(|e| e as Box<dyn Error>)?,
+        ));
+        if let Some(audit_topic) = self.audit_topic.as_ref().filter(|audit_topic| *audit_topic != topic_name) {
+            let record = build_audit_record(self.clock.as_ref(), &self.audit_source, topic_name, key, timestamp_ms);
+            produced.push((
+                audit_topic.clone(),
+                key.unwrap_or_default().to_string(),
+                JsonCodec.serialize(&record).map_err(|e| e as Box<dyn Error>)?,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Publishes a message to the listeners created by this mock that are subscribed to the topic,
+    /// allowing tests to feed events to a service.
+    pub fn publish<T: Serialize>(&self, topic_name: &str, payload: &T) {
+        self.publish_with_key(topic_name, MessageKey::none(), payload);
+    }
+
+    /// Publishes a message with a key, like `publish`, so that listeners receive the key along with
+    /// the message, see `KafkaListener::get_received_receiver`.
+    pub fn publish_with_key<T: Serialize>(&self, topic_name: &str, key: impl Into<MessageKey>, payload: &T) {
+        let payload = JsonCodec.serialize(payload).expect("Payload should serialize");
+        // there may be no listeners yet, in which case the message is dropped like an unconsumed topic
+        let _ = self.messages.send((topic_name.to_string(), key.into(), payload));
     }
 
     pub fn set_produces_error(&mut self, does_produce_error: bool) {
         self.produces_error = does_produce_error;
     }
+
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::SystemTime;
+
+    #[test]
+    fn test_create_event_uses_clock() {
+        // prepare
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let sut = EventBus::with_clock("localhost:9092", Box::new(MockClock::new(time)));
+
+        // act
+        let event = sut.create_event("test_event".to_string(), 1, "test".to_string(), None, None);
+
+        // assert
+        assert_eq!(event.timestamp, time);
+    }
+
+    #[test]
+    fn test_mock_create_event_uses_clock() {
+        // prepare
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(42);
+        let mut sut = MockEventBus::new();
+        sut.set_clock(Box::new(MockClock::new(time)));
+
+        // act
+        let event = sut.create_event("test_event".to_string(), 1, "test".to_string(), None, None);
+
+        // assert
+        assert_eq!(event.timestamp, time);
+    }
+
+    #[test]
+    fn test_consumer_config_offset_reset() {
+        for (offset_reset, expected) in [(OffsetReset::Earliest, "earliest"), (OffsetReset::Latest, "latest")] {
+            // act
+            let config = consumer_config("localhost:9092", "group", true, offset_reset);
+
+            // assert
+            assert_eq!(config.get("auto.offset.reset"), Some(expected));
+        }
+        assert_eq!(OffsetReset::default(), OffsetReset::Earliest);
+    }
+
+    #[test]
+    fn test_build_record_with_timestamp() {
+        // act
+        let record = build_record("topic", b"message", Some("key"), Some(1_700_000_000_000), None);
+
+        // assert
+        assert_eq!(record.timestamp, Some(1_700_000_000_000));
+        assert_eq!(record.topic, "topic");
+        assert_eq!(record.key, Some("key"));
+    }
+
+    #[test]
+    fn test_build_record_uses_broker_time_by_default() {
+        // act
+        let record = build_record("topic", b"message", Some("key"), None, None);
+
+        // assert
+        assert_eq!(record.timestamp, None);
+        assert_eq!(record.partition, None);
+    }
+
+    #[test]
+    fn test_build_record_with_partition() {
+        // act
+        let record = build_record("topic", b"message", Some("key"), None, Some(3));
+
+        // assert
+        assert_eq!(record.partition, Some(3));
+        assert_eq!(record.topic, "topic");
+        assert_eq!(record.key, Some("key"));
+    }
+
+    #[test]
+    fn test_build_record_without_key() {
+        // act
+        let record = build_record("topic", b"message", None, None, None);
+
+        // assert
+        assert_eq!(record.key, None);
+        assert_eq!(record.topic, "topic");
+        assert_eq!(record.payload, Some(&b"message"[..]));
+    }
+
+    // a custom codec that wraps json in a marker, used to check the event bus routes through its codec
+    #[derive(Clone)]
+    struct MarkedJsonCodec;
+
+    impl EventSerializer for MarkedJsonCodec {
+        fn serialize<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, serialization::SerializationError> {
+            let mut bytes = b"marked:".to_vec();
+            bytes.extend(JsonCodec.serialize(value)?);
+            Ok(bytes)
+        }
+    }
+
+    impl EventDeserializer for MarkedJsonCodec {
+        fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, serialization::SerializationError> {
+            let json = bytes.strip_prefix(b"marked:").ok_or("Message is not marked")?;
+            JsonCodec.deserialize(json)
+        }
+    }
+
+    #[test]
+    fn test_encode_uses_custom_codec() {
+        // prepare
+        let sut = EventBus::with_codec("localhost:9092", Box::new(SystemClock), MarkedJsonCodec);
+        let event = sut.create_event("test_event".to_string(), 7u32, "test".to_string(), None, None);
+
+        // act
+        let bytes = sut.encode(&event).unwrap();
+        let decoded: Event<u32> = MarkedJsonCodec.deserialize(&bytes).unwrap();
+
+        // assert
+        assert!(bytes.starts_with(b"marked:"));
+        assert_eq!(decoded.event_id, event.event_id);
+        assert_eq!(decoded.payload, 7);
+        assert!(JsonCodec.deserialize::<Event<u32>>(&bytes).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_event_rejects_message_too_large() {
+        // prepare
+        let mut sut = EventBus::new("localhost:9092");
+        sut.set_max_message_bytes(8);
+        let event = sut.create_event("test_event".to_string(), 1u32, "test".to_string(), None, None);
+        let size = sut.encode(&event).unwrap().len();
+
+        // act
+        let result = sut.broadcast_event(event, "topic", "key").await;
+
+        // assert
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<BroadcastError>(),
+            Some(&BroadcastError::MessageTooLarge { size, limit: 8 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bounded_sends_wait_beyond_max_in_flight() {
+        // prepare
+        let mut sut = EventBus::new("localhost:9092");
+        sut.set_max_in_flight(2);
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let send = || async {
+            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+        };
+
+        // act by starting more sends than may be in flight at once
+        tokio::join!(
+            sut.bounded(send()),
+            sut.bounded(send()),
+            sut.bounded(send()),
+            sut.bounded(send()),
+            sut.bounded(send())
+        );
+
+        // assert
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        assert_eq!(sut.in_flight.available_permits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_zero_max_in_flight_is_raised_to_one() {
+        // prepare
+        let mut sut = EventBus::new("localhost:9092");
+
+        // act
+        sut.set_max_in_flight(0);
+        let completed = tokio::time::timeout(Duration::from_secs(1), sut.bounded(async {})).await;
+
+        // assert
+        assert!(completed.is_ok());
+        assert_eq!(sut.in_flight.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_event_sync_unacknowledged_delivery_is_nack() {
+        // prepare an event bus whose broker cannot be reached
+        let mut sut = EventBus::new("127.0.0.1:1");
+        sut.set_delivery_timeout(Duration::from_millis(100));
+        let event = sut.create_event("test_event".to_string(), 1u32, "test".to_string(), None, None);
+
+        // act
+        let result = sut.broadcast_event_sync(event, "topic", "key").await;
+
+        // assert
+        let err = result.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<BroadcastError>(),
+            Some(BroadcastError::Nack(_))
+        ));
+        assert!(sut.acked_producer.get().is_some());
+    }
+
+    #[test]
+    fn test_acked_producer_is_created_lazily() {
+        // prepare
+        let mut sut = EventBus::new("127.0.0.1:1");
+
+        // act
+        sut.set_delivery_timeout(Duration::from_millis(100));
+
+        // assert
+        assert!(sut.acked_producer.get().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_event_sync_rejects_message_too_large() {
+        // prepare
+        let mut sut = EventBus::new("localhost:9092");
+        sut.set_max_message_bytes(8);
+        let event = sut.create_event("test_event".to_string(), 1u32, "test".to_string(), None, None);
+
+        // act
+        let result = sut.broadcast_event_sync(event, "topic", "key").await;
+
+        // assert that the message is rejected before being queued
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<BroadcastError>(),
+            Some(BroadcastError::MessageTooLarge { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mock_broadcast_event_sync_records_message() {
+        // prepare
+        let sut = MockEventBus::new();
+
+        // act
+        let result = sut.broadcast_event_sync(1u32, "topic", "key").await;
+
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(
+            sut.produced(),
+            vec![("topic".to_string(), "key".to_string(), b"1".to_vec())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_broadcast_event_to_partition_records_message() {
+        // prepare
+        let sut = MockEventBus::new();
+
+        // act
+        let result = sut.broadcast_event_to_partition(1u32, "topic", "key", 0).await;
+
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(
+            sut.produced(),
+            vec![("topic".to_string(), "key".to_string(), b"1".to_vec())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_event_to_partition_rejects_negative_partition() {
+        // prepare
+        let sut = EventBus::new("localhost:9092");
+        let mock = MockEventBus::new();
+
+        // act
+        let result = sut.broadcast_event_to_partition(1u32, "topic", "key", -1).await;
+        let mock_result = mock.broadcast_event_to_partition(1u32, "topic", "key", -1).await;
+
+        // assert
+        assert_eq!(
+            result.unwrap_err().downcast_ref::<BroadcastError>(),
+            Some(&BroadcastError::InvalidPartition(-1))
+        );
+        assert_eq!(
+            mock_result.unwrap_err().downcast_ref::<BroadcastError>(),
+            Some(&BroadcastError::InvalidPartition(-1))
+        );
+        assert!(mock.produced().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mock_broadcast_event_without_key() {
+        // prepare
+        let sut = MockEventBus::new();
+
+        // act
+        let result = sut.broadcast_event(1u32, "topic", MessageKey::none()).await;
+
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(
+            sut.produced(),
+            vec![("topic".to_string(), String::new(), b"1".to_vec())]
+        );
+    }
+
+    #[test]
+    fn test_check_message_size_within_limit() {
+        // prepare
+        let mut sut = EventBus::new("localhost:9092");
+        sut.set_max_message_bytes(8);
+
+        // act
+        let result = sut.check_message_size(b"12345678");
+
+        // assert
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn test_build_partition_list() {
+        // act
+        let partition_list = build_partition_list("topic", &[0, 2]);
+
+        // assert
+        let elements = partition_list.elements();
+        assert_eq!(elements.len(), 2);
+        assert!(elements.iter().all(|element| element.topic() == "topic"));
+        assert_eq!(elements[0].partition(), 0);
+        assert_eq!(elements[1].partition(), 2);
+        assert_eq!(elements[0].offset(), Offset::Stored);
+    }
+
+    #[tokio::test]
+    async fn test_create_assigned_consumer() {
+        // prepare
+        let sut = EventBus::new("localhost:9092");
+
+        // act
+        let consumer = sut.create_assigned_consumer("group", "topic", &[1]).unwrap();
+
+        // assert
+        let assignment = consumer.assignment().unwrap();
+        assert_eq!(assignment.count(), 1);
+        assert!(assignment.find_partition("topic", 1).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_listener_topics_match_subscribed_topics() {
+        // prepare
+        let sut = EventBus::new("127.0.0.1:1");
+
+        // act
+        let listener =
+            sut.create_event_listener::<u32>("group", &[topic::ORDER_PLACED, topic::BACKORDER_PLACED]).unwrap();
+
+        // assert
+        let mut topics = listener.topics().to_vec();
+        topics.sort();
+        assert_eq!(
+            topics,
+            vec![topic::BACKORDER_PLACED.to_string(), topic::ORDER_PLACED.to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_listener_topics_match_subscribed_topics() {
+        // prepare
+        let sut = MockEventBus::new();
+
+        // act
+        let listener = sut.create_event_listener::<u32>("group", &[topic::ORDER_PLACED]).unwrap();
+
+        // assert
+        assert_eq!(listener.topics(), &[topic::ORDER_PLACED.to_string()]);
+    }
+
+    #[test]
+    fn test_is_mock() {
+        assert!(!EventBus::new("localhost:9092").is_mock());
+        assert!(MockEventBus::new().is_mock());
+    }
+
+    #[tokio::test]
+    async fn test_mock_publish_reaches_subscribed_listener() {
+        // prepare
+        let sut = MockEventBus::new();
+        let listener = sut.create_event_listener::<u32>("group", &["topic"]).unwrap();
+        let mut receiver = listener.get_receiver();
+
+        // act
+        sut.publish("other_topic", &1u32);
+        sut.publish("topic", &2u32);
+
+        // assert
+        assert_eq!(receiver.recv().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_event_rejects_invalid_topic_name() {
+        // prepare
+        let sut = EventBus::new("localhost:9092");
+
+        // act
+        let result = sut.broadcast_event(1u32, "invalid topic", "key").await;
+
+        // assert
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<topic::TopicError>(),
+            Some(&topic::TopicError::IllegalCharacter(' '))
+        );
+    }
+
+    #[test]
+    fn test_create_event_listener_rejects_invalid_topic_name() {
+        // prepare
+        let sut = EventBus::new("localhost:9092");
+
+        // act
+        let result = sut.create_event_listener::<u32>("group", &[topic::ORDER_PLACED, ""]);
+
+        // assert
+        let err = result.err().unwrap();
+        assert_eq!(err.downcast_ref::<topic::TopicError>(), Some(&topic::TopicError::Empty));
+    }
+
+    #[tokio::test]
+    async fn test_mock_broadcast_event_produces_audit_record() {
+        // prepare
+        let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+        let mut sut = MockEventBus::new();
+        sut.set_clock(Box::new(MockClock::new(time)));
+        sut.set_audit_topic("audit", "Order").unwrap();
+
+        // act
+        sut.broadcast_event(5u32, topic::ORDER_PLACED, "key").await.unwrap();
+
+        // assert
+        let produced = sut.produced();
+        assert_eq!(produced.len(), 2);
+        assert_eq!(
+            produced[0],
+            (topic::ORDER_PLACED.to_string(), "key".to_string(), b"5".to_vec())
+        );
+        assert_eq!(produced[1].0, "audit");
+        assert_eq!(
+            JsonCodec.deserialize::<AuditRecord>(&produced[1].2).unwrap(),
+            AuditRecord::new("Order", topic::ORDER_PLACED, "key", 1_700_000_000_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_broadcast_event_without_audit_topic() {
+        // prepare
+        let sut = MockEventBus::new();
+
+        // act
+        sut.broadcast_event(5u32, topic::ORDER_PLACED, "key").await.unwrap();
+
+        // assert
+        assert_eq!(sut.produced().len(), 1);
+    }
+
+    #[test]
+    fn test_build_audit_record_prefers_explicit_timestamp() {
+        // prepare
+        let clock = MockClock::new(SystemTime::UNIX_EPOCH + Duration::from_millis(10));
+
+        // act
+        let record = build_audit_record(&clock, "Catalog", "topic", Some("key"), Some(20));
+
+        // assert
+        assert_eq!(record, AuditRecord::new("Catalog", "topic", "key", 20));
+    }
+
+    #[test]
+    fn test_audit_message_encodes_record_for_audit_topic() {
+        // prepare
+        let clock = MockClock::new(SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_000));
+        let mut sut = EventBus::with_clock("localhost:9092", Box::new(clock));
+        sut.set_audit_topic("audit", "Order").unwrap();
+
+        // act
+        let (audit_topic, message) = sut.audit_message(topic::ORDER_PLACED, Some("key"), None).unwrap();
+
+        // assert
+        assert_eq!(audit_topic, "audit");
+        assert_eq!(
+            JsonCodec.deserialize::<AuditRecord>(&message).unwrap(),
+            AuditRecord::new("Order", topic::ORDER_PLACED, "key", 1_700_000_000_000)
+        );
+    }
+
+    #[test]
+    fn test_audit_message_skips_audit_topic_and_unaudited_bus() {
+        // prepare
+        let mut sut = EventBus::new("localhost:9092");
+        let unaudited = sut.audit_message(topic::ORDER_PLACED, Some("key"), Some(20)).is_none();
+        sut.set_audit_topic("audit", "Order").unwrap();
+
+        // act
+        let audit_of_audit = sut.audit_message("audit", Some("key"), Some(20));
+
+        // assert
+        assert!(unaudited);
+        assert!(audit_of_audit.is_none());
+    }
+
+    #[test]
+    fn test_set_audit_topic_rejects_invalid_topic_name() {
+        // prepare
+        let mut sut = EventBus::new("localhost:9092");
+
+        // act
+        let result = sut.set_audit_topic("audit topic", "Order");
+
+        // assert
+        assert_eq!(result, Err(topic::TopicError::IllegalCharacter(' ')));
+        assert_eq!(sut.audit_topic, None);
+    }
 }
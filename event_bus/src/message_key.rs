@@ -0,0 +1,178 @@
+use std::fmt::{Display, Formatter};
+
+// separates the parts of a composite key
+const COMPOSITE_SEPARATOR: char = ':';
+// escapes a separator or escape character within a part of a composite key
+const COMPOSITE_ESCAPE: char = '\\';
+// encodes an absent part of a composite key, which an escaped part can never contain as the escape
+// character is only ever followed by a separator or another escape character
+const ABSENT_PART: &str = "\\0";
+// how a keyless message's key is displayed, e.g. in logs
+pub(crate) const NO_KEY: &str = "<none>";
+
+/// The key of a Kafka message, which decides the partition the message is written to.
+///
+/// Keys are built from the values events are keyed by, so that every producer encodes the same value
+/// into the same bytes and its messages land on the same partition:
+/// - Integers are encoded as their decimal digits in UTF-8, e.g. `42u32` as `b"42"`.
+/// - Strings are encoded as their UTF-8 bytes.
+/// - Tuples are composite keys, whose parts are encoded as above and joined by `:`, e.g.
+///   `(7u32, "M")` as `b"7:M"`. A `:` or `\` within a part is escaped with a `\`, so that parts
+///   containing the separator cannot collide, e.g. `("a:b", "c")` is `b"a\:b:c"` and `("a", "b:c")`
+///   is `b"a:b\:c"`. A nested tuple is a part like any other, so its separators are escaped too.
+///   An absent part, e.g. `(7u32, None::<&str>)`, is encoded as `\0`, which no present part encodes to.
+/// - `None` and `MessageKey::none()` leave the message without a key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MessageKey(Option<String>);
+
+impl MessageKey {
+    /// The absence of a key, producing a message with a null key.
+    ///
+    /// Kafka spreads keyless messages across the partitions of a topic, so unlike keyed messages
+    /// they do not preserve the order of the events of an entity, e.g. two stock updates of the
+    /// same item may be consumed in either order. Only use it for events that carry no per-entity
+    /// ordering, e.g. broadcasts to every consumer.
+    pub fn none() -> Self {
+        MessageKey(None)
+    }
+
+    /// The key as a string, e.g. for logging or predicting its partition, or `None` for a keyless message.
+    pub fn as_str(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+
+    /// The bytes of the key, as written to Kafka, or `None` for a keyless message.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        self.0.as_ref().map(String::as_bytes)
+    }
+}
+
+impl Display for MessageKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.as_deref().unwrap_or(NO_KEY))
+    }
+}
+
+impl From<&str> for MessageKey {
+    fn from(key: &str) -> Self {
+        MessageKey(Some(key.to_string()))
+    }
+}
+
+impl From<String> for MessageKey {
+    fn from(key: String) -> Self {
+        MessageKey(Some(key))
+    }
+}
+
+impl From<&String> for MessageKey {
+    fn from(key: &String) -> Self {
+        MessageKey(Some(key.clone()))
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for MessageKey {
+                fn from(key: $integer) -> Self {
+                    MessageKey(Some(key.to_string()))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(u32, u64, i32, i64);
+
+impl<A: Into<MessageKey>, B: Into<MessageKey>> From<(A, B)> for MessageKey {
+    fn from((first, second): (A, B)) -> Self {
+        MessageKey(Some(format!(
+            "{}{}{}",
+            composite_part(first.into()),
+            COMPOSITE_SEPARATOR,
+            composite_part(second.into())
+        )))
+    }
+}
+
+// encodes a part of a composite key, escaping the separator and escape characters within it
+fn composite_part(part: MessageKey) -> String {
+    let Some(part) = part.0 else {
+        return ABSENT_PART.to_string();
+    };
+    let mut escaped = String::with_capacity(part.len());
+    for c in part.chars() {
+        if c == COMPOSITE_SEPARATOR || c == COMPOSITE_ESCAPE {
+            escaped.push(COMPOSITE_ESCAPE);
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl<K: Into<MessageKey>> From<Option<K>> for MessageKey {
+    fn from(key: Option<K>) -> Self {
+        key.map_or_else(MessageKey::none, Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeric_keys_are_decimal_digits() {
+        assert_eq!(MessageKey::from(42u32).as_bytes(), Some(&b"42"[..]));
+        assert_eq!(MessageKey::from(0u64).as_bytes(), Some(&b"0"[..]));
+        assert_eq!(MessageKey::from(-7i64).as_bytes(), Some(&b"-7"[..]));
+    }
+
+    #[test]
+    fn test_string_keys_are_utf8() {
+        assert_eq!(MessageKey::from("TSHIRT-001").as_bytes(), Some(&b"TSHIRT-001"[..]));
+        assert_eq!(
+            MessageKey::from("caf\u{e9}".to_string()).as_bytes(),
+            Some(&[99, 97, 102, 195, 169][..])
+        );
+    }
+
+    #[test]
+    fn test_numeric_and_stringified_keys_match() {
+        // keys produced before `MessageKey` stringified ids, so they keep their partitions
+        assert_eq!(MessageKey::from(42u32), MessageKey::from(42u32.to_string()));
+    }
+
+    #[test]
+    fn test_composite_keys_join_parts() {
+        assert_eq!(MessageKey::from((7u32, "M")).as_bytes(), Some(&b"7:M"[..]));
+        assert_eq!(MessageKey::from(((1u32, 2u32), 3u32)).as_str(), Some("1\\:2:3"));
+    }
+
+    #[test]
+    fn test_composite_keys_escape_separators_within_parts() {
+        assert_eq!(MessageKey::from(("a:b", "c")).as_str(), Some("a\\:b:c"));
+        assert_eq!(MessageKey::from(("a", "b:c")).as_str(), Some("a:b\\:c"));
+        assert_eq!(MessageKey::from(("a\\", "b")).as_str(), Some("a\\\\:b"));
+        assert_ne!(
+            MessageKey::from(((1u32, 2u32), 3u32)),
+            MessageKey::from((1u32, (2u32, 3u32)))
+        );
+    }
+
+    #[test]
+    fn test_composite_keys_encode_absent_parts_unambiguously() {
+        assert_eq!(MessageKey::from((7u32, None::<&str>)).as_str(), Some("7:\\0"));
+        assert_ne!(MessageKey::from((7u32, None::<&str>)), MessageKey::from((7u32, NO_KEY)));
+        assert_ne!(MessageKey::from((7u32, None::<&str>)), MessageKey::from((7u32, "\\0")));
+        assert_ne!(MessageKey::from((7u32, None::<&str>)), MessageKey::from((7u32, "")));
+    }
+
+    #[test]
+    fn test_keyless_keys_have_no_bytes() {
+        assert_eq!(MessageKey::none().as_bytes(), None);
+        assert_eq!(MessageKey::from(None::<u32>), MessageKey::none());
+        assert_eq!(MessageKey::from(Some(42u32)), MessageKey::from(42u32));
+        assert_eq!(MessageKey::none().to_string(), "<none>");
+    }
+}
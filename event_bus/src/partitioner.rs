@@ -0,0 +1,102 @@
+// the seed used by kafka's murmur2 implementation
+const MURMUR2_SEED: u32 = 0x9747_b28c;
+const MURMUR2_M: u32 = 0x5bd1_e995;
+const MURMUR2_R: u32 = 24;
+
+/// Computes the 32-bit murmur2 hash of the data, exactly as Kafka's Java client does.
+///
+/// # Arguments
+///
+/// * `data`: The bytes to hash.
+///
+/// # Returns
+///
+/// Returns the hash as a signed integer, matching the value returned by `org.apache.kafka.common.utils.Utils.murmur2`.
+pub fn murmur2(data: &[u8]) -> i32 {
+    let mut h = MURMUR2_SEED ^ data.len() as u32;
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(MURMUR2_M);
+        k ^= k >> MURMUR2_R;
+        k = k.wrapping_mul(MURMUR2_M);
+        h = h.wrapping_mul(MURMUR2_M);
+        h ^= k;
+    }
+
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        for (i, byte) in remainder.iter().enumerate().rev() {
+            h ^= (*byte as u32) << (8 * i);
+        }
+        h = h.wrapping_mul(MURMUR2_M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(MURMUR2_M);
+    h ^= h >> 15;
+    h as i32
+}
+
+/// Predicts the partition that a keyed message is written to by Kafka's default partitioner.
+///
+/// The `EventBus` producer is configured with the `murmur2_random` partitioner, which places keyed
+/// messages exactly like the Java client's default partitioner, so events keyed by the same value
+/// always land on the same partition regardless of which producer sent them.
+///
+/// # Arguments
+///
+/// * `key`: The key of the message.
+/// * `num_partitions`: The number of partitions of the topic, which must be greater than zero.
+///
+/// # Returns
+///
+/// Returns the partition, between `0` and `num_partitions - 1`.
+///
+/// # Panics
+///
+/// Panics if `num_partitions` is not greater than zero.
+pub fn default_partition(key: &str, num_partitions: i32) -> i32 {
+    assert!(num_partitions > 0, "A topic must have at least one partition");
+    (murmur2(key.as_bytes()) & 0x7fff_ffff) % num_partitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_murmur2_matches_kafka() {
+        // known outputs of kafka's java implementation
+        assert_eq!(murmur2(b"21"), -973932308);
+        assert_eq!(murmur2(b"foobar"), -790332482);
+        assert_eq!(murmur2(b"a-little-bit-long-string"), -985981536);
+        assert_eq!(murmur2(b"a-little-bit-longer-string"), -1486304829);
+        assert_eq!(murmur2(b"lkjh234lh9fiuh90y23oiuhsafujhadof229phr9h19h89h8"), -58897971);
+        assert_eq!(murmur2(b"abc"), 479470107);
+    }
+
+    #[test]
+    fn test_default_partition() {
+        assert_eq!(default_partition("21", 10), (-973932308i32 & 0x7fff_ffff) % 10);
+        assert_eq!(default_partition("abc", 1), 0);
+    }
+
+    #[test]
+    fn test_default_partition_is_stable_and_in_range() {
+        for item_id in 0..100 {
+            let key = item_id.to_string();
+            let partition = default_partition(&key, 6);
+
+            assert!((0..6).contains(&partition));
+            assert_eq!(partition, default_partition(&key, 6));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_default_partition_without_partitions() {
+        default_partition("abc", 0);
+    }
+}
@@ -0,0 +1,194 @@
+use log::warn;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use std::future::Future;
+use std::time::Duration;
+
+/// Controls how the `EventBus` retries producing a message that failed with a transient error.
+///
+/// The delay before each retry doubles from `initial_backoff`, up to `max_backoff`. Permanent
+/// errors, e.g. an unknown topic or an oversized message, fail straight away.
+///
+/// # Fields
+///
+/// * `max_attempts`: The number of times a message is produced before giving up, including the
+///   first attempt. Defaults to `3`, and `1` disables retries.
+/// * `initial_backoff`: The delay before the first retry. Defaults to 100 milliseconds.
+/// * `max_backoff`: The largest delay between retries. Defaults to 2 seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    // the delay before the given retry, starting at 1
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+
+    // runs the operation until it succeeds, fails with a permanent error or runs out of attempts
+    pub(crate) async fn run<T, F, Fut>(&self, mut operation: F) -> Result<T, KafkaError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, KafkaError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(e) if attempt < self.max_attempts && is_retryable(&e) => {
+                    warn!("Producing a message failed, attempt: {}, retrying: {:?}", attempt, e);
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Checks whether producing a message failed with a transient error, which may succeed if retried,
+/// e.g. because the producer's queue is full or the broker is temporarily unavailable.
+pub fn is_retryable(error: &KafkaError) -> bool {
+    match error {
+        KafkaError::MessageProduction(code) => matches!(
+            code,
+            RDKafkaErrorCode::QueueFull
+                | RDKafkaErrorCode::MessageTimedOut
+                | RDKafkaErrorCode::BrokerTransportFailure
+                | RDKafkaErrorCode::AllBrokersDown
+                | RDKafkaErrorCode::LeaderNotAvailable
+                | RDKafkaErrorCode::NotLeaderForPartition
+                | RDKafkaErrorCode::RequestTimedOut
+                | RDKafkaErrorCode::BrokerNotAvailable
+                | RDKafkaErrorCode::NetworkException
+                | RDKafkaErrorCode::NotEnoughReplicas
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn generate_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+        }
+    }
+
+    // a mock producer that fails with the given errors, in order, before succeeding
+    struct MockProducer {
+        failures: Vec<KafkaError>,
+        attempts: AtomicU32,
+    }
+
+    impl MockProducer {
+        fn new(failures: Vec<KafkaError>) -> Self {
+            MockProducer {
+                failures,
+                attempts: AtomicU32::new(0),
+            }
+        }
+
+        async fn produce(&self) -> Result<(), KafkaError> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) as usize;
+            match self.failures.get(attempt) {
+                Some(e) => Err(e.clone()),
+                None => Ok(()),
+            }
+        }
+
+        fn attempts(&self) -> u32 {
+            self.attempts.load(Ordering::SeqCst)
+        }
+    }
+
+    fn queue_full() -> KafkaError {
+        KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull)
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(350),
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+        assert_eq!(policy.backoff(40), Duration::from_millis(350));
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_failures_then_succeeds() {
+        // prepare
+        let producer = MockProducer::new(vec![
+            queue_full(),
+            KafkaError::MessageProduction(RDKafkaErrorCode::BrokerTransportFailure),
+        ]);
+
+        // act
+        let result = generate_policy(3).run(|| producer.produce()).await;
+
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(producer.attempts(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        // prepare
+        let producer = MockProducer::new(vec![queue_full(), queue_full(), queue_full()]);
+
+        // act
+        let result = generate_policy(2).run(|| producer.produce()).await;
+
+        // assert
+        assert_eq!(result, Err(queue_full()));
+        assert_eq!(producer.attempts(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_permanent_failures() {
+        // prepare
+        let permanent = KafkaError::MessageProduction(RDKafkaErrorCode::UnknownTopicOrPartition);
+        let producer = MockProducer::new(vec![permanent.clone()]);
+
+        // act
+        let result = generate_policy(3).run(|| producer.produce()).await;
+
+        // assert
+        assert_eq!(result, Err(permanent));
+        assert_eq!(producer.attempts(), 1);
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(&queue_full()));
+        assert!(is_retryable(&KafkaError::MessageProduction(
+            RDKafkaErrorCode::NotLeaderForPartition
+        )));
+        assert!(!is_retryable(&KafkaError::MessageProduction(
+            RDKafkaErrorCode::MessageSizeTooLarge
+        )));
+        assert!(!is_retryable(&KafkaError::Canceled));
+    }
+}
@@ -0,0 +1,222 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+
+/// The error returned when a message cannot be encoded or decoded.
+pub type SerializationError = Box<dyn Error + Send + Sync>;
+
+/// Encodes values into the raw bytes that are sent across the event bus.
+///
+/// Producers route all encoding through an `EventSerializer`, so that the wire format can be
+/// changed without touching the code that broadcasts events.
+pub trait EventSerializer: Send + Sync {
+    /// Encodes the given value into bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `value`: The value to encode, typically an `Event`.
+    fn serialize<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, SerializationError>;
+}
+
+/// Decodes the raw bytes received from the event bus into values.
+///
+/// Listeners route all decoding through an `EventDeserializer`, which must understand the format
+/// produced by the `EventSerializer` used on the producer side.
+pub trait EventDeserializer: Send + Sync {
+    /// Decodes the given bytes into a value of type `T`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: The raw message payload.
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, SerializationError>;
+}
+
+/// The default codec, which encodes messages as JSON.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonCodec;
+
+impl EventSerializer for JsonCodec {
+    fn serialize<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, SerializationError> {
+        serde_json::to_vec(value).map_err(|e| Box::new(e) as SerializationError)
+    }
+}
+
+impl EventDeserializer for JsonCodec {
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, SerializationError> {
+        serde_json::from_slice(bytes).map_err(|e| Box::new(e) as SerializationError)
+    }
+}
+
+/// A codec for listeners that inspect messages without knowing their type, e.g. for monitoring.
+///
+/// Each message is decoded as untyped JSON, or as a JSON string of its bytes if it is not valid
+/// JSON, so that any message can be decoded into a `serde_json::Value`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RawJsonCodec;
+
+impl EventDeserializer for RawJsonCodec {
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, SerializationError> {
+        let value = serde_json::from_slice(bytes)
+            .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(bytes).into_owned()));
+        serde_json::from_value(value).map_err(|e| Box::new(e) as SerializationError)
+    }
+}
+
+// the wire names of the `Event` fields that are renamed by the camelCase profile
+const CAMEL_CASE_EVENT_FIELDS: [(&str, &str); 4] = [
+    ("event_id", "eventId"),
+    ("schema_version", "schemaVersion"),
+    ("event_type", "type"),
+    ("correlation_id", "correlationId"),
+];
+
+/// A codec encoding messages as JSON whose `Event` fields are named for camelCase consumers, e.g.
+/// `type` and `correlationId` rather than `event_type` and `correlation_id`.
+///
+/// Only the top level fields of a message are renamed, so an `Event` keeps its Rust field names
+/// and the fields of its payload are left as they are. Listeners using this codec also decode
+/// events with snake_case fields, so consumers can be switched before their producers.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CamelCaseJsonCodec;
+
+impl EventSerializer for CamelCaseJsonCodec {
+    fn serialize<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, SerializationError> {
+        let value = serde_json::to_value(value).map_err(|e| Box::new(e) as SerializationError)?;
+        let value = rename_fields(value, CAMEL_CASE_EVENT_FIELDS.iter().copied());
+        serde_json::to_vec(&value).map_err(|e| Box::new(e) as SerializationError)
+    }
+}
+
+impl EventDeserializer for CamelCaseJsonCodec {
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, SerializationError> {
+        let value = serde_json::from_slice(bytes).map_err(|e| Box::new(e) as SerializationError)?;
+        let value = rename_fields(
+            value,
+            CAMEL_CASE_EVENT_FIELDS.iter().map(|(field, wire)| (*wire, *field)),
+        );
+        serde_json::from_value(value).map_err(|e| Box::new(e) as SerializationError)
+    }
+}
+
+// renames the top level fields of a json object, leaving any other value unchanged
+fn rename_fields<'a>(value: serde_json::Value, renames: impl Iterator<Item = (&'a str, &'a str)>) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(mut fields) => {
+            for (from, to) in renames {
+                if let Some(field) = fields.remove(from) {
+                    fields.insert(to.to_string(), field);
+                }
+            }
+            serde_json::Value::Object(fields)
+        }
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Event;
+
+    #[test]
+    fn test_json_codec_round_trip() {
+        // prepare
+        let sut = JsonCodec;
+        let event = Event::new("test_event".to_string(), 1u32, "test".to_string(), None, None);
+
+        // act
+        let bytes = sut.serialize(&event).unwrap();
+        let decoded: Event<u32> = sut.deserialize(&bytes).unwrap();
+
+        // assert
+        assert_eq!(decoded.event_id, event.event_id);
+        assert_eq!(decoded.payload, 1);
+    }
+
+    #[derive(Serialize, serde::Deserialize, Debug, PartialEq, Clone)]
+    struct OrderPayload {
+        item_id: u32,
+    }
+
+    fn generate_event() -> Event<OrderPayload> {
+        Event::new(
+            "order_placed".to_string(),
+            OrderPayload { item_id: 1 },
+            "test".to_string(),
+            Some("correlation".to_string()),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_json_codec_uses_snake_case_fields() {
+        // prepare
+        let event = generate_event();
+
+        // act
+        let bytes = JsonCodec.serialize(&event).unwrap();
+        let wire: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let decoded: Event<OrderPayload> = JsonCodec.deserialize(&bytes).unwrap();
+
+        // assert
+        assert_eq!(wire["event_type"], "order_placed");
+        assert_eq!(wire["correlation_id"], "correlation");
+        assert!(wire.get("type").is_none());
+        assert_eq!(decoded.event_type, event.event_type);
+        assert_eq!(decoded.correlation_id, event.correlation_id);
+    }
+
+    #[test]
+    fn test_camel_case_json_codec_round_trip() {
+        // prepare
+        let event = generate_event().with_schema_version(2);
+
+        // act
+        let bytes = CamelCaseJsonCodec.serialize(&event).unwrap();
+        let wire: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let decoded: Event<OrderPayload> = CamelCaseJsonCodec.deserialize(&bytes).unwrap();
+
+        // assert that the envelope is renamed, but not the payload
+        assert_eq!(wire["type"], "order_placed");
+        assert_eq!(wire["eventId"], event.event_id.as_str());
+        assert_eq!(wire["schemaVersion"], 2);
+        assert_eq!(wire["correlationId"], "correlation");
+        assert_eq!(wire["payload"]["item_id"], 1);
+        assert!(wire.get("event_type").is_none());
+        assert_eq!(decoded.event_id, event.event_id);
+        assert_eq!(decoded.schema_version, 2);
+        assert_eq!(decoded.event_type, event.event_type);
+        assert_eq!(decoded.correlation_id, event.correlation_id);
+        assert_eq!(decoded.payload, event.payload);
+    }
+
+    #[test]
+    fn test_camel_case_json_codec_decodes_snake_case_events() {
+        let bytes = JsonCodec.serialize(&generate_event()).unwrap();
+
+        let result = CamelCaseJsonCodec.deserialize::<Event<OrderPayload>>(&bytes);
+
+        assert!(result.is_ok_and(|event| event.event_type == "order_placed"));
+    }
+
+    #[test]
+    fn test_json_codec_rejects_invalid_bytes() {
+        let result = JsonCodec.deserialize::<Event<u32>>(b"not json");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_raw_json_codec_decodes_any_json() {
+        let result: serde_json::Value = RawJsonCodec.deserialize(br#"{"unknown":[1,"two",null]}"#).unwrap();
+
+        assert_eq!(result, serde_json::json!({"unknown": [1, "two", null]}));
+    }
+
+    #[test]
+    fn test_raw_json_codec_decodes_invalid_json_as_string() {
+        let result: serde_json::Value = RawJsonCodec.deserialize(b"not json").unwrap();
+
+        assert_eq!(result, serde_json::Value::String("not json".to_string()));
+    }
+}
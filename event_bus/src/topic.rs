@@ -1 +1,231 @@
+use log::error;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
 pub const ORDER_PLACED: &str = "ORDER_PLACED";
+/// `ORDER_PLACED` events that failed to be applied, republished to be retried after a delay.
+pub const ORDER_PLACED_RETRY: &str = "ORDER_PLACED_RETRY";
+/// `ORDER_PLACED` events that still failed to be applied once their retries were exhausted.
+pub const ORDER_PLACED_DEAD_LETTER: &str = "ORDER_PLACED_DEAD_LETTER";
+pub const ORDER_CANCELLED: &str = "ORDER_CANCELLED";
+pub const BACKORDER_PLACED: &str = "BACKORDER_PLACED";
+pub const CATALOG_ITEM_CHANGED: &str = "CATALOG_ITEM_CHANGED";
+
+/// Every topic above, which are the logical names a `TopicConfig` maps onto concrete topics.
+pub const ALL_TOPICS: [&str; 6] = [
+    ORDER_PLACED,
+    ORDER_PLACED_RETRY,
+    ORDER_PLACED_DEAD_LETTER,
+    ORDER_CANCELLED,
+    BACKORDER_PLACED,
+    CATALOG_ITEM_CHANGED,
+];
+
+// the prefix of the environment variable overriding the concrete topic of a logical name, e.g.
+// `TOPIC_ORDER_PLACED=staging.order-placed`
+const TOPIC_VAR_PREFIX: &str = "TOPIC_";
+
+// the longest topic name accepted by kafka
+const MAX_TOPIC_NAME_LENGTH: usize = 249;
+
+/// An error describing why a topic name is not accepted by Kafka.
+///
+/// # Variants
+///
+/// * `Empty`: The topic name is empty.
+/// * `TooLong`: The topic name is longer than 249 characters.
+/// * `IllegalCharacter`: The topic name contains a character other than ASCII alphanumerics, `.`, `_` and `-`.
+/// * `Reserved`: The topic name is `.` or `..`.
+#[derive(Debug, PartialEq)]
+pub enum TopicError {
+    Empty,
+    TooLong { length: usize },
+    IllegalCharacter(char),
+    Reserved,
+}
+
+impl Display for TopicError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TopicError::Empty => write!(f, "The topic name is empty"),
+            TopicError::TooLong { length } => write!(
+                f,
+                "The topic name is {} characters, which exceeds the limit of {}",
+                length, MAX_TOPIC_NAME_LENGTH
+            ),
+            TopicError::IllegalCharacter(c) => write!(f, "The topic name contains the illegal character {:?}", c),
+            TopicError::Reserved => write!(f, "The topic name cannot be '.' or '..'"),
+        }
+    }
+}
+
+impl Error for TopicError {}
+
+/// `TopicConfig` maps the logical name of each topic, e.g. `ORDER_PLACED`, onto the concrete topic
+/// events are produced to and consumed from, so the same binary can run against differently named
+/// topics, e.g. in staging and production.
+///
+/// Logical names without a mapping resolve to themselves, so an empty config uses the topics as named
+/// by the constants of this module.
+///
+/// Fields:
+/// - `topics`: The concrete topic of each mapped logical name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TopicConfig {
+    topics: HashMap<String, String>,
+}
+
+impl TopicConfig {
+    pub fn new() -> Self {
+        TopicConfig::default()
+    }
+
+    /// Reads the concrete topic of each of `ALL_TOPICS` from its `TOPIC_<name>` environment variable,
+    /// e.g. `TOPIC_ORDER_PLACED`, leaving logical names whose variable is unset unmapped.
+    pub fn from_env() -> Self {
+        TopicConfig::from_lookup(|var| std::env::var(var).ok())
+    }
+
+    // reads the config through the given lookup of variables, so it can be tested without the environment.
+    // Invalid topics are logged and left unmapped, so the service still runs against the default topic
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let mut config = TopicConfig::new();
+        for logical_name in ALL_TOPICS {
+            let var = format!("{}{}", TOPIC_VAR_PREFIX, logical_name);
+            if let Some(topic_name) = lookup(&var).filter(|value| !value.trim().is_empty()) {
+                if let Err(e) = config.set(logical_name, &topic_name) {
+                    error!("Invalid {}: {}, {}, defaulting to {}", var, topic_name, e, logical_name);
+                }
+            }
+        }
+        config
+    }
+
+    /// Maps a logical name onto a concrete topic, replacing any previous mapping.
+    ///
+    /// # Arguments
+    ///
+    /// * `logical_name`: The logical name of the topic, e.g. `ORDER_PLACED`.
+    /// * `topic_name`: The concrete topic, which must be a valid topic name.
+    pub fn set(&mut self, logical_name: &str, topic_name: &str) -> Result<(), TopicError> {
+        validate_topic_name(topic_name)?;
+        self.topics.insert(logical_name.to_string(), topic_name.to_string());
+        Ok(())
+    }
+
+    /// Resolves a logical name into the concrete topic events are produced to and consumed from,
+    /// which is the logical name itself unless it is mapped.
+    ///
+    /// # Arguments
+    ///
+    /// * `logical_name`: The logical name of the topic, e.g. `topic::ORDER_PLACED`.
+    pub fn resolve<'a>(&'a self, logical_name: &'a str) -> &'a str {
+        self.topics.get(logical_name).map_or(logical_name, String::as_str)
+    }
+}
+
+/// Validates a topic name against Kafka's naming rules.
+///
+/// A topic name must be between 1 and 249 characters, may only contain ASCII alphanumerics,
+/// `.`, `_` and `-`, and cannot be `.` or `..`.
+///
+/// # Arguments
+///
+/// * `name`: The topic name to validate.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the name is valid, or the `TopicError` describing the first violated rule.
+pub fn validate_topic_name(name: &str) -> Result<(), TopicError> {
+    if name.is_empty() {
+        return Err(TopicError::Empty);
+    }
+
+    if name.len() > MAX_TOPIC_NAME_LENGTH {
+        return Err(TopicError::TooLong { length: name.len() });
+    }
+
+    if let Some(c) = name.chars().find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '.' | '_' | '-')) {
+        return Err(TopicError::IllegalCharacter(c));
+    }
+
+    if name == "." || name == ".." {
+        return Err(TopicError::Reserved);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_topic_name_valid() {
+        assert_eq!(validate_topic_name(ORDER_PLACED), Ok(()));
+        assert_eq!(validate_topic_name(CATALOG_ITEM_CHANGED), Ok(()));
+        assert_eq!(validate_topic_name("orders.v2-replay_1"), Ok(()));
+        assert_eq!(validate_topic_name(&"a".repeat(249)), Ok(()));
+    }
+
+    #[test]
+    fn test_topic_config_resolves_mapped_topics() {
+        let mut sut = TopicConfig::new();
+
+        sut.set(ORDER_PLACED, "staging.order-placed").unwrap();
+
+        assert_eq!(sut.resolve(ORDER_PLACED), "staging.order-placed");
+        assert_eq!(sut.resolve(ORDER_CANCELLED), ORDER_CANCELLED);
+        assert_eq!(
+            sut.set(ORDER_CANCELLED, "order cancelled"),
+            Err(TopicError::IllegalCharacter(' '))
+        );
+    }
+
+    #[test]
+    fn test_topic_config_from_lookup() {
+        let sut = TopicConfig::from_lookup(|var| match var {
+            "TOPIC_ORDER_PLACED" => Some("prod.order-placed".to_string()),
+            "TOPIC_BACKORDER_PLACED" => Some("invalid/topic".to_string()),
+            "TOPIC_ORDER_CANCELLED" => Some(" ".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(sut.resolve(ORDER_PLACED), "prod.order-placed");
+        assert_eq!(sut.resolve(BACKORDER_PLACED), BACKORDER_PLACED);
+        assert_eq!(sut.resolve(ORDER_CANCELLED), ORDER_CANCELLED);
+    }
+
+    #[test]
+    fn test_validate_topic_name_empty() {
+        assert_eq!(validate_topic_name(""), Err(TopicError::Empty));
+    }
+
+    #[test]
+    fn test_validate_topic_name_too_long() {
+        assert_eq!(
+            validate_topic_name(&"a".repeat(250)),
+            Err(TopicError::TooLong { length: 250 })
+        );
+    }
+
+    #[test]
+    fn test_validate_topic_name_illegal_characters() {
+        assert_eq!(
+            validate_topic_name("order placed"),
+            Err(TopicError::IllegalCharacter(' '))
+        );
+        assert_eq!(
+            validate_topic_name("orders/placed"),
+            Err(TopicError::IllegalCharacter('/'))
+        );
+        assert_eq!(validate_topic_name("ordérs"), Err(TopicError::IllegalCharacter('é')));
+    }
+
+    #[test]
+    fn test_validate_topic_name_reserved() {
+        assert_eq!(validate_topic_name("."), Err(TopicError::Reserved));
+        assert_eq!(validate_topic_name(".."), Err(TopicError::Reserved));
+    }
+}
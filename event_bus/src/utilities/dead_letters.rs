@@ -0,0 +1,222 @@
+use crate::event::{RETRY_ATTEMPT_METADATA_KEY, RETRY_AT_METADATA_KEY};
+use crate::{EventListener, EventProducer};
+use log::{error, info, warn};
+use serde_json::Value;
+use std::error::Error;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+/// The consumer group used to replay dead-lettered messages, separate from the group of any service
+/// so that replaying does not move the offsets of the services' own listeners.
+pub const REPLAY_GROUP_ID: &str = "dead-letter-replay";
+
+/// Appended to the `event_id` of a replayed event, see `replay_dead_letters`.
+pub const REPLAYED_EVENT_ID_SUFFIX: &str = "-replay";
+
+// how long the replay waits for the next dead-lettered message before deciding the topic is drained
+const REPLAY_IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Moves dead-lettered messages back to the topic they were dead-lettered from, e.g. once the bug
+/// that stopped them from being processed has been fixed.
+///
+/// The dead letter topic is consumed from its earliest uncommitted message by the
+/// `REPLAY_GROUP_ID` consumer group, and each message is republished to `to_topic` with its
+/// original key, so the events of an item stay in order, until `max` messages have been replayed or
+/// no message arrives for 5 seconds. The consumer never commits on its own: each message is
+/// committed once it has been republished, so messages received past `max` are left for the next
+/// replay rather than lost.
+///
+/// A consumer deduplicating by event id has already seen the id of a dead-lettered event, so the
+/// `event_id` of a replayed event is suffixed with `REPLAYED_EVENT_ID_SUFFIX`, and its retry count
+/// and retry time are cleared so it is retried as often as a new event. The new id is derived from
+/// the old one, so an event republished again because its commit failed is still deduplicated.
+/// Messages that are not event envelopes are republished unchanged, and messages that are not JSON
+/// at all are logged and skipped by the listener.
+///
+/// # Arguments
+///
+/// * `event_bus`: The event bus to consume the dead letter topic from and republish to.
+/// * `from_topic`: The dead letter topic, e.g. `topic::ORDER_PLACED_DEAD_LETTER`.
+/// * `to_topic`: The topic the messages were dead-lettered from, e.g. `topic::ORDER_PLACED`.
+/// * `max`: The largest number of messages replayed.
+///
+/// # Returns
+///
+/// Returns the number of messages replayed, or the error of the listener that could not be created,
+/// of the first message that could not be republished or of the first commit that failed.
+pub async fn replay_dead_letters<E>(
+    event_bus: &E,
+    from_topic: &str,
+    to_topic: &str,
+    max: usize,
+) -> Result<usize, Box<dyn Error>>
+where
+    E: EventListener + EventProducer + Sync,
+{
+    let listener = event_bus.create_manual_commit_listener::<Value>(REPLAY_GROUP_ID, &[from_topic])?;
+    let mut receiver = listener.get_received_receiver();
+    info!(
+        "Replaying up to {} dead-lettered messages from {} to {}",
+        max, from_topic, to_topic
+    );
+
+    let mut replayed = 0;
+    let result = loop {
+        if replayed == max {
+            break Ok(replayed);
+        }
+        let received = match tokio::time::timeout(REPLAY_IDLE_TIMEOUT, receiver.recv()).await {
+            Ok(Ok(received)) => received,
+            Ok(Err(RecvError::Lagged(skipped))) => {
+                // the skipped messages are not committed, so they are received by the next replay
+                warn!("Skipped {} dead-lettered messages whilst replaying", skipped);
+                continue;
+            }
+            Ok(Err(RecvError::Closed)) | Err(_) => break Ok(replayed),
+        };
+        let message = prepare_replay(received.payload);
+        let event_id = message.get("event_id").and_then(Value::as_str).unwrap_or("unknown").to_string();
+        if let Err(e) = event_bus.broadcast_event(message, to_topic, received.key).await {
+            error!(
+                "Failed to replay dead-lettered event: {} to {}, {} replayed: {:?}",
+                event_id, to_topic, replayed, e
+            );
+            break Err(e);
+        }
+        if let Some(position) = &received.position {
+            if let Err(e) = listener.commit_message(position) {
+                error!(
+                    "Failed to commit replayed event: {} of {}, {} replayed: {:?}",
+                    event_id, from_topic, replayed, e
+                );
+                break Err(Box::new(e) as Box<dyn Error>);
+            }
+        }
+        info!("Replayed dead-lettered event: {} to {}", event_id, to_topic);
+        replayed += 1;
+    };
+
+    listener.unsubscribe();
+    info!(
+        "Replayed {} dead-lettered messages from {} to {}",
+        replayed, from_topic, to_topic
+    );
+    result
+}
+
+// gives a dead-lettered event envelope a new id derived from its own and clears its retry count and time,
+// leaving any other message unchanged
+fn prepare_replay(mut message: Value) -> Value {
+    let envelope = match message.as_object_mut() {
+        Some(envelope) => envelope,
+        None => return message,
+    };
+    if let Some(event_id) = envelope.get("event_id").and_then(Value::as_str) {
+        let replayed_id = format!("{}{}", event_id, REPLAYED_EVENT_ID_SUFFIX);
+        envelope.insert("event_id".to_string(), Value::String(replayed_id));
+    }
+    if let Some(metadata) = envelope.get_mut("metadata").and_then(Value::as_object_mut) {
+        metadata.remove(RETRY_ATTEMPT_METADATA_KEY);
+        metadata.remove(RETRY_AT_METADATA_KEY);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Event;
+    use crate::topic;
+    use crate::MockEventBus;
+
+    #[tokio::test]
+    async fn test_replay_stops_at_max() {
+        // prepare
+        let sut = MockEventBus::new();
+
+        // act, publishing once the replay is listening
+        let (replayed, _) = tokio::join!(
+            replay_dead_letters(&sut, topic::ORDER_PLACED_DEAD_LETTER, topic::ORDER_PLACED, 2),
+            async {
+                for quantity in 1..=3 {
+                    sut.publish(
+                        topic::ORDER_PLACED_DEAD_LETTER,
+                        &serde_json::json!({ "quantity": quantity }),
+                    );
+                }
+            }
+        );
+
+        // assert
+        assert_eq!(replayed.unwrap(), 2);
+        let produced = sut.produced();
+        assert_eq!(produced.len(), 2);
+        assert!(produced.iter().all(|(topic_name, _, _)| topic_name == topic::ORDER_PLACED));
+        assert_eq!(produced[1].2, br#"{"quantity":2}"#.to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_replay_keeps_the_key() {
+        // prepare
+        let sut = MockEventBus::new();
+
+        // act
+        let (replayed, _) = tokio::join!(
+            replay_dead_letters(&sut, topic::ORDER_PLACED_DEAD_LETTER, topic::ORDER_PLACED, 2),
+            async {
+                sut.publish_with_key(topic::ORDER_PLACED_DEAD_LETTER, "7", &serde_json::json!(1));
+                sut.publish(topic::ORDER_PLACED_DEAD_LETTER, &serde_json::json!(2));
+            }
+        );
+
+        // assert
+        assert_eq!(replayed.unwrap(), 2);
+        let keys: Vec<String> = sut.produced().into_iter().map(|(_, key, _)| key).collect();
+        assert_eq!(keys, ["7", ""]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_fails_when_republishing_fails() {
+        // prepare
+        let mut sut = MockEventBus::new();
+        sut.set_produces_error(true);
+
+        // act
+        let (replayed, _) = tokio::join!(
+            replay_dead_letters(&sut, topic::ORDER_PLACED_DEAD_LETTER, topic::ORDER_PLACED, 2),
+            async { sut.publish(topic::ORDER_PLACED_DEAD_LETTER, &serde_json::json!({ "quantity": 1 })) }
+        );
+
+        // assert
+        assert!(replayed.is_err());
+    }
+
+    #[test]
+    fn test_prepare_replay_renames_event_and_clears_retry_attempt() {
+        // prepare
+        let mut event = Event::new("order_placed".to_string(), 5, "Order".to_string(), None, None)
+            .with_retry_attempt(2)
+            .with_retry_at(std::time::SystemTime::now());
+        event.metadata.as_mut().unwrap().insert("tenant".to_string(), "uk".to_string());
+        let event_id = event.event_id.clone();
+
+        // act
+        let result: Event<u32> = serde_json::from_value(prepare_replay(serde_json::to_value(event).unwrap())).unwrap();
+
+        // assert
+        assert_eq!(result.event_id, format!("{}{}", event_id, REPLAYED_EVENT_ID_SUFFIX));
+        assert_eq!(result.retry_attempt(), 0);
+        assert_eq!(result.retry_at(), None);
+        assert_eq!(result.metadata.unwrap().get("tenant").map(String::as_str), Some("uk"));
+        assert_eq!(result.payload, 5);
+    }
+
+    #[test]
+    fn test_prepare_replay_leaves_other_messages_unchanged() {
+        assert_eq!(prepare_replay(serde_json::json!([1, 2])), serde_json::json!([1, 2]));
+        assert_eq!(
+            prepare_replay(serde_json::json!({ "quantity": 1 })),
+            serde_json::json!({ "quantity": 1 })
+        );
+    }
+}
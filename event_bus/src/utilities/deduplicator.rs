@@ -0,0 +1,94 @@
+use std::collections::{HashSet, VecDeque};
+
+/// Tracks the ids of recently processed events so that redelivered messages can be skipped.
+///
+/// Kafka guarantees at-least-once delivery, so a consumer may receive the same event more than
+/// once. The deduplicator remembers the last `capacity` event ids it has seen; once full, the
+/// oldest id is forgotten to keep memory usage bounded.
+///
+/// # Fields
+///
+/// * `capacity`: The maximum number of event ids retained.
+/// * `seen`: The set of retained event ids, used for fast lookups.
+/// * `order`: The retained event ids in insertion order, used to evict the oldest id.
+pub struct EventDeduplicator {
+    capacity: usize,
+    seen: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl EventDeduplicator {
+    /// Creates a new `EventDeduplicator` that remembers up to `capacity` event ids.
+    pub fn new(capacity: usize) -> Self {
+        EventDeduplicator {
+            capacity,
+            seen: HashSet::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Records the given event id and reports whether it has already been seen.
+    ///
+    /// Empty ids are never treated as duplicates, as events produced before `event_id` was
+    /// introduced deserialize with an empty id.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_id`: The id of the event being processed.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the event id was seen recently and the event should be skipped.
+    pub fn is_duplicate(&mut self, event_id: &str) -> bool {
+        if event_id.is_empty() || self.capacity == 0 {
+            return false;
+        }
+
+        if self.seen.contains(event_id) {
+            return true;
+        }
+
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+
+        self.seen.insert(event_id.to_string());
+        self.order.push_back(event_id.to_string());
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_duplicate() {
+        let mut sut = EventDeduplicator::new(10);
+
+        assert!(!sut.is_duplicate("event-1"));
+        assert!(sut.is_duplicate("event-1"));
+        assert!(!sut.is_duplicate("event-2"));
+    }
+
+    #[test]
+    fn test_is_duplicate_evicts_oldest() {
+        let mut sut = EventDeduplicator::new(2);
+        sut.is_duplicate("event-1");
+        sut.is_duplicate("event-2");
+        sut.is_duplicate("event-3");
+
+        assert!(!sut.is_duplicate("event-1"));
+        assert!(sut.is_duplicate("event-3"));
+    }
+
+    #[test]
+    fn test_is_duplicate_ignores_empty_ids() {
+        let mut sut = EventDeduplicator::new(10);
+
+        assert!(!sut.is_duplicate(""));
+        assert!(!sut.is_duplicate(""));
+    }
+}
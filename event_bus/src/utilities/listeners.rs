@@ -1,9 +1,324 @@
-use log::error;
-use rdkafka::consumer::StreamConsumer;
-use rdkafka::message::Message;
+use crate::message_key::MessageKey;
+use crate::serialization::{EventDeserializer, JsonCodec, RawJsonCodec};
+use log::{error, info, warn};
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::message::{Headers, Message};
+use rdkafka::{Offset, TopicPartitionList};
 use serde::de::DeserializeOwned;
-use serde_json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::TryRecvError;
+use tokio::task::JoinHandle;
+
+// how long a seek waits for the consumer to reposition each partition
+const SEEK_TIMEOUT: Duration = Duration::from_secs(10);
+// the variables `BatchCommitConfig::from_env` reads its batch size and interval from
+const COMMIT_BATCH_SIZE_VAR: &str = "COMMIT_BATCH_SIZE";
+const COMMIT_BATCH_INTERVAL_MS_VAR: &str = "COMMIT_BATCH_INTERVAL_MS";
+
+// the consumer currently used by a listener's task, replaced whenever a supervised task restarts
+type ConsumerHandle = Arc<Mutex<Option<Arc<StreamConsumer>>>>;
+
+/// The health of the task consuming messages for a `KafkaListener`.
+///
+/// # Variants
+///
+/// * `Running`: The consumer task is running.
+/// * `Restarting`: The consumer task ended and is being restarted, `attempt` counts the restarts so far.
+/// * `Failed`: The consumer task ended and the restart budget is exhausted, so no more messages will be received.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListenerHealth {
+    Running,
+    Restarting { attempt: u32 },
+    Failed,
+}
+
+/// Controls how a supervised `KafkaListener` restarts its consumer task when it ends.
+///
+/// The delay before each restart doubles from `initial_backoff`, up to `max_backoff`.
+///
+/// # Fields
+///
+/// * `max_restarts`: The number of times the task is restarted before the listener is marked as failed.
+///   Defaults to `5`.
+/// * `initial_backoff`: The delay before the first restart. Defaults to 500 milliseconds.
+/// * `max_backoff`: The largest delay between restarts. Defaults to 30 seconds.
+/// * `stable_after`: How long the task must run before it ends for its restarts to be counted afresh, so
+///   that a long-lived listener is not failed by restarts spread over its lifetime. Defaults to 60 seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SupervisorConfig {
+    pub max_restarts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub stable_after: Duration,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        SupervisorConfig {
+            max_restarts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            stable_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl SupervisorConfig {
+    // the delay before the given restart attempt, starting at 1
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// Controls when a `KafkaListener` in batched-commit mode commits the offsets of the messages it
+/// has delivered to its channel.
+///
+/// Offsets are committed once `max_messages` messages have been delivered since the last commit,
+/// or `max_interval` after the first of them was delivered, whichever comes first. A message counts
+/// as delivered once it is sent across the channel, before any subscriber has handled it. Larger
+/// batches commit less often, at the cost of more messages being received again after a crash.
+///
+/// # Fields
+///
+/// * `max_messages`: The number of delivered messages that triggers a commit. Defaults to `100`.
+/// * `max_interval`: The longest a delivered message waits to be committed. Defaults to 5 seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchCommitConfig {
+    pub max_messages: usize,
+    pub max_interval: Duration,
+}
+
+impl Default for BatchCommitConfig {
+    fn default() -> Self {
+        BatchCommitConfig {
+            max_messages: 100,
+            max_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+impl BatchCommitConfig {
+    /// Reads the batched-commit mode of the event listeners from the `COMMIT_BATCH_SIZE` and
+    /// `COMMIT_BATCH_INTERVAL_MS` environment variables, keeping the default of each that is unset or
+    /// invalid.
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` when neither variable is set, in which case batched commits stay disabled.
+    pub fn from_env() -> Option<Self> {
+        BatchCommitConfig::from_lookup(|var| std::env::var(var).ok())
+    }
+
+    // reads the config through the given lookup of variables, so it can be tested without the environment
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let max_messages = lookup(COMMIT_BATCH_SIZE_VAR);
+        let max_interval_ms = lookup(COMMIT_BATCH_INTERVAL_MS_VAR);
+        if max_messages.is_none() && max_interval_ms.is_none() {
+            return None;
+        }
+        let mut config = BatchCommitConfig::default();
+        if let Some(max_messages) = max_messages.and_then(|value| value.parse().ok()).filter(|value| *value > 0) {
+            config.max_messages = max_messages;
+        }
+        if let Some(max_interval_ms) = max_interval_ms.and_then(|value| value.parse().ok()) {
+            config.max_interval = Duration::from_millis(max_interval_ms);
+        }
+        Some(config)
+    }
+}
+
+/// Where a listener starts reading a topic when its consumer group has no committed offset, e.g.
+/// the first time a new consumer group is used.
+///
+/// # Variants
+///
+/// * `Earliest`: Replays the topic from its oldest message. The default.
+/// * `Latest`: Skips the messages already in the topic, only receiving those produced afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OffsetReset {
+    #[default]
+    Earliest,
+    Latest,
+}
+
+impl OffsetReset {
+    /// The value of the consumer's `auto.offset.reset` setting.
+    pub fn as_config_value(&self) -> &'static str {
+        match self {
+            OffsetReset::Earliest => "earliest",
+            OffsetReset::Latest => "latest",
+        }
+    }
+}
+
+// tracks the messages delivered since the last commit to decide when the next commit is due
+struct CommitBatcher {
+    config: BatchCommitConfig,
+    pending: usize,
+    first_pending_at: Instant,
+}
+
+impl CommitBatcher {
+    fn new(config: BatchCommitConfig) -> Self {
+        CommitBatcher {
+            config,
+            pending: 0,
+            first_pending_at: Instant::now(),
+        }
+    }
+
+    // records a delivered message and returns whether a commit is due
+    fn record(&mut self, now: Instant) -> bool {
+        if self.pending == 0 {
+            self.first_pending_at = now;
+        }
+        self.pending += 1;
+        self.is_due(now)
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        self.pending > 0
+            && (self.pending >= self.config.max_messages
+                || now.saturating_duration_since(self.first_pending_at) >= self.config.max_interval)
+    }
+
+    // how long until the time window of the pending messages closes, or `None` if nothing is pending
+    fn time_until_due(&self, now: Instant) -> Option<Duration> {
+        if self.pending == 0 {
+            return None;
+        }
+        let elapsed = now.saturating_duration_since(self.first_pending_at);
+        Some(self.config.max_interval.saturating_sub(elapsed))
+    }
+
+    fn committed(&mut self) {
+        self.pending = 0;
+    }
+}
+
+/// An event received by a `KafkaListener`, along with the headers, key and position of the Kafka
+/// message it was decoded from.
+///
+/// # Fields
+///
+/// * `payload`: The decoded event.
+/// * `headers`: The headers of the message keyed by their name. Headers without a value or whose
+///   value is not UTF-8 are left out, and messages produced without headers, e.g. by an older
+///   producer, have none.
+/// * `key`: The key of the message, which is `MessageKey::none()` for a keyless message or a key
+///   that is not UTF-8.
+/// * `position`: Where the message is in its topic, used to commit it with `KafkaListener::commit_message`,
+///   or `None` for a message received by a mock listener.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedEvent<T> {
+    pub payload: T,
+    pub headers: HashMap<String, String>,
+    pub key: MessageKey,
+    pub position: Option<MessagePosition>,
+}
+
+/// The position of a message in a Kafka topic.
+///
+/// # Fields
+///
+/// * `topic`: The topic of the message.
+/// * `partition`: The partition of the topic the message is in.
+/// * `offset`: The offset of the message in its partition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessagePosition {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+}
+
+impl<T> ReceivedEvent<T> {
+    // a message received without a position, e.g. by a mock listener
+    fn unpositioned(payload: T, key: MessageKey) -> Self {
+        ReceivedEvent {
+            payload,
+            headers: HashMap::new(),
+            key,
+            position: None,
+        }
+    }
+
+    /// Retrieves the value of a header, or `None` if the message did not have the header.
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers.get(key).map(String::as_str)
+    }
+}
+
+// the broadcast channels a listener's task sends each decoded message across
+struct ListenerSenders<T> {
+    events: broadcast::Sender<T>,
+    received: broadcast::Sender<ReceivedEvent<T>>,
+}
+
+impl<T> Clone for ListenerSenders<T> {
+    fn clone(&self) -> Self {
+        ListenerSenders {
+            events: self.events.clone(),
+            received: self.received.clone(),
+        }
+    }
+}
+
+impl<T: Clone> ListenerSenders<T> {
+    fn new(buffer_size: usize) -> Self {
+        ListenerSenders {
+            events: broadcast::channel(buffer_size).0,
+            received: broadcast::channel(buffer_size).0,
+        }
+    }
+
+    // sends a decoded message to every receiver, returning whether there was any receiver to send it to
+    fn send(&self, received: ReceivedEvent<T>) -> bool {
+        let mut sent = false;
+        if self.received.receiver_count() > 0 {
+            sent |= self.received.send(received.clone()).is_ok();
+        }
+        sent |= self.events.send(received.payload).is_ok();
+        sent
+    }
+}
+
+// reads the headers of a message, treating a message without headers as having none and skipping
+// any header without a value or whose value is not UTF-8, rather than failing to receive the message
+fn read_headers<M: Message>(message: &M) -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+    let message_headers = match message.headers() {
+        Some(message_headers) => message_headers,
+        None => return headers,
+    };
+    for header in (0..message_headers.count()).filter_map(|index| message_headers.try_get(index)) {
+        match header.value.map(std::str::from_utf8) {
+            Some(Ok(value)) => {
+                headers.insert(header.key.to_string(), value.to_string());
+            }
+            Some(Err(_)) => warn!("Skipping the header: {} as its value is not UTF-8", header.key),
+            None => {}
+        }
+    }
+    headers
+}
+
+// reads the key of a message, treating a key that is not UTF-8 as no key, as every key produced by
+// the event bus is a string
+fn read_key<M: Message>(message: &M) -> MessageKey {
+    match message.key().map(std::str::from_utf8) {
+        Some(Ok(key)) => MessageKey::from(key),
+        Some(Err(_)) => {
+            warn!("Ignoring the key of a message as it is not UTF-8");
+            MessageKey::none()
+        }
+        None => MessageKey::none(),
+    }
+}
 
 /// A Kafka listener that asynchronously listens to messages from a Kafka topic and broadcasts them.
 ///
@@ -13,26 +328,32 @@ use tokio::sync::broadcast;
 ///
 /// # Type Parameters
 ///
-/// * `T`: The type of the message payload. Must be deserializable from JSON, cloneable, and safe to send across threads.
+/// * `T`: The type of the message payload. Must be deserializable, cloneable, and safe to send across threads.
 ///
 /// # Fields
 ///
-/// * `tx`: The broadcast channel sender used to send messages to receivers.
+/// * `senders`: The broadcast channel senders used to send messages, and messages with their
+///   headers, to receivers.
+/// * `health`: The current `ListenerHealth` of the consumer task.
+/// * `consumer`: A handle to the Kafka consumer used by the task, retained so that it can be repositioned.
+/// * `mock_buffer`: A receiver buffering the messages of a mock listener until they are drained, see `drain`.
+/// * `topics`: The topics the listener is subscribed to, see `topics`.
 pub struct KafkaListener<T>
 where
     T: DeserializeOwned + Send + 'static,
 {
-    tx: broadcast::Sender<T>,
+    senders: ListenerSenders<T>,
+    health: Arc<Mutex<ListenerHealth>>,
+    consumer: ConsumerHandle,
+    mock_buffer: Option<Mutex<broadcast::Receiver<ReceivedEvent<T>>>>,
+    topics: Vec<String>,
 }
 
 impl<T> KafkaListener<T>
 where
     T: DeserializeOwned + Send + 'static + Clone,
 {
-    /// Creates a new `KafkaListener`.
-    ///
-    /// Initializes a Tokio broadcast channel and spawns an asynchronous task that listens to messages from a Kafka topic.
-    /// Each message is deserialized into type `T` and sent across the broadcast channel to all subscribed receivers.
+    /// Creates a new `KafkaListener` that decodes messages from JSON.
     ///
     /// # Arguments
     ///
@@ -42,42 +363,276 @@ where
     /// # Returns
     ///
     /// Returns a new instance of `KafkaListener<T>`.
+    pub fn new(consumer: StreamConsumer, buffer_size: usize) -> Self {
+        KafkaListener::with_deserializer(consumer, buffer_size, JsonCodec)
+    }
+
+    /// Creates a new `KafkaListener` that decodes messages using the given deserializer.
     ///
-    /// # Panics
+    /// Initializes a Tokio broadcast channel and spawns an asynchronous task that listens to messages from a
+    /// Kafka topic. Each message is decoded into type `T` by the `deserializer` and sent across the broadcast
+    /// channel to all subscribed receivers. A message that cannot be decoded is logged and skipped, so it does
+    /// not stop the listener.
     ///
-    /// Panics if there is a JSON parsing error for the Kafka messages, or if the broadcast channel's sender fails.
-    pub fn new(consumer: StreamConsumer, buffer_size: usize) -> Self {
-        let (tx, _) = broadcast::channel::<T>(buffer_size);
+    /// # Arguments
+    ///
+    /// * `consumer`: The Kafka `StreamConsumer` to listen for messages.
+    /// * `buffer_size`: The size of the broadcast channel buffer.
+    /// * `deserializer`: The `EventDeserializer` used to decode each message payload.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new instance of `KafkaListener<T>`.
+    pub fn with_deserializer<D>(consumer: StreamConsumer, buffer_size: usize, deserializer: D) -> Self
+    where
+        D: EventDeserializer + 'static,
+    {
+        let senders = ListenerSenders::new(buffer_size);
+        let health = Arc::new(Mutex::new(ListenerHealth::Running));
+
+        let topics = subscribed_topics(&consumer);
+        let consumer = Arc::new(consumer);
 
-        // safe to clone as channel is retained, only handler is different
-        let tx_clone = tx.clone();
+        // the task is not supervised, so the listener has failed as soon as it ends
+        let handle = consume(consumer.clone(), senders.clone(), deserializer, None);
+        let task_health = health.clone();
         tokio::spawn(async move {
-            loop {
-                match consumer.recv().await {
-                    Ok(borrowed_message) => {
-                        if let Some(payload) = borrowed_message.payload() {
-                            match serde_json::from_slice::<T>(payload) {
-                                Ok(parsed_message) => {
-                                    if tx_clone.send(parsed_message).is_err() {
-                                        error!("Could not send message across the broadcast channel");
-                                        break;
-                                    }
-                                }
-                                Err(e) => {
-                                    error!("JSON parsing error: {:?}", e);
-                                    panic!("Could not parse the kafka message");
-                                }
-                            }
-                        }
-                    }
+            let _ = handle.await;
+            error!("The listener task ended and is not supervised");
+            *task_health.lock().unwrap() = ListenerHealth::Failed;
+        });
+
+        KafkaListener {
+            senders,
+            health,
+            consumer: Arc::new(Mutex::new(Some(consumer))),
+            mock_buffer: None,
+            topics,
+        }
+    }
+
+    /// Creates a new supervised `KafkaListener`.
+    ///
+    /// Behaves like `with_deserializer`, except that whenever the consumer task ends, e.g. due to a
+    /// fatal consumer error, a new consumer is created and the task is restarted with backoff. Once
+    /// the restart budget in `config` is exhausted the listener's health becomes `ListenerHealth::Failed`.
+    ///
+    /// # Arguments
+    ///
+    /// * `create_consumer`: Creates and subscribes a new Kafka `StreamConsumer`, called on start and on every restart.
+    /// * `buffer_size`: The size of the broadcast channel buffer.
+    /// * `deserializer`: The `EventDeserializer` used to decode each message payload.
+    /// * `config`: The `SupervisorConfig` controlling restarts.
+    /// * `batch_commit`: When set, offsets are committed in batches as described by `BatchCommitConfig`,
+    ///   in which case the consumers must be created with `enable.auto.commit` set to `false`.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new instance of `KafkaListener<T>`, or a `KafkaError` if the first consumer cannot be created.
+    pub fn supervised<F, D>(
+        create_consumer: F,
+        buffer_size: usize,
+        deserializer: D,
+        config: SupervisorConfig,
+        batch_commit: Option<BatchCommitConfig>,
+    ) -> Result<Self, KafkaError>
+    where
+        F: Fn() -> Result<StreamConsumer, KafkaError> + Send + 'static,
+        D: EventDeserializer + Clone + 'static,
+    {
+        let senders = ListenerSenders::new(buffer_size);
+        let health = Arc::new(Mutex::new(ListenerHealth::Running));
+
+        let first_consumer = create_consumer()?;
+        // restarted consumers are created by the same function, so they subscribe to the same topics
+        let topics = subscribed_topics(&first_consumer);
+        let mut first_consumer = Some(first_consumer);
+        let consumer_handle: ConsumerHandle = Arc::new(Mutex::new(None));
+        let task_consumer_handle = consumer_handle.clone();
+        let task_senders = senders.clone();
+        let start_task = move || {
+            let consumer = match first_consumer.take() {
+                Some(consumer) => consumer,
+                None => match create_consumer() {
+                    Ok(consumer) => consumer,
                     Err(e) => {
-                        error!("A Kafka error occurred: {:?}", e);
+                        error!("Could not recreate the Kafka consumer: {:?}", e);
+                        return None;
                     }
-                }
-            }
-        });
+                },
+            };
+            let consumer = Arc::new(consumer);
+            *task_consumer_handle.lock().unwrap() = Some(consumer.clone());
+            Some(consume(
+                consumer,
+                task_senders.clone(),
+                deserializer.clone(),
+                batch_commit,
+            ))
+        };
+        tokio::spawn(supervise(start_task, config, health.clone()));
+
+        Ok(KafkaListener {
+            senders,
+            health,
+            consumer: consumer_handle,
+            mock_buffer: None,
+            topics,
+        })
+    }
+
+    /// Retrieves the topics the listener is subscribed to, as passed to `create_event_listener`.
+    ///
+    /// Listeners whose consumer is assigned partitions rather than subscribed, e.g. one created
+    /// with `EventBus::create_assigned_consumer`, and plain mock listeners have no topics.
+    pub fn topics(&self) -> &[String] {
+        &self.topics
+    }
+
+    /// Retrieves the current health of the listener's consumer task.
+    pub fn health(&self) -> ListenerHealth {
+        *self.health.lock().unwrap()
+    }
 
-        KafkaListener { tx }
+    /// Checks whether the listener's consumer is connected, i.e. it has been assigned partitions to
+    /// consume from.
+    ///
+    /// A listener whose consumer cannot reach the broker is never assigned partitions, so this is
+    /// `false` until the consumer group has assigned it at least one partition, and whenever the
+    /// listener has no consumer or its task has failed.
+    pub fn is_connected(&self) -> bool {
+        if self.health() == ListenerHealth::Failed {
+            return false;
+        }
+        match self.consumer.lock().unwrap().clone() {
+            Some(consumer) => consumer.assignment().is_ok_and(|assignment| assignment.count() > 0),
+            None => false,
+        }
+    }
+
+    /// Seeks the listener's consumer to the beginning of all of its assigned partitions, so that
+    /// every retained message is received again.
+    ///
+    /// Only partitions that are currently assigned are affected, so for a subscribed consumer this
+    /// should be called once the consumer group has assigned partitions.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once every assigned partition has been repositioned, or a `KafkaError` if
+    /// the listener has no consumer or any partition could not be repositioned.
+    pub fn seek_to_beginning(&self) -> Result<(), KafkaError> {
+        self.seek(Offset::Beginning)
+    }
+
+    /// Seeks the listener's consumer to the end of all of its assigned partitions, so that only
+    /// messages produced from now on are received.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once every assigned partition has been repositioned, or a `KafkaError` if
+    /// the listener has no consumer or any partition could not be repositioned.
+    pub fn seek_to_end(&self) -> Result<(), KafkaError> {
+        self.seek(Offset::End)
+    }
+
+    /// Stops the listener's consumer from fetching messages from its assigned partitions, without
+    /// leaving its consumer group, so that messages wait in Kafka rather than in the listener whilst
+    /// they cannot be processed.
+    ///
+    /// Messages that have already been received remain available to the listener's receivers.
+    /// Partitions assigned after the pause, e.g. by a rebalance, are not paused.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once the assigned partitions are paused, or when the listener has no
+    /// consumer. Returns a `KafkaError` if the partitions could not be paused.
+    pub fn pause(&self) -> Result<(), KafkaError> {
+        match self.consumer.lock().unwrap().clone() {
+            Some(consumer) => consumer.pause(&consumer.assignment()?),
+            None => Ok(()),
+        }
+    }
+
+    /// Resumes fetching messages from the partitions paused by `pause`.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once the assigned partitions are resumed, or when the listener has no
+    /// consumer. Returns a `KafkaError` if the partitions could not be resumed.
+    pub fn resume(&self) -> Result<(), KafkaError> {
+        match self.consumer.lock().unwrap().clone() {
+            Some(consumer) => consumer.resume(&consumer.assignment()?),
+            None => Ok(()),
+        }
+    }
+
+    /// Stops the listener's consumer from receiving new messages by unsubscribing it from its topics.
+    ///
+    /// Messages that have already been received remain available to the listener's receivers. Does
+    /// nothing if the listener has no consumer.
+    pub fn unsubscribe(&self) {
+        if let Some(consumer) = self.consumer.lock().unwrap().clone() {
+            consumer.unsubscribe();
+        }
+    }
+
+    /// Synchronously commits the offsets of the messages received by the listener's consumer.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once the offsets are committed, or when there is nothing to commit because
+    /// the listener has no consumer or has not received any messages. Returns a `KafkaError` if the
+    /// commit fails.
+    pub fn commit(&self) -> Result<(), KafkaError> {
+        let consumer = match self.consumer.lock().unwrap().clone() {
+            Some(consumer) => consumer,
+            None => return Ok(()),
+        };
+        match consumer.commit_consumer_state(CommitMode::Sync) {
+            Err(KafkaError::ConsumerCommit(RDKafkaErrorCode::NoOffset)) => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Synchronously commits a single received message, so that its consumer group resumes just
+    /// after it, whatever the consumer has received since. Only meaningful for listeners created by
+    /// `create_manual_commit_listener`, as other consumers also commit on their own.
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: The position of the message, see `ReceivedEvent::position`.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once the offset is committed, or when there is nothing to commit because the
+    /// listener has no consumer. Returns a `KafkaError` if the commit fails.
+    pub fn commit_message(&self, position: &MessagePosition) -> Result<(), KafkaError> {
+        let consumer = match self.consumer.lock().unwrap().clone() {
+            Some(consumer) => consumer,
+            None => return Ok(()),
+        };
+        let mut offsets = TopicPartitionList::with_capacity(1);
+        // the committed offset is that of the next message to consume
+        offsets.add_partition_offset(&position.topic, position.partition, Offset::Offset(position.offset + 1))?;
+        consumer.commit(&offsets, CommitMode::Sync)
+    }
+
+    // repositions every assigned partition of the current consumer to the given offset
+    fn seek(&self, offset: Offset) -> Result<(), KafkaError> {
+        let consumer = self
+            .consumer
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| KafkaError::Seek("The listener has no consumer".to_string()))?;
+
+        let mut assignment = consumer.assignment()?;
+        assignment.set_all_offsets(offset)?;
+        let result = consumer.seek_partitions(assignment, SEEK_TIMEOUT)?;
+        for element in result.elements() {
+            element.error()?;
+        }
+        Ok(())
     }
 
     /// Retrieves a receiver for the broadcast channel.
@@ -90,18 +645,800 @@ where
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// // Assuming `listener` is an instance of `KafkaListener<MyType>`
     /// let receiver = listener.get_receiver();
     /// // Use `receiver` to asynchronously receive messages of type `MyType`
     /// ```
     pub fn get_receiver(&self) -> broadcast::Receiver<T> {
-        self.tx.subscribe()
+        self.senders.events.subscribe()
+    }
+
+    /// Retrieves a receiver for the messages broadcast by the `KafkaListener` along with their headers.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `broadcast::Receiver<ReceivedEvent<T>>`, which receives each message as a `ReceivedEvent`.
+    pub fn get_received_receiver(&self) -> broadcast::Receiver<ReceivedEvent<T>> {
+        self.senders.received.subscribe()
+    }
+
+    /// Retrieves the number of receivers currently subscribed to the broadcast channel.
+    ///
+    /// Each call to `get_receiver` adds a receiver, which is removed when it is dropped. This is
+    /// useful for debugging listeners whose receivers are dropped unexpectedly.
+    pub fn receiver_count(&self) -> usize {
+        self.senders.events.receiver_count()
+    }
+
+    // mock method necessary for testing, forwards the raw messages published to the given topics
+    pub fn mock_with_messages(
+        mut messages: broadcast::Receiver<(String, MessageKey, Vec<u8>)>,
+        topics: &[&str],
+    ) -> Self {
+        let mut listener = KafkaListener::mock();
+        let senders = listener.senders.clone();
+        let topics: Vec<String> = topics.iter().map(|topic| topic.to_string()).collect();
+        listener.topics = topics.clone();
+        tokio::spawn(async move {
+            while let Ok((topic, key, payload)) = messages.recv().await {
+                if !topics.contains(&topic) {
+                    continue;
+                }
+                match JsonCodec.deserialize::<T>(&payload) {
+                    Ok(parsed_message) => {
+                        senders.send(ReceivedEvent::unpositioned(parsed_message, key));
+                    }
+                    Err(e) => error!("Message parsing error: {:?}", e),
+                }
+            }
+        });
+        listener
+    }
+
+    // mock method necessary for testing, marks the listener as failed, as its supervisor does once the
+    // restart budget of its consumer task is exhausted
+    pub fn mock_fail(&self) {
+        *self.health.lock().unwrap() = ListenerHealth::Failed;
     }
 
-    // mock method necessary for testing
+    // mock method necessary for testing, buffers every message until it is drained
     pub fn mock() -> Self {
-        let (tx, _) = broadcast::channel::<T>(1);
-        KafkaListener { tx }
+        let senders = ListenerSenders::new(100);
+        // buffered from the channel with headers, so the buffer is not counted by `receiver_count`
+        let mock_buffer = Some(Mutex::new(senders.received.subscribe()));
+        KafkaListener {
+            senders,
+            health: Arc::new(Mutex::new(ListenerHealth::Running)),
+            consumer: Arc::new(Mutex::new(None)),
+            mock_buffer,
+            topics: vec![],
+        }
+    }
+
+    /// Takes every message buffered by a mock listener without waiting, so that synchronous tests can
+    /// assert on what was delivered.
+    ///
+    /// The buffer holds the last 100 messages, older messages are skipped with a warning. Listeners
+    /// consuming from Kafka do not buffer messages, so nothing is drained from them.
+    ///
+    /// # Returns
+    ///
+    /// Returns the messages received since the mock listener was created or last drained, in order.
+    pub fn drain(&self) -> Vec<T> {
+        let mut buffer = match &self.mock_buffer {
+            Some(buffer) => buffer.lock().unwrap(),
+            None => return vec![],
+        };
+        let mut drained = vec![];
+        loop {
+            match buffer.try_recv() {
+                Ok(received) => drained.push(received.payload),
+                Err(TryRecvError::Lagged(skipped)) => warn!("Skipped {} messages overflowing the mock buffer", skipped),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => return drained,
+            }
+        }
+    }
+}
+
+impl KafkaListener<serde_json::Value> {
+    /// Creates a new `KafkaListener` that receives every message as a `serde_json::Value`, without
+    /// decoding it into a concrete type.
+    ///
+    /// This is useful for consumers that inspect whatever arrives on a topic, e.g. to log or forward
+    /// unknown events. Messages that are not valid JSON are received as a JSON string, see `RawJsonCodec`.
+    ///
+    /// # Arguments
+    ///
+    /// * `consumer`: The Kafka `StreamConsumer` to listen for messages.
+    /// * `buffer_size`: The size of the broadcast channel buffer.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new instance of `KafkaListener<serde_json::Value>`.
+    pub fn new_raw(consumer: StreamConsumer, buffer_size: usize) -> Self {
+        KafkaListener::with_deserializer(consumer, buffer_size, RawJsonCodec)
+    }
+}
+
+// the topics a consumer is subscribed to, which is none if it is assigned partitions instead
+fn subscribed_topics(consumer: &StreamConsumer) -> Vec<String> {
+    consumer
+        .subscription()
+        .map(|subscription| subscription.elements().iter().map(|element| element.topic().to_string()).collect())
+        .unwrap_or_default()
+}
+
+// spawns the task that decodes messages from the consumer and sends them across the broadcast channel,
+// committing the offsets of delivered messages in batches when `batch_commit` is set
+fn consume<T, D>(
+    consumer: Arc<StreamConsumer>,
+    senders: ListenerSenders<T>,
+    deserializer: D,
+    batch_commit: Option<BatchCommitConfig>,
+) -> JoinHandle<()>
+where
+    T: DeserializeOwned + Send + Clone + 'static,
+    D: EventDeserializer + 'static,
+{
+    tokio::spawn(async move {
+        let mut batcher = batch_commit.map(CommitBatcher::new);
+        loop {
+            let wait = batcher.as_ref().and_then(|batcher| batcher.time_until_due(Instant::now()));
+            let received = match wait {
+                Some(wait) => match tokio::time::timeout(wait, consumer.recv()).await {
+                    Ok(received) => received,
+                    Err(_) => {
+                        // the time window closed without the batch filling up
+                        if let Some(batcher) = batcher.as_mut() {
+                            commit_batch(&consumer, batcher);
+                        }
+                        continue;
+                    }
+                },
+                None => consumer.recv().await,
+            };
+            match received {
+                Ok(borrowed_message) => {
+                    if let Some(received) = decode_message(&deserializer, &borrowed_message) {
+                        if !senders.send(received) {
+                            error!("Could not send message across the broadcast channel");
+                            break;
+                        }
+                    }
+                    // a skipped message is delivered as far as the offsets are concerned, so it is committed
+                    if let Some(batcher) = batcher.as_mut() {
+                        if batcher.record(Instant::now()) {
+                            commit_batch(&consumer, batcher);
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("A Kafka error occurred: {:?}", e);
+                }
+            }
+        }
+    })
+}
+
+// decodes a message into a received event, or `None` when it has no payload or cannot be decoded. A
+// message that cannot be decoded would fail on every redelivery, so it is logged and skipped rather
+// than stopping the listener
+fn decode_message<T, D, M>(deserializer: &D, message: &M) -> Option<ReceivedEvent<T>>
+where
+    T: DeserializeOwned,
+    D: EventDeserializer,
+    M: Message,
+{
+    let payload = message.payload()?;
+    match deserializer.deserialize::<T>(payload) {
+        Ok(parsed_message) => Some(ReceivedEvent {
+            payload: parsed_message,
+            headers: read_headers(message),
+            key: read_key(message),
+            position: Some(MessagePosition {
+                topic: message.topic().to_string(),
+                partition: message.partition(),
+                offset: message.offset(),
+            }),
+        }),
+        Err(e) => {
+            error!(
+                "Skipping the message at offset {} of partition {} of {}, it could not be parsed: {:?}",
+                message.offset(),
+                message.partition(),
+                message.topic(),
+                e
+            );
+            None
+        }
+    }
+}
+
+// commits the position of the consumer, which is just past the last delivered message
+fn commit_batch(consumer: &StreamConsumer, batcher: &mut CommitBatcher) {
+    match consumer.commit_consumer_state(CommitMode::Async) {
+        Ok(()) | Err(KafkaError::ConsumerCommit(RDKafkaErrorCode::NoOffset)) => batcher.committed(),
+        // the batch stays pending, so the commit is retried after the next message
+        Err(e) => error!("Could not commit a batch of {} messages: {:?}", batcher.pending, e),
+    }
+}
+
+// restarts the task returned by `start_task` whenever it ends, until the restart budget is exhausted,
+// `start_task` returns `None` when the task could not be started, which counts as a failed attempt. The
+// budget is restored whenever a task ran for `stable_after` before ending
+async fn supervise<F>(mut start_task: F, config: SupervisorConfig, health: Arc<Mutex<ListenerHealth>>)
+where
+    F: FnMut() -> Option<JoinHandle<()>>,
+{
+    let mut restarts = 0;
+    loop {
+        if let Some(handle) = start_task() {
+            *health.lock().unwrap() = ListenerHealth::Running;
+            let started = Instant::now();
+            match handle.await {
+                Ok(()) => warn!("The listener task ended"),
+                Err(e) => error!("The listener task died: {:?}", e),
+            }
+            if started.elapsed() >= config.stable_after {
+                restarts = 0;
+            }
+        }
+
+        if restarts >= config.max_restarts {
+            error!("The listener task could not be restarted after {} attempts", restarts);
+            *health.lock().unwrap() = ListenerHealth::Failed;
+            return;
+        }
+
+        restarts += 1;
+        *health.lock().unwrap() = ListenerHealth::Restarting { attempt: restarts };
+        tokio::time::sleep(config.backoff(restarts)).await;
+        info!("Restarting the listener task, attempt {}", restarts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdkafka::message::{Header, OwnedHeaders, OwnedMessage};
+    use rdkafka::Timestamp;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn generate_config(max_restarts: u32) -> SupervisorConfig {
+        SupervisorConfig {
+            max_restarts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            stable_after: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let config = SupervisorConfig {
+            max_restarts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(350),
+            stable_after: Duration::from_secs(60),
+        };
+
+        assert_eq!(config.backoff(1), Duration::from_millis(100));
+        assert_eq!(config.backoff(2), Duration::from_millis(200));
+        assert_eq!(config.backoff(3), Duration::from_millis(350));
+        assert_eq!(config.backoff(40), Duration::from_millis(350));
+    }
+
+    #[tokio::test]
+    async fn test_supervise_restarts_task_that_ends() {
+        // prepare
+        let starts = Arc::new(AtomicU32::new(0));
+        let health = Arc::new(Mutex::new(ListenerHealth::Running));
+        let starts_clone = starts.clone();
+        let start_task = move || {
+            // the first task ends immediately, the restarted task keeps running
+            let start = starts_clone.fetch_add(1, Ordering::SeqCst);
+            Some(tokio::spawn(async move {
+                if start > 0 {
+                    std::future::pending::<()>().await;
+                }
+            }))
+        };
+
+        // act
+        tokio::spawn(supervise(start_task, generate_config(3), health.clone()));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // assert
+        assert_eq!(starts.load(Ordering::SeqCst), 2);
+        assert_eq!(*health.lock().unwrap(), ListenerHealth::Running);
+    }
+
+    #[tokio::test]
+    async fn test_supervise_fails_when_restart_budget_exhausted() {
+        // prepare
+        let starts = Arc::new(AtomicU32::new(0));
+        let health = Arc::new(Mutex::new(ListenerHealth::Running));
+        let starts_clone = starts.clone();
+        let start_task = move || {
+            starts_clone.fetch_add(1, Ordering::SeqCst);
+            Some(tokio::spawn(async {}))
+        };
+
+        // act
+        supervise(start_task, generate_config(2), health.clone()).await;
+
+        // assert
+        assert_eq!(starts.load(Ordering::SeqCst), 3);
+        assert_eq!(*health.lock().unwrap(), ListenerHealth::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_supervise_restores_restart_budget_after_stable_run() {
+        // prepare
+        let starts = Arc::new(AtomicU32::new(0));
+        let health = Arc::new(Mutex::new(ListenerHealth::Running));
+        let starts_clone = starts.clone();
+        let start_task = move || {
+            // every task runs past `stable_after` before ending, the fourth keeps running
+            let start = starts_clone.fetch_add(1, Ordering::SeqCst);
+            Some(tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                if start >= 3 {
+                    std::future::pending::<()>().await;
+                }
+            }))
+        };
+        let config = SupervisorConfig {
+            stable_after: Duration::from_millis(1),
+            ..generate_config(1)
+        };
+
+        // act
+        tokio::spawn(supervise(start_task, config, health.clone()));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // assert
+        assert_eq!(starts.load(Ordering::SeqCst), 4);
+        assert_eq!(*health.lock().unwrap(), ListenerHealth::Running);
+    }
+
+    #[test]
+    fn test_batch_commit_config_from_lookup() {
+        // prepare
+        let vars = HashMap::from([(COMMIT_BATCH_SIZE_VAR, "10"), (COMMIT_BATCH_INTERVAL_MS_VAR, "invalid")]);
+
+        // act
+        let config = BatchCommitConfig::from_lookup(|var| vars.get(var).map(|value| value.to_string()));
+        let unset = BatchCommitConfig::from_lookup(|_| None);
+
+        // assert
+        assert_eq!(
+            config,
+            Some(BatchCommitConfig {
+                max_messages: 10,
+                ..BatchCommitConfig::default()
+            })
+        );
+        assert_eq!(unset, None);
+    }
+
+    fn generate_batcher(max_messages: usize, max_interval_ms: u64) -> CommitBatcher {
+        CommitBatcher::new(BatchCommitConfig {
+            max_messages,
+            max_interval: Duration::from_millis(max_interval_ms),
+        })
+    }
+
+    #[test]
+    fn test_batch_commit_due_once_batch_size_reached() {
+        // prepare
+        let mut sut = generate_batcher(3, 60_000);
+        let now = Instant::now();
+
+        // act
+        let due: Vec<bool> = (0..3).map(|_| sut.record(now)).collect();
+
+        // assert
+        assert_eq!(due, vec![false, false, true]);
+    }
+
+    #[test]
+    fn test_batch_commit_due_once_time_window_closes() {
+        // prepare
+        let mut sut = generate_batcher(100, 50);
+        let start = Instant::now();
+
+        // act
+        let due_at_start = sut.record(start);
+        let wait = sut.time_until_due(start + Duration::from_millis(20));
+        let due_in_window = sut.record(start + Duration::from_millis(20));
+        let due_after_window = sut.is_due(start + Duration::from_millis(50));
+
+        // assert
+        assert!(!due_at_start);
+        assert_eq!(wait, Some(Duration::from_millis(30)));
+        assert!(!due_in_window);
+        assert!(due_after_window);
+    }
+
+    #[test]
+    fn test_batch_commit_window_starts_with_first_message_after_commit() {
+        // prepare
+        let mut sut = generate_batcher(2, 50);
+        let start = Instant::now();
+        sut.record(start);
+        sut.record(start);
+        sut.committed();
+
+        // act
+        let later = start + Duration::from_secs(10);
+        let due = sut.record(later);
+
+        // assert that a long idle period does not make the next message due straight away
+        assert!(!due);
+        assert_eq!(sut.time_until_due(later), Some(Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn test_batch_commit_nothing_pending_is_never_due() {
+        // prepare
+        let sut = generate_batcher(1, 0);
+
+        // assert
+        assert!(!sut.is_due(Instant::now()));
+        assert_eq!(sut.time_until_due(Instant::now()), None);
+    }
+
+    #[tokio::test]
+    async fn test_receiver_count_follows_receivers() {
+        // prepare
+        let sut = KafkaListener::<u32>::mock();
+
+        // act
+        let before = sut.receiver_count();
+        let first = sut.get_receiver();
+        let second = sut.get_receiver();
+        let subscribed = sut.receiver_count();
+        drop(first);
+        let after_drop = sut.receiver_count();
+        drop(second);
+
+        // assert
+        assert_eq!(before, 0);
+        assert_eq!(subscribed, 2);
+        assert_eq!(after_drop, 1);
+        assert_eq!(sut.receiver_count(), 0);
+    }
+
+    fn generate_message(headers: Option<OwnedHeaders>) -> OwnedMessage {
+        generate_keyed_message(None, headers)
+    }
+
+    fn generate_keyed_message(key: Option<&[u8]>, headers: Option<OwnedHeaders>) -> OwnedMessage {
+        OwnedMessage::new(
+            Some(b"1".to_vec()),
+            key.map(<[u8]>::to_vec),
+            "topic".to_string(),
+            Timestamp::NotAvailable,
+            0,
+            0,
+            headers,
+        )
+    }
+
+    #[test]
+    fn test_decode_message_reads_payload_and_position() {
+        // prepare
+        let message = generate_message(None);
+
+        // act
+        let received = decode_message::<u32, _, _>(&JsonCodec, &message).unwrap();
+
+        // assert
+        assert_eq!(received.payload, 1);
+        let position = received.position.unwrap();
+        assert_eq!(position.topic, "topic");
+        assert_eq!(position.offset, 0);
+    }
+
+    #[test]
+    fn test_decode_message_skips_poison_message() {
+        // prepare
+        let message = OwnedMessage::new(
+            Some(b"not json".to_vec()),
+            None,
+            "topic".to_string(),
+            Timestamp::NotAvailable,
+            0,
+            7,
+            None,
+        );
+
+        // act
+        let received = decode_message::<u32, _, _>(&JsonCodec, &message);
+
+        // assert
+        assert!(received.is_none());
+    }
+
+    #[test]
+    fn test_read_headers_all_headers() {
+        // prepare
+        let headers = OwnedHeaders::new()
+            .insert(Header {
+                key: "correlation_id",
+                value: Some("request-1"),
+            })
+            .insert(Header {
+                key: "schema_version",
+                value: Some("2"),
+            });
+
+        // act
+        let result = read_headers(&generate_message(Some(headers)));
+
+        // assert
+        assert_eq!(
+            result,
+            HashMap::from([
+                ("correlation_id".to_string(), "request-1".to_string()),
+                ("schema_version".to_string(), "2".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_read_headers_skips_unreadable_headers() {
+        // prepare
+        let headers = OwnedHeaders::new()
+            .insert(Header {
+                key: "correlation_id",
+                value: Some("request-1"),
+            })
+            .insert(Header::<&str> {
+                key: "empty",
+                value: None,
+            })
+            .insert(Header {
+                key: "binary",
+                value: Some(&[0xff, 0xfe][..]),
+            });
+
+        // act
+        let result = read_headers(&generate_message(Some(headers)));
+
+        // assert that only the readable header is kept, without failing the others
+        assert_eq!(
+            result,
+            HashMap::from([("correlation_id".to_string(), "request-1".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_read_headers_no_headers() {
+        assert!(read_headers(&generate_message(None)).is_empty());
+        assert!(read_headers(&generate_message(Some(OwnedHeaders::new()))).is_empty());
+    }
+
+    #[test]
+    fn test_read_key() {
+        assert_eq!(
+            read_key(&generate_keyed_message(Some(b"7"), None)),
+            MessageKey::from("7")
+        );
+        assert_eq!(read_key(&generate_keyed_message(None, None)), MessageKey::none());
+        assert_eq!(
+            read_key(&generate_keyed_message(Some(&[0xff, 0xfe]), None)),
+            MessageKey::none()
+        );
+    }
+
+    #[test]
+    fn test_received_event_missing_header_is_none() {
+        // prepare
+        let sut = ReceivedEvent {
+            headers: HashMap::from([("correlation_id".to_string(), "request-1".to_string())]),
+            ..ReceivedEvent::unpositioned(1, MessageKey::none())
+        };
+
+        // assert
+        assert_eq!(sut.header("correlation_id"), Some("request-1"));
+        assert_eq!(sut.header("schema_version"), None);
+    }
+
+    #[tokio::test]
+    async fn test_received_receiver_gets_payload_and_headers() {
+        // prepare
+        let (tx, rx) = broadcast::channel(10);
+        let sut = KafkaListener::<u32>::mock_with_messages(rx, &["topic"]);
+        let mut receiver = sut.get_receiver();
+        let mut received_receiver = sut.get_received_receiver();
+
+        // act
+        tx.send(("topic".to_string(), MessageKey::from("1"), b"7".to_vec())).unwrap();
+
+        // assert that both receivers get the message, which has no headers when none were sent
+        assert_eq!(receiver.recv().await.unwrap(), 7);
+        assert_eq!(
+            received_receiver.recv().await.unwrap(),
+            ReceivedEvent {
+                payload: 7,
+                headers: HashMap::new(),
+                key: MessageKey::from("1"),
+                position: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_raw_listener_receives_arbitrary_json() {
+        // prepare
+        let (tx, rx) = broadcast::channel(10);
+        let sut = KafkaListener::<serde_json::Value>::mock_with_messages(rx, &["topic"]);
+        let mut receiver = sut.get_receiver();
+
+        // act
+        tx.send((
+            "topic".to_string(),
+            MessageKey::none(),
+            br#"{"event_type":"unknown","payload":{"id":[1,2]}}"#.to_vec(),
+        ))
+        .unwrap();
+        tx.send(("topic".to_string(), MessageKey::none(), b"42".to_vec())).unwrap();
+
+        // assert
+        assert_eq!(
+            receiver.recv().await.unwrap(),
+            serde_json::json!({"event_type": "unknown", "payload": {"id": [1, 2]}})
+        );
+        assert_eq!(receiver.recv().await.unwrap(), serde_json::json!(42));
+    }
+
+    #[tokio::test]
+    async fn test_new_raw_listener_consumes_subscribed_topics() {
+        // prepare a consumer whose broker cannot be reached, so no message arrives
+        let consumer: StreamConsumer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", "127.0.0.1:1")
+            .set("group.id", "group-raw")
+            .create()
+            .unwrap();
+        consumer.subscribe(&["topic"]).unwrap();
+
+        // act
+        let sut = KafkaListener::new_raw(consumer, 10);
+
+        // assert
+        assert_eq!(sut.topics(), ["topic".to_string()]);
+        assert_eq!(sut.health(), ListenerHealth::Running);
+    }
+
+    #[test]
+    fn test_decode_message_with_raw_json_codec() {
+        // prepare
+        let json = OwnedMessage::new(
+            Some(br#"{"event_type":"unknown","payload":{"id":[1,2]}}"#.to_vec()),
+            None,
+            "topic".to_string(),
+            Timestamp::NotAvailable,
+            0,
+            0,
+            None,
+        );
+        let not_json = OwnedMessage::new(
+            Some(b"not json".to_vec()),
+            None,
+            "topic".to_string(),
+            Timestamp::NotAvailable,
+            0,
+            1,
+            None,
+        );
+
+        // act
+        let json = decode_message::<serde_json::Value, _, _>(&RawJsonCodec, &json).unwrap();
+        let not_json = decode_message::<serde_json::Value, _, _>(&RawJsonCodec, &not_json).unwrap();
+
+        // assert
+        assert_eq!(
+            json.payload,
+            serde_json::json!({"event_type": "unknown", "payload": {"id": [1, 2]}})
+        );
+        assert_eq!(not_json.payload, serde_json::json!("not json"));
+    }
+
+    #[test]
+    fn test_mock_drain_collects_buffered_events() {
+        // prepare
+        let sut = KafkaListener::<u32>::mock();
+        for event in [1, 2, 3] {
+            sut.senders.send(ReceivedEvent::unpositioned(event, MessageKey::none()));
+        }
+
+        // act
+        let drained = sut.drain();
+
+        // assert
+        assert_eq!(drained, vec![1, 2, 3]);
+        assert!(sut.drain().is_empty());
+        assert_eq!(sut.receiver_count(), 0);
+    }
+
+    #[test]
+    fn test_is_connected_without_consumer() {
+        let sut = KafkaListener::<u32>::mock();
+
+        assert!(!sut.is_connected());
+    }
+
+    #[test]
+    fn test_seek_without_consumer() {
+        // prepare
+        let sut = KafkaListener::<u32>::mock();
+
+        // act
+        let result = sut.seek_to_beginning();
+
+        // assert
+        assert!(matches!(result, Err(KafkaError::Seek(_))));
+    }
+
+    #[test]
+    fn test_pause_and_resume_without_consumer() {
+        let sut = KafkaListener::<u32>::mock();
+
+        assert!(sut.pause().is_ok());
+        assert!(sut.resume().is_ok());
+    }
+
+    // the following tests require a kafka broker running on localhost:9092 with a `seek_test` topic, so
+    // they are ignored even with the feature enabled, run them with
+    // `cargo test --features live-broker -- --ignored`
+    #[cfg(feature = "live-broker")]
+    fn create_live_listener() -> KafkaListener<serde_json::Value> {
+        let consumer = crate::EventBus::new("localhost:9092")
+            .create_assigned_consumer("seek_test_group", "seek_test", &[0])
+            .unwrap();
+        KafkaListener::new(consumer, 100)
+    }
+
+    #[cfg(feature = "live-broker")]
+    #[tokio::test]
+    #[ignore = "requires a kafka broker on localhost:9092"]
+    async fn test_seek_to_beginning_repositions_assigned_partitions() {
+        // prepare
+        let sut = create_live_listener();
+
+        // act
+        let result = sut.seek_to_beginning();
+
+        // assert
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "live-broker")]
+    #[tokio::test]
+    #[ignore = "requires a kafka broker on localhost:9092"]
+    async fn test_seek_to_end_repositions_assigned_partitions() {
+        // prepare
+        let sut = create_live_listener();
+
+        // act
+        let result = sut.seek_to_end();
+
+        // assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_supervise_counts_failed_starts() {
+        // prepare
+        let health = Arc::new(Mutex::new(ListenerHealth::Running));
+
+        // act
+        supervise(|| None, generate_config(1), health.clone()).await;
+
+        // assert
+        assert_eq!(*health.lock().unwrap(), ListenerHealth::Failed);
     }
 }
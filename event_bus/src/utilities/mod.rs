@@ -1 +1,3 @@
-pub mod listeners;
\ No newline at end of file
+pub mod dead_letters;
+pub mod deduplicator;
+pub mod listeners;
@@ -0,0 +1,252 @@
+use crate::event::Event;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// An error that prevents an event from being decoded by `decode_versioned`.
+///
+/// # Variants
+///
+/// * `Malformed`: The bytes are not a JSON encoded event, or its payload does not match its schema.
+/// * `UnsupportedVersion`: The event's schema `version` is newer than the `supported` version of
+///   the payload type, or is an older version the payload type cannot migrate from.
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    Malformed(String),
+    UnsupportedVersion { version: u32, supported: u32 },
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Malformed(reason) => write!(f, "The event could not be decoded: {}", reason),
+            DecodeError::UnsupportedVersion { version, supported } => write!(
+                f,
+                "The event has schema version {}, which cannot be decoded as version {}",
+                version, supported
+            ),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// A payload type with a versioned schema, whose older versions can be migrated to the current one.
+///
+/// # Examples
+///
+/// ```ignore
+/// impl VersionedPayload for OrderPlacedEvent {
+///     const SCHEMA_VERSION: u32 = 2;
+///
+///     fn migrate(version: u32, payload: Value) -> Result<Self, DecodeError> {
+///         match version {
+///             // version 1 did not include the order's total
+///             1 => serde_json::from_value(payload).map_err(|e| DecodeError::Malformed(e.to_string())),
+///             _ => Err(DecodeError::UnsupportedVersion { version, supported: Self::SCHEMA_VERSION }),
+///         }
+///     }
+/// }
+/// ```
+pub trait VersionedPayload: DeserializeOwned {
+    /// The current schema version of the payload.
+    const SCHEMA_VERSION: u32;
+
+    /// Converts the JSON payload of an older schema version into the current payload.
+    ///
+    /// The default implementation rejects every older version.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The schema version of `payload`, which is older than `SCHEMA_VERSION`.
+    /// * `payload` - The undecoded JSON payload.
+    #[allow(unused_variables)]
+    fn migrate(version: u32, payload: Value) -> Result<Self, DecodeError> {
+        Err(DecodeError::UnsupportedVersion {
+            version,
+            supported: Self::SCHEMA_VERSION,
+        })
+    }
+}
+
+/// Decodes a JSON encoded event whose payload may be of an older schema version.
+///
+/// The schema version of the event is read first. Payloads of the current version are decoded
+/// directly, whereas older versions are passed to `VersionedPayload::migrate`. Events from a newer
+/// version than the payload type supports are rejected, as they may contain data this consumer
+/// would silently drop.
+///
+/// # Arguments
+///
+/// * `bytes` - The JSON encoded event, as consumed from Kafka.
+///
+/// # Returns
+///
+/// Returns the decoded `Event<T>`, with its `schema_version` set to `T::SCHEMA_VERSION`, or a
+/// `DecodeError` if it cannot be decoded.
+pub fn decode_versioned<T: VersionedPayload>(bytes: &[u8]) -> Result<Event<T>, DecodeError> {
+    let event: Event<Value> = serde_json::from_slice(bytes).map_err(|e| DecodeError::Malformed(e.to_string()))?;
+    let version = event.schema_version;
+    let payload = match version {
+        v if v == T::SCHEMA_VERSION => {
+            serde_json::from_value(event.payload).map_err(|e| DecodeError::Malformed(e.to_string()))?
+        }
+        v if v < T::SCHEMA_VERSION => T::migrate(v, event.payload)?,
+        _ => {
+            return Err(DecodeError::UnsupportedVersion {
+                version,
+                supported: T::SCHEMA_VERSION,
+            })
+        }
+    };
+
+    Ok(Event {
+        event_id: event.event_id,
+        schema_version: T::SCHEMA_VERSION,
+        event_type: event.event_type,
+        payload,
+        timestamp: event.timestamp,
+        source: event.source,
+        correlation_id: event.correlation_id,
+        metadata: event.metadata,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    // version 2 of a payload, which split the `name` of version 1 into a first and last name
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Customer {
+        first_name: String,
+        last_name: String,
+    }
+
+    #[derive(Serialize)]
+    struct CustomerV1 {
+        name: String,
+    }
+
+    impl VersionedPayload for Customer {
+        const SCHEMA_VERSION: u32 = 2;
+
+        fn migrate(version: u32, payload: Value) -> Result<Self, DecodeError> {
+            match version {
+                1 => {
+                    let name = payload["name"].as_str().ok_or(DecodeError::Malformed("missing name".to_string()))?;
+                    let (first_name, last_name) = name.split_once(' ').unwrap_or((name, ""));
+                    Ok(Customer {
+                        first_name: first_name.to_string(),
+                        last_name: last_name.to_string(),
+                    })
+                }
+                _ => Err(DecodeError::UnsupportedVersion {
+                    version,
+                    supported: Self::SCHEMA_VERSION,
+                }),
+            }
+        }
+    }
+
+    fn encode<T: Serialize>(payload: T, schema_version: u32) -> Vec<u8> {
+        let event = Event::new("customer_created".to_string(), payload, "test".to_string(), None, None)
+            .with_schema_version(schema_version);
+        serde_json::to_vec(&event).unwrap()
+    }
+
+    #[test]
+    fn test_decode_current_version() {
+        // prepare
+        let customer = Customer {
+            first_name: "Bugs".to_string(),
+            last_name: "Bunny".to_string(),
+        };
+        let bytes = encode(customer.clone(), 2);
+
+        // act
+        let event = decode_versioned::<Customer>(&bytes).unwrap();
+
+        // assert
+        assert_eq!(event.payload, customer);
+        assert_eq!(event.schema_version, 2);
+    }
+
+    #[test]
+    fn test_decode_migrates_older_version() {
+        // prepare
+        let bytes = encode(
+            CustomerV1 {
+                name: "Bugs Bunny".to_string(),
+            },
+            1,
+        );
+
+        // act
+        let event = decode_versioned::<Customer>(&bytes).unwrap();
+
+        // assert
+        assert_eq!(
+            event.payload,
+            Customer {
+                first_name: "Bugs".to_string(),
+                last_name: "Bunny".to_string()
+            }
+        );
+        assert_eq!(event.schema_version, 2);
+        assert_eq!(event.event_type, "customer_created");
+    }
+
+    #[test]
+    fn test_decode_rejects_future_version() {
+        // prepare
+        let bytes = encode(Value::Null, 3);
+
+        // act
+        let result = decode_versioned::<Customer>(&bytes);
+
+        // assert
+        let err = result.unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::UnsupportedVersion {
+                version: 3,
+                supported: 2
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "The event has schema version 3, which cannot be decoded as version 2"
+        );
+    }
+
+    #[test]
+    fn test_decode_without_migration_rejects_older_version() {
+        // prepare
+        #[derive(Deserialize)]
+        struct Unmigrated;
+        impl VersionedPayload for Unmigrated {
+            const SCHEMA_VERSION: u32 = 2;
+        }
+        let bytes = encode(Value::Null, 1);
+
+        // act
+        let result = decode_versioned::<Unmigrated>(&bytes);
+
+        // assert
+        assert!(matches!(
+            result,
+            Err(DecodeError::UnsupportedVersion { version: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_bytes() {
+        assert!(matches!(
+            decode_versioned::<Customer>(b"not an event"),
+            Err(DecodeError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{NetworkError, NetworkErrorType};
+use log::warn;
+use std::time::Duration;
+
+/// The status code of an injected error when the `FaultInjector` has no `status_codes`.
+pub const DEFAULT_INJECTED_STATUS_CODE: u16 = 503;
+
+/// `FaultInjector` injects faults into the requests made through the networking crate, so the
+/// resilience of a service, e.g. its retries, circuit breaker and timeouts, can be tested without a
+/// flaky server. It is only available with the `chaos` feature, and is set on the shared client
+/// through `NetworkConfig::fault_injector`.
+///
+/// Before a request is sent it may be delayed, and then it may fail with a synthetic `Standard`
+/// error instead of being sent at all, as if the server had responded with an error status code.
+///
+/// # Fields
+///
+/// * `error_probability`: The probability, from `0.0` to `1.0`, that a request fails with an injected error.
+/// * `status_codes`: The status codes of injected errors, one of which is picked at random for each
+///   error, so a single status code forces every injected error to it. Defaults to `503` when empty.
+/// * `delay_probability`: The probability, from `0.0` to `1.0`, that a request is delayed.
+/// * `delay`: How long a delayed request waits before it is sent or fails.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultInjector {
+    pub error_probability: f64,
+    pub status_codes: Vec<u16>,
+    pub delay_probability: f64,
+    pub delay: Duration,
+}
+
+impl FaultInjector {
+    /// Possibly delays a request and fails it, as decided at random by the probabilities of the injector.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the request, which is logged with each injected fault.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Err(NetworkError)` with the injected status code if the request should fail, or
+    /// `Ok(())` if it should be sent.
+    pub async fn inject(&self, url: &str) -> Result<(), NetworkError> {
+        if rand::random_bool(self.delay_probability.clamp(0.0, 1.0)) {
+            warn!("Injecting a delay of {:?} into the request to: {}", self.delay, url);
+            tokio::time::sleep(self.delay).await;
+        }
+        if !rand::random_bool(self.error_probability.clamp(0.0, 1.0)) {
+            return Ok(());
+        }
+
+        let status_code = match self.status_codes.len() {
+            0 => DEFAULT_INJECTED_STATUS_CODE,
+            len => self.status_codes[rand::random_range(0..len)],
+        };
+        warn!("Injecting a {} error into the request to: {}", status_code, url);
+        Err(NetworkError {
+            status_code: Some(status_code),
+            error: NetworkErrorType::Standard,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NetworkConfig;
+    use crate::{execute_request_with, HttpMethod, SharedClient};
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn test_request_through_client_returns_injected_status_code() {
+        // prepare a client that fails every request, to an address that would otherwise refuse the connection
+        let client = SharedClient::new(&NetworkConfig {
+            fault_injector: Some(FaultInjector {
+                error_probability: 1.0,
+                status_codes: vec![418],
+                ..FaultInjector::default()
+            }),
+            ..NetworkConfig::default()
+        });
+
+        // act
+        let result =
+            execute_request_with::<u32>(&client, HttpMethod::Get { params: None }, "http://127.0.0.1:1", None).await;
+
+        // assert
+        let err = result.unwrap_err();
+        assert_eq!(err.status_code, Some(418));
+        assert!(matches!(err.error, NetworkErrorType::Standard));
+    }
+
+    #[tokio::test]
+    async fn test_inject_forced_status_code() {
+        // prepare
+        let sut = FaultInjector {
+            error_probability: 1.0,
+            status_codes: vec![429],
+            ..FaultInjector::default()
+        };
+
+        // act
+        let err = sut.inject("http://catalog/1").await.unwrap_err();
+
+        // assert
+        assert_eq!(err.status_code, Some(429));
+        assert!(err.is_client_error());
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_inject_defaults_to_service_unavailable() {
+        // prepare
+        let sut = FaultInjector {
+            error_probability: 1.0,
+            ..FaultInjector::default()
+        };
+
+        // act
+        let err = sut.inject("http://catalog/1").await.unwrap_err();
+
+        // assert
+        assert_eq!(err.status_code, Some(DEFAULT_INJECTED_STATUS_CODE));
+        assert!(err.is_server_error());
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_inject_picks_one_of_the_status_codes() {
+        // prepare
+        let sut = FaultInjector {
+            error_probability: 1.0,
+            status_codes: vec![500, 502],
+            ..FaultInjector::default()
+        };
+
+        // act
+        for _ in 0..20 {
+            let err = sut.inject("http://catalog/1").await.unwrap_err();
+
+            // assert
+            assert!(matches!(err.status_code, Some(500) | Some(502)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inject_delays_request() {
+        // prepare
+        let sut = FaultInjector {
+            delay_probability: 1.0,
+            delay: Duration::from_millis(50),
+            ..FaultInjector::default()
+        };
+        let start = Instant::now();
+
+        // act
+        let result = sut.inject("http://catalog/1").await;
+
+        // assert
+        assert!(result.is_ok());
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_default_injects_no_faults() {
+        // prepare
+        let sut = FaultInjector {
+            delay: Duration::from_secs(5),
+            status_codes: vec![500],
+            ..FaultInjector::default()
+        };
+        let start = Instant::now();
+
+        // act
+        let mut results = vec![];
+        for _ in 0..20 {
+            results.push(sut.inject("http://catalog/1").await);
+        }
+
+        // assert
+        assert!(results.iter().all(Result::is_ok));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
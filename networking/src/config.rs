@@ -0,0 +1,255 @@
+use reqwest::{Client, ClientBuilder, NoProxy, Proxy};
+use std::time::Duration;
+
+/// The HTTP protocol version used by the shared client.
+///
+/// # Variants
+///
+/// * `Auto`: Negotiates the version with the server, using ALPN over TLS and HTTP/1.1 otherwise.
+/// * `Http1Only`: Only ever uses HTTP/1.1.
+/// * `Http2PriorKnowledge`: Always uses HTTP/2 without negotiation, for servers that are known to support
+///   HTTP/2 but do not perform the upgrade, such as some gateways.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum HttpVersion {
+    #[default]
+    Auto,
+    Http1Only,
+    Http2PriorKnowledge,
+}
+
+/// An HTTP proxy that all outbound requests are routed through.
+///
+/// # Fields
+///
+/// * `url`: The URL of the proxy, e.g. `http://proxy.internal:3128`.
+/// * `no_proxy`: Hosts, domains or IP ranges that bypass the proxy, e.g. `["localhost", "10.0.0.0/8"]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxyConfig {
+    pub url: String,
+    pub no_proxy: Vec<String>,
+}
+
+/// Configuration for the HTTP client shared by all network requests.
+///
+/// The defaults bound the number of idle connections kept open to any single host while still
+/// reusing connections between requests to the same microservice.
+///
+/// # Fields
+///
+/// * `pool_max_idle_per_host`: The maximum number of idle connections kept open per host. Defaults to `32`.
+/// * `pool_idle_timeout`: How long an idle connection is kept in the pool before being closed, or `None`
+///   to keep idle connections indefinitely. Defaults to 90 seconds.
+/// * `tcp_keepalive`: The interval between TCP keep-alive probes, or `None` to disable them. Defaults
+///   to 60 seconds.
+/// * `http_version`: The HTTP protocol version to use. Defaults to `HttpVersion::Auto`.
+/// * `proxy`: An explicit proxy for all requests. When `None` the standard `HTTP_PROXY`, `HTTPS_PROXY`
+///   and `NO_PROXY` environment variables are honoured instead. Defaults to `None`.
+/// * `forward_correlation_id`: Whether requests forward the correlation id of their task in the
+///   `X-Correlation-Id` header, see `with_correlation_id`. Defaults to `true`.
+/// * `fault_injector`: The faults injected into every request, only available with the `chaos`
+///   feature. Defaults to `None`, injecting no faults.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkConfig {
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Option<Duration>,
+    pub tcp_keepalive: Option<Duration>,
+    pub http_version: HttpVersion,
+    pub proxy: Option<ProxyConfig>,
+    pub forward_correlation_id: bool,
+    #[cfg(feature = "chaos")]
+    pub fault_injector: Option<crate::chaos::FaultInjector>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            http_version: HttpVersion::Auto,
+            proxy: None,
+            forward_correlation_id: true,
+            #[cfg(feature = "chaos")]
+            fault_injector: None,
+        }
+    }
+}
+
+// where the shared client takes its proxy from
+enum ProxySetting {
+    // the standard `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables, which reqwest reads by default
+    Environment,
+    // the proxy of the config, which all requests are routed through
+    Explicit(Proxy),
+}
+
+impl NetworkConfig {
+    /// Builds a reqwest `Client` configured with these settings.
+    pub fn build_client(&self) -> reqwest::Result<Client> {
+        self.client_builder()?.build()
+    }
+
+    fn client_builder(&self) -> reqwest::Result<ClientBuilder> {
+        let mut builder = Client::builder()
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive);
+        if let ProxySetting::Explicit(proxy) = self.proxy_setting()? {
+            builder = builder.proxy(proxy);
+        }
+
+        Ok(match self.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1Only => builder.http1_only(),
+            HttpVersion::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+        })
+    }
+
+    fn proxy_setting(&self) -> reqwest::Result<ProxySetting> {
+        let Some(proxy) = &self.proxy else {
+            return Ok(ProxySetting::Environment);
+        };
+        let no_proxy = NoProxy::from_string(&proxy.no_proxy.join(","));
+        Ok(ProxySetting::Explicit(Proxy::all(&proxy.url)?.no_proxy(no_proxy)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = NetworkConfig::default();
+
+        assert_eq!(config.pool_max_idle_per_host, 32);
+        assert_eq!(config.pool_idle_timeout, Some(Duration::from_secs(90)));
+        assert_eq!(config.tcp_keepalive, Some(Duration::from_secs(60)));
+        assert_eq!(config.http_version, HttpVersion::Auto);
+        assert_eq!(config.proxy, None);
+        assert!(config.forward_correlation_id);
+        assert!(config.build_client().is_ok());
+    }
+
+    #[test]
+    fn test_build_client_with_custom_limits() {
+        let config = NetworkConfig {
+            pool_max_idle_per_host: 2,
+            pool_idle_timeout: None,
+            tcp_keepalive: Some(Duration::from_secs(5)),
+            http_version: HttpVersion::Auto,
+            proxy: None,
+            forward_correlation_id: true,
+            #[cfg(feature = "chaos")]
+            fault_injector: None,
+        };
+
+        assert!(config.build_client().is_ok());
+    }
+
+    // generates the debug output of the builder, which lists any forced protocol version
+    fn describe_builder(http_version: HttpVersion) -> String {
+        let config = NetworkConfig {
+            http_version,
+            ..NetworkConfig::default()
+        };
+        assert!(config.build_client().is_ok());
+        format!("{:?}", config.client_builder().unwrap())
+    }
+
+    #[test]
+    fn test_build_client_auto_http_version() {
+        let description = describe_builder(HttpVersion::Auto);
+
+        assert!(!description.contains("http1_only"));
+        assert!(!description.contains("http2_prior_knowledge"));
+    }
+
+    #[test]
+    fn test_build_client_http1_only() {
+        let description = describe_builder(HttpVersion::Http1Only);
+
+        assert!(description.contains("http1_only"));
+        assert!(!description.contains("http2_prior_knowledge"));
+    }
+
+    #[test]
+    fn test_build_client_http2_prior_knowledge() {
+        let description = describe_builder(HttpVersion::Http2PriorKnowledge);
+
+        assert!(description.contains("http2_prior_knowledge"));
+        assert!(!description.contains("http1_only"));
+    }
+
+    #[test]
+    fn test_build_client_with_proxy() {
+        let config = NetworkConfig {
+            proxy: Some(ProxyConfig {
+                url: "http://proxy.internal:3128".to_string(),
+                no_proxy: vec!["localhost".to_string(), "10.0.0.0/8".to_string()],
+            }),
+            ..NetworkConfig::default()
+        };
+
+        assert!(matches!(config.proxy_setting(), Ok(ProxySetting::Explicit(_))));
+        assert!(config.build_client().is_ok());
+    }
+
+    #[test]
+    fn test_build_client_without_proxy() {
+        let config = NetworkConfig::default();
+
+        assert!(matches!(config.proxy_setting(), Ok(ProxySetting::Environment)));
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_routed_through_proxy() {
+        // prepare a proxy that responds with the request line of each request it receives
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            while let Ok((mut connection, _)) = listener.accept().await {
+                let mut request = vec![0; 4096];
+                let read = connection.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..read]).to_string();
+                let body = request.lines().next().unwrap_or_default().to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = connection.write_all(response.as_bytes()).await;
+            }
+        });
+        let config = NetworkConfig {
+            proxy: Some(ProxyConfig {
+                url: format!("http://{}", address),
+                no_proxy: vec![],
+            }),
+            ..NetworkConfig::default()
+        };
+
+        // act
+        let response = config.build_client().unwrap().get("http://catalog.internal/stock").send().await.unwrap();
+
+        // assert
+        assert_eq!(
+            response.text().await.unwrap(),
+            "GET http://catalog.internal/stock HTTP/1.1"
+        );
+    }
+
+    #[test]
+    fn test_build_client_with_invalid_proxy() {
+        let config = NetworkConfig {
+            proxy: Some(ProxyConfig {
+                url: "not a url".to_string(),
+                no_proxy: vec![],
+            }),
+            ..NetworkConfig::default()
+        };
+
+        assert!(config.build_client().is_err());
+    }
+}
@@ -1,9 +1,94 @@
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod config;
+
+use crate::config::NetworkConfig;
+use common::error::ServiceError;
+pub use common::utilities::request_id::CORRELATION_ID_HEADER;
 use log::{debug, error};
-use reqwest::header::HeaderMap;
+use reqwest::header::{HeaderMap, HeaderValue};
 use reqwest::Client;
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::OnceLock;
+
+// the client shared by all requests so that connections are pooled and reused
+static CLIENT: OnceLock<SharedClient> = OnceLock::new();
+
+tokio::task_local! {
+    // the correlation id of the request being handled by the current task, if any
+    static CORRELATION_ID: Option<String>;
+}
+
+// the shared client, whether its requests forward the correlation id of their task, and the faults
+// injected into its requests
+struct SharedClient {
+    client: Client,
+    forward_correlation_id: bool,
+    #[cfg(feature = "chaos")]
+    fault_injector: Option<chaos::FaultInjector>,
+}
+
+impl SharedClient {
+    fn new(config: &NetworkConfig) -> Self {
+        SharedClient {
+            client: config.build_client().expect("Failed to build the HTTP client"),
+            forward_correlation_id: config.forward_correlation_id,
+            #[cfg(feature = "chaos")]
+            fault_injector: config.fault_injector.clone(),
+        }
+    }
+}
+
+/// Initializes the shared HTTP client with the given configuration. This should be called once at
+/// startup, before any requests are made, otherwise the client is lazily built with the default
+/// `NetworkConfig`.
+///
+/// # Arguments
+///
+/// * `config` - The configuration used to build the shared client.
+///
+/// # Returns
+///
+/// Returns `false` if the shared client had already been initialized, in which case the configuration is ignored.
+pub fn initialize(config: &NetworkConfig) -> bool {
+    CLIENT.set(SharedClient::new(config)).is_ok()
+}
+
+fn shared_client() -> &'static SharedClient {
+    CLIENT.get_or_init(|| SharedClient::new(&NetworkConfig::default()))
+}
+
+/// Runs a future with the given correlation id, which every request it makes forwards in the
+/// `X-Correlation-Id` header, so that the requests can be traced back to the request that caused them.
+///
+/// # Arguments
+///
+/// * `correlation_id` - The correlation id, e.g. the id of an incoming HTTP request, or `None` to
+///   forward no correlation id.
+/// * `future` - The future making the requests.
+pub async fn with_correlation_id<F: Future>(correlation_id: Option<String>, future: F) -> F::Output {
+    CORRELATION_ID.scope(correlation_id, future).await
+}
+
+/// Retrieves the correlation id of the current task, as set by `with_correlation_id`.
+pub fn correlation_id() -> Option<String> {
+    CORRELATION_ID.try_with(|correlation_id| correlation_id.clone()).ok().flatten()
+}
+
+// adds the correlation id of the current task to the headers, unless they already carry one
+fn with_correlation_id_header(headers: Option<HeaderMap>) -> Option<HeaderMap> {
+    let correlation_id = match correlation_id().and_then(|id| HeaderValue::from_str(&id).ok()) {
+        Some(correlation_id) => correlation_id,
+        None => return headers,
+    };
+    let mut headers = headers.unwrap_or_default();
+    if !headers.contains_key(CORRELATION_ID_HEADER) {
+        headers.insert(CORRELATION_ID_HEADER, correlation_id);
+    }
+    Some(headers)
+}
 
 /// Executes a Standard RESTful GET request over the network. This method can only be accessed within the networking
 /// crate. A developer should create simple network level services that prepare data for these base functions.
@@ -41,15 +126,34 @@ async fn execute_request<T: serde::de::DeserializeOwned>(
     method: HttpMethod,
     url: &str,
     headers: Option<HeaderMap>,
+) -> Result<T, NetworkError> {
+    execute_request_with(shared_client(), method, url, headers).await
+}
+
+// makes a request with the given client, which is the shared client outside of tests
+async fn execute_request_with<T: serde::de::DeserializeOwned>(
+    shared_client: &SharedClient,
+    method: HttpMethod,
+    url: &str,
+    headers: Option<HeaderMap>,
 ) -> Result<T, NetworkError> {
     debug!("Making a {:?} request to: {}", method, url);
-    let client = Client::builder().build().unwrap();
+    #[cfg(feature = "chaos")]
+    if let Some(fault_injector) = &shared_client.fault_injector {
+        fault_injector.inject(url).await?;
+    }
+    let client = &shared_client.client;
+    let headers = if shared_client.forward_correlation_id {
+        with_correlation_id_header(headers)
+    } else {
+        headers
+    };
     let mut request_builder = match &method {
         HttpMethod::Get { params } => {
             let mut full_url = url.to_string();
             if let Some(parameters) = params {
                 let query_string = serde_urlencoded::to_string(parameters).unwrap();
-                full_url.push_str("?");
+                full_url.push('?');
                 full_url.push_str(&query_string);
             }
             client.get(&full_url)
@@ -122,3 +226,236 @@ pub enum NetworkErrorType {
     RequestError(reqwest::Error),
     JsonError(reqwest::Error),
 }
+
+impl NetworkError {
+    /// Whether the request could succeed if it was made again. This is the case for timeouts,
+    /// failures to connect, rate limiting (`429`) and server errors, but not for client errors or
+    /// responses that could not be deserialized.
+    pub fn is_retryable(&self) -> bool {
+        match &self.error {
+            NetworkErrorType::RequestError(err) => err.is_timeout() || err.is_connect(),
+            NetworkErrorType::JsonError(_) => false,
+            NetworkErrorType::Standard => self.status_code == Some(429) || self.is_server_error(),
+        }
+    }
+
+    /// Whether the server responded with a `4xx` status code.
+    pub fn is_client_error(&self) -> bool {
+        matches!(self.error, NetworkErrorType::Standard) && matches!(self.status_code, Some(400..=499))
+    }
+
+    /// Whether the server responded with a `5xx` status code.
+    pub fn is_server_error(&self) -> bool {
+        matches!(self.error, NetworkErrorType::Standard) && matches!(self.status_code, Some(500..=599))
+    }
+
+    /// Whether the request timed out before a response was received.
+    pub fn is_timeout(&self) -> bool {
+        matches!(&self.error, NetworkErrorType::RequestError(err) if err.is_timeout())
+    }
+}
+
+impl From<NetworkError> for ServiceError {
+    fn from(err: NetworkError) -> Self {
+        match err.status_code {
+            Some(status_code) => {
+                ServiceError::Upstream(format!("An upstream request failed with status code: {}", status_code))
+            }
+            None => ServiceError::Upstream("An upstream request failed".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_error_into_service_error() {
+        let err = NetworkError {
+            status_code: Some(500),
+            error: NetworkErrorType::Standard,
+        };
+
+        let service_error = ServiceError::from(err);
+
+        assert!(matches!(
+            service_error,
+            ServiceError::Upstream(message) if message == "An upstream request failed with status code: 500"
+        ));
+    }
+
+    #[test]
+    fn test_network_error_without_status_code_into_service_error() {
+        let service_error = ServiceError::from(standard_error(None));
+
+        assert!(matches!(
+            service_error,
+            ServiceError::Upstream(message) if message == "An upstream request failed"
+        ));
+    }
+
+    fn standard_error(status_code: Option<u16>) -> NetworkError {
+        NetworkError {
+            status_code,
+            error: NetworkErrorType::Standard,
+        }
+    }
+
+    // makes a request with the given client and returns the resulting request error
+    async fn request_error(client: Client, url: &str) -> NetworkError {
+        let err = client.get(url).send().await.unwrap_err();
+        NetworkError {
+            status_code: None,
+            error: NetworkErrorType::RequestError(err),
+        }
+    }
+
+    #[test]
+    fn test_client_error_classification() {
+        for status_code in [400, 404, 409] {
+            let err = standard_error(Some(status_code));
+            assert!(err.is_client_error());
+            assert!(!err.is_server_error());
+            assert!(!err.is_retryable());
+            assert!(!err.is_timeout());
+        }
+    }
+
+    #[test]
+    fn test_rate_limited_is_retryable_client_error() {
+        let err = standard_error(Some(429));
+
+        assert!(err.is_client_error());
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn test_server_error_classification() {
+        for status_code in [500, 502, 503, 504] {
+            let err = standard_error(Some(status_code));
+            assert!(err.is_server_error());
+            assert!(!err.is_client_error());
+            assert!(err.is_retryable());
+            assert!(!err.is_timeout());
+        }
+    }
+
+    #[test]
+    fn test_error_without_status_code_classification() {
+        let err = standard_error(None);
+
+        assert!(!err.is_client_error());
+        assert!(!err.is_server_error());
+        assert!(!err.is_retryable());
+        assert!(!err.is_timeout());
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused_classification() {
+        // act
+        let err = request_error(Client::new(), "http://127.0.0.1:1").await;
+
+        // assert
+        assert!(err.is_retryable());
+        assert!(!err.is_timeout());
+        assert!(!err.is_client_error());
+        assert!(!err.is_server_error());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_classification() {
+        // prepare a server that accepts connections but never responds
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut connections = vec![];
+            while let Ok((connection, _)) = listener.accept().await {
+                connections.push(connection);
+            }
+        });
+        let client = Client::builder().timeout(std::time::Duration::from_millis(50)).build().unwrap();
+
+        // act
+        let err = request_error(client, &format!("http://{}", address)).await;
+
+        // assert
+        assert!(err.is_timeout());
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_json_error_is_not_retryable() {
+        // prepare a server that responds with a body that is not JSON
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            while let Ok((mut connection, _)) = listener.accept().await {
+                let _ =
+                    connection.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 3\r\nconnection: close\r\n\r\nabc").await;
+            }
+        });
+
+        // act
+        let result = execute_get_request::<u32>(&format!("http://{}", address), None, None).await;
+
+        // assert
+        let err = result.unwrap_err();
+        assert!(matches!(err.error, NetworkErrorType::JsonError(_)));
+        assert!(!err.is_retryable());
+        assert!(!err.is_client_error());
+        assert!(!err.is_timeout());
+    }
+
+    #[tokio::test]
+    async fn test_request_forwards_correlation_id() {
+        // prepare a server that responds with the correlation id header of each request
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            while let Ok((mut connection, _)) = listener.accept().await {
+                let mut request = vec![0; 4096];
+                let read = connection.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+                let correlation_id =
+                    request.lines().find_map(|line| line.strip_prefix("x-correlation-id: ")).unwrap_or("");
+                let body = serde_json::to_string(correlation_id).unwrap();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = connection.write_all(response.as_bytes()).await;
+            }
+        });
+        let url = format!("http://{}", address);
+
+        // act
+        let forwarded = with_correlation_id(
+            Some("order-123".to_string()),
+            execute_get_request::<String>(&url, None, None),
+        )
+        .await;
+        let without = execute_get_request::<String>(&url, None, None).await;
+
+        // assert
+        assert_eq!(forwarded.unwrap(), "order-123");
+        assert_eq!(without.unwrap(), "");
+    }
+
+    #[test]
+    fn test_correlation_id_header_keeps_supplied_header() {
+        // prepare
+        let mut headers = HeaderMap::new();
+        headers.insert(CORRELATION_ID_HEADER, HeaderValue::from_static("supplied"));
+
+        // act
+        let headers = CORRELATION_ID.sync_scope(Some("task".to_string()), || with_correlation_id_header(Some(headers)));
+
+        // assert
+        assert_eq!(headers.unwrap()[CORRELATION_ID_HEADER], "supplied");
+        assert_eq!(with_correlation_id_header(None), None);
+    }
+}
@@ -1,28 +1,374 @@
 use crate::db::order_db::OrderDbClient;
-use crate::model::OrderRequest;
+use crate::model::{OrderConfirmation, OrderRequest};
+use crate::networking::cached_catalog_network_service::CachedCatalogNetworkService;
 use crate::networking::catalog_network_service::CatalogApiClient;
-use crate::services::order_service::{OrderService, PlaceOrderError};
+use crate::services::order_service::OrderService;
 use actix_web::{post, web, Responder};
+use common::dto::api_response::ApiResponse;
+use common::error::ServiceError;
+use common::utilities::json_body::JsonBody;
+use common::utilities::request_id::RequestId;
 use event_bus::EventBus;
 use std::sync::Arc;
 
 #[post("/order")]
+// responds with an `OrderConfirmation`, or why the order could not be placed, wrapped in an `ApiResponse`
 pub async fn place_order(
-    order_request: web::Json<OrderRequest>,
-    order_service: web::Data<Arc<OrderService<EventBus, OrderDbClient, CatalogApiClient>>>,
+    order_request: JsonBody<OrderRequest>,
+    request_id: Option<web::ReqData<RequestId>>,
+    order_service: web::Data<Arc<OrderService<EventBus, OrderDbClient, CachedCatalogNetworkService<CatalogApiClient>>>>,
 ) -> impl Responder {
-    let result = order_service.get_ref().place_order(&order_request).await;
-    if let Err(err) = result {
-        return match err {
-            PlaceOrderError::ItemOutOfStock => format!("This item is out of stock"),
-            PlaceOrderError::CatalogNetworkError => {
-                format!("An error occurred and some of our systems are down, please try again later.")
-            }
-        };
-    }
-
-    return format!(
-        "Order has been placed successfully! It's on its way to: {} at {}",
-        order_request.name, order_request.address
-    );
+    // the requests made to the catalog are traced back to this request by its id
+    let correlation_id = request_id.map(|id| id.into_inner().0);
+    let total = networking::with_correlation_id(
+        correlation_id.clone(),
+        order_service.get_ref().place_order(&order_request, correlation_id.clone()),
+    )
+    .await;
+    let confirmation = total.map(|total| OrderConfirmation::new(&order_request, total)).map_err(ServiceError::from);
+    ApiResponse::from_result(confirmation, &correlation_id.unwrap_or_default()).to_http_response()
+}
+
+#[post("/orders")]
+// responds with the total of each order, or why the orders could not be placed, wrapped in an `ApiResponse`
+pub async fn place_orders(
+    order_requests: JsonBody<Vec<OrderRequest>>,
+    request_id: Option<web::ReqData<RequestId>>,
+    order_service: web::Data<Arc<OrderService<EventBus, OrderDbClient, CachedCatalogNetworkService<CatalogApiClient>>>>,
+) -> impl Responder {
+    let correlation_id = request_id.map(|id| id.into_inner().0);
+    let totals = if order_requests.is_empty() {
+        Err(ServiceError::BadRequest(
+            "At least one order must be provided".to_string(),
+        ))
+    } else {
+        networking::with_correlation_id(
+            correlation_id.clone(),
+            order_service.get_ref().place_orders(&order_requests, correlation_id.clone()),
+        )
+        .await
+        .map_err(ServiceError::from)
+    };
+    ApiResponse::from_result(totals, &correlation_id.unwrap_or_default()).to_http_response()
+}
+
+#[post("/order/{order_id}/cancel")]
+pub async fn cancel_order(
+    path: web::Path<u32>,
+    order_service: web::Data<Arc<OrderService<EventBus, OrderDbClient, CachedCatalogNetworkService<CatalogApiClient>>>>,
+) -> Result<impl Responder, ServiceError> {
+    let order_id = path.into_inner();
+    order_service.get_ref().cancel_order(order_id).await?;
+    Ok(format!("Order {} has been cancelled successfully!", order_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::order_db::MockOrderDb;
+    use crate::db::order_db::OrderDb;
+    use crate::networking::cached_catalog_network_service::CatalogCache;
+    use crate::networking::catalog_network_service::MockCatalogNetworkService;
+    use actix_web::http::header::CONTENT_TYPE;
+    use actix_web::http::StatusCode;
+    use actix_web::middleware::from_fn;
+    use actix_web::{get, test, App, HttpResponse, HttpServer};
+    use common::types::money::Money;
+    use common::utilities::{request_id, server_config};
+    use event_bus::event::Event;
+    use event_bus::events::order_placed_event::OrderPlacedEvent;
+    use event_bus::MockEventBus;
+
+    #[get("/catalog/stock/{item_id}")]
+    async fn fake_get_stock() -> impl Responder {
+        HttpResponse::Ok().json(ApiResponse::success(5, ""))
+    }
+
+    #[get("/catalog/{item_id}")]
+    async fn fake_get_catalog_item() -> impl Responder {
+        HttpResponse::Ok().body(
+            r#"{"id":1,"name":"T-Shirt","description":"desc","sizes":["M"],"price":"GBP 20.00",
+            "currency":"GBP","images":[],"video":"https://example.com/t-shirt-video.mp4"}"#,
+        )
+    }
+
+    // starts a fake catalog microservice on a random port and returns its host
+    fn start_fake_catalog() -> String {
+        let server = HttpServer::new(|| App::new().service(fake_get_stock).service(fake_get_catalog_item))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let address = server.addrs()[0];
+        tokio::spawn(server.run());
+        format!("http://{}", address)
+    }
+
+    fn generate_order_service(
+        catalog_host: String,
+    ) -> Arc<OrderService<EventBus, OrderDbClient, CachedCatalogNetworkService<CatalogApiClient>>> {
+        let catalog_cache = Arc::new(CatalogCache::new());
+        Arc::new(OrderService::new(
+            OrderDbClient::new(),
+            EventBus::new("localhost:9092"),
+            CachedCatalogNetworkService::new(CatalogApiClient::new(&catalog_host), catalog_cache.clone()),
+            catalog_cache,
+        ))
+    }
+
+    fn generate_order_request(quantity: u32) -> test::TestRequest {
+        test::TestRequest::post().uri("/order").set_json(serde_json::json!({
+            "item_id": 1,
+            "name": "James",
+            "address": "22 Bugs Bunny Street, London, E1 4AH, United Kingdom",
+            "quantity": quantity
+        }))
+    }
+
+    #[actix_web::test]
+    async fn test_place_order_item_out_of_stock_is_conflict() {
+        // prepare
+        let order_service = generate_order_service(start_fake_catalog());
+        let app = test::init_service(App::new().app_data(web::Data::new(order_service)).service(place_order)).await;
+
+        // act
+        let resp = test::call_service(&app, generate_order_request(10).to_request()).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        let body: ApiResponse<serde_json::Value> = test::read_body_json(resp).await;
+        assert_eq!(body.data, None);
+        assert_eq!(body.error.unwrap().message, "This item is out of stock");
+    }
+
+    #[actix_web::test]
+    async fn test_place_order_returns_request_id() {
+        // prepare
+        let order_service = generate_order_service(start_fake_catalog());
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn(request_id::request_id))
+                .app_data(web::Data::new(order_service))
+                .service(place_order),
+        )
+        .await;
+
+        // act
+        let req = generate_order_request(10).insert_header((request_id::REQUEST_ID_HEADER, "request-1")).to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.headers().get(request_id::REQUEST_ID_HEADER).unwrap(), "request-1");
+    }
+
+    #[actix_web::test]
+    async fn test_request_id_is_correlation_id_of_produced_event() {
+        // prepare
+        let mut catalog_network_service = MockCatalogNetworkService::new();
+        catalog_network_service.expect_get_stock().returning(|_| Ok(5));
+        catalog_network_service.expect_get_item_price().returning(|_| Ok(Money::new(2000, "GBP")));
+        let order_service = Arc::new(OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        ));
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn(request_id::request_id))
+                .app_data(web::Data::new(order_service.clone()))
+                .route(
+                    "/order",
+                    web::post().to(
+                        |order_request: web::Json<OrderRequest>,
+                         request_id: web::ReqData<RequestId>,
+                         order_service: web::Data<
+                            Arc<OrderService<MockEventBus, MockOrderDb, MockCatalogNetworkService>>,
+                        >| async move {
+                            order_service.place_order(&order_request, Some(request_id.into_inner().0)).await?;
+                            Ok::<_, ServiceError>(HttpResponse::Ok().finish())
+                        },
+                    ),
+                ),
+        )
+        .await;
+
+        // act
+        let resp = test::call_service(&app, generate_order_request(1).to_request()).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::OK);
+        let request_id = resp.headers().get(request_id::REQUEST_ID_HEADER).unwrap().to_str().unwrap();
+        let produced = order_service.event_bus.produced();
+        let event: Event<OrderPlacedEvent> = serde_json::from_slice(&produced[0].2).unwrap();
+        assert_eq!(event.correlation_id.as_deref(), Some(request_id));
+    }
+
+    #[actix_web::test]
+    async fn test_place_order_catalog_unreachable_is_service_unavailable() {
+        // prepare
+        let order_service = generate_order_service("http://127.0.0.1:1".to_string());
+        let app = test::init_service(App::new().app_data(web::Data::new(order_service)).service(place_order)).await;
+
+        // act
+        let resp = test::call_service(&app, generate_order_request(1).to_request()).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[actix_web::test]
+    async fn test_place_order_oversized_body_is_payload_too_large() {
+        // prepare
+        let order_service = generate_order_service("http://127.0.0.1:1".to_string());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(order_service))
+                .app_data(server_config::json_config(1024))
+                .service(place_order),
+        )
+        .await;
+
+        // act
+        let req = test::TestRequest::post()
+            .uri("/order")
+            .set_json(serde_json::json!({
+                "item_id": 1,
+                "name": "James",
+                "address": "A".repeat(2048),
+                "quantity": 1
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[actix_web::test]
+    async fn test_place_order_non_json_content_type_is_unsupported_media_type() {
+        // prepare
+        let order_service = generate_order_service("http://127.0.0.1:1".to_string());
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn(request_id::request_id))
+                .app_data(web::Data::new(order_service))
+                .app_data(server_config::json_config(1024))
+                .service(place_order),
+        )
+        .await;
+
+        // act with a body that is valid json, sent as plain text
+        let req = test::TestRequest::post()
+            .uri("/order")
+            .insert_header((request_id::REQUEST_ID_HEADER, "abc-123"))
+            .insert_header((CONTENT_TYPE, "text/plain"))
+            .set_payload(r#"{"item_id":1,"name":"James","address":"London","quantity":1}"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["message"], "The Content-Type must be application/json.");
+        assert_eq!(body["request_id"], "abc-123");
+    }
+
+    #[actix_web::test]
+    async fn test_place_order_wrong_typed_field_is_bad_request() {
+        // prepare
+        let order_service = generate_order_service("http://127.0.0.1:1".to_string());
+        let app = test::init_service(App::new().app_data(web::Data::new(order_service)).service(place_order)).await;
+
+        // act with the quantity sent as a string
+        let req = test::TestRequest::post()
+            .uri("/order")
+            .set_json(serde_json::json!({"item_id": 1, "name": "James", "address": "London", "quantity": "2"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["field"], "quantity");
+        assert_eq!(body["error"]["reason"], "invalid type: string \"2\", expected u32");
+    }
+
+    #[actix_web::test]
+    async fn test_place_order_without_item_is_bad_request() {
+        // prepare
+        let order_service = generate_order_service("http://127.0.0.1:1".to_string());
+        let app = test::init_service(App::new().app_data(web::Data::new(order_service)).service(place_order)).await;
+
+        // act with neither an item_id nor a sku
+        let req = test::TestRequest::post()
+            .uri("/order")
+            .set_json(serde_json::json!({"name": "James", "address": "London", "quantity": 1}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_place_orders_missing_field_is_bad_request() {
+        // prepare
+        let order_service = generate_order_service("http://127.0.0.1:1".to_string());
+        let app = test::init_service(App::new().app_data(web::Data::new(order_service)).service(place_orders)).await;
+
+        // act with the second order missing its address
+        let req = test::TestRequest::post()
+            .uri("/orders")
+            .set_json(serde_json::json!([
+                {"item_id": 1, "name": "James", "address": "London"},
+                {"item_id": 2, "name": "James"}
+            ]))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["field"], "[1].address");
+        assert_eq!(
+            body["error"]["message"],
+            "Invalid field `[1].address`: missing field `address`"
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_place_no_orders_is_bad_request() {
+        // prepare
+        let order_service = generate_order_service("http://127.0.0.1:1".to_string());
+        let app = test::init_service(App::new().app_data(web::Data::new(order_service)).service(place_orders)).await;
+
+        // act
+        let req = test::TestRequest::post().uri("/orders").set_json(serde_json::json!([])).to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "data": null,
+                "error": {"status": 400, "message": "At least one order must be provided"},
+                "request_id": ""
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_cancel_unknown_order_is_not_found() {
+        // prepare
+        let order_service = generate_order_service("http://127.0.0.1:1".to_string());
+        let app = test::init_service(App::new().app_data(web::Data::new(order_service)).service(cancel_order)).await;
+
+        // act
+        let resp = test::call_service(&app, test::TestRequest::post().uri("/order/1/cancel").to_request()).await;
+
+        // assert
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -0,0 +1,114 @@
+use common::constants::global_constants;
+use common::utilities::server_config;
+use std::env;
+
+// environment variable used to override the address the http server binds to, e.g. `0.0.0.0:8080`
+const BIND_ADDRESS_VAR: &str = "BIND_ADDRESS";
+// environment variable used to override the base url of the catalog microservice
+const CATALOG_SERVICE_URL_VAR: &str = "CATALOG_SERVICE_URL";
+// environment variable used to override the comma separated kafka brokers
+const KAFKA_BROKERS_VAR: &str = "KAFKA_BROKERS";
+
+/// `OrderServiceConfig` holds where the order microservice listens and the services it connects to.
+///
+/// Fields:
+/// - `bind_address`: The address the HTTP server binds to, e.g. `127.0.0.1:8080`.
+/// - `catalog_base_url`: The base URL of the Catalog Microservice, e.g. `http://127.0.0.1:8081`.
+/// - `kafka_brokers`: The comma separated Kafka brokers, e.g. `127.0.0.1:9092`.
+/// - `workers`: The number of HTTP worker threads, or `None` for one per CPU.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderServiceConfig {
+    pub bind_address: String,
+    pub catalog_base_url: String,
+    pub kafka_brokers: String,
+    pub workers: Option<usize>,
+}
+
+impl Default for OrderServiceConfig {
+    fn default() -> Self {
+        OrderServiceConfig {
+            bind_address: format!("{}:{}", global_constants::HOST, global_constants::ORDER_SERVICE_PORT),
+            catalog_base_url: format!(
+                "http://{}:{}",
+                global_constants::HOST,
+                global_constants::CATALOG_SERVICE_PORT
+            ),
+            kafka_brokers: format!("{}:{}", global_constants::HOST, global_constants::EVENT_BUS_PORT),
+            workers: None,
+        }
+    }
+}
+
+impl OrderServiceConfig {
+    /// Reads the configuration from the `BIND_ADDRESS`, `CATALOG_SERVICE_URL`, `KAFKA_BROKERS` and
+    /// `HTTP_WORKERS` environment variables, using the default of each variable that is unset.
+    pub fn from_env() -> Self {
+        OrderServiceConfig::from_lookup(|var| env::var(var).ok())
+    }
+
+    // reads the configuration through the given lookup of variables, so it can be tested without the environment
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = OrderServiceConfig::default();
+        // empty variables are treated as unset, as an empty address or url is never valid
+        let lookup = |var: &str| lookup(var).filter(|value| !value.trim().is_empty());
+        OrderServiceConfig {
+            bind_address: lookup(BIND_ADDRESS_VAR).unwrap_or(defaults.bind_address),
+            catalog_base_url: lookup(CATALOG_SERVICE_URL_VAR).unwrap_or(defaults.catalog_base_url),
+            kafka_brokers: lookup(KAFKA_BROKERS_VAR).unwrap_or(defaults.kafka_brokers),
+            workers: server_config::parse_workers(lookup(server_config::HTTP_WORKERS_VAR).as_deref()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> =
+            vars.iter().map(|(var, value)| (var.to_string(), value.to_string())).collect();
+        move |var| vars.get(var).cloned()
+    }
+
+    #[test]
+    fn test_defaults_from_constants() {
+        let config = OrderServiceConfig::from_lookup(lookup(&[]));
+
+        assert_eq!(config.bind_address, "127.0.0.1:8080");
+        assert_eq!(config.catalog_base_url, "http://127.0.0.1:8081");
+        assert_eq!(config.kafka_brokers, "127.0.0.1:9092");
+        assert_eq!(config.workers, None);
+    }
+
+    #[test]
+    fn test_from_lookup_overrides_defaults() {
+        let config = OrderServiceConfig::from_lookup(lookup(&[
+            (BIND_ADDRESS_VAR, "0.0.0.0:9000"),
+            (CATALOG_SERVICE_URL_VAR, "http://catalog:8081"),
+            (KAFKA_BROKERS_VAR, "kafka-1:9092,kafka-2:9092"),
+            (server_config::HTTP_WORKERS_VAR, "4"),
+        ]));
+
+        assert_eq!(
+            config,
+            OrderServiceConfig {
+                bind_address: "0.0.0.0:9000".to_string(),
+                catalog_base_url: "http://catalog:8081".to_string(),
+                kafka_brokers: "kafka-1:9092,kafka-2:9092".to_string(),
+                workers: Some(4),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_lookup_falls_back_on_empty_or_invalid_values() {
+        let config = OrderServiceConfig::from_lookup(lookup(&[
+            (BIND_ADDRESS_VAR, ""),
+            (KAFKA_BROKERS_VAR, "  "),
+            (server_config::HTTP_WORKERS_VAR, "none"),
+        ]));
+
+        assert_eq!(config, OrderServiceConfig::default());
+    }
+}
@@ -1,5 +1,7 @@
-use crate::model::OrderRequest;
+use crate::model::ResolvedOrderRequest;
+use common::error::ServiceError;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 
 /// `OrderDbClient` is a mock database structure used for simulating
 /// a order database in a testing or development environment.
@@ -27,7 +29,7 @@ pub trait OrderDb<'a> {
     /// Returns a new instance of the implementor of the `OrderDb` trait.
     ///
     /// # Examples
-    /// ```
+    /// ```ignore
     /// use your_crate::OrderDb;
     /// use your_crate::OrderDbClient;
     ///
@@ -37,21 +39,24 @@ pub trait OrderDb<'a> {
 
     /// Adds a new order to the database.
     ///
-    /// This method takes an `OrderRequest` and creates a new `Order` object,
+    /// This method takes a `ResolvedOrderRequest` and creates a new `Order` object,
     /// assigning it a unique order ID before storing it in the database.
     ///
     /// # Arguments
     /// * `order_request` - The details of the order to be added.
     ///
+    /// # Returns
+    /// Returns the ID assigned to the new order.
+    ///
     /// # Examples
-    /// ```
-    /// use your_crate::{OrderDb, OrderDbClient, model::OrderRequest};
+    /// ```ignore
+    /// use your_crate::{OrderDb, OrderDbClient, model::ResolvedOrderRequest};
     ///
     /// let mut db_client = OrderDbClient::new();
-    /// let order_request = OrderRequest { /* ... */ };
-    /// db_client.add_order(order_request);
+    /// let order_request = ResolvedOrderRequest { /* ... */ };
+    /// let order_id = db_client.add_order(order_request);
     /// ```
-    fn add_order(&mut self, order_request: OrderRequest);
+    fn add_order(&mut self, order_request: ResolvedOrderRequest) -> u32;
 
     /// Retrieves an order by its ID.
     ///
@@ -66,14 +71,80 @@ pub trait OrderDb<'a> {
     /// it returns `Some(&Order)`, otherwise `None`.
     ///
     /// # Examples
-    /// ```
+    /// ```ignore
     /// use your_crate::{OrderDb, OrderDbClient};
     ///
     /// let mut db_client = OrderDbClient::new();
     /// // Assuming an order with ID 1 has been added...
     /// let order = db_client.get_order(1);
     /// ```
+    #[allow(dead_code)]
     fn get_order(&'a self, order_id: u32) -> Option<&'a Order>;
+
+    /// Cancels an order by setting its status to `OrderStatus::Cancelled`.
+    ///
+    /// # Arguments
+    /// * `order_id` - The unique identifier of the order to cancel.
+    ///
+    /// # Returns
+    /// Returns `Ok(())` if the order was cancelled, otherwise an `OrderError` describing why the
+    /// order cannot be cancelled.
+    ///
+    /// # Errors
+    /// * `OrderError::NotFound` - If no order with the given ID exists.
+    /// * `OrderError::AlreadyShipped` - If the order has already been shipped.
+    /// * `OrderError::AlreadyCancelled` - If the order has already been cancelled.
+    fn cancel_order(&mut self, order_id: u32) -> Result<(), OrderError>;
+}
+
+/// An error describing why an order cannot be changed.
+///
+/// # Variants
+///
+/// * `NotFound`: No order exists with the given ID.
+/// * `AlreadyShipped`: The order has been shipped and can no longer be cancelled.
+/// * `AlreadyCancelled`: The order has already been cancelled.
+/// * `PersistenceError`: The order database is unavailable, as its lock was poisoned by a panic.
+#[derive(Debug, PartialEq)]
+pub enum OrderError {
+    NotFound,
+    AlreadyShipped,
+    AlreadyCancelled,
+    PersistenceError,
+}
+
+impl Display for OrderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderError::NotFound => write!(f, "The order does not exist"),
+            OrderError::AlreadyShipped => write!(f, "The order has already been shipped"),
+            OrderError::AlreadyCancelled => write!(f, "The order has already been cancelled"),
+            OrderError::PersistenceError => write!(f, "The order could not be changed, please try again later."),
+        }
+    }
+}
+
+impl From<OrderError> for ServiceError {
+    fn from(err: OrderError) -> Self {
+        match err {
+            OrderError::NotFound => ServiceError::NotFound(err.to_string()),
+            OrderError::AlreadyShipped | OrderError::AlreadyCancelled => ServiceError::Conflict(err.to_string()),
+            OrderError::PersistenceError => ServiceError::Internal(err.to_string()),
+        }
+    }
+}
+
+// moves an order to the cancelled status, rejecting orders that have been shipped or cancelled
+fn cancel(order: Option<&mut Order>) -> Result<(), OrderError> {
+    let order = order.ok_or(OrderError::NotFound)?;
+    match order.status {
+        OrderStatus::Placed => {
+            order.status = OrderStatus::Cancelled;
+            Ok(())
+        }
+        OrderStatus::Shipped => Err(OrderError::AlreadyShipped),
+        OrderStatus::Cancelled => Err(OrderError::AlreadyCancelled),
+    }
 }
 
 impl<'a> OrderDb<'a> for OrderDbClient {
@@ -84,36 +155,51 @@ impl<'a> OrderDb<'a> for OrderDbClient {
         }
     }
 
-    fn add_order(&mut self, order_request: OrderRequest) {
+    fn add_order(&mut self, order_request: ResolvedOrderRequest) -> u32 {
         self.latest_order_id += 1;
         let order = Order::new(self.latest_order_id, order_request);
         self.orders.insert(order.order_id, order);
+        self.latest_order_id
     }
 
     fn get_order(&'a self, order_id: u32) -> Option<&'a Order> {
         self.orders.get(&order_id)
     }
+
+    fn cancel_order(&mut self, order_id: u32) -> Result<(), OrderError> {
+        cancel(self.orders.get_mut(&order_id))
+    }
 }
 
 // mocks
+#[cfg(test)]
 pub struct MockOrderDb {
     expected_order: Option<Order>,
 }
 
+#[cfg(test)]
 impl<'a> OrderDb<'a> for MockOrderDb {
     fn new() -> Self {
         MockOrderDb { expected_order: None }
     }
 
     #[allow(unused_variables)]
-    fn add_order(&mut self, order_request: OrderRequest) {}
+    fn add_order(&mut self, order_request: ResolvedOrderRequest) -> u32 {
+        self.expected_order.as_ref().map_or(0, |order| order.order_id)
+    }
 
     #[allow(unused_variables)]
     fn get_order(&'a self, order_id: u32) -> Option<&'a Order> {
         self.expected_order.as_ref()
     }
+
+    #[allow(unused_variables)]
+    fn cancel_order(&mut self, order_id: u32) -> Result<(), OrderError> {
+        cancel(self.expected_order.as_mut())
+    }
 }
 
+#[cfg(test)]
 impl MockOrderDb {
     pub fn set_expected_order(&mut self, order: Option<Order>) {
         self.expected_order = order;
@@ -130,39 +216,56 @@ impl MockOrderDb {
 /// - `item_id`: The ID of the item ordered.
 /// - `name`: The name of the customer who placed the order.
 /// - `address`: The delivery address for the order.
+/// - `quantity`: The quantity of the item ordered.
+/// - `status`: Where the order is in its lifecycle.
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// use your_crate::model::Order;
 ///
 /// let order = Order::new(1, /* OrderRequest instance */);
 /// ```
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct Order {
     pub order_id: u32,
     pub item_id: u32,
     pub name: String,
     pub address: String,
+    pub quantity: u32,
+    pub status: OrderStatus,
 }
 
 impl Order {
-    pub fn new(order_id: u32, order_request: OrderRequest) -> Self {
+    pub fn new(order_id: u32, order_request: ResolvedOrderRequest) -> Self {
         Order {
             order_id,
             item_id: order_request.item_id,
             name: order_request.name,
             address: order_request.address,
+            quantity: order_request.quantity,
+            status: OrderStatus::Placed,
         }
     }
 }
 
+/// Where an order is in its lifecycle. Orders are `Placed` when added and can be cancelled until
+/// they are `Shipped`.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderStatus {
+    Placed,
+    Shipped,
+    Cancelled,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn produce_fake_order_request() -> OrderRequest {
-        OrderRequest {
+    fn produce_fake_order_request() -> ResolvedOrderRequest {
+        ResolvedOrderRequest {
             item_id: 123,
             name: "James".to_string(),
             address: "23 Bugs Bunny Street, London, E1 4AH".to_string(),
@@ -187,9 +290,10 @@ mod tests {
         let order_request = produce_fake_order_request();
 
         // act
-        client.add_order(order_request.clone());
+        let order_id = client.add_order(order_request.clone());
 
         // assert
+        assert_eq!(order_id, 1);
         assert_eq!(client.latest_order_id, 1);
         assert_eq!(client.orders.len(), 1);
         assert!(client.orders.contains_key(&1));
@@ -210,4 +314,59 @@ mod tests {
         assert!(order.is_some());
         assert!(non_existent_order.is_none());
     }
+
+    #[test]
+    fn test_cancel_order() {
+        // prepare
+        let mut client = OrderDbClient::new();
+        let order_id = client.add_order(produce_fake_order_request());
+
+        // act
+        let result = client.cancel_order(order_id);
+
+        // assert
+        assert_eq!(result, Ok(()));
+        assert_eq!(client.get_order(order_id).unwrap().status, OrderStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_cancel_order_not_found() {
+        // prepare
+        let mut client = OrderDbClient::new();
+
+        // act
+        let result = client.cancel_order(1);
+
+        // assert
+        assert_eq!(result, Err(OrderError::NotFound));
+    }
+
+    #[test]
+    fn test_cancel_order_already_shipped() {
+        // prepare
+        let mut client = OrderDbClient::new();
+        let order_id = client.add_order(produce_fake_order_request());
+        client.orders.get_mut(&order_id).unwrap().status = OrderStatus::Shipped;
+
+        // act
+        let result = client.cancel_order(order_id);
+
+        // assert
+        assert_eq!(result, Err(OrderError::AlreadyShipped));
+        assert_eq!(client.get_order(order_id).unwrap().status, OrderStatus::Shipped);
+    }
+
+    #[test]
+    fn test_cancel_order_already_cancelled() {
+        // prepare
+        let mut client = OrderDbClient::new();
+        let order_id = client.add_order(produce_fake_order_request());
+        client.cancel_order(order_id).unwrap();
+
+        // act
+        let result = client.cancel_order(order_id);
+
+        // assert
+        assert_eq!(result, Err(OrderError::AlreadyCancelled));
+    }
 }
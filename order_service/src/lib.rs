@@ -0,0 +1,9 @@
+pub mod api;
+pub mod config;
+pub mod db;
+pub mod model;
+pub mod networking;
+pub mod server;
+pub mod services;
+
+pub const MICROSERVICE_NAME: &str = "Order";
@@ -1,16 +1,99 @@
-use serde::Deserialize;
+use common::types::money::Money;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct OrderRequest {
-    pub item_id: u32,
+    /// The ID of the ordered item. Can be left out when the item is referenced by `sku` instead, in
+    /// which case the order service resolves it before placing the order.
+    #[serde(default)]
+    pub item_id: Option<u32>,
+    /// The SKU of the ordered item, which takes precedence over `item_id` when present.
+    #[serde(default)]
+    pub sku: Option<String>,
     pub name: String,
     pub address: String,
+    /// Defaults to 1 when missing from the request.
+    #[serde(default = "default_quantity")]
     pub quantity: u32,
 }
 
+fn default_quantity() -> u32 {
+    1
+}
+
 impl Display for OrderRequest {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "OrderReq = ItemId: {}, Quantity: {}", self.item_id, self.quantity)
+        match (&self.sku, self.item_id) {
+            (Some(sku), _) => write!(f, "OrderReq = Sku: {}, Quantity: {}", sku, self.quantity),
+            (None, Some(item_id)) => write!(f, "OrderReq = ItemId: {}, Quantity: {}", item_id, self.quantity),
+            (None, None) => write!(f, "OrderReq = No item, Quantity: {}", self.quantity),
+        }
+    }
+}
+
+/// An `OrderRequest` whose item has been resolved to its ID, as placed and stored by the order service.
+#[derive(Debug, Clone)]
+pub struct ResolvedOrderRequest {
+    pub item_id: u32,
+    pub name: String,
+    pub address: String,
+    pub quantity: u32,
+}
+
+impl ResolvedOrderRequest {
+    pub fn new(item_id: u32, order_request: &OrderRequest) -> Self {
+        ResolvedOrderRequest {
+            item_id,
+            name: order_request.name.clone(),
+            address: order_request.address.clone(),
+            quantity: order_request.quantity,
+        }
+    }
+}
+
+/// The data of the response to a placed order, confirming its total and where it is delivered to.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderConfirmation {
+    pub message: String,
+    pub total: Money,
+}
+
+impl OrderConfirmation {
+    pub fn new(order_request: &OrderRequest, total: Money) -> Self {
+        OrderConfirmation {
+            message: format!(
+                "Order has been placed successfully! The total is {}. It's on its way to: {} at {}",
+                total, order_request.name, order_request.address
+            ),
+            total,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_quantity_defaults_to_one() {
+        let order_request: OrderRequest =
+            serde_json::from_str(r#"{"item_id":1,"name":"James","address":"London"}"#).unwrap();
+        assert_eq!(order_request.quantity, 1);
+    }
+
+    #[test]
+    fn test_quantity_is_deserialized() {
+        let order_request: OrderRequest =
+            serde_json::from_str(r#"{"item_id":1,"name":"James","address":"London","quantity":3}"#).unwrap();
+        assert_eq!(order_request.quantity, 3);
+    }
+
+    #[test]
+    fn test_sku_is_deserialized_without_item_id() {
+        let order_request: OrderRequest =
+            serde_json::from_str(r#"{"sku":"TSHIRT-001","name":"James","address":"London"}"#).unwrap();
+        assert_eq!(order_request.sku.as_deref(), Some("TSHIRT-001"));
+        assert_eq!(order_request.item_id, None);
     }
 }
@@ -0,0 +1,201 @@
+use crate::networking::catalog_network_service::CatalogNetworkService;
+use async_trait::async_trait;
+use common::dto::clothing_item_dto::ClothingItemDTO;
+use common::types::money::Money;
+use networking::NetworkError;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// A cache of catalog item details fetched from the Catalog Microservice.
+///
+/// Entries are kept until the catalog announces that the item has changed through a
+/// `CatalogItemChangedEvent`, which the `OrderService` listens for.
+///
+/// # Fields
+/// - `items`: The cached item details, keyed by item id.
+#[derive(Default)]
+pub struct CatalogCache {
+    items: RwLock<HashMap<u32, ClothingItemDTO>>,
+}
+
+impl CatalogCache {
+    /// Creates a new, empty `CatalogCache`.
+    pub fn new() -> Self {
+        CatalogCache::default()
+    }
+
+    /// Retrieves the cached details of an item, if present.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - A unique identifier for the clothing item.
+    pub fn get(&self, item_id: u32) -> Option<ClothingItemDTO> {
+        self.items.read().unwrap().get(&item_id).cloned()
+    }
+
+    /// Caches the details of an item, replacing any existing entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The details of the clothing item.
+    pub fn insert(&self, item: ClothingItemDTO) {
+        self.items.write().unwrap().insert(item.id, item);
+    }
+
+    /// Removes the cached details of an item, so that they are fetched again on the next request.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - A unique identifier for the clothing item.
+    pub fn invalidate(&self, item_id: u32) {
+        self.items.write().unwrap().remove(&item_id);
+    }
+}
+
+/// A `CatalogNetworkService` that caches item details in a `CatalogCache`.
+///
+/// Item details and prices are served from the cache when possible. Stock is always fetched from
+/// the Catalog Microservice, as it changes with every order placed.
+///
+/// # Fields
+/// - `inner`: The `CatalogNetworkService` used on a cache miss.
+/// - `cache`: The `CatalogCache` shared with the listener that invalidates it.
+pub struct CachedCatalogNetworkService<N: CatalogNetworkService> {
+    inner: N,
+    cache: Arc<CatalogCache>,
+}
+
+impl<N: CatalogNetworkService> CachedCatalogNetworkService<N> {
+    /// Creates a new `CachedCatalogNetworkService`.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The `CatalogNetworkService` used on a cache miss.
+    /// * `cache` - The `CatalogCache` holding the cached item details.
+    pub fn new(inner: N, cache: Arc<CatalogCache>) -> Self {
+        CachedCatalogNetworkService { inner, cache }
+    }
+}
+
+#[async_trait]
+impl<N: CatalogNetworkService + Send + Sync> CatalogNetworkService for CachedCatalogNetworkService<N> {
+    async fn get_stock(&self, item_id: u32) -> Result<u32, NetworkError> {
+        self.inner.get_stock(item_id).await
+    }
+
+    async fn get_stock_batch(&self, item_ids: &[u32]) -> Result<HashMap<u32, u32>, NetworkError> {
+        self.inner.get_stock_batch(item_ids).await
+    }
+
+    async fn get_item_price(&self, item_id: u32) -> Result<Money, NetworkError> {
+        self.get_item_detail(item_id).await.map(|item| item.price)
+    }
+
+    async fn get_item_detail(&self, item_id: u32) -> Result<ClothingItemDTO, NetworkError> {
+        if let Some(item) = self.cache.get(item_id) {
+            return Ok(item);
+        }
+
+        let item = self.inner.get_item_detail(item_id).await?;
+        self.cache.insert(item.clone());
+        Ok(item)
+    }
+
+    async fn get_item_detail_by_sku(&self, sku: &str) -> Result<ClothingItemDTO, NetworkError> {
+        // the cache is keyed by item id, so the item is always fetched, but cached for later lookups by id
+        let item = self.inner.get_item_detail_by_sku(sku).await?;
+        self.cache.insert(item.clone());
+        Ok(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networking::catalog_network_service::{
+        MockCatalogNetworkService, SequenceMockCatalogNetworkService, WhenExhausted,
+    };
+
+    fn generate_item(item_id: u32) -> ClothingItemDTO {
+        ClothingItemDTO {
+            id: item_id,
+            sku: String::new(),
+            name: "T-Shirt".to_string(),
+            description: "desc".to_string(),
+            sizes: vec!["M".to_string()],
+            price: Money::new(2000, "GBP"),
+            currency: "GBP".to_string(),
+            images: vec![],
+            allow_backorder: false,
+            video: "https://example.com/t-shirt-video.mp4".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_item_detail_is_cached() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_item_detail().times(1).returning(|id| Ok(generate_item(id)));
+        let sut = CachedCatalogNetworkService::new(mock_catalog_network_service, Arc::new(CatalogCache::new()));
+
+        // act
+        let first = sut.get_item_detail(1).await.unwrap();
+        let price = sut.get_item_price(1).await.unwrap();
+
+        // assert
+        assert_eq!(first.id, 1);
+        assert_eq!(price, Money::new(2000, "GBP"));
+    }
+
+    #[tokio::test]
+    async fn test_get_stock_is_not_cached() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().times(2).returning(|_| Ok(5));
+        let sut = CachedCatalogNetworkService::new(mock_catalog_network_service, Arc::new(CatalogCache::new()));
+
+        // act
+        sut.get_stock(1).await.unwrap();
+        let stock = sut.get_stock(1).await.unwrap();
+
+        // assert
+        assert_eq!(stock, 5);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_refetches_item_detail() {
+        // prepare
+        let cache = Arc::new(CatalogCache::new());
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_item_detail().times(3).returning(|id| Ok(generate_item(id)));
+        let sut = CachedCatalogNetworkService::new(mock_catalog_network_service, cache.clone());
+        sut.get_item_detail(1).await.unwrap();
+        sut.get_item_detail(2).await.unwrap();
+
+        // act
+        cache.invalidate(1);
+
+        // assert
+        assert!(cache.get(1).is_none());
+        assert!(cache.get(2).is_some());
+        sut.get_item_detail(1).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_item_detail_is_not_cached() {
+        // prepare
+        let mut sequence_catalog_network_service = SequenceMockCatalogNetworkService::new(WhenExhausted::Error);
+        sequence_catalog_network_service.set_item_detail_sequence(vec![Err(Some(503)), Ok(generate_item(1))]);
+        let sut = CachedCatalogNetworkService::new(sequence_catalog_network_service, Arc::new(CatalogCache::new()));
+
+        // act
+        let first = sut.get_item_detail(1).await;
+        let second = sut.get_item_detail(1).await;
+        let third = sut.get_item_detail(1).await;
+
+        // assert that the third call is served from the cache rather than the exhausted sequence
+        assert_eq!(first.unwrap_err().status_code, Some(503));
+        assert_eq!(second.unwrap().id, 1);
+        assert_eq!(third.unwrap().id, 1);
+    }
+}
@@ -1,5 +1,27 @@
 use async_trait::async_trait;
-use networking::NetworkError;
+use common::dto::api_response::ApiResponse;
+use common::dto::clothing_item_dto::ClothingItemDTO;
+use common::types::money::Money;
+use networking::{NetworkError, NetworkErrorType};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::Deserialize;
+use std::collections::HashMap;
+#[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(test)]
+use std::time::Duration;
+
+// the characters encoded in a path segment, leaving only the unreserved characters of RFC 3986
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+// the body of `GET /catalog/stock/{id}`, the stock wrapped in an `ApiResponse`, or the bare stock
+// returned by catalogs from before the envelope was introduced
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StockBody {
+    Enveloped(ApiResponse<u32>),
+    Bare(u32),
+}
 
 /// A client for interacting with the Catalog Microservice.
 ///
@@ -7,21 +29,49 @@ use networking::NetworkError;
 /// Catalog Microservice, handling tasks such as retrieving stock information.
 ///
 /// # Fields
-/// - `host`: The base URL or host address of the Catalog Microservice.
+/// - `host`: The base URL of the Catalog Microservice, optionally including a base path. Trailing
+///   slashes are removed on construction so that endpoint URLs never contain a double slash.
 ///
 /// # Examples
 ///
-/// ```
-/// let api_client = CatalogApiClient {
-///     host: "http://localhost:3000/".to_string(),
-/// };
+/// ```ignore
+/// let api_client = CatalogApiClient::new("http://localhost:3000/");
 /// ```
 pub struct CatalogApiClient {
-    pub host: String,
+    host: String,
+}
+
+impl CatalogApiClient {
+    /// Creates a new `CatalogApiClient` for the Catalog Microservice at the given base URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The base URL of the Catalog Microservice, e.g. `http://localhost:8080` or
+    ///   `http://gateway/api/`.
+    pub fn new(host: &str) -> Self {
+        CatalogApiClient {
+            host: host.trim_end_matches('/').to_string(),
+        }
+    }
+
+    // joins an endpoint path onto the base url, ensuring exactly one slash between them
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.host, path.trim_start_matches('/'))
+    }
+}
+
+// the path of the catalog item with the given id or sku, encoding the sku so that one containing e.g. `/`
+// or `?` stays a single path segment
+fn item_path(item_ref: &str) -> String {
+    format!("catalog/{}", utf8_percent_encode(item_ref, PATH_SEGMENT))
 }
 
 /// Defines network service operations for interacting with the Catalog Microservice.
-#[mockall::automock]
+///
+/// Every call made to the Catalog Microservice belongs on this trait. In tests, `mockall` generates
+/// `MockCatalogNetworkService` from it, so each method added here can be stubbed with its
+/// `expect_*` counterpart without writing any mock code.
+#[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait CatalogNetworkService {
     /// Asynchronously retrieves the amount of stock available for a specific clothing item.
@@ -40,25 +90,489 @@ pub trait CatalogNetworkService {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// # async fn run() -> Result<(), NetworkError> {
-    /// let api_client = CatalogApiClient {
-    ///     host: "http://localhost:3000/".to_string(),
-    /// };
+    /// let api_client = CatalogApiClient::new("http://localhost:3000/");
     /// let stock = api_client.get_stock(123).await?;
     /// # Ok(())
     /// # }
     /// ```
     async fn get_stock(&self, item_id: u32) -> Result<u32, NetworkError>;
+
+    /// Asynchronously retrieves the stock of several clothing items in a single request.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_ids` - The unique identifiers of the clothing items.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` which, on success, contains the stock of each item keyed by its
+    /// identifier, omitting items that do not exist. On failure, returns a `NetworkError`.
+    async fn get_stock_batch(&self, item_ids: &[u32]) -> Result<HashMap<u32, u32>, NetworkError>;
+
+    /// Asynchronously retrieves the price of a specific clothing item.
+    ///
+    /// This method queries the item detail endpoint of the Catalog Microservice and
+    /// extracts the price of the item specified by `item_id`.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - A unique identifier for the clothing item.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` which, on success, contains the price (`Money`) of the
+    /// specified item. On failure, returns a `NetworkError`.
+    async fn get_item_price(&self, item_id: u32) -> Result<Money, NetworkError>;
+
+    /// Asynchronously retrieves the full details of a specific clothing item.
+    ///
+    /// This method queries the `GET /catalog/{id}` endpoint of the Catalog Microservice. Items
+    /// are returned regardless of their stock level.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - A unique identifier for the clothing item.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` which, on success, contains the `ClothingItemDTO` of the
+    /// specified item. On failure, returns a `NetworkError`, with a `404` status code if
+    /// the item does not exist.
+    async fn get_item_detail(&self, item_id: u32) -> Result<ClothingItemDTO, NetworkError>;
+
+    /// Asynchronously retrieves the full details of a clothing item referenced by its SKU.
+    ///
+    /// This method queries the same `GET /catalog/{sku}` endpoint as `get_item_detail`, and is used
+    /// to resolve a SKU into the ID of the item.
+    ///
+    /// # Arguments
+    ///
+    /// * `sku` - The SKU of the clothing item.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` which, on success, contains the `ClothingItemDTO` of the
+    /// specified item. On failure, returns a `NetworkError`, with a `404` status code if
+    /// no item has the SKU.
+    async fn get_item_detail_by_sku(&self, sku: &str) -> Result<ClothingItemDTO, NetworkError>;
 }
 
 #[async_trait]
 impl CatalogNetworkService for CatalogApiClient {
     async fn get_stock(&self, item_id: u32) -> Result<u32, NetworkError> {
-        let url = self.host.clone() + &format!("/catalog/stock/{item_id}");
-        return match networking::execute_get_request::<u32>(&url, None, None).await {
-            Ok(response_data) => Ok(response_data),
-            Err(e) => Err(e),
+        let url = self.url(&format!("catalog/stock/{item_id}"));
+        // an enveloped error is only expected with an error status
+        match networking::execute_get_request::<StockBody>(&url, None, None).await? {
+            StockBody::Enveloped(response) => response.into_result().map_err(|error| NetworkError {
+                status_code: Some(error.status),
+                error: NetworkErrorType::Standard,
+            }),
+            StockBody::Bare(stock) => Ok(stock),
+        }
+    }
+
+    async fn get_stock_batch(&self, item_ids: &[u32]) -> Result<HashMap<u32, u32>, NetworkError> {
+        let url = self.url("catalog/stock");
+        let ids: Vec<String> = item_ids.iter().map(u32::to_string).collect();
+        let mut params = HashMap::new();
+        params.insert("ids".to_string(), ids.join(","));
+        networking::execute_get_request::<HashMap<u32, u32>>(&url, None, Some(params)).await
+    }
+
+    async fn get_item_price(&self, item_id: u32) -> Result<Money, NetworkError> {
+        self.get_item_detail(item_id).await.map(|item| item.price)
+    }
+
+    async fn get_item_detail(&self, item_id: u32) -> Result<ClothingItemDTO, NetworkError> {
+        self.get_item_detail_by_sku(&item_id.to_string()).await
+    }
+
+    async fn get_item_detail_by_sku(&self, sku: &str) -> Result<ClothingItemDTO, NetworkError> {
+        // the catalog resolves the path segment to an item by its id or sku
+        let url = self.url(&item_path(sku));
+        let mut params = HashMap::new();
+        params.insert("include_out_of_stock".to_string(), "true".to_string());
+        networking::execute_get_request::<ClothingItemDTO>(&url, None, Some(params)).await
+    }
+}
+
+/// What a `SequenceMockCatalogNetworkService` returns once a sequence of results has been used up.
+#[cfg(test)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WhenExhausted {
+    /// Starts again from the first result.
+    Cycle,
+    /// Returns a `NetworkError` without a status code.
+    Error,
+}
+
+// a pre-programmed sequence of results, where an error is given as the status code of the `NetworkError`
+#[cfg(test)]
+struct ResultSequence<T> {
+    results: Vec<Result<T, Option<u16>>>,
+    calls: AtomicUsize,
+}
+
+#[cfg(test)]
+impl<T: Clone> ResultSequence<T> {
+    fn new(results: Vec<Result<T, Option<u16>>>) -> Self {
+        ResultSequence {
+            results,
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    // returns the result for the current call and advances the sequence
+    fn next(&self, when_exhausted: WhenExhausted) -> Result<T, NetworkError> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        let result = match when_exhausted {
+            _ if self.results.is_empty() => None,
+            WhenExhausted::Cycle => self.results.get(call % self.results.len()),
+            WhenExhausted::Error => self.results.get(call),
         };
+        match result {
+            Some(Ok(value)) => Ok(value.clone()),
+            Some(Err(status_code)) => Err(NetworkError {
+                status_code: *status_code,
+                error: NetworkErrorType::Standard,
+            }),
+            None => Err(NetworkError {
+                status_code: None,
+                error: NetworkErrorType::Standard,
+            }),
+        }
+    }
+}
+
+/// A mock `CatalogNetworkService` that returns a pre-programmed sequence of results across
+/// successive calls, used to model a flaky Catalog Microservice, e.g. one that fails twice and then
+/// succeeds. Each method has its own sequence, where an `Err` holds the status code of the returned
+/// `NetworkError`, and every call can be delayed to model a slow Catalog Microservice.
+#[cfg(test)]
+pub struct SequenceMockCatalogNetworkService {
+    when_exhausted: WhenExhausted,
+    delay: Duration,
+    stock: ResultSequence<u32>,
+    stock_batches: ResultSequence<HashMap<u32, u32>>,
+    prices: ResultSequence<Money>,
+    item_details: ResultSequence<ClothingItemDTO>,
+}
+
+#[cfg(test)]
+impl SequenceMockCatalogNetworkService {
+    pub fn new(when_exhausted: WhenExhausted) -> Self {
+        SequenceMockCatalogNetworkService {
+            when_exhausted,
+            delay: Duration::ZERO,
+            stock: ResultSequence::new(vec![]),
+            stock_batches: ResultSequence::new(vec![]),
+            prices: ResultSequence::new(vec![]),
+            item_details: ResultSequence::new(vec![]),
+        }
+    }
+
+    /// Delays every call by the given duration before it returns its result.
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay = delay;
+    }
+
+    // waits for the configured delay, if any
+    async fn wait(&self) {
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+    }
+
+    pub fn set_stock_sequence(&mut self, results: Vec<Result<u32, Option<u16>>>) {
+        self.stock = ResultSequence::new(results);
+    }
+
+    pub fn set_stock_batch_sequence(&mut self, results: Vec<Result<HashMap<u32, u32>, Option<u16>>>) {
+        self.stock_batches = ResultSequence::new(results);
+    }
+
+    pub fn set_price_sequence(&mut self, results: Vec<Result<Money, Option<u16>>>) {
+        self.prices = ResultSequence::new(results);
+    }
+
+    pub fn set_item_detail_sequence(&mut self, results: Vec<Result<ClothingItemDTO, Option<u16>>>) {
+        self.item_details = ResultSequence::new(results);
+    }
+
+    /// Returns how many times `get_stock` has been called.
+    pub fn stock_calls(&self) -> usize {
+        self.stock.calls.load(Ordering::SeqCst)
+    }
+
+    /// Returns how many times `get_stock_batch` has been called.
+    pub fn stock_batch_calls(&self) -> usize {
+        self.stock_batches.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl CatalogNetworkService for SequenceMockCatalogNetworkService {
+    #[allow(unused_variables)]
+    async fn get_stock(&self, item_id: u32) -> Result<u32, NetworkError> {
+        self.wait().await;
+        self.stock.next(self.when_exhausted)
+    }
+
+    #[allow(unused_variables)]
+    async fn get_stock_batch(&self, item_ids: &[u32]) -> Result<HashMap<u32, u32>, NetworkError> {
+        self.wait().await;
+        self.stock_batches.next(self.when_exhausted)
+    }
+
+    #[allow(unused_variables)]
+    async fn get_item_price(&self, item_id: u32) -> Result<Money, NetworkError> {
+        self.wait().await;
+        self.prices.next(self.when_exhausted)
+    }
+
+    #[allow(unused_variables)]
+    async fn get_item_detail(&self, item_id: u32) -> Result<ClothingItemDTO, NetworkError> {
+        self.wait().await;
+        self.item_details.next(self.when_exhausted)
+    }
+
+    #[allow(unused_variables)]
+    async fn get_item_detail_by_sku(&self, sku: &str) -> Result<ClothingItemDTO, NetworkError> {
+        self.wait().await;
+        self.item_details.next(self.when_exhausted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+
+    // responds with the stock of item 1 in an envelope, and of item 2 bare, as older catalogs did
+    #[get("/catalog/stock/{item_id}")]
+    async fn fake_get_stock(item_id: web::Path<u32>) -> impl Responder {
+        match item_id.into_inner() {
+            1 => HttpResponse::Ok().json(ApiResponse::success(5, "abc-123")),
+            _ => HttpResponse::Ok().json(7),
+        }
+    }
+
+    #[get("/catalog/{item}")]
+    async fn fake_get_catalog_item(item: web::Path<String>) -> impl Responder {
+        if item.as_str() != "1" && item.as_str() != "TSHIRT-001" {
+            return HttpResponse::NotFound().body("This item does not exist.");
+        }
+
+        HttpResponse::Ok().body(
+            r#"{"id":1,"name":"T-Shirt","description":"desc","sizes":["M"],"price":"GBP 20.00",
+            "currency":"GBP","images":[],"video":"https://example.com/t-shirt-video.mp4"}"#,
+        )
+    }
+
+    // calls every method of a catalog network service, so that each must be implemented by it
+    async fn call_every_method<C: CatalogNetworkService>(
+        sut: &C,
+    ) -> (u32, HashMap<u32, u32>, Money, ClothingItemDTO, ClothingItemDTO) {
+        (
+            sut.get_stock(1).await.unwrap(),
+            sut.get_stock_batch(&[1, 2]).await.unwrap(),
+            sut.get_item_price(1).await.unwrap(),
+            sut.get_item_detail(1).await.unwrap(),
+            sut.get_item_detail_by_sku("TSHIRT-001").await.unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_generated_mock_stubs_every_method() {
+        // prepare
+        let item = ClothingItemDTO {
+            id: 1,
+            sku: String::new(),
+            name: "T-Shirt".to_string(),
+            description: "desc".to_string(),
+            sizes: vec![],
+            price: Money::new(2000, "GBP"),
+            currency: "GBP".to_string(),
+            images: vec![],
+            allow_backorder: false,
+            video: "".to_string(),
+        };
+        let mut sut = MockCatalogNetworkService::new();
+        sut.expect_get_stock().times(1).returning(|_| Ok(5));
+        sut.expect_get_stock_batch()
+            .times(1)
+            .returning(|item_ids| Ok(item_ids.iter().map(|item_id| (*item_id, 5)).collect()));
+        sut.expect_get_item_price().times(1).returning(|_| Ok(Money::new(2000, "GBP")));
+        let expected_item = item.clone();
+        sut.expect_get_item_detail().times(1).return_once(move |_| Ok(expected_item));
+        let expected_item = item.clone();
+        sut.expect_get_item_detail_by_sku().times(1).return_once(move |_| Ok(expected_item));
+
+        // act
+        let (stock, stock_batch, price, item_detail, item_detail_by_sku) = call_every_method(&sut).await;
+
+        // assert
+        assert_eq!(stock, 5);
+        assert_eq!(stock_batch, HashMap::from([(1, 5), (2, 5)]));
+        assert_eq!(price, Money::new(2000, "GBP"));
+        assert_eq!(item_detail.name, item.name);
+        assert_eq!(item_detail_by_sku.id, item.id);
+    }
+
+    // starts a fake catalog microservice on a random port and returns a client pointed at it
+    fn start_fake_catalog() -> CatalogApiClient {
+        let server = HttpServer::new(|| App::new().service(fake_get_stock).service(fake_get_catalog_item))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let address = server.addrs()[0];
+        tokio::spawn(server.run());
+        CatalogApiClient::new(&format!("http://{}", address))
+    }
+
+    #[test]
+    fn test_url_without_trailing_slash() {
+        let sut = CatalogApiClient::new("http://localhost:8080");
+
+        assert_eq!(sut.url("catalog/stock/1"), "http://localhost:8080/catalog/stock/1");
+    }
+
+    #[test]
+    fn test_url_with_trailing_slash() {
+        let sut = CatalogApiClient::new("http://localhost:8080/");
+
+        assert_eq!(sut.url("/catalog/stock/1"), "http://localhost:8080/catalog/stock/1");
+    }
+
+    #[test]
+    fn test_url_with_base_path() {
+        let sut = CatalogApiClient::new("http://gateway/api/");
+
+        assert_eq!(sut.url("catalog/1"), "http://gateway/api/catalog/1");
+    }
+
+    #[test]
+    fn test_item_path_encodes_the_sku() {
+        assert_eq!(item_path("TSHIRT-001"), "catalog/TSHIRT-001");
+        assert_eq!(item_path("A/B?c=1 #2"), "catalog/A%2FB%3Fc%3D1%20%232");
+    }
+
+    #[actix_web::test]
+    async fn test_get_stock_accepts_enveloped_and_bare_stock() {
+        // prepare
+        let sut = start_fake_catalog();
+
+        // act
+        let enveloped = sut.get_stock(1).await;
+        let bare = sut.get_stock(2).await;
+
+        // assert
+        assert_eq!(enveloped.unwrap(), 5);
+        assert_eq!(bare.unwrap(), 7);
+    }
+
+    #[actix_web::test]
+    async fn test_get_item_detail_success() {
+        // prepare
+        let sut = start_fake_catalog();
+
+        // act
+        let result = sut.get_item_detail(1).await;
+
+        // assert
+        let item = result.unwrap();
+        assert_eq!(item.name, "T-Shirt");
+        assert_eq!(item.price, Money::new(2000, "GBP"));
+    }
+
+    #[actix_web::test]
+    async fn test_get_item_detail_not_found() {
+        // prepare
+        let sut = start_fake_catalog();
+
+        // act
+        let result = sut.get_item_detail(2).await;
+
+        // assert
+        assert_eq!(result.unwrap_err().status_code, Some(404));
+    }
+
+    #[actix_web::test]
+    async fn test_get_item_detail_by_sku() {
+        // prepare
+        let sut = start_fake_catalog();
+
+        // act
+        let found = sut.get_item_detail_by_sku("TSHIRT-001").await;
+        let missing = sut.get_item_detail_by_sku("UNKNOWN-001").await;
+
+        // assert
+        assert_eq!(found.unwrap().id, 1);
+        assert_eq!(missing.unwrap_err().status_code, Some(404));
+    }
+
+    #[tokio::test]
+    async fn test_sequence_mock_advances_on_each_call() {
+        // prepare
+        let mut sut = SequenceMockCatalogNetworkService::new(WhenExhausted::Error);
+        sut.set_stock_sequence(vec![Err(Some(500)), Err(None), Ok(5)]);
+
+        // act
+        let first = sut.get_stock(1).await;
+        let second = sut.get_stock(1).await;
+        let third = sut.get_stock(1).await;
+
+        // assert
+        assert_eq!(first.unwrap_err().status_code, Some(500));
+        assert_eq!(second.unwrap_err().status_code, None);
+        assert_eq!(third.unwrap(), 5);
+        assert_eq!(sut.stock_calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_sequence_mock_errors_when_exhausted() {
+        // prepare
+        let mut sut = SequenceMockCatalogNetworkService::new(WhenExhausted::Error);
+        sut.set_price_sequence(vec![Ok(Money::new(2000, "GBP"))]);
+
+        // act
+        let first = sut.get_item_price(1).await;
+        let second = sut.get_item_price(1).await;
+
+        // assert
+        assert_eq!(first.unwrap(), Money::new(2000, "GBP"));
+        assert!(second.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sequence_mock_cycles_when_exhausted() {
+        // prepare
+        let mut sut = SequenceMockCatalogNetworkService::new(WhenExhausted::Cycle);
+        sut.set_stock_sequence(vec![Err(Some(503)), Ok(7)]);
+
+        // act
+        let mut results = vec![];
+        for _ in 0..4 {
+            results.push(sut.get_stock(1).await.map_err(|e| e.status_code));
+        }
+
+        // assert
+        assert_eq!(results, vec![Err(Some(503)), Ok(7), Err(Some(503)), Ok(7)]);
+        assert_eq!(sut.stock_calls(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_sequence_mock_empty_sequence_errors() {
+        // prepare
+        let sut = SequenceMockCatalogNetworkService::new(WhenExhausted::Cycle);
+
+        // act
+        let result = sut.get_item_detail(1).await;
+
+        // assert
+        assert!(result.is_err());
     }
 }
@@ -1 +1,2 @@
+pub mod cached_catalog_network_service;
 pub mod catalog_network_service;
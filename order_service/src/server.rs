@@ -0,0 +1,145 @@
+use crate::api;
+use crate::config::OrderServiceConfig;
+use crate::db::order_db::{OrderDb, OrderDbClient};
+use crate::networking::cached_catalog_network_service::{CachedCatalogNetworkService, CatalogCache};
+use crate::networking::catalog_network_service::CatalogApiClient;
+use crate::services::order_service::{OrderService, DEFAULT_MAX_QUANTITY_PER_ORDER, DEFAULT_SLOW_ORDER_THRESHOLD};
+use crate::MICROSERVICE_NAME;
+use actix_web::dev::{Server, ServerHandle};
+use actix_web::middleware::{from_fn, NormalizePath, TrailingSlash};
+use actix_web::{web, App, HttpServer};
+use common::traits::listener_service::ListenerService;
+use common::utilities::{not_found, request_id, server_config};
+use event_bus::topic::TopicConfig;
+use event_bus::utilities::listeners::BatchCommitConfig;
+use event_bus::EventBus;
+use log::error;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+// environment variable used to record every produced event to an audit topic
+const AUDIT_TOPIC_VAR: &str = "AUDIT_TOPIC";
+// environment variable used to override the largest quantity that can be ordered in a single order
+const MAX_QUANTITY_PER_ORDER_VAR: &str = "MAX_QUANTITY_PER_ORDER";
+// environment variable used to override the latency budget of placing an order, in milliseconds
+const SLOW_ORDER_THRESHOLD_MS_VAR: &str = "SLOW_ORDER_THRESHOLD_MS";
+
+/// `OrderServer` is the HTTP server of the Order Microservice, bound to its address, with the event
+/// listeners of its order service started.
+///
+/// Fields:
+/// - `server`: The HTTP server, which serves requests once it is run.
+/// - `addrs`: The addresses the server is bound to.
+pub struct OrderServer {
+    server: Server,
+    addrs: Vec<SocketAddr>,
+}
+
+impl OrderServer {
+    /// Returns the addresses the server is bound to, e.g. to learn the port of a server bound to port `0`.
+    pub fn addrs(&self) -> &[SocketAddr] {
+        &self.addrs
+    }
+
+    /// Returns a handle used to stop the server, e.g. when another service of the process stops.
+    pub fn handle(&self) -> ServerHandle {
+        self.server.handle()
+    }
+
+    /// Serves requests until the server is stopped.
+    pub async fn run(self) -> std::io::Result<()> {
+        self.server.await
+    }
+}
+
+/// Starts the Order Microservice, serving requests until it is stopped, e.g. by Ctrl-C.
+///
+/// Arguments:
+/// - `config`: Where the service listens and the services it connects to.
+pub async fn initialize_server(config: OrderServiceConfig) -> std::io::Result<()> {
+    bind_server(config, true)?.run().await
+}
+
+/// Builds the order service, starts its event listeners and binds its HTTP server to the address of
+/// the configuration.
+///
+/// Arguments:
+/// - `config`: Where the service listens and the services it connects to, e.g. a `bind_address` of
+///   `127.0.0.1:0` for any free port.
+/// - `handle_signals`: Whether the server stops itself on Ctrl-C, which should be `false` when whoever
+///   runs it handles the signals, e.g. to stop it gracefully.
+///
+/// Returns:
+/// - `OrderServer`: The bound server, which serves requests once it is run.
+pub fn bind_server(config: OrderServiceConfig, handle_signals: bool) -> std::io::Result<OrderServer> {
+    let mock_db = OrderDbClient::new();
+    let mut event_bus = EventBus::new(&config.kafka_brokers);
+    if let Ok(audit_topic) = env::var(AUDIT_TOPIC_VAR) {
+        if let Err(e) = event_bus.set_audit_topic(&audit_topic, MICROSERVICE_NAME) {
+            error!(
+                "Invalid audit topic: {}, {}, events will not be audited",
+                audit_topic, e
+            );
+        }
+    }
+    if let Some(batch_commit) = BatchCommitConfig::from_env() {
+        event_bus.set_batch_commit_config(batch_commit);
+    }
+    let catalog_cache = Arc::new(CatalogCache::new());
+    let catalog_network_service =
+        CachedCatalogNetworkService::new(CatalogApiClient::new(&config.catalog_base_url), catalog_cache.clone());
+    let mut raw_order_service = OrderService::new(mock_db, event_bus, catalog_network_service, catalog_cache);
+    raw_order_service.enable_stock_view();
+    if let Ok(max_quantity) = env::var(MAX_QUANTITY_PER_ORDER_VAR) {
+        match max_quantity.parse() {
+            Ok(max_quantity) => raw_order_service.set_max_quantity_per_order(max_quantity),
+            Err(e) => error!(
+                "Invalid {}: {}, {}, defaulting to {}",
+                MAX_QUANTITY_PER_ORDER_VAR, max_quantity, e, DEFAULT_MAX_QUANTITY_PER_ORDER
+            ),
+        }
+    }
+    if let Ok(threshold_ms) = env::var(SLOW_ORDER_THRESHOLD_MS_VAR) {
+        match threshold_ms.parse() {
+            Ok(threshold_ms) => raw_order_service.set_slow_order_threshold(Duration::from_millis(threshold_ms)),
+            Err(e) => error!(
+                "Invalid {}: {}, {}, defaulting to {}ms",
+                SLOW_ORDER_THRESHOLD_MS_VAR,
+                threshold_ms,
+                e,
+                DEFAULT_SLOW_ORDER_THRESHOLD.as_millis()
+            ),
+        }
+    }
+    raw_order_service.set_topic_config(TopicConfig::from_env());
+    raw_order_service.start_event_listeners();
+    let order_service = Arc::new(raw_order_service);
+    let max_payload_bytes = server_config::max_payload_bytes_from_env();
+    let mut server = HttpServer::new(move || {
+        App::new()
+            .wrap(NormalizePath::new(TrailingSlash::Trim))
+            .wrap(from_fn(request_id::request_id))
+            .app_data(web::Data::new(order_service.clone()))
+            .app_data(server_config::json_config(max_payload_bytes))
+            .app_data(server_config::payload_config(max_payload_bytes))
+            .service(api::place_order)
+            .service(api::place_orders)
+            .service(api::cancel_order)
+            .default_service(web::to(not_found::not_found))
+    })
+    .keep_alive(server_config::keep_alive_from_env());
+    if let Some(workers) = config.workers {
+        server = server.workers(workers);
+    }
+    if !handle_signals {
+        server = server.disable_signals();
+    }
+    let server = server.bind(config.bind_address)?;
+    let addrs = server.addrs();
+    Ok(OrderServer {
+        server: server.run(),
+        addrs,
+    })
+}
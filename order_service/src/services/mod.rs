@@ -1 +1,3 @@
 pub mod order_service;
+pub mod stock_reservations;
+pub mod stock_view;
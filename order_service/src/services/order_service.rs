@@ -1,20 +1,151 @@
-use crate::db::order_db::OrderDb;
-use crate::model::OrderRequest;
+use crate::db::order_db::{OrderDb, OrderError};
+use crate::model::{OrderRequest, ResolvedOrderRequest};
+use crate::networking::cached_catalog_network_service::CatalogCache;
 use crate::networking::catalog_network_service::CatalogNetworkService;
+use crate::services::stock_reservations::StockReservations;
+use crate::services::stock_view::StockView;
 use crate::MICROSERVICE_NAME;
+use common::error::ServiceError;
+use common::traits::listener_service::ListenerService;
+use common::types::money::Money;
 use event_bus::event::Event;
+use event_bus::events::backorder_placed_event::BackorderPlacedEvent;
+use event_bus::events::catalog_item_changed_event::CatalogItemChangedEvent;
+use event_bus::events::order_cancelled_event::OrderCancelledEvent;
 use event_bus::events::order_placed_event::OrderPlacedEvent;
-use event_bus::{topic, EventProducer};
-use log::{error, info};
-use std::sync::Mutex;
+use event_bus::topic::TopicConfig;
+use event_bus::utilities::listeners::OffsetReset;
+use event_bus::{topic, EventListener, EventProducer};
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
 
-pub struct OrderService<E: EventProducer, D: for<'a> OrderDb<'a>, C: CatalogNetworkService> {
-    event_bus: E,
+/// `OrderService` places orders for catalog items and reacts to changes in the catalog.
+///
+/// Fields:
+/// - `event_bus`: Used to broadcast placed orders and to listen for catalog changes.
+/// - `db`: The order database.
+/// - `catalog_network_service`: Used to contact the catalog microservice.
+/// - `catalog_cache`: The cache of catalog items invalidated when the catalog changes.
+/// - `max_quantity_per_order`: The largest quantity that can be ordered in a single order.
+/// - `slow_order_threshold`: The latency budget of `place_order`, slower orders are logged as a warning.
+/// - `reservations`: The stock reserved by orders that are being placed.
+/// - `stock_view`: The local read model of the stock of catalog items, when enabled by `enable_stock_view`.
+/// - `source`: The `source` of every event broadcast by the service.
+/// - `topics`: The concrete topics events are produced to and consumed from.
+pub struct OrderService<E: EventProducer + EventListener, D: for<'a> OrderDb<'a>, C: CatalogNetworkService> {
+    pub(crate) event_bus: E,
     db: Mutex<D>,
     catalog_network_service: C,
+    catalog_cache: Arc<CatalogCache>,
+    max_quantity_per_order: u32,
+    slow_order_threshold: Duration,
+    reservations: StockReservations,
+    stock_view: Option<Arc<StockView>>,
+    source: String,
+    topics: TopicConfig,
 }
 
-impl<E: EventProducer, D: for<'a> OrderDb<'a>, C: CatalogNetworkService> OrderService<E, D, C> {
+/// The largest quantity that can be ordered in a single order unless configured otherwise.
+pub const DEFAULT_MAX_QUANTITY_PER_ORDER: u32 = 100;
+
+/// The latency budget of placing an order unless configured otherwise.
+pub const DEFAULT_SLOW_ORDER_THRESHOLD: Duration = Duration::from_millis(500);
+
+impl<E: EventProducer + EventListener, D: for<'a> OrderDb<'a>, C: CatalogNetworkService> ListenerService
+    for OrderService<E, D, C>
+{
+    fn start_event_listeners(&mut self) {
+        let listener = self
+            .event_bus
+            .create_event_listener::<Event<CatalogItemChangedEvent>>(
+                "order-service",
+                &[self.topics.resolve(topic::CATALOG_ITEM_CHANGED)],
+            )
+            .unwrap_or_else(|_| panic!("Failed to initialize the {} listener", topic::CATALOG_ITEM_CHANGED));
+
+        let catalog_cache = self.catalog_cache.clone();
+        let stock_view = self.stock_view.clone();
+        let mut receiver = listener.get_receiver();
+        tokio::spawn(async move {
+            while let Ok(event) = receiver.recv().await {
+                handle_catalog_item_changed(&catalog_cache, stock_view.as_deref(), &event);
+            }
+        });
+
+        if let Some(stock_view) = &self.stock_view {
+            self.start_stock_view_listeners(stock_view);
+        }
+    }
+}
+
+impl<E: EventProducer + EventListener, D: for<'a> OrderDb<'a>, C: CatalogNetworkService> OrderService<E, D, C> {
+    // keeps the stock view up to date from the orders placed by every order service, applying only the
+    // events the catalog applies to its stock. Each instance consumes in its own consumer group, as an
+    // instance sharing a group would only be assigned some partitions and miss the orders of the others.
+    // The stock of an item is only learnt once it is checked, so the listeners start from the latest
+    // events, as earlier events would be applied to stock that already includes them
+    fn start_stock_view_listeners(&self, stock_view: &Arc<StockView>) {
+        let group_id = format!("{}-{}", STOCK_VIEW_GROUP_ID_PREFIX, Uuid::new_v4());
+        let order_placed_listener = self
+            .event_bus
+            .create_event_listener_with_offset_reset::<Event<OrderPlacedEvent>>(
+                &group_id,
+                &[self.topics.resolve(topic::ORDER_PLACED)],
+                OffsetReset::Latest,
+            )
+            .unwrap_or_else(|_| panic!("Failed to initialize the {} listener", topic::ORDER_PLACED));
+        let view = stock_view.clone();
+        let mut receiver = order_placed_listener.get_receiver();
+        tokio::spawn(async move {
+            while let Ok(event) = receiver.recv().await {
+                view.remove_quantity(event.payload.item_id, event.payload.quantity);
+            }
+        });
+
+        // the catalog decides how much of a backorder is taken from its stock, so the item is forgotten
+        let backorder_placed_listener = self
+            .event_bus
+            .create_event_listener_with_offset_reset::<Event<BackorderPlacedEvent>>(
+                &group_id,
+                &[self.topics.resolve(topic::BACKORDER_PLACED)],
+                OffsetReset::Latest,
+            )
+            .unwrap_or_else(|_| panic!("Failed to initialize the {} listener", topic::BACKORDER_PLACED));
+        let view = stock_view.clone();
+        let mut receiver = backorder_placed_listener.get_receiver();
+        tokio::spawn(async move {
+            while let Ok(event) = receiver.recv().await {
+                view.invalidate(event.payload.item_id);
+            }
+        });
+    }
+}
+
+// the prefix of the consumer group of the stock view listeners, made unique per instance and separate
+// from the group invalidating the catalog cache
+const STOCK_VIEW_GROUP_ID_PREFIX: &str = "order-service-stock-view";
+
+// removes the changed item from the catalog cache so that its details, e.g. its price, are fetched
+// again, and from the stock view as the change may have set its stock
+fn handle_catalog_item_changed(
+    catalog_cache: &CatalogCache,
+    stock_view: Option<&StockView>,
+    event: &Event<CatalogItemChangedEvent>,
+) {
+    info!(
+        "Invalidating cached item: {} after it was {:?}",
+        event.payload.item_id, event.payload.change
+    );
+    catalog_cache.invalidate(event.payload.item_id);
+    if let Some(stock_view) = stock_view {
+        stock_view.invalidate(event.payload.item_id);
+    }
+}
+
+impl<E: EventProducer + EventListener, D: for<'a> OrderDb<'a>, C: CatalogNetworkService> OrderService<E, D, C> {
     /// Creates a new instance of `OrderService`.
     ///
     /// This method initializes the service with a given mock order database, a network service to
@@ -24,73 +155,359 @@ impl<E: EventProducer, D: for<'a> OrderDb<'a>, C: CatalogNetworkService> OrderSe
     /// - `db`: An instance of `MockOrderDb` to be used by this service.
     /// - `event_bus`: An instance of `EventBus` to be used by this service.
     /// - `catalog_network_service`: An instance of `CatalogNetworkService` to be used by this service.
+    /// - `catalog_cache`: The cache of catalog items invalidated when the catalog changes, which should
+    ///   be the cache used by the `catalog_network_service`.
     ///
     /// Returns:
     /// - `OrderService`: A new instance of `OrderService`.
-    pub fn new(db: D, event_bus: E, catalog_network_service: C) -> OrderService<E, D, C> {
+    pub fn new(
+        db: D,
+        event_bus: E,
+        catalog_network_service: C,
+        catalog_cache: Arc<CatalogCache>,
+    ) -> OrderService<E, D, C> {
         let db = Mutex::new(db);
         OrderService {
             event_bus,
             db,
             catalog_network_service,
+            catalog_cache,
+            max_quantity_per_order: DEFAULT_MAX_QUANTITY_PER_ORDER,
+            slow_order_threshold: DEFAULT_SLOW_ORDER_THRESHOLD,
+            reservations: StockReservations::new(),
+            stock_view: None,
+            source: MICROSERVICE_NAME.to_string(),
+            topics: TopicConfig::new(),
         }
     }
 
+    /// Sets the largest quantity that can be ordered in a single order.
+    ///
+    /// Arguments:
+    /// - `max_quantity_per_order`: The maximum quantity, orders above it are rejected with
+    ///   `PlaceOrderError::QuantityTooLarge`.
+    pub fn set_max_quantity_per_order(&mut self, max_quantity_per_order: u32) {
+        self.max_quantity_per_order = max_quantity_per_order;
+    }
+
+    /// Sets the latency budget of placing an order. An order taking longer to place, e.g. because the
+    /// catalog is slow to respond, is logged as a warning with its item id and duration.
+    ///
+    /// Arguments:
+    /// - `slow_order_threshold`: The latency budget. Defaults to `DEFAULT_SLOW_ORDER_THRESHOLD`.
+    pub fn set_slow_order_threshold(&mut self, slow_order_threshold: Duration) {
+        self.slow_order_threshold = slow_order_threshold;
+    }
+
+    /// Sets the `source` of every event broadcast by the service, e.g. when the service is embedded
+    /// in another binary. Defaults to `MICROSERVICE_NAME`.
+    ///
+    /// Arguments:
+    /// - `source`: The name of the service broadcasting the events.
+    pub fn set_source(&mut self, source: &str) {
+        self.source = source.to_string();
+    }
+
+    /// Sets the concrete topics the service produces events to and consumes them from, which must be
+    /// set before `start_event_listeners` to take effect on the listeners.
+    ///
+    /// Arguments:
+    /// - `topics`: The mapping of logical topics to concrete topics, e.g. `TopicConfig::from_env()`.
+    pub fn set_topic_config(&mut self, topics: TopicConfig) {
+        self.topics = topics;
+    }
+
+    /// Enables the local stock view, which `place_order` checks the stock of an item in before
+    /// contacting the catalog, see `StockView`. The view is kept up to date by listeners of the
+    /// `order_placed`, `backorder_placed` and `catalog_item_changed` events.
+    ///
+    /// This must be called before `start_event_listeners` to take effect, as a view that is not
+    /// kept up to date would keep granting stock the catalog no longer has.
+    pub fn enable_stock_view(&mut self) {
+        self.stock_view = Some(Arc::new(StockView::new()));
+    }
+
     /// Places an order for a clothing item.
     ///
     /// This method handles the process of placing an order, including checking stock availability,
-    /// updating the database with the new order, and broadcasting an event to indicate that an order has been placed.
+    /// pricing the order, updating the database with the new order, and broadcasting an event to
+    /// indicate that an order has been placed.
     ///
     /// The function performs the following operations:
-    /// 1. Checks the stock of the requested item using the `catalog_network_service`.
-    /// 2. If the requested quantity exceeds the available stock, it returns an `ItemOutOfStock` error.
-    /// 3. Adds the order to the database.
-    /// 4. Broadcasts an `order_placed` event to notify other parts of the system.
+    /// 1. Rejects the order with a `QuantityTooLarge` error if it exceeds `max_quantity_per_order`.
+    /// 2. Resolves the SKU of the requested item into its ID, if the item is referenced by SKU.
+    /// 3. Checks the stock of the requested item in the stock view, if enabled, or otherwise using the
+    ///    `catalog_network_service`, and reserves the requested quantity from the stock not already
+    ///    reserved by other orders being placed.
+    /// 4. If the requested quantity exceeds the available stock, it fetches the item detail and returns
+    ///    an `ItemOutOfStock` error unless the item has `allow_backorder` set.
+    /// 5. Fetches the price of the item and multiplies it by the quantity to compute the order total.
+    /// 6. Adds the order to the database.
+    /// 7. Broadcasts an `order_placed` event, including the total, to notify other parts of the system,
+    ///    or a `backorder_placed` event if there was not enough stock for the order.
     ///
     /// Note: In case of a failure while broadcasting the event, the error is logged but not propagated.
     ///       The order placement is considered successful even if event broadcasting fails.
     ///
+    /// Latency: an order taking longer than `slow_order_threshold` to place, whether it succeeds or
+    /// fails, is logged as a warning.
+    ///
+    /// Cancellation: the returned future may be dropped at any `.await`, e.g. when the client
+    /// disconnects. The following invariants hold wherever it is dropped:
+    /// * The reservation is held by a drop guard, so it is released whether the order is recorded,
+    ///   rejected or abandoned, and an abandoned order never leaks reserved stock.
+    /// * Nothing is written before the item is priced, so an order dropped before then leaves no trace.
+    /// * The order is written to the database without an intervening `.await`, and the event is
+    ///   broadcast straight after. An order dropped whilst its event is being broadcast is recorded,
+    ///   but may not be announced to the catalog.
+    ///
     /// Arguments:
-    /// * `order_request`: The `OrderRequest` object containing details of the item to be ordered, including item ID and quantity.
+    /// * `order_request`: The `OrderRequest` object containing details of the item to be ordered, including item ID
+    ///   and quantity.
+    /// * `correlation_id`: The correlation id of the `order_placed` event, e.g. the id of the HTTP request.
     ///
     /// Returns:
-    /// * `Result<(), PlaceOrderError>`: Ok(()) if the order is successfully placed, or an appropriate error in case of failure.
+    /// * `Result<Money, PlaceOrderError>`: The total cost of the order if it is successfully placed, or an
+    ///   appropriate error in case of failure.
     ///
     /// Errors:
+    /// * `QuantityTooLarge`: If the requested quantity exceeds the maximum quantity per order.
+    /// * `UnknownItem`: If no item has the requested SKU.
+    /// * `MissingItem`: If the request has neither an item ID nor a SKU.
     /// * `CatalogNetworkError`: If there is a failure in network communication with the catalog service.
-    /// * `ItemOutOfStock`: If the requested quantity exceeds the available stock.
-    pub async fn place_order(&self, order_request: &OrderRequest) -> Result<(), PlaceOrderError> {
+    /// * `ItemOutOfStock`: If the requested quantity exceeds the available stock and the item cannot be
+    ///   backordered.
+    /// * `PriceUnavailable`: If the price of the item could not be retrieved from the catalog service.
+    /// * `PersistenceError`: If the order could not be added to the database, as its lock is poisoned.
+    pub async fn place_order(
+        &self,
+        order_request: &OrderRequest,
+        correlation_id: Option<String>,
+    ) -> Result<Money, PlaceOrderError> {
         info!("Handling a request to place an order: {}", order_request);
-        // check the stock of the item
-        let stock = self.catalog_network_service.get_stock(order_request.item_id).await.map_err(|err| {
+        let started = Instant::now();
+        let result = self.place_order_unmeasured(order_request, correlation_id).await;
+        let elapsed = started.elapsed();
+        if elapsed > self.slow_order_threshold {
+            warn!(
+                item_id = order_request.item_id, elapsed_ms = elapsed.as_millis() as u64;
+                "Placing an order took {}ms, exceeding the latency budget of {}ms",
+                elapsed.as_millis(),
+                self.slow_order_threshold.as_millis()
+            );
+        }
+        result
+    }
+
+    // places an order as described by `place_order`, which measures how long it takes
+    async fn place_order_unmeasured(
+        &self,
+        order_request: &OrderRequest,
+        correlation_id: Option<String>,
+    ) -> Result<Money, PlaceOrderError> {
+        self.check_quantity(order_request)?;
+        let order_request = &self.resolve_item(order_request).await?;
+        let stock = self.get_stock(order_request.item_id).await?;
+
+        // released when this function returns or its future is dropped, see the cancellation invariants
+        let reservation = self.reservations.reserve(order_request.item_id, order_request.quantity, stock);
+        if reservation.shortfall() > 0 {
+            if !self.allows_backorder(order_request.item_id).await? {
+                return Err(PlaceOrderError::ItemOutOfStock);
+            }
+            let total = self.price_order(order_request).await?;
+            self.record_backorder(order_request, reservation.shortfall(), &total, correlation_id).await?;
+            return Ok(total);
+        }
+
+        let total = self.price_order(order_request).await?;
+        self.record_order(order_request, &total, correlation_id).await?;
+        Ok(total)
+    }
+
+    // checks the stock of an item in the stock view, falling back to the catalog for items it does not know
+    async fn get_stock(&self, item_id: u32) -> Result<u32, PlaceOrderError> {
+        if let Some(stock) = self.stock_view.as_ref().and_then(|stock_view| stock_view.get(item_id)) {
+            return Ok(stock);
+        }
+        let stock = self.catalog_network_service.get_stock(item_id).await.map_err(|err| {
+            error!("An error has occurred whilst contacting Catalog: {:?}", err);
+            PlaceOrderError::CatalogNetworkError
+        })?;
+        if let Some(stock_view) = &self.stock_view {
+            stock_view.insert(item_id, stock);
+        }
+        Ok(stock)
+    }
+
+    // rejects an order for more than the maximum quantity per order
+    fn check_quantity(&self, order_request: &OrderRequest) -> Result<(), PlaceOrderError> {
+        if order_request.quantity > self.max_quantity_per_order {
+            return Err(PlaceOrderError::QuantityTooLarge);
+        }
+        Ok(())
+    }
+
+    // resolves the item of an order into its id, looking up the sku of an order referencing its item by sku
+    async fn resolve_item(&self, order_request: &OrderRequest) -> Result<ResolvedOrderRequest, PlaceOrderError> {
+        let sku = match (&order_request.sku, order_request.item_id) {
+            (Some(sku), _) => sku,
+            (None, Some(item_id)) => return Ok(ResolvedOrderRequest::new(item_id, order_request)),
+            (None, None) => return Err(PlaceOrderError::MissingItem),
+        };
+        let item = self.catalog_network_service.get_item_detail_by_sku(sku).await.map_err(|err| {
+            if err.status_code == Some(404) {
+                return PlaceOrderError::UnknownItem;
+            }
+            error!("An error has occurred whilst contacting Catalog: {:?}", err);
+            PlaceOrderError::CatalogNetworkError
+        })?;
+        Ok(ResolvedOrderRequest::new(item.id, order_request))
+    }
+
+    // checks the item detail in the catalog for whether the item can be ordered beyond its stock
+    async fn allows_backorder(&self, item_id: u32) -> Result<bool, PlaceOrderError> {
+        let item = self.catalog_network_service.get_item_detail(item_id).await.map_err(|err| {
             error!("An error has occurred whilst contacting Catalog: {:?}", err);
             PlaceOrderError::CatalogNetworkError
         })?;
+        Ok(item.allow_backorder)
+    }
+
+    /// Places several orders at once, e.g. the contents of a cart.
+    ///
+    /// Rather than checking the stock of each item separately, the stock of every item in the cart
+    /// is fetched with a single `get_stock_batch` call, falling back to a `get_stock` call per item
+    /// only if the catalog does not serve the batch endpoint, i.e. it responds 404 or 405. Any other
+    /// failure, e.g. a 5xx or a timeout, fails the orders rather than multiplying the load on the
+    /// catalog. The orders are only placed if no order exceeds `max_quantity_per_order`, every item
+    /// has enough stock for its total quantity across the cart, and every order can be priced; items
+    /// missing from the batch response are treated as out of stock. Items referenced by SKU are
+    /// resolved into their IDs first, and an unknown SKU fails every order with `UnknownItem`.
+    ///
+    /// Arguments:
+    /// * `order_requests`: The orders to place.
+    /// * `correlation_id`: The correlation id of every `order_placed` event, e.g. the id of the HTTP request.
+    ///
+    /// Returns:
+    /// * `Result<Vec<Money>, PlaceOrderError>`: The total cost of each order, in the order they were
+    ///   requested, or an appropriate error if none of the orders were placed.
+    pub async fn place_orders(
+        &self,
+        order_requests: &[OrderRequest],
+        correlation_id: Option<String>,
+    ) -> Result<Vec<Money>, PlaceOrderError> {
+        info!("Handling a request to place {} orders", order_requests.len());
+        let mut resolved_requests = Vec::with_capacity(order_requests.len());
+        for order_request in order_requests {
+            self.check_quantity(order_request)?;
+            resolved_requests.push(self.resolve_item(order_request).await?);
+        }
+        let order_requests = &resolved_requests;
+
+        let mut item_ids = vec![];
+        let mut quantities: HashMap<u32, u32> = HashMap::new();
+        for order_request in order_requests {
+            let quantity = quantities.entry(order_request.item_id).or_insert_with(|| {
+                item_ids.push(order_request.item_id);
+                0
+            });
+            *quantity = quantity.saturating_add(order_request.quantity);
+        }
+
+        let stock = self.get_stock_levels(&item_ids).await?;
+        // released when this function returns or its future is dropped, as with `place_order`
+        let mut reservations = Vec::with_capacity(quantities.len());
+        for (item_id, quantity) in &quantities {
+            let reservation = self.reservations.reserve(*item_id, *quantity, stock.get(item_id).copied().unwrap_or(0));
+            if reservation.shortfall() > 0 {
+                return Err(PlaceOrderError::ItemOutOfStock);
+            }
+            reservations.push(reservation);
+        }
+
+        let mut totals = Vec::with_capacity(order_requests.len());
+        for order_request in order_requests {
+            totals.push(self.price_order(order_request).await?);
+        }
+        for (order_request, total) in order_requests.iter().zip(&totals) {
+            self.record_order(order_request, total, correlation_id.clone()).await?;
+        }
+        Ok(totals)
+    }
+
+    // fetches the stock of the items in a single request, or one request per item if the catalog does not
+    // serve the batch endpoint
+    async fn get_stock_levels(&self, item_ids: &[u32]) -> Result<HashMap<u32, u32>, PlaceOrderError> {
+        match self.catalog_network_service.get_stock_batch(item_ids).await {
+            Ok(stock) => return Ok(stock),
+            Err(err) if matches!(err.status_code, Some(404) | Some(405)) => warn!(
+                "The batch stock endpoint is unavailable, checking the stock of each item instead: {:?}",
+                err
+            ),
+            Err(err) => {
+                error!("An error has occurred whilst contacting Catalog: {:?}", err);
+                return Err(PlaceOrderError::CatalogNetworkError);
+            }
+        }
 
-        if order_request.quantity > stock {
-            return Err(PlaceOrderError::ItemOutOfStock);
+        let mut stock = HashMap::new();
+        for item_id in item_ids {
+            let item_stock = self.catalog_network_service.get_stock(*item_id).await.map_err(|err| {
+                error!("An error has occurred whilst contacting Catalog: {:?}", err);
+                PlaceOrderError::CatalogNetworkError
+            })?;
+            stock.insert(*item_id, item_stock);
         }
+        Ok(stock)
+    }
 
-        // place order
-        let mut db_guard = self.db.lock().unwrap();
-        db_guard.add_order(order_request.clone());
+    // computes the total cost of an order from the item's price in the catalog
+    async fn price_order(&self, order_request: &ResolvedOrderRequest) -> Result<Money, PlaceOrderError> {
+        let price = self.catalog_network_service.get_item_price(order_request.item_id).await.map_err(|err| {
+            error!(
+                "An error has occurred whilst fetching the price from Catalog: {:?}",
+                err
+            );
+            PlaceOrderError::PriceUnavailable
+        })?;
+        price.checked_mul(order_request.quantity).map_err(|err| {
+            error!(
+                "The total cost of {} of item: {} cannot be computed: {:?}",
+                order_request.quantity, order_request.item_id, err
+            );
+            PlaceOrderError::PriceUnavailable
+        })
+    }
+
+    // adds an order whose stock has been checked to the database and broadcasts that it was placed
+    async fn record_order(
+        &self,
+        order_request: &ResolvedOrderRequest,
+        total: &Money,
+        correlation_id: Option<String>,
+    ) -> Result<(), PlaceOrderError> {
+        let order_id = self.lock_db(PlaceOrderError::PersistenceError)?.add_order(order_request.clone());
+        info!(order_id = order_id, item_id = order_request.item_id; "Order placed");
 
         // send event for order placed
         let inner_event = OrderPlacedEvent {
             item_id: order_request.item_id,
             quantity: order_request.quantity,
+            total: Some(total.clone()),
+            reservation_id: None,
         };
 
-        let event = Event::new(
+        let event = self.event_bus.create_event(
             "order_placed".to_string(),
             inner_event,
-            MICROSERVICE_NAME.to_string(),
-            None,
+            self.source.clone(),
+            correlation_id,
             None,
         );
 
         self.event_bus
-            .broadcast_event(event, topic::ORDER_PLACED, &order_request.item_id.to_string())
+            .broadcast_event(event, self.topics.resolve(topic::ORDER_PLACED), order_request.item_id)
             .await
             .map_err(|err| {
                 error!(
@@ -100,7 +517,110 @@ impl<E: EventProducer, D: for<'a> OrderDb<'a>, C: CatalogNetworkService> OrderSe
                 );
                 // consider how to handle this error for example, log it, alert, or retry
                 // currently, this error is logged but not propagated
-                ()
+            })
+            .ok();
+        Ok(())
+    }
+
+    // adds an order placed on backorder to the database and broadcasts that it was placed
+    async fn record_backorder(
+        &self,
+        order_request: &ResolvedOrderRequest,
+        backordered_quantity: u32,
+        total: &Money,
+        correlation_id: Option<String>,
+    ) -> Result<(), PlaceOrderError> {
+        let order_id = self.lock_db(PlaceOrderError::PersistenceError)?.add_order(order_request.clone());
+        info!(order_id = order_id, item_id = order_request.item_id; "Order placed on backorder");
+
+        let inner_event = BackorderPlacedEvent {
+            item_id: order_request.item_id,
+            quantity: order_request.quantity,
+            backordered_quantity,
+            total: Some(total.clone()),
+        };
+
+        let event = self.event_bus.create_event(
+            "backorder_placed".to_string(),
+            inner_event,
+            self.source.clone(),
+            correlation_id,
+            None,
+        );
+
+        self.event_bus
+            .broadcast_event(
+                event,
+                self.topics.resolve(topic::BACKORDER_PLACED),
+                order_request.item_id,
+            )
+            .await
+            .map_err(|err| {
+                error!(
+                    "Could not send {} event, error occurred: {:?}",
+                    topic::BACKORDER_PLACED,
+                    err
+                );
+            })
+            .ok();
+        Ok(())
+    }
+
+    // locks the database, failing with `err` rather than panicking when a panic whilst it was locked poisoned it
+    fn lock_db<Err>(&self, err: Err) -> Result<MutexGuard<'_, D>, Err> {
+        self.db.lock().map_err(|_| {
+            error!("The order database is unavailable, as its lock was poisoned by a panic");
+            err
+        })
+    }
+
+    /// Cancels a placed order.
+    ///
+    /// Once the order is cancelled in the database, an `order_cancelled` event is broadcast so that
+    /// the catalog can restock the ordered quantity. As with `place_order`, a failure to broadcast
+    /// the event is logged but not propagated.
+    ///
+    /// Arguments:
+    /// * `order_id`: The ID of the order to cancel.
+    ///
+    /// Returns:
+    /// * `Result<(), OrderError>`: `Ok(())` if the order was cancelled, or why it could not be.
+    ///
+    /// Errors:
+    /// * `NotFound`: If the order does not exist.
+    /// * `AlreadyShipped`: If the order has already been shipped.
+    /// * `AlreadyCancelled`: If the order has already been cancelled.
+    /// * `PersistenceError`: If the database is unavailable, as its lock is poisoned.
+    pub async fn cancel_order(&self, order_id: u32) -> Result<(), OrderError> {
+        info!("Handling a request to cancel order: {}", order_id);
+        let order = {
+            let mut db = self.lock_db(OrderError::PersistenceError)?;
+            db.cancel_order(order_id)?;
+            db.get_order(order_id).cloned().ok_or(OrderError::NotFound)?
+        };
+        info!(order_id = order_id, item_id = order.item_id; "Order cancelled");
+
+        let event = self.event_bus.create_event(
+            "order_cancelled".to_string(),
+            OrderCancelledEvent {
+                order_id,
+                item_id: order.item_id,
+                quantity: order.quantity,
+            },
+            self.source.clone(),
+            None,
+            None,
+        );
+
+        self.event_bus
+            .broadcast_event(event, self.topics.resolve(topic::ORDER_CANCELLED), order.item_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Could not send {} event, error occurred: {:?}",
+                    topic::ORDER_CANCELLED,
+                    err
+                );
             })
             .ok();
 
@@ -108,23 +628,56 @@ impl<E: EventProducer, D: for<'a> OrderDb<'a>, C: CatalogNetworkService> OrderSe
     }
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Debug)]
 pub enum PlaceOrderError {
     ItemOutOfStock,
     CatalogNetworkError,
+    PriceUnavailable,
+    QuantityTooLarge,
+    UnknownItem,
+    MissingItem,
+    PersistenceError,
+}
+
+impl From<PlaceOrderError> for ServiceError {
+    fn from(err: PlaceOrderError) -> Self {
+        match err {
+            PlaceOrderError::ItemOutOfStock => ServiceError::Conflict("This item is out of stock".to_string()),
+            PlaceOrderError::UnknownItem => ServiceError::NotFound("This item does not exist.".to_string()),
+            PlaceOrderError::MissingItem => {
+                ServiceError::BadRequest("The order must reference its item by item_id or sku".to_string())
+            }
+            PlaceOrderError::QuantityTooLarge => {
+                ServiceError::BadRequest("The quantity exceeds the maximum quantity per order".to_string())
+            }
+            PlaceOrderError::CatalogNetworkError | PlaceOrderError::PriceUnavailable => ServiceError::Upstream(
+                "An error occurred and some of our systems are down, please try again later.".to_string(),
+            ),
+            PlaceOrderError::PersistenceError => {
+                ServiceError::Internal("The order could not be saved, please try again later.".to_string())
+            }
+        }
+    }
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::order_db::{MockOrderDb, Order};
-    use crate::networking::catalog_network_service::MockCatalogNetworkService;
+    use crate::db::order_db::{MockOrderDb, Order, OrderDbClient, OrderStatus};
+    use crate::networking::catalog_network_service::{
+        MockCatalogNetworkService, SequenceMockCatalogNetworkService, WhenExhausted,
+    };
+    use common::dto::clothing_item_dto::ClothingItemDTO;
+    use common::utilities::logger;
+    use event_bus::events::catalog_item_changed_event::CatalogItemChange;
     use event_bus::*;
     use networking::{NetworkError, NetworkErrorType};
+    use std::time::Duration;
 
     fn generate_random_order() -> Order {
         Order::new(
             1,
-            OrderRequest {
+            ResolvedOrderRequest {
                 item_id: 1,
                 name: "something".to_string(),
                 address: "hello".to_string(),
@@ -135,7 +688,8 @@ mod tests {
 
     fn generate_random_order_request() -> OrderRequest {
         OrderRequest {
-            item_id: 1,
+            item_id: Some(1),
+            sku: None,
             name: "something".to_string(),
             address: "hello".to_string(),
             quantity: 22,
@@ -151,7 +705,12 @@ mod tests {
         mock_order_db.set_expected_order(Some(generate_random_order()));
 
         // act
-        let sut = OrderService::new(mock_order_db, mock_event_listener, mock_catalog_network_service);
+        let sut = OrderService::new(
+            mock_order_db,
+            mock_event_listener,
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
 
         // assert that db is mocked and accessible to confirm initialization
         assert_eq!(
@@ -172,10 +731,15 @@ mod tests {
                 error: NetworkErrorType::Standard,
             })
         });
-        let sut = OrderService::new(mock_order_db, mock_event_listener, mock_catalog_network_service);
+        let sut = OrderService::new(
+            mock_order_db,
+            mock_event_listener,
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
 
         // act
-        let result = sut.place_order(&generate_random_order_request()).await;
+        let result = sut.place_order(&generate_random_order_request(), None).await;
 
         // assert
         assert!(result.is_err());
@@ -189,16 +753,94 @@ mod tests {
         let mock_order_db = MockOrderDb::new();
         let mut mock_catalog_network_service = MockCatalogNetworkService::new();
         mock_catalog_network_service.expect_get_stock().return_once(move |_| Ok(21));
-        let sut = OrderService::new(mock_order_db, mock_event_listener, mock_catalog_network_service);
+        mock_catalog_network_service
+            .expect_get_item_detail()
+            .return_once(move |item_id| Ok(generate_item_detail(item_id, false)));
+        let sut = OrderService::new(
+            mock_order_db,
+            mock_event_listener,
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
 
         // act
-        let result = sut.place_order(&generate_random_order_request()).await;
+        let result = sut.place_order(&generate_random_order_request(), None).await;
 
         // assert
         assert!(result.is_err());
         assert!(result.unwrap_err() == PlaceOrderError::ItemOutOfStock)
     }
 
+    fn generate_item_detail(item_id: u32, allow_backorder: bool) -> ClothingItemDTO {
+        ClothingItemDTO {
+            id: item_id,
+            sku: String::new(),
+            name: "T-Shirt".to_string(),
+            description: "desc".to_string(),
+            sizes: vec![],
+            price: Money::new(2000, "GBP"),
+            currency: "GBP".to_string(),
+            images: vec![],
+            allow_backorder,
+            video: "".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_place_order_backorder_allowed() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().return_once(move |_| Ok(5));
+        mock_catalog_network_service
+            .expect_get_item_detail()
+            .return_once(move |item_id| Ok(generate_item_detail(item_id, true)));
+        mock_catalog_network_service
+            .expect_get_item_price()
+            .return_once(move |_| Ok(Money::new(2000, "GBP")));
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.place_order(&generate_random_order_request(), None).await;
+
+        // assert that the 17 items not in stock are backordered
+        assert_eq!(result.unwrap(), Money::new(44000, "GBP"));
+        let produced = sut.event_bus.produced();
+        assert_eq!(produced.len(), 1);
+        assert_eq!(produced[0].0, topic::BACKORDER_PLACED);
+        let event: Event<BackorderPlacedEvent> = serde_json::from_slice(&produced[0].2).unwrap();
+        assert_eq!(event.payload.quantity, 22);
+        assert_eq!(event.payload.backordered_quantity, 17);
+    }
+
+    #[tokio::test]
+    async fn test_place_order_backorder_disallowed() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().return_once(move |_| Ok(5));
+        mock_catalog_network_service
+            .expect_get_item_detail()
+            .return_once(move |item_id| Ok(generate_item_detail(item_id, false)));
+        mock_catalog_network_service.expect_get_item_price().times(0);
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.place_order(&generate_random_order_request(), None).await;
+
+        // assert
+        assert_eq!(result.unwrap_err(), PlaceOrderError::ItemOutOfStock);
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
     #[tokio::test]
     async fn test_place_order_success() {
         // prepare
@@ -206,12 +848,942 @@ mod tests {
         let mock_order_db = MockOrderDb::new();
         let mut mock_catalog_network_service = MockCatalogNetworkService::new();
         mock_catalog_network_service.expect_get_stock().return_once(move |_| Ok(25));
-        let sut = OrderService::new(mock_order_db, mock_event_listener, mock_catalog_network_service);
+        mock_catalog_network_service
+            .expect_get_item_price()
+            .return_once(move |_| Ok(Money::new(2000, "GBP")));
+        let sut = OrderService::new(
+            mock_order_db,
+            mock_event_listener,
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.place_order(&generate_random_order_request(), None).await;
+
+        // assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_place_order_with_poisoned_db_is_persistence_error() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().returning(|_| Ok(25));
+        mock_catalog_network_service.expect_get_item_price().returning(|_| Ok(Money::new(2000, "GBP")));
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = sut.db.lock().unwrap();
+            panic!("poisoning the db lock");
+        }));
+
+        // act
+        let first = sut.place_order(&generate_random_order_request(), None).await;
+        let second = sut.place_order(&generate_random_order_request(), None).await;
+
+        // assert
+        assert_eq!(first, Err(PlaceOrderError::PersistenceError));
+        assert_eq!(second, Err(PlaceOrderError::PersistenceError));
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_place_order_by_sku_resolves_item_id() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service
+            .expect_get_item_detail_by_sku()
+            .withf(|sku| sku == "TSHIRT-001")
+            .return_once(|_| Ok(generate_item_detail(7, false)));
+        mock_catalog_network_service
+            .expect_get_stock()
+            .withf(|item_id| *item_id == 7)
+            .return_once(|_| Ok(25));
+        mock_catalog_network_service
+            .expect_get_item_price()
+            .withf(|item_id| *item_id == 7)
+            .return_once(|_| Ok(Money::new(2000, "GBP")));
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        let order_request = OrderRequest {
+            item_id: None,
+            sku: Some("TSHIRT-001".to_string()),
+            ..generate_random_order_request()
+        };
+
+        // act
+        let result = sut.place_order(&order_request, None).await;
+
+        // assert
+        assert!(result.is_ok());
+        let produced = sut.event_bus.produced();
+        assert_eq!(produced.len(), 1);
+        assert_eq!(produced[0].1, "7");
+    }
+
+    #[tokio::test]
+    async fn test_place_order_without_item_id_or_sku() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().never();
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        let order_request = OrderRequest {
+            item_id: None,
+            ..generate_random_order_request()
+        };
+
+        // act
+        let result = sut.place_order(&order_request, None).await;
+
+        // assert
+        assert_eq!(result.unwrap_err(), PlaceOrderError::MissingItem);
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_place_order_unknown_sku() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_item_detail_by_sku().return_once(|_| {
+            Err(NetworkError {
+                status_code: Some(404),
+                error: NetworkErrorType::Standard,
+            })
+        });
+        mock_catalog_network_service.expect_get_stock().never();
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        let order_request = OrderRequest {
+            sku: Some("UNKNOWN-001".to_string()),
+            ..generate_random_order_request()
+        };
+
+        // act
+        let result = sut.place_order(&order_request, None).await;
+
+        // assert
+        assert_eq!(result, Err(PlaceOrderError::UnknownItem));
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
+    // a catalog whose item has stock, but whose price is never returned
+    struct StalledPriceCatalogNetworkService;
+
+    #[async_trait::async_trait]
+    impl CatalogNetworkService for StalledPriceCatalogNetworkService {
+        async fn get_stock(&self, _item_id: u32) -> Result<u32, NetworkError> {
+            Ok(25)
+        }
+
+        async fn get_stock_batch(&self, item_ids: &[u32]) -> Result<HashMap<u32, u32>, NetworkError> {
+            Ok(item_ids.iter().map(|item_id| (*item_id, 25)).collect())
+        }
+
+        async fn get_item_price(&self, _item_id: u32) -> Result<Money, NetworkError> {
+            std::future::pending().await
+        }
+
+        async fn get_item_detail(&self, item_id: u32) -> Result<ClothingItemDTO, NetworkError> {
+            Ok(generate_item_detail(item_id, false))
+        }
+
+        async fn get_item_detail_by_sku(&self, _sku: &str) -> Result<ClothingItemDTO, NetworkError> {
+            Ok(generate_item_detail(1, false))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_place_order_dropped_mid_flight_releases_reservation() {
+        // prepare
+        let sut = OrderService::new(
+            OrderDbClient::new(),
+            MockEventBus::new(),
+            StalledPriceCatalogNetworkService,
+            Arc::new(CatalogCache::new()),
+        );
+        let order_request = generate_random_order_request();
+
+        // act by dropping the order whilst it is being priced, after its stock has been reserved
+        {
+            let mut place_order = Box::pin(sut.place_order(&order_request, None));
+            let pending = tokio::time::timeout(Duration::from_millis(20), &mut place_order).await;
+            assert!(pending.is_err());
+            assert_eq!(sut.reservations.reserved(1), order_request.quantity);
+        }
+
+        // assert that no stock is leaked and no order was recorded
+        assert_eq!(sut.reservations.reserved(1), 0);
+        assert!(sut.db.lock().unwrap().get_order(1).is_none());
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_place_order_rejects_stock_reserved_by_concurrent_order() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().return_once(|_| Ok(30));
+        mock_catalog_network_service
+            .expect_get_item_detail()
+            .return_once(|item_id| Ok(generate_item_detail(item_id, false)));
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        let _concurrent = sut.reservations.reserve(1, 10, 30);
+
+        // act
+        let result = sut.place_order(&generate_random_order_request(), None).await;
+
+        // assert that only 20 of the 30 in stock were available for the order of 22
+        assert_eq!(result, Err(PlaceOrderError::ItemOutOfStock));
+        assert_eq!(sut.reservations.reserved(1), 10);
+    }
+
+    #[tokio::test]
+    async fn test_slow_place_order_is_logged_as_warning() {
+        // prepare a catalog slower than the latency budget
+        let sink = logger::initialize_for_test();
+        let mut catalog_network_service = SequenceMockCatalogNetworkService::new(WhenExhausted::Cycle);
+        catalog_network_service.set_stock_sequence(vec![Ok(25)]);
+        catalog_network_service.set_price_sequence(vec![Ok(Money::new(2000, "GBP"))]);
+        catalog_network_service.set_delay(Duration::from_millis(20));
+        let mut sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        sut.set_slow_order_threshold(Duration::from_millis(30));
+        let order_request = OrderRequest {
+            item_id: Some(4711),
+            ..generate_random_order_request()
+        };
 
         // act
-        let result = sut.place_order(&generate_random_order_request()).await;
+        let result = sut.place_order(&order_request, None).await;
 
         // assert
         assert!(result.is_ok());
+        let warning = sink
+            .lines()
+            .into_iter()
+            .find(|line| line.contains("[WARN]") && line.contains("item_id=4711"))
+            .expect("A slow order should be logged as a warning");
+        assert!(warning.contains("exceeding the latency budget of 30ms"));
+    }
+
+    #[tokio::test]
+    async fn test_fast_place_order_is_not_logged_as_warning() {
+        // prepare
+        let sink = logger::initialize_for_test();
+        let mut catalog_network_service = SequenceMockCatalogNetworkService::new(WhenExhausted::Cycle);
+        catalog_network_service.set_stock_sequence(vec![Ok(25)]);
+        catalog_network_service.set_price_sequence(vec![Ok(Money::new(2000, "GBP"))]);
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        let order_request = OrderRequest {
+            item_id: Some(4712),
+            ..generate_random_order_request()
+        };
+
+        // act
+        let result = sut.place_order(&order_request, None).await;
+
+        // assert
+        assert!(result.is_ok());
+        assert!(!sink.lines().iter().any(|line| line.contains("[WARN]") && line.contains("item_id=4712")));
+    }
+
+    #[tokio::test]
+    async fn test_place_order_computes_total() {
+        // prepare
+        let mock_event_listener = MockEventBus::new();
+        let mock_order_db = MockOrderDb::new();
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().return_once(move |_| Ok(25));
+        mock_catalog_network_service
+            .expect_get_item_price()
+            .return_once(move |_| Ok(Money::new(1999, "GBP")));
+        let sut = OrderService::new(
+            mock_order_db,
+            mock_event_listener,
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.place_order(&generate_random_order_request(), None).await;
+
+        // assert that 22 items at 19.99 each are priced exactly
+        assert_eq!(result.unwrap(), Money::new(43978, "GBP"));
+    }
+
+    #[tokio::test]
+    async fn test_place_order_event_carries_correlation_id() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().return_once(move |_| Ok(25));
+        mock_catalog_network_service
+            .expect_get_item_price()
+            .return_once(move |_| Ok(Money::new(2000, "GBP")));
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.place_order(&generate_random_order_request(), Some("request-1".to_string())).await;
+
+        // assert
+        assert!(result.is_ok());
+        let produced = sut.event_bus.produced();
+        let event: Event<OrderPlacedEvent> = serde_json::from_slice(&produced[0].2).unwrap();
+        assert_eq!(event.correlation_id, Some("request-1".to_string()));
+        assert_eq!(event.source, MICROSERVICE_NAME);
+    }
+
+    #[tokio::test]
+    async fn test_place_order_event_carries_configured_source() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().return_once(move |_| Ok(25));
+        mock_catalog_network_service
+            .expect_get_item_price()
+            .return_once(move |_| Ok(Money::new(2000, "GBP")));
+        let mut sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        sut.set_source("Order-EU");
+
+        // act
+        let result = sut.place_order(&generate_random_order_request(), None).await;
+
+        // assert
+        assert!(result.is_ok());
+        let produced = sut.event_bus.produced();
+        let event: Event<OrderPlacedEvent> = serde_json::from_slice(&produced[0].2).unwrap();
+        assert_eq!(event.source, "Order-EU");
+    }
+
+    #[tokio::test]
+    async fn test_place_order_price_unavailable_error() {
+        // prepare
+        let mock_event_listener = MockEventBus::new();
+        let mock_order_db = MockOrderDb::new();
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().return_once(move |_| Ok(25));
+        mock_catalog_network_service.expect_get_item_price().return_once(move |_| {
+            Err(NetworkError {
+                status_code: Some(404),
+                error: NetworkErrorType::Standard,
+            })
+        });
+        let sut = OrderService::new(
+            mock_order_db,
+            mock_event_listener,
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.place_order(&generate_random_order_request(), None).await;
+
+        // assert
+        assert!(result.unwrap_err() == PlaceOrderError::PriceUnavailable);
+    }
+
+    fn generate_order_request(item_id: u32, quantity: u32) -> OrderRequest {
+        OrderRequest {
+            item_id: Some(item_id),
+            quantity,
+            ..generate_random_order_request()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_place_order_produces_to_the_configured_topic() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().returning(|_| Ok(5));
+        mock_catalog_network_service.expect_get_item_price().returning(|_| Ok(Money::new(1000, "GBP")));
+        let mut topics = TopicConfig::new();
+        topics.set(topic::ORDER_PLACED, "staging.order-placed").unwrap();
+        let mut sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        sut.set_topic_config(topics);
+
+        // act
+        let result = sut.place_order(&generate_order_request(1, 2), None).await;
+
+        // assert
+        assert!(result.is_ok());
+        let produced = sut.event_bus.produced();
+        assert_eq!(produced.len(), 1);
+        assert_eq!(produced[0].0, "staging.order-placed");
+    }
+
+    #[tokio::test]
+    async fn test_place_orders_checks_stock_with_one_batch_request() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service
+            .expect_get_stock_batch()
+            .times(1)
+            .returning(|_| Ok(HashMap::from([(1, 5), (2, 5)])));
+        mock_catalog_network_service.expect_get_stock().times(0);
+        mock_catalog_network_service
+            .expect_get_item_price()
+            .returning(|item_id| Ok(Money::new(1000 * item_id as i64, "GBP")));
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        let order_requests = vec![
+            generate_order_request(1, 2),
+            generate_order_request(2, 1),
+            generate_order_request(1, 3),
+        ];
+
+        // act
+        let result = sut.place_orders(&order_requests, None).await;
+
+        // assert
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                Money::new(2000, "GBP"),
+                Money::new(2000, "GBP"),
+                Money::new(3000, "GBP")
+            ]
+        );
+        let produced = sut.event_bus.produced();
+        assert_eq!(produced.len(), 3);
+        assert!(produced.iter().all(|(topic, _, _)| topic == topic::ORDER_PLACED));
+    }
+
+    #[tokio::test]
+    async fn test_place_orders_checks_total_quantity_of_each_item() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock_batch().returning(|_| Ok(HashMap::from([(1, 4)])));
+        mock_catalog_network_service.expect_get_item_price().returning(|_| Ok(Money::new(1000, "GBP")));
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act that each order fits the stock on its own, but not together
+        let result = sut.place_orders(&[generate_order_request(1, 2), generate_order_request(1, 3)], None).await;
+
+        // assert
+        assert_eq!(result.unwrap_err(), PlaceOrderError::ItemOutOfStock);
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_place_orders_unknown_item_is_out_of_stock() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock_batch().returning(|_| Ok(HashMap::from([(1, 4)])));
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.place_orders(&[generate_order_request(1, 1), generate_order_request(2, 1)], None).await;
+
+        // assert
+        assert_eq!(result.unwrap_err(), PlaceOrderError::ItemOutOfStock);
+    }
+
+    #[tokio::test]
+    async fn test_place_orders_falls_back_to_stock_per_item() {
+        // prepare
+        let mut catalog_network_service = SequenceMockCatalogNetworkService::new(WhenExhausted::Error);
+        catalog_network_service.set_stock_batch_sequence(vec![Err(Some(404))]);
+        catalog_network_service.set_stock_sequence(vec![Ok(5), Ok(5)]);
+        catalog_network_service.set_price_sequence(vec![Ok(Money::new(1000, "GBP")), Ok(Money::new(1000, "GBP"))]);
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.place_orders(&[generate_order_request(1, 1), generate_order_request(2, 1)], None).await;
+
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(sut.catalog_network_service.stock_batch_calls(), 1);
+        assert_eq!(sut.catalog_network_service.stock_calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_place_orders_batch_server_error_does_not_fall_back() {
+        // prepare
+        let mut catalog_network_service = SequenceMockCatalogNetworkService::new(WhenExhausted::Error);
+        catalog_network_service.set_stock_batch_sequence(vec![Err(Some(503))]);
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.place_orders(&[generate_order_request(1, 1), generate_order_request(2, 1)], None).await;
+
+        // assert
+        assert_eq!(result.unwrap_err(), PlaceOrderError::CatalogNetworkError);
+        assert_eq!(sut.catalog_network_service.stock_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_place_orders_fallback_error_is_catalog_network_error() {
+        // prepare
+        let mut catalog_network_service = SequenceMockCatalogNetworkService::new(WhenExhausted::Error);
+        catalog_network_service.set_stock_batch_sequence(vec![Err(Some(405))]);
+        catalog_network_service.set_stock_sequence(vec![Err(Some(500))]);
+        let sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.place_orders(&[generate_order_request(1, 1)], None).await;
+
+        // assert
+        assert_eq!(result.unwrap_err(), PlaceOrderError::CatalogNetworkError);
+    }
+
+    #[tokio::test]
+    async fn test_place_order_max_quantity_is_accepted() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().return_once(move |_| Ok(25));
+        mock_catalog_network_service
+            .expect_get_item_price()
+            .return_once(move |_| Ok(Money::new(2000, "GBP")));
+        let mut sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        sut.set_max_quantity_per_order(10);
+
+        // act
+        let result = sut.place_order(&generate_order_request(1, 10), None).await;
+
+        // assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_place_order_above_max_quantity_error() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().times(0);
+        let mut sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        sut.set_max_quantity_per_order(10);
+
+        // act
+        let result = sut.place_order(&generate_order_request(1, 11), None).await;
+
+        // assert
+        assert_eq!(result.unwrap_err(), PlaceOrderError::QuantityTooLarge);
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_place_orders_above_max_quantity_error() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock_batch().times(0);
+        let mut sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        sut.set_max_quantity_per_order(10);
+
+        // act
+        let result = sut.place_orders(&[generate_order_request(1, 10), generate_order_request(2, 11)], None).await;
+
+        // assert
+        assert_eq!(result.unwrap_err(), PlaceOrderError::QuantityTooLarge);
+    }
+
+    #[test]
+    fn test_place_order_error_into_service_error() {
+        assert!(matches!(
+            ServiceError::from(PlaceOrderError::ItemOutOfStock),
+            ServiceError::Conflict(_)
+        ));
+        assert!(matches!(
+            ServiceError::from(PlaceOrderError::CatalogNetworkError),
+            ServiceError::Upstream(_)
+        ));
+        assert!(matches!(
+            ServiceError::from(PlaceOrderError::PriceUnavailable),
+            ServiceError::Upstream(_)
+        ));
+        assert!(matches!(
+            ServiceError::from(PlaceOrderError::QuantityTooLarge),
+            ServiceError::BadRequest(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_start_event_listeners_invalidates_changed_item() {
+        // prepare
+        let catalog_cache = Arc::new(CatalogCache::new());
+        for item_id in [1, 2] {
+            catalog_cache.insert(ClothingItemDTO {
+                id: item_id,
+                sku: String::new(),
+                name: "T-Shirt".to_string(),
+                description: "desc".to_string(),
+                sizes: vec![],
+                price: Money::new(2000, "GBP"),
+                currency: "GBP".to_string(),
+                images: vec![],
+                allow_backorder: false,
+                video: "".to_string(),
+            });
+        }
+        let mut sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            MockCatalogNetworkService::new(),
+            catalog_cache.clone(),
+        );
+
+        // act
+        sut.start_event_listeners();
+        sut.event_bus.publish(
+            topic::CATALOG_ITEM_CHANGED,
+            &Event::new(
+                "catalog_item_changed".to_string(),
+                CatalogItemChangedEvent {
+                    item_id: 1,
+                    change: CatalogItemChange::Removed,
+                },
+                "Catalog".to_string(),
+                None,
+                None,
+            ),
+        );
+
+        // assert
+        for _ in 0..100 {
+            if catalog_cache.get(1).is_none() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(catalog_cache.get(1).is_none());
+        assert!(catalog_cache.get(2).is_some());
+    }
+
+    // waits for the stock view listeners to apply the published events to the stock of an item
+    async fn wait_for_stock_view<E: EventProducer + EventListener, D: for<'a> OrderDb<'a>, C: CatalogNetworkService>(
+        sut: &OrderService<E, D, C>,
+        item_id: u32,
+        expected: Option<u32>,
+    ) {
+        let stock_view = sut.stock_view.as_ref().unwrap();
+        for _ in 0..100 {
+            if stock_view.get(item_id) == expected {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(stock_view.get(item_id), expected);
+    }
+
+    #[tokio::test]
+    async fn test_place_order_checks_stock_in_stock_view() {
+        // prepare with the stock of the item learnt from the catalog by a first order
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().times(1).returning(|_| Ok(25));
+        mock_catalog_network_service.expect_get_item_price().returning(|_| Ok(Money::new(2000, "GBP")));
+        let mut sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        sut.enable_stock_view();
+        sut.start_event_listeners();
+        assert!(sut.place_order(&generate_random_order_request(), None).await.is_ok());
+
+        // act
+        sut.event_bus.publish(
+            topic::ORDER_PLACED,
+            &Event::new(
+                "order_placed".to_string(),
+                OrderPlacedEvent {
+                    item_id: 1,
+                    quantity: 3,
+                    total: None,
+                    reservation_id: None,
+                },
+                "Order".to_string(),
+                None,
+                None,
+            ),
+        );
+        wait_for_stock_view(&sut, 1, Some(22)).await;
+        let result = sut.place_order(&generate_random_order_request(), None).await;
+
+        // assert that the second order was checked without contacting the catalog
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_place_order_rejects_order_beyond_stock_view() {
+        // prepare
+        let mut mock_catalog_network_service = MockCatalogNetworkService::new();
+        mock_catalog_network_service.expect_get_stock().never();
+        mock_catalog_network_service
+            .expect_get_item_detail()
+            .return_once(move |item_id| Ok(generate_item_detail(item_id, false)));
+        let mut sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            mock_catalog_network_service,
+            Arc::new(CatalogCache::new()),
+        );
+        sut.enable_stock_view();
+        sut.stock_view.as_ref().unwrap().insert(1, 21);
+
+        // act
+        let result = sut.place_order(&generate_random_order_request(), None).await;
+
+        // assert
+        assert_eq!(result, Err(PlaceOrderError::ItemOutOfStock));
+    }
+
+    #[tokio::test]
+    async fn test_stock_view_forgets_changed_and_backordered_items() {
+        // prepare
+        let mut sut = OrderService::new(
+            MockOrderDb::new(),
+            MockEventBus::new(),
+            MockCatalogNetworkService::new(),
+            Arc::new(CatalogCache::new()),
+        );
+        sut.enable_stock_view();
+        sut.start_event_listeners();
+        let stock_view = sut.stock_view.clone().unwrap();
+        stock_view.insert(1, 10);
+        stock_view.insert(2, 10);
+
+        // act
+        sut.event_bus.publish(
+            topic::CATALOG_ITEM_CHANGED,
+            &Event::new(
+                "catalog_item_changed".to_string(),
+                CatalogItemChangedEvent {
+                    item_id: 1,
+                    change: CatalogItemChange::Updated,
+                },
+                "Catalog".to_string(),
+                None,
+                None,
+            ),
+        );
+        sut.event_bus.publish(
+            topic::BACKORDER_PLACED,
+            &Event::new(
+                "backorder_placed".to_string(),
+                BackorderPlacedEvent {
+                    item_id: 2,
+                    quantity: 12,
+                    backordered_quantity: 2,
+                    total: None,
+                },
+                "Order".to_string(),
+                None,
+                None,
+            ),
+        );
+
+        // assert
+        wait_for_stock_view(&sut, 1, None).await;
+        wait_for_stock_view(&sut, 2, None).await;
+    }
+
+    #[tokio::test]
+    async fn test_cancel_order_broadcasts_order_cancelled() {
+        // prepare
+        let mut mock_order_db = MockOrderDb::new();
+        mock_order_db.set_expected_order(Some(generate_random_order()));
+        let sut = OrderService::new(
+            mock_order_db,
+            MockEventBus::new(),
+            MockCatalogNetworkService::new(),
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.cancel_order(1).await;
+
+        // assert
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            sut.db.lock().unwrap().get_order(1).unwrap().status,
+            OrderStatus::Cancelled
+        );
+        let produced = sut.event_bus.produced();
+        assert_eq!(produced.len(), 1);
+        assert_eq!(produced[0].0, topic::ORDER_CANCELLED);
+        let event: Event<OrderCancelledEvent> = serde_json::from_slice(&produced[0].2).unwrap();
+        assert_eq!(
+            event.payload,
+            OrderCancelledEvent {
+                order_id: 1,
+                item_id: 1,
+                quantity: 22
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_order_rejects_shipped_order() {
+        // prepare
+        let mut order = generate_random_order();
+        order.status = OrderStatus::Shipped;
+        let mut mock_order_db = MockOrderDb::new();
+        mock_order_db.set_expected_order(Some(order));
+        let sut = OrderService::new(
+            mock_order_db,
+            MockEventBus::new(),
+            MockCatalogNetworkService::new(),
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.cancel_order(1).await;
+
+        // assert
+        assert_eq!(result, Err(OrderError::AlreadyShipped));
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_order_rejects_cancelled_order() {
+        // prepare
+        let mut order = generate_random_order();
+        order.status = OrderStatus::Cancelled;
+        let mut mock_order_db = MockOrderDb::new();
+        mock_order_db.set_expected_order(Some(order));
+        let sut = OrderService::new(
+            mock_order_db,
+            MockEventBus::new(),
+            MockCatalogNetworkService::new(),
+            Arc::new(CatalogCache::new()),
+        );
+
+        // act
+        let result = sut.cancel_order(1).await;
+
+        // assert
+        assert_eq!(result, Err(OrderError::AlreadyCancelled));
+        assert!(sut.event_bus.produced().is_empty());
+    }
+
+    #[test]
+    fn test_order_error_into_service_error() {
+        assert!(matches!(
+            ServiceError::from(OrderError::NotFound),
+            ServiceError::NotFound(_)
+        ));
+        assert!(matches!(
+            ServiceError::from(OrderError::AlreadyShipped),
+            ServiceError::Conflict(_)
+        ));
+        assert!(matches!(
+            ServiceError::from(OrderError::AlreadyCancelled),
+            ServiceError::Conflict(_)
+        ));
+        assert!(matches!(
+            ServiceError::from(OrderError::PersistenceError),
+            ServiceError::Internal(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_order_poisoned_db_is_persistence_error() {
+        // prepare
+        let mut mock_order_db = MockOrderDb::new();
+        mock_order_db.set_expected_order(Some(generate_random_order()));
+        let sut = OrderService::new(
+            mock_order_db,
+            MockEventBus::new(),
+            MockCatalogNetworkService::new(),
+            Arc::new(CatalogCache::new()),
+        );
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = sut.db.lock().unwrap();
+            panic!("poisoning the db lock");
+        }));
+
+        // act
+        let result = sut.cancel_order(1).await;
+
+        // assert
+        assert_eq!(result, Err(OrderError::PersistenceError));
+        assert!(sut.event_bus.produced().is_empty());
     }
 }
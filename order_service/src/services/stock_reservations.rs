@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// `StockReservations` holds the stock reserved by orders that are being placed.
+///
+/// The catalog only removes the stock of an order once it receives the `order_placed` event, so
+/// an order whose stock has been checked, but which is yet to be recorded, reserves its quantity
+/// here. Later stock checks subtract the reserved quantities, so concurrent orders for the same
+/// item cannot both be granted the last of its stock.
+///
+/// Fields:
+/// - `reserved`: The quantity reserved of each item, keyed by item id.
+#[derive(Default)]
+pub struct StockReservations {
+    reserved: Mutex<HashMap<u32, u32>>,
+}
+
+impl StockReservations {
+    pub fn new() -> Self {
+        StockReservations::default()
+    }
+
+    /// Reserves as much of a quantity of an item as the stock allows, once the quantity already
+    /// reserved by other orders is subtracted from it.
+    ///
+    /// Arguments:
+    /// - `item_id`: The unique identifier of the clothing item.
+    /// - `quantity`: The quantity to reserve.
+    /// - `stock`: The stock of the item in the catalog.
+    ///
+    /// Returns:
+    /// - `StockReservation`: A guard releasing the reserved quantity when it is dropped, whether the
+    ///   order is recorded or abandoned, e.g. because the request was cancelled.
+    pub fn reserve(&self, item_id: u32, quantity: u32, stock: u32) -> StockReservation<'_> {
+        let mut reserved = self.reserved.lock().unwrap();
+        let item_reserved = reserved.entry(item_id).or_insert(0);
+        let available = stock.saturating_sub(*item_reserved);
+        let quantity_reserved = quantity.min(available);
+        *item_reserved += quantity_reserved;
+
+        StockReservation {
+            reservations: self,
+            item_id,
+            quantity: quantity_reserved,
+            shortfall: quantity - quantity_reserved,
+        }
+    }
+
+    /// Retrieves the quantity of an item currently reserved.
+    ///
+    /// Arguments:
+    /// - `item_id`: The unique identifier of the clothing item.
+    pub fn reserved(&self, item_id: u32) -> u32 {
+        self.reserved.lock().unwrap().get(&item_id).copied().unwrap_or(0)
+    }
+
+    // returns a reserved quantity, removing items with nothing left reserved
+    fn release(&self, item_id: u32, quantity: u32) {
+        let mut reserved = self.reserved.lock().unwrap();
+        if let Some(item_reserved) = reserved.get_mut(&item_id) {
+            *item_reserved = item_reserved.saturating_sub(quantity);
+            if *item_reserved == 0 {
+                reserved.remove(&item_id);
+            }
+        }
+    }
+}
+
+/// A quantity of an item reserved by `StockReservations::reserve`, released when dropped.
+///
+/// Fields:
+/// - `reservations`: The `StockReservations` the quantity is reserved in.
+/// - `item_id`: The unique identifier of the clothing item.
+/// - `quantity`: The quantity reserved.
+/// - `shortfall`: The quantity requested that could not be reserved, as there was not enough stock.
+pub struct StockReservation<'a> {
+    reservations: &'a StockReservations,
+    item_id: u32,
+    quantity: u32,
+    shortfall: u32,
+}
+
+impl StockReservation<'_> {
+    /// The quantity requested that could not be reserved, which is `0` if the whole quantity was.
+    pub fn shortfall(&self) -> u32 {
+        self.shortfall
+    }
+}
+
+impl Drop for StockReservation<'_> {
+    fn drop(&mut self) {
+        self.reservations.release(self.item_id, self.quantity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_within_stock() {
+        let sut = StockReservations::new();
+
+        let reservation = sut.reserve(1, 3, 5);
+
+        assert_eq!(reservation.shortfall(), 0);
+        assert_eq!(sut.reserved(1), 3);
+    }
+
+    #[test]
+    fn test_reserve_subtracts_existing_reservations() {
+        let sut = StockReservations::new();
+        let _first = sut.reserve(1, 3, 5);
+
+        let second = sut.reserve(1, 3, 5);
+
+        assert_eq!(second.shortfall(), 1);
+        assert_eq!(sut.reserved(1), 5);
+    }
+
+    #[test]
+    fn test_dropped_reservation_is_released() {
+        let sut = StockReservations::new();
+        let first = sut.reserve(1, 3, 5);
+        let second = sut.reserve(1, 3, 5);
+
+        drop(first);
+        assert_eq!(sut.reserved(1), 2);
+        drop(second);
+        assert_eq!(sut.reserved(1), 0);
+    }
+}
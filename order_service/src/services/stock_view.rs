@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// `StockView` is a local read model of the stock of catalog items, kept up to date from the
+/// events of the catalog so that orders can check the stock of an item without contacting it.
+///
+/// The events only describe changes to the stock, so the stock of an item is learnt from the
+/// catalog the first time it is checked, and then reduced by every `order_placed` event for the
+/// item, the only event the catalog applies to its stock. Cancelled orders are not returned to the
+/// view, as the catalog does not restock them. Items whose stock is changed in ways the events do
+/// not describe, e.g. a backorder or an edit of the item, are forgotten and learnt again on their
+/// next check. Reservations made in the catalog emit no event, so the view can be above the stock
+/// available to order; it is eventually consistent, the catalog remains the authority on the stock.
+///
+/// Fields:
+/// - `stock`: The stock of each item known to the view, keyed by item id.
+#[derive(Default)]
+pub struct StockView {
+    stock: Mutex<HashMap<u32, u32>>,
+}
+
+impl StockView {
+    pub fn new() -> Self {
+        StockView::default()
+    }
+
+    /// Retrieves the stock of an item, or `None` if the view does not know it.
+    ///
+    /// Arguments:
+    /// - `item_id`: The unique identifier of the clothing item.
+    pub fn get(&self, item_id: u32) -> Option<u32> {
+        self.stock.lock().unwrap().get(&item_id).copied()
+    }
+
+    /// Records the stock of an item as read from the catalog, replacing any stock already known.
+    ///
+    /// Arguments:
+    /// - `item_id`: The unique identifier of the clothing item.
+    /// - `stock`: The stock of the item in the catalog.
+    pub fn insert(&self, item_id: u32, stock: u32) {
+        self.stock.lock().unwrap().insert(item_id, stock);
+    }
+
+    /// Removes an ordered quantity from the stock of an item, if the view knows it.
+    ///
+    /// Arguments:
+    /// - `item_id`: The unique identifier of the clothing item.
+    /// - `quantity`: The quantity ordered, the stock never falls below zero.
+    pub fn remove_quantity(&self, item_id: u32, quantity: u32) {
+        if let Some(stock) = self.stock.lock().unwrap().get_mut(&item_id) {
+            *stock = stock.saturating_sub(quantity);
+        }
+    }
+
+    /// Forgets the stock of an item, so that it is read from the catalog on its next check.
+    ///
+    /// Arguments:
+    /// - `item_id`: The unique identifier of the clothing item.
+    pub fn invalidate(&self, item_id: u32) {
+        self.stock.lock().unwrap().remove(&item_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjusts_known_stock() {
+        let sut = StockView::new();
+        sut.insert(1, 10);
+
+        sut.remove_quantity(1, 4);
+
+        assert_eq!(sut.get(1), Some(6));
+    }
+
+    #[test]
+    fn test_ignores_changes_to_unknown_items() {
+        let sut = StockView::new();
+
+        sut.remove_quantity(1, 4);
+
+        assert_eq!(sut.get(1), None);
+    }
+
+    #[test]
+    fn test_stock_never_falls_below_zero() {
+        let sut = StockView::new();
+        sut.insert(1, 3);
+
+        sut.remove_quantity(1, 5);
+
+        assert_eq!(sut.get(1), Some(0));
+    }
+
+    #[test]
+    fn test_invalidate_forgets_stock() {
+        let sut = StockView::new();
+        sut.insert(1, 3);
+        sut.insert(2, 5);
+
+        sut.invalidate(1);
+
+        assert_eq!(sut.get(1), None);
+        assert_eq!(sut.get(2), Some(5));
+    }
+}
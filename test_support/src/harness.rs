@@ -0,0 +1,268 @@
+use crate::in_memory_event_bus::InMemoryEventBus;
+use crate::in_process_catalog::InProcessCatalogNetworkService;
+use catalog_service::db::catalog_db::{CatalogDb, CatalogDbClient};
+use catalog_service::services::catalog_service::CatalogService;
+use common::traits::listener_service::ListenerService;
+use order_service::db::order_db::{OrderDb, OrderDbClient};
+use order_service::networking::cached_catalog_network_service::CatalogCache;
+use order_service::services::order_service::OrderService;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The `CatalogService` of a `Harness`.
+pub type HarnessCatalogService = CatalogService<InMemoryEventBus, CatalogDbClient>;
+
+/// The `OrderService` of a `Harness`, whose calls to the catalog are answered by the `CatalogService`
+/// of the harness.
+pub type HarnessOrderService =
+    OrderService<InMemoryEventBus, OrderDbClient, InProcessCatalogNetworkService<InMemoryEventBus, CatalogDbClient>>;
+
+// a setup applied to a service before its listeners are started
+type Setup<S> = Box<dyn FnOnce(&mut S)>;
+
+// how long `wait_for_stock` waits for the events in flight to be applied before giving up
+const WAIT_FOR_STOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `Harness` wires an `OrderService` and a `CatalogService` together in memory, so an end-to-end
+/// test can place an order and assert how the catalog reacted to it.
+///
+/// Both services share an `InMemoryEventBus` and have their event listeners started, so the events
+/// broadcast by one service are delivered to the other, and the order service calls the catalog
+/// through an `InProcessCatalogNetworkService`. Events are delivered asynchronously, as by Kafka,
+/// so assertions on the effects of an event should wait for them, e.g. with `wait_for_stock`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let harness = Harness::builder().build();
+/// harness.order_service.place_order(&order_request, None).await?;
+/// assert_eq!(harness.wait_for_stock(1, 98).await, Some(98));
+/// ```
+///
+/// Fields:
+/// - `event_bus`: The bus shared by both services, e.g. to assert on the events they broadcast.
+/// - `catalog_service`: The catalog, seeded with the items of the `CatalogDbClient` it was built with.
+/// - `order_service`: The order service, calling the catalog in process.
+pub struct Harness {
+    pub event_bus: InMemoryEventBus,
+    pub catalog_service: Arc<HarnessCatalogService>,
+    pub order_service: HarnessOrderService,
+}
+
+/// `HarnessBuilder` configures the services of a `Harness` before their listeners are started.
+///
+/// Fields:
+/// - `catalog_db`: The database of the catalog, the built-in items of `CatalogDbClient::new` by default.
+/// - `catalog_setup`: The setup applied to the catalog before its listeners are started.
+/// - `order_setup`: The setup applied to the order service before its listeners are started.
+pub struct HarnessBuilder {
+    catalog_db: CatalogDbClient,
+    catalog_setup: Vec<Setup<HarnessCatalogService>>,
+    order_setup: Vec<Setup<HarnessOrderService>>,
+}
+
+impl Default for HarnessBuilder {
+    fn default() -> Self {
+        HarnessBuilder {
+            catalog_db: CatalogDbClient::new(),
+            catalog_setup: vec![],
+            order_setup: vec![],
+        }
+    }
+}
+
+impl HarnessBuilder {
+    /// Seeds the catalog with the items of the given database instead of the built-in items.
+    ///
+    /// Arguments:
+    /// - `catalog_db`: The database of the catalog.
+    pub fn with_catalog_db(mut self, catalog_db: CatalogDbClient) -> Self {
+        self.catalog_db = catalog_db;
+        self
+    }
+
+    /// Applies a setup to the catalog before its listeners are started, e.g. a call to
+    /// `set_stock_underflow_policy`.
+    ///
+    /// Arguments:
+    /// - `setup`: The setup, called with the catalog.
+    pub fn configure_catalog(mut self, setup: impl FnOnce(&mut HarnessCatalogService) + 'static) -> Self {
+        self.catalog_setup.push(Box::new(setup));
+        self
+    }
+
+    /// Applies a setup to the order service before its listeners are started, e.g. a call to
+    /// `enable_stock_view`.
+    ///
+    /// Arguments:
+    /// - `setup`: The setup, called with the order service.
+    pub fn configure_orders(mut self, setup: impl FnOnce(&mut HarnessOrderService) + 'static) -> Self {
+        self.order_setup.push(Box::new(setup));
+        self
+    }
+
+    /// Builds both services and starts their event listeners, which must be done within a tokio
+    /// runtime, e.g. in a `#[tokio::test]`.
+    pub fn build(self) -> Harness {
+        let event_bus = InMemoryEventBus::new();
+        let mut catalog_service = CatalogService::new(self.catalog_db, event_bus.clone());
+        for setup in self.catalog_setup {
+            setup(&mut catalog_service);
+        }
+        catalog_service.start_event_listeners();
+        let catalog_service = Arc::new(catalog_service);
+
+        let mut order_service = OrderService::new(
+            OrderDbClient::new(),
+            event_bus.clone(),
+            InProcessCatalogNetworkService::new(catalog_service.clone()),
+            Arc::new(CatalogCache::new()),
+        );
+        for setup in self.order_setup {
+            setup(&mut order_service);
+        }
+        order_service.start_event_listeners();
+
+        Harness {
+            event_bus,
+            catalog_service,
+            order_service,
+        }
+    }
+}
+
+impl Harness {
+    /// Creates a builder of a harness, seeded with the built-in items of `CatalogDbClient::new`.
+    pub fn builder() -> HarnessBuilder {
+        HarnessBuilder::default()
+    }
+
+    /// Waits for the stock of an item to reach the expected level, as the events changing it are
+    /// applied, returning the stock it reached. The stock differs from the expected level if it was
+    /// not reached within `WAIT_FOR_STOCK_TIMEOUT`, and is `None` if the item does not exist.
+    ///
+    /// Arguments:
+    /// - `item_id`: The identifier of the catalog item.
+    /// - `expected`: The stock the item is expected to reach.
+    pub async fn wait_for_stock(&self, item_id: u32, expected: u32) -> Option<u32> {
+        let _ = tokio::time::timeout(WAIT_FOR_STOCK_TIMEOUT, async {
+            while self.catalog_service.get_stock(item_id).ok() != Some(expected) {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await;
+        self.catalog_service.get_stock(item_id).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use event_bus::event::Event;
+    use event_bus::events::order_placed_event::OrderPlacedEvent;
+    use event_bus::topic;
+    use event_bus::utilities::dead_letters::{replay_dead_letters, REPLAYED_EVENT_ID_SUFFIX};
+    use event_bus::EventProducer;
+    use order_service::model::OrderRequest;
+    use order_service::services::order_service::PlaceOrderError;
+
+    fn order_request(item_id: u32, quantity: u32) -> OrderRequest {
+        OrderRequest {
+            item_id: Some(item_id),
+            sku: None,
+            name: "James".to_string(),
+            address: "1 Test Street".to_string(),
+            quantity,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_placed_order_is_taken_from_the_catalog_stock() {
+        // prepare
+        let harness = Harness::builder().build();
+        let initial_stock = harness.catalog_service.get_stock(1).unwrap();
+
+        // act
+        let total = harness.order_service.place_order(&order_request(1, 2), None).await;
+
+        // assert
+        assert!(total.is_ok());
+        assert_eq!(
+            harness.wait_for_stock(1, initial_stock - 2).await,
+            Some(initial_stock - 2)
+        );
+        assert_eq!(
+            harness.catalog_service.get_order_summary(1).unwrap().total_quantity_ordered,
+            2
+        );
+        assert!(harness.event_bus.produced().iter().any(|(topic, _, _)| topic == topic::ORDER_PLACED));
+    }
+
+    #[tokio::test]
+    async fn test_order_beyond_the_catalog_stock_is_rejected() {
+        // prepare
+        let harness = Harness::builder().build();
+        harness.catalog_service.set_stock(1, 3).await.unwrap();
+
+        // act
+        let result = harness.order_service.place_order(&order_request(1, 4), None).await;
+
+        // assert
+        assert_eq!(result.unwrap_err(), PlaceOrderError::ItemOutOfStock);
+        assert_eq!(harness.catalog_service.get_stock(1).unwrap(), 3);
+        assert!(!harness.event_bus.produced().iter().any(|(topic, _, _)| topic == topic::ORDER_PLACED));
+    }
+
+    #[tokio::test]
+    async fn test_replayed_dead_letter_is_applied_by_the_catalog() {
+        // prepare, the catalog skipping the event for lack of stock so it has seen its id
+        let harness = Harness::builder().build();
+        harness.catalog_service.set_stock(1, 1).await.unwrap();
+        let payload = OrderPlacedEvent {
+            item_id: 1,
+            quantity: 3,
+            total: None,
+            reservation_id: None,
+        };
+        let event =
+            Event::new("order_placed".to_string(), payload, "Order".to_string(), None, None).with_retry_attempt(2);
+        harness.event_bus.broadcast_event(event.clone(), topic::ORDER_PLACED, 1).await.unwrap();
+        let _ = tokio::time::timeout(Duration::from_secs(2), async {
+            while harness.catalog_service.get_order_summary(1).unwrap().total_quantity_ordered != 3 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await;
+        harness.catalog_service.set_stock(1, 10).await.unwrap();
+
+        // act, dead-lettering the event once the replay is listening
+        let (replayed, _) = tokio::join!(
+            replay_dead_letters(
+                &harness.event_bus,
+                topic::ORDER_PLACED_DEAD_LETTER,
+                topic::ORDER_PLACED,
+                1
+            ),
+            async {
+                harness.event_bus.broadcast_event(event.clone(), topic::ORDER_PLACED_DEAD_LETTER, 1).await.unwrap()
+            }
+        );
+
+        // assert
+        assert_eq!(replayed.unwrap(), 1);
+        assert_eq!(harness.wait_for_stock(1, 7).await, Some(7));
+        let (_, key, message) = harness
+            .event_bus
+            .produced()
+            .into_iter()
+            .rfind(|(topic_name, _, _)| topic_name == topic::ORDER_PLACED)
+            .unwrap();
+        let replay: Event<OrderPlacedEvent> = serde_json::from_slice(&message).unwrap();
+        assert_eq!(key, "1");
+        assert_eq!(
+            replay.event_id,
+            format!("{}{}", event.event_id, REPLAYED_EVENT_ID_SUFFIX)
+        );
+        assert_eq!(replay.retry_attempt(), 0);
+    }
+}
@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use event_bus::clock::Clock;
+use event_bus::message_key::MessageKey;
+use event_bus::utilities::listeners::{KafkaListener, OffsetReset};
+use event_bus::{EventListener, EventProducer, MockEventBus};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+use std::sync::Arc;
+
+/// `InMemoryEventBus` is an event bus held in memory and shared by the services of a test, so that
+/// the events broadcast by one service are delivered to the listeners of every service, as Kafka
+/// would deliver them.
+///
+/// Clones share the same bus, so each service is given a clone. Like a `MockEventBus`, listeners
+/// only receive the events broadcast after they were created, whatever their offset reset, so the
+/// listeners of every service should be started before any event is broadcast.
+///
+/// Fields:
+/// - `bus`: The `MockEventBus` recording the broadcast events and feeding them to the listeners.
+#[derive(Clone, Default)]
+pub struct InMemoryEventBus {
+    bus: Arc<MockEventBus>,
+}
+
+impl InMemoryEventBus {
+    pub fn new() -> Self {
+        InMemoryEventBus::default()
+    }
+
+    /// Returns the topic, key and JSON payload of every event broadcast on the bus, in the order they
+    /// were broadcast. Keyless events have an empty key.
+    pub fn produced(&self) -> Vec<(String, String, Vec<u8>)> {
+        self.bus.produced()
+    }
+
+    /// Delivers an event to the listeners subscribed to the topic without recording it, as if it was
+    /// broadcast by a service outside of the test.
+    ///
+    /// Arguments:
+    /// - `topic_name`: The topic of the event.
+    /// - `payload`: The event.
+    pub fn publish<T: Serialize>(&self, topic_name: &str, payload: &T) {
+        self.bus.publish(topic_name, payload);
+    }
+}
+
+impl EventListener for InMemoryEventBus {
+    fn create_event_listener_with_offset_reset<T>(
+        &self,
+        group_id: &str,
+        topics: &[&str],
+        offset_reset: OffsetReset,
+    ) -> Result<KafkaListener<T>, Box<dyn Error>>
+    where
+        T: Send + DeserializeOwned + 'static + Clone,
+    {
+        self.bus.create_event_listener_with_offset_reset(group_id, topics, offset_reset)
+    }
+
+    fn is_mock(&self) -> bool {
+        true
+    }
+}
+
+#[async_trait]
+impl EventProducer for InMemoryEventBus {
+    fn clock(&self) -> &dyn Clock {
+        self.bus.clock()
+    }
+
+    async fn broadcast_event<T: Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+    ) -> Result<(), Box<dyn Error>> {
+        let (payload, key) = (serde_json::to_value(payload)?, key.into());
+        self.bus.broadcast_event(payload.clone(), topic_name, key.clone()).await?;
+        self.bus.publish_with_key(topic_name, key, &payload);
+        Ok(())
+    }
+
+    async fn broadcast_event_at<T: Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+        timestamp_ms: i64,
+    ) -> Result<(), Box<dyn Error>> {
+        let (payload, key) = (serde_json::to_value(payload)?, key.into());
+        self.bus.broadcast_event_at(payload.clone(), topic_name, key.clone(), timestamp_ms).await?;
+        self.bus.publish_with_key(topic_name, key, &payload);
+        Ok(())
+    }
+
+    async fn broadcast_event_sync<T: Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+    ) -> Result<(), Box<dyn Error>> {
+        let (payload, key) = (serde_json::to_value(payload)?, key.into());
+        self.bus.broadcast_event_sync(payload.clone(), topic_name, key.clone()).await?;
+        self.bus.publish_with_key(topic_name, key, &payload);
+        Ok(())
+    }
+
+    async fn broadcast_event_to_partition<T: Serialize + Send>(
+        &self,
+        payload: T,
+        topic_name: &str,
+        key: impl Into<MessageKey> + Send,
+        partition: i32,
+    ) -> Result<(), Box<dyn Error>> {
+        let (payload, key) = (serde_json::to_value(payload)?, key.into());
+        self.bus.broadcast_event_to_partition(payload.clone(), topic_name, key.clone(), partition).await?;
+        self.bus.publish_with_key(topic_name, key, &payload);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use event_bus::topic;
+    use event_bus::utilities::dead_letters::replay_dead_letters;
+
+    #[tokio::test]
+    async fn test_broadcast_events_are_delivered_to_every_clone() {
+        // prepare
+        let sut = InMemoryEventBus::new();
+        let listener = sut.clone().create_event_listener::<String>("group-1", &[topic::ORDER_PLACED]).unwrap();
+        let mut receiver = listener.get_receiver();
+
+        // act
+        sut.broadcast_event("placed".to_string(), topic::ORDER_PLACED, 1).await.unwrap();
+
+        // assert
+        assert_eq!(receiver.recv().await.unwrap(), "placed");
+        assert_eq!(sut.produced().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letters_are_replayed_to_the_source_topic() {
+        // prepare
+        let sut = InMemoryEventBus::new();
+        let listener = sut.create_event_listener::<String>("group-1", &[topic::ORDER_PLACED]).unwrap();
+        let mut receiver = listener.get_receiver();
+
+        // act, dead-lettering the events once the replay is listening
+        let (replayed, _) = tokio::join!(
+            replay_dead_letters(&sut, topic::ORDER_PLACED_DEAD_LETTER, topic::ORDER_PLACED, 2),
+            async {
+                sut.broadcast_event("first".to_string(), topic::ORDER_PLACED_DEAD_LETTER, 1).await.unwrap();
+                sut.broadcast_event("second".to_string(), topic::ORDER_PLACED_DEAD_LETTER, 2).await.unwrap();
+            }
+        );
+
+        // assert
+        assert_eq!(replayed.unwrap(), 2);
+        assert_eq!(receiver.recv().await.unwrap(), "first");
+        assert_eq!(receiver.recv().await.unwrap(), "second");
+        let replayed: Vec<(String, String)> =
+            sut.produced().into_iter().map(|(topic_name, key, _)| (topic_name, key)).collect();
+        assert_eq!(
+            replayed[2..],
+            [
+                (topic::ORDER_PLACED.to_string(), "1".to_string()),
+                (topic::ORDER_PLACED.to_string(), "2".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use catalog_service::db::catalog_db::CatalogDb;
+use catalog_service::services::catalog_service::{CatalogService, ItemNotFoundError};
+use common::dto::clothing_item_dto::ClothingItemDTO;
+use common::types::item_ref::ItemRef;
+use common::types::money::Money;
+use event_bus::{EventListener, EventProducer};
+use networking::{NetworkError, NetworkErrorType};
+use order_service::networking::catalog_network_service::CatalogNetworkService;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// `InProcessCatalogNetworkService` answers the calls an `OrderService` makes to the Catalog
+/// Microservice by calling a `CatalogService` directly, so both services can run in a single test
+/// without an HTTP server.
+///
+/// Items the catalog does not hold are reported as a `404`, as `GET /catalog/{id}` would report them.
+///
+/// Fields:
+/// - `catalog`: The catalog every call is answered by.
+pub struct InProcessCatalogNetworkService<E: EventListener, D: for<'a> CatalogDb<'a>> {
+    catalog: Arc<CatalogService<E, D>>,
+}
+
+impl<E: EventListener, D: for<'a> CatalogDb<'a>> InProcessCatalogNetworkService<E, D> {
+    pub fn new(catalog: Arc<CatalogService<E, D>>) -> Self {
+        InProcessCatalogNetworkService { catalog }
+    }
+}
+
+#[async_trait]
+impl<E, D> CatalogNetworkService for InProcessCatalogNetworkService<E, D>
+where
+    E: EventListener + EventProducer + Send + Sync + 'static,
+    D: for<'a> CatalogDb<'a> + Send + Sync + 'static,
+{
+    async fn get_stock(&self, item_id: u32) -> Result<u32, NetworkError> {
+        self.catalog.get_stock(item_id).map_err(not_found)
+    }
+
+    async fn get_stock_batch(&self, item_ids: &[u32]) -> Result<HashMap<u32, u32>, NetworkError> {
+        Ok(self.catalog.get_stock_batch(item_ids))
+    }
+
+    async fn get_item_price(&self, item_id: u32) -> Result<Money, NetworkError> {
+        self.get_item_detail(item_id).await.map(|item| item.price)
+    }
+
+    async fn get_item_detail(&self, item_id: u32) -> Result<ClothingItemDTO, NetworkError> {
+        self.catalog.get_item_dto(item_id, true).map_err(not_found)
+    }
+
+    async fn get_item_detail_by_sku(&self, sku: &str) -> Result<ClothingItemDTO, NetworkError> {
+        let item_id = self.catalog.resolve_item_id(&ItemRef::Sku(sku.to_string())).map_err(not_found)?;
+        self.get_item_detail(item_id).await
+    }
+}
+
+// the error the catalog responds with for an item it does not hold
+fn not_found(_: ItemNotFoundError) -> NetworkError {
+    NetworkError {
+        status_code: Some(404),
+        error: NetworkErrorType::Standard,
+    }
+}
@@ -0,0 +1,3 @@
+pub mod harness;
+pub mod in_memory_event_bus;
+pub mod in_process_catalog;